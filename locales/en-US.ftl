@@ -0,0 +1,153 @@
+# The messages of mcmod in English. Copy this file to <data>/locales/<language>.ftl, e.g.
+# de.ftl, and translate each message to add a language. Messages that are not translated are
+# shown in English.
+
+## Resolving
+
+workspace-config = Config { $config }
+collecting = Collecting { $name }
+using-lockfile = Using { $path }, which is up to date with the config. Use --update to look for newer versions
+collecting-optional = Collecting { $name } (optional)
+found-projects = { $count ->
+    [one] Found { $count } project
+   *[other] Found { $count } projects
+}
+error = Error: { $error }
+skipping-unresolved = Skipping, it could not be resolved
+skipping-requires = Skipping { $name }, a project it requires was not resolved
+skipping-side = Skipping { $project }, it is not used on the { $target }
+replacing-required = Replacing { $old } with { $new }, which is required by a dependency
+trying-older = Trying { $version }, as the dependencies of newer versions conflict
+frozen-at = Frozen at { $version }
+frozen-not-updated = Not updating { $name }, it is frozen at { $version }
+tolerated-game-version = No version for the configured game version, using one for { $version }
+choose-groups = Choose the optional groups to install
+next-resolve-failed = Check the name, game version, and loader of the project that failed, or run with `--refresh` if it was published recently
+next-skipped = Check the game version and loader of the skipped optional projects, or remove them from the config
+skipped-optional = { $count ->
+    [one] Skipped { $count } optional project:
+   *[other] Skipped { $count } optional projects:
+}
+
+## Downloading and installing
+
+getting-files = Getting files for { $version } : { $name }
+using-cached = Using cached file { $file }
+cache-corrupt = Cached file { $file } is corrupted, downloading it again
+downloading = Downloading file { $file }
+download-failed = Could not download { $file }: { $error }
+icon-failed = Could not download the icon of { $name }: { $error }
+download-progress = { $total ->
+    [one] Downloaded { $done } of { $total } file
+   *[other] Downloaded { $done } of { $total } files
+}{ $failed ->
+    [0] {""}
+   *[other] , { $failed } failed
+}
+downloads-failed = Failed to download { $count } of { $total } files:
+retry-downloads = Run `mcmod resume` to retry the failed downloads, finished downloads are kept
+skipping-shader = Skipping { $name }, no shader loader can load it
+installing = Installing { $path }
+installed-into = { $count ->
+    [one] Installed { $count } file into { $path }
+   *[other] Installed { $count } files into { $path }
+}
+install-failed = Failed to install into { $path }: { $error }
+next-install-failed = Close the game if it is running, then run `mcmod resume` to retry the install and `mcmod verify` to check the installed files
+summary = Done: { $resolved } resolved, { $downloaded } downloaded, { $cached } cached, { $installed } installed, { $skipped } skipped, { $failed } failed
+next-steps = Next steps:
+world-too-old = Warning: { $world } was last played on { $world_version }, open it in { $game_version } before the data packs can be read
+before-data-versions = a version before 1.9
+kept-settings = Moved the settings { $from } to { $to }
+index-title = Installed projects
+wrote-index = Wrote the list of installed projects to { $path }
+dev-installed = Installed { $path }
+dev-watching = Watching { $count } jars for changes, press Ctrl-C to stop
+copied-overrides = Copied { $count } files from { $path }, { $unchanged } already up to date
+pruned = Removed { $path }, it is no longer resolved
+pruned-kept = Kept { $path }, it is no longer resolved but was modified
+rolling-back = { $count ->
+    [one] Undoing { $count } file added or replaced by this install
+   *[other] Undoing { $count } files added or replaced by this install
+}
+interrupted = Interrupted. Run `mcmod resume` to continue, finished downloads are kept
+
+## Commands
+
+removed-version = Removed cached version { $version }
+removed-count = { $count ->
+    [one] Removed { $count } cached version
+   *[other] Removed { $count } cached versions
+}
+wrote-template = Wrote { $path } from template { $template }
+wrote-plan = { $count ->
+    [one] Wrote { $path } with { $count } change
+   *[other] Wrote { $path } with { $count } changes
+}
+promoted = { $count ->
+    [one] Promoted { $from } to { $to } with { $count } change
+   *[other] Promoted { $from } to { $to } with { $count } changes
+}
+wrote-properties = Updated { $path }
+accepted-eula = Accepted the Minecraft EULA ({ $url })
+eula-not-accepted = The server will not start until the Minecraft EULA ({ $url }) is accepted, use --accept-eula to accept it
+adopted-config = { $count ->
+    [one] Wrote { $path } with { $count } project adopted from { $origin }
+   *[other] Wrote { $path } with { $count } projects adopted from { $origin }
+}
+adopt-skipped = Skipped { $entry }, which is not on Modrinth
+already-configured = { $name } is already in the config
+added-project = Added { $name } to { $path }
+not-configured = { $name } is not in the config
+removed-project = Removed { $name } from { $path }
+removed-dependency = Removed { $name }, which no other project needs
+changelog-failed = Could not get the changelog of { $name }: { $error }
+verified = Verified { $count } files, { $problems } problems
+verify-failed = Failure to verify files: { $error }
+no-advisories = No advisory feeds are configured in security.advisories
+audited = { $affected ->
+    [0] Audited { $count } projects and files, none are affected by an advisory
+    [one] Audited { $count } projects and files, 1 is affected by an advisory
+   *[other] Audited { $count } projects and files, { $affected } are affected by an advisory
+}
+audit-failed = Failure to audit: { $error }
+stale-project = Not updated since { $updated }, more than { $age } ago, so it may be abandoned
+unknown-hash = No allowlist or reputation service knows { $file }
+curseforge-no-key = Set { $var } to find files on CurseForge, until then no file is found there
+curseforge-exported = Exported { $path } with { $referenced } files from CurseForge and { $unmatched } not found there
+curseforge-bundled = Not found on CurseForge, bundled in the overrides: { $files }. Check that their licenses allow redistribution.
+curseforge-left-out = Not found on CurseForge, left out of the pack: { $files }. Players must add them, or pass --bundle-unmatched to bundle them in the overrides if their licenses allow redistribution.
+
+## Listing
+
+column-project = Project
+column-version = Version
+column-file = File
+column-reason = Reason
+column-published = Published
+column-loader = Loader
+column-directory = Directory
+column-state = State
+column-downloads = Downloads
+column-followers = Followers
+column-updated = Updated
+column-note = Note
+state-installed = installed
+state-cached = cached
+state-missing = missing
+state-unresolved = unresolved
+reason-configured = configured
+reason-optional = optional
+reason-pinned = pinned
+reason-dependency = dependency of { $dependent }
+
+## Prompts
+
+multiple-roots = Found multiple data directories for { $launcher }
+not-interactive = Not interactive, using { $choice }
+not-interactive-defaults = Not interactive, using the defaults
+choose-one = Choose [1-{ $count }]:{" "}
+choose-any = Choose any of [1-{ $count }], or - for none:{" "}
+no-search-results = No projects match "{ $query }"
+invalid-choice = Invalid choice "{ $choice }"
+remembered-answer = { $question } { $choice }, as answered before. Use --reconfigure to answer again
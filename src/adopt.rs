@@ -0,0 +1,736 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::launcher::Launcher;
+use crate::lockfile::{LockedFile, LockedProject, Lockfile};
+use crate::types::{MinecraftVersion, ModLoader, Registry};
+
+/// Enumeration of the tools whose mod lists can be adopted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum AdoptSource {
+    /// Ferium, a command line mod manager storing its profiles as JSON
+    Ferium,
+    /// An ATLauncher instance
+    #[strum(to_string = "atlauncher")]
+    #[value(name = "atlauncher")]
+    ATLauncher,
+    /// A Modrinth App profile
+    ModrinthApp,
+}
+
+impl AdoptSource {
+    /// Get the launcher whose instances are adopted, if any
+    fn launcher(&self) -> Option<Launcher> {
+        match self {
+            AdoptSource::Ferium => None,
+            AdoptSource::ATLauncher => Some(Launcher::ATLauncher),
+            AdoptSource::ModrinthApp => Some(Launcher::ModrinthApp),
+        }
+    }
+
+    /// Find what to read when no path is given: the file the tool keeps its mod lists in, or the
+    /// directory of the named instance, which may be left out when the launcher has only one
+    pub fn locate(&self, profile: Option<&str>) -> Result<PathBuf> {
+        match self.launcher() {
+            Some(launcher) => launcher.find_instance(profile),
+            None => std::env::var_os("FERIUM_CONFIG_FILE")
+                .map(PathBuf::from)
+                .or_else(|| {
+                    dirs::home_dir().map(|x| x.join(".config").join("ferium").join("config.json"))
+                })
+                .ok_or_else(|| Error::AdoptFailed("No home directory to find Ferium in".into())),
+        }
+    }
+
+    /// Read the mod list of a Ferium profile from its file, or of a launcher instance from its
+    /// directory
+    pub fn load(&self, path: &Path, profile: Option<&str>) -> Result<Adopted> {
+        match self {
+            AdoptSource::Ferium => from_ferium(&std::fs::read_to_string(path)?, profile),
+            AdoptSource::ATLauncher => from_atlauncher(path),
+            AdoptSource::ModrinthApp => from_modrinth_app(path),
+        }
+    }
+}
+
+/// A project adopted from another tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdoptedProject {
+    /// The Modrinth project id or slug
+    pub key: String,
+    /// Whether the key is already the slug, so it does not need to be looked up
+    pub is_slug: bool,
+    /// The loader of the project, when it is not the loader of the pack, like for shaders
+    pub loader: Option<ModLoader>,
+    /// Why the project is in the config, like the version it was pinned to
+    pub note: Option<String>,
+}
+
+impl AdoptedProject {
+    /// Construct a project by its Modrinth id
+    fn from_id(id: &str, note: Option<String>) -> Self {
+        Self {
+            key: id.into(),
+            is_slug: false,
+            loader: None,
+            note,
+        }
+    }
+}
+
+/// The mod list of another tool, to be written as a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adopted {
+    /// What the mod list was adopted from, like `the Ferium profile "Default"`
+    pub origin: String,
+    pub game_version: Option<MinecraftVersion>,
+    pub loader: Option<ModLoader>,
+    /// The .minecraft directory the tool installed into
+    pub dot_minecraft: Option<PathBuf>,
+    /// The launcher instance the tool installed into, targeted instead of a directory
+    pub instance: Option<(Launcher, String)>,
+    pub projects: Vec<AdoptedProject>,
+    /// The versions installed in the instance, written as the lockfile
+    pub locked: Vec<LockedProject>,
+    /// The entries that were not adopted and why, described for the user
+    pub skipped: Vec<String>,
+}
+
+/// Quote a TOML key, unless it is a bare key
+fn toml_key(key: &str) -> String {
+    match key
+        .chars()
+        .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
+    {
+        true => key.to_string(),
+        false => toml_string(key),
+    }
+}
+
+/// Quote a TOML string
+fn toml_string(text: &str) -> String {
+    toml::Value::String(text.to_string()).to_string()
+}
+
+impl Adopted {
+    /// Replace the project ids with slugs, so the config is readable. Projects that can not be
+    /// looked up keep their id, which resolves just the same.
+    pub fn use_slugs(&mut self, client: &labrinth::Client) {
+        for project in self.projects.iter_mut().filter(|x| !x.is_slug) {
+            if let Ok(x) = client.get_project(&project.key) {
+                project.key = x.slug.to_string();
+            }
+        }
+    }
+
+    /// Write the config, with the game version and loader given when the mod list has none.
+    /// Entries that were skipped are listed in a comment at the end.
+    pub fn to_toml(
+        &self,
+        game_version: Option<MinecraftVersion>,
+        loader: Option<ModLoader>,
+    ) -> Result<String> {
+        let game_version = game_version.or(self.game_version).ok_or_else(|| {
+            Error::AdoptFailed(format!(
+                "{} has no game version, use --game-version",
+                self.origin
+            ))
+        })?;
+        let loader = loader.or(self.loader).ok_or_else(|| {
+            Error::AdoptFailed(format!("{} has no loader, use --loader", self.origin))
+        })?;
+        let mut text = format!("# Adopted from {}\n\n", self.origin);
+        text.push_str("[defaults]\n");
+        text.push_str(&format!("game_version = \"{game_version}\"\n"));
+        text.push_str(&format!("loader = \"{loader}\"\n"));
+        if let Some((launcher, instance)) = &self.instance {
+            text.push_str("\n[paths]\n");
+            text.push_str(&format!("launcher = \"{launcher}\"\n"));
+            text.push_str(&format!("instance = {}\n", toml_string(instance)));
+        } else if let Some(dir) = &self.dot_minecraft {
+            text.push_str("\n[paths]\n");
+            text.push_str(&format!(
+                "dot_minecraft = {}\n",
+                toml_string(&dir.to_string_lossy())
+            ));
+        }
+        text.push_str("\n[projects]\n");
+        for project in &self.projects {
+            let fields = [
+                project.loader.map(|x| format!("loader = \"{x}\"")),
+                project
+                    .note
+                    .as_ref()
+                    .map(|x| format!("note = {}", toml_string(x))),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            let value = match fields.is_empty() {
+                true => "{}".into(),
+                false => format!("{{ {} }}", fields.join(", ")),
+            };
+            text.push_str(&format!("{} = {value}\n", toml_key(&project.key)));
+        }
+        if !self.skipped.is_empty() {
+            text.push_str("\n# Not adopted:\n");
+            for entry in &self.skipped {
+                text.push_str(&format!("# - {entry}\n"));
+            }
+        }
+        Ok(text)
+    }
+
+    /// Get the lockfile of the versions installed in the instance, if they are known
+    pub fn lockfile(&self) -> Option<Lockfile> {
+        if self.locked.is_empty() {
+            return None;
+        }
+        let mut projects = self.locked.clone();
+        projects.sort_by(|l, r| l.slug.cmp(&r.slug));
+        Some(Lockfile {
+            config_hash: None,
+            projects,
+        })
+    }
+}
+
+/// The config file of Ferium
+#[derive(serde::Deserialize)]
+struct FeriumConfig {
+    #[serde(default)]
+    active_profile: usize,
+    #[serde(default)]
+    profiles: Vec<FeriumProfile>,
+}
+
+/// A Ferium profile. Older versions of Ferium give the game version and loader directly, and
+/// newer versions give them as filters.
+#[derive(serde::Deserialize)]
+struct FeriumProfile {
+    name: String,
+    #[serde(default)]
+    output_dir: Option<PathBuf>,
+    #[serde(default)]
+    game_version: Option<String>,
+    #[serde(default)]
+    mod_loader: Option<String>,
+    #[serde(default)]
+    filters: Vec<serde_json::Value>,
+    #[serde(default)]
+    mods: Vec<FeriumMod>,
+}
+
+/// A mod of a Ferium profile
+#[derive(serde::Deserialize)]
+struct FeriumMod {
+    name: String,
+    identifier: FeriumIdentifier,
+}
+
+/// Where Ferium finds a mod
+#[derive(serde::Deserialize)]
+enum FeriumIdentifier {
+    ModrinthProject(String),
+    PinnedModrinthProject(String, String),
+    CurseForgeProject(i64),
+    PinnedCurseForgeProject(i64, serde::de::IgnoredAny),
+    GitHubRepository(String, String),
+    PinnedGitHubRepository((String, String), serde::de::IgnoredAny),
+}
+
+impl FeriumProfile {
+    /// Get the first value of a filter, like the game version of `GameVersionStrict`
+    fn filter(&self, names: &[&str]) -> Option<String> {
+        self.filters.iter().find_map(|filter| {
+            names
+                .iter()
+                .find_map(|x| filter.get(x))
+                .and_then(|x| x.get(0))
+                .and_then(|x| x.as_str())
+                .map(str::to_string)
+        })
+    }
+}
+
+/// Adopt a Ferium profile by name, or else the active profile
+pub fn from_ferium(text: &str, profile: Option<&str>) -> Result<Adopted> {
+    let config = serde_json::from_str::<FeriumConfig>(text)?;
+    let available = config.profiles.iter().map(|x| x.name.clone()).collect();
+    let found = match profile {
+        Some(name) => config.profiles.iter().find(|x| x.name == name),
+        None => config.profiles.get(config.active_profile),
+    };
+    let Some(profile) = found else {
+        return Err(Error::UnknownProfile {
+            name: profile.unwrap_or_default().into(),
+            available,
+        });
+    };
+    let game_version = profile
+        .game_version
+        .clone()
+        .or_else(|| profile.filter(&["GameVersionStrict", "GameVersionMinor"]))
+        .map(MinecraftVersion::try_from)
+        .transpose()?;
+    let loader = profile
+        .mod_loader
+        .clone()
+        .or_else(|| profile.filter(&["ModLoaderPrefer", "ModLoaderAny"]))
+        .map(|x| x.to_lowercase().parse::<ModLoader>())
+        .transpose()?;
+    let dot_minecraft = profile
+        .output_dir
+        .as_ref()
+        .filter(|x| x.file_name().is_some_and(|x| x == "mods"))
+        .and_then(|x| x.parent())
+        .map(Path::to_path_buf);
+    let mut projects = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in &profile.mods {
+        match &entry.identifier {
+            FeriumIdentifier::ModrinthProject(id) => {
+                projects.push(AdoptedProject::from_id(id, None))
+            }
+            FeriumIdentifier::PinnedModrinthProject(id, version) => projects.push(
+                AdoptedProject::from_id(id, Some(format!("Pinned to version {version} in Ferium"))),
+            ),
+            FeriumIdentifier::CurseForgeProject(id)
+            | FeriumIdentifier::PinnedCurseForgeProject(id, _) => skipped.push(format!(
+                "{}, CurseForge project {id}, which is not on Modrinth",
+                entry.name
+            )),
+            FeriumIdentifier::GitHubRepository(owner, repo)
+            | FeriumIdentifier::PinnedGitHubRepository((owner, repo), _) => skipped.push(format!(
+                "{}, GitHub repository {owner}/{repo}, which is not on Modrinth",
+                entry.name
+            )),
+        }
+    }
+    Ok(Adopted {
+        origin: format!("the Ferium profile {:?}", profile.name),
+        game_version,
+        loader,
+        dot_minecraft,
+        instance: None,
+        projects,
+        locked: vec![],
+        skipped,
+    })
+}
+
+/// A Modrinth project as launchers record it
+#[derive(serde::Deserialize)]
+struct ModrinthProject {
+    id: String,
+    slug: String,
+    title: String,
+    #[serde(default)]
+    icon_url: Option<String>,
+}
+
+/// A Modrinth version as launchers record it
+#[derive(serde::Deserialize)]
+struct ModrinthVersion {
+    id: String,
+    version_number: String,
+    #[serde(default)]
+    loaders: Vec<String>,
+    #[serde(default)]
+    files: Vec<ModrinthFile>,
+}
+
+/// A file of a Modrinth version
+#[derive(serde::Deserialize)]
+struct ModrinthFile {
+    url: String,
+    filename: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    hashes: std::collections::BTreeMap<String, String>,
+}
+
+/// Adopt a Modrinth project installed in an instance, locking the installed version. Projects
+/// whose versions are not for the loader of the pack, like shaders and resource packs, keep the
+/// loader of their version.
+fn adopt_modrinth(
+    pack_loader: Option<ModLoader>,
+    project: ModrinthProject,
+    version: ModrinthVersion,
+) -> (AdoptedProject, LockedProject) {
+    let loaders = version
+        .loaders
+        .iter()
+        .filter_map(|x| x.parse::<ModLoader>().ok())
+        .collect::<Vec<_>>();
+    let loader = match pack_loader {
+        Some(x) if loaders.contains(&x) => None,
+        _ => loaders.first().copied(),
+    };
+    let adopted = AdoptedProject {
+        key: project.slug.clone(),
+        is_slug: true,
+        loader,
+        note: None,
+    };
+    let locked = LockedProject {
+        project_id: project.id,
+        slug: project.slug,
+        name: project.title,
+        version_id: version.id,
+        version_number: version.version_number,
+        loader: loader.or(pack_loader),
+        license: None,
+        registry: Registry::default().to_string(),
+        reasons: vec!["configured".into()],
+        note: None,
+        icon_url: project.icon_url,
+        files: version
+            .files
+            .into_iter()
+            .map(|x| LockedFile {
+                name: x.filename,
+                url: x.url,
+                size: x.size,
+                hashes: x.hashes,
+            })
+            .collect(),
+    };
+    (adopted, locked)
+}
+
+/// Get the name of an instance from its directory
+fn instance_name(instance_dir: &Path) -> String {
+    instance_dir
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// The `instance.json` of an ATLauncher instance. The game version is the id of the Minecraft
+/// version it extends.
+#[derive(serde::Deserialize)]
+struct ATLauncherInstance {
+    #[serde(alias = "minecraftVersion")]
+    id: Option<String>,
+    launcher: ATLauncherSettings,
+}
+
+/// The launcher settings of an ATLauncher instance
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ATLauncherSettings {
+    #[serde(default)]
+    loader_version: Option<ATLauncherLoader>,
+    #[serde(default)]
+    mods: Vec<ATLauncherMod>,
+}
+
+/// The loader of an ATLauncher instance
+#[derive(serde::Deserialize)]
+struct ATLauncherLoader {
+    #[serde(rename = "type")]
+    loader_type: String,
+}
+
+/// A mod installed in an ATLauncher instance
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ATLauncherMod {
+    name: String,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    modrinth_project: Option<ModrinthProject>,
+    #[serde(default)]
+    modrinth_version: Option<ModrinthVersion>,
+    #[serde(default)]
+    curse_forge_project_id: Option<i64>,
+}
+
+/// Adopt an ATLauncher instance from its `instance.json`
+pub fn from_atlauncher(instance_dir: &Path) -> Result<Adopted> {
+    let path = instance_dir.join("instance.json");
+    let instance = serde_json::from_str::<ATLauncherInstance>(&std::fs::read_to_string(&path)?)?;
+    let name = instance_name(instance_dir);
+    let loader = instance
+        .launcher
+        .loader_version
+        .map(|x| x.loader_type.to_lowercase().parse::<ModLoader>())
+        .transpose()?;
+    let mut adopted = Adopted {
+        origin: format!("the ATLauncher instance {name:?}"),
+        game_version: instance.id.map(MinecraftVersion::try_from).transpose()?,
+        loader,
+        dot_minecraft: None,
+        instance: Some((Launcher::ATLauncher, name)),
+        projects: vec![],
+        locked: vec![],
+        skipped: vec![],
+    };
+    for entry in instance.launcher.mods {
+        match (entry.modrinth_project, entry.modrinth_version) {
+            _ if entry.disabled => adopted
+                .skipped
+                .push(format!("{}, which is disabled", entry.name)),
+            (Some(project), Some(version)) => {
+                let (project, locked) = adopt_modrinth(loader, project, version);
+                adopted.projects.push(project);
+                adopted.locked.push(locked);
+            }
+            _ => adopted.skipped.push(match entry.curse_forge_project_id {
+                Some(id) => format!(
+                    "{}, CurseForge project {id}, which is not on Modrinth",
+                    entry.name
+                ),
+                None => format!("{}, which was not installed from Modrinth", entry.name),
+            }),
+        }
+    }
+    Ok(adopted)
+}
+
+/// The `profile.json` of a Modrinth App profile
+#[derive(serde::Deserialize)]
+struct ModrinthAppProfile {
+    metadata: ModrinthAppMetadata,
+    #[serde(default)]
+    projects: std::collections::BTreeMap<String, ModrinthAppProject>,
+}
+
+/// The game version and loader of a Modrinth App profile
+#[derive(serde::Deserialize)]
+struct ModrinthAppMetadata {
+    game_version: String,
+    loader: String,
+}
+
+/// A file installed in a Modrinth App profile, by its path in the profile
+#[derive(serde::Deserialize)]
+struct ModrinthAppProject {
+    #[serde(default)]
+    disabled: bool,
+    metadata: ModrinthAppProjectMetadata,
+}
+
+/// Where a file of a Modrinth App profile came from
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ModrinthAppProjectMetadata {
+    Modrinth {
+        project: Box<ModrinthProject>,
+        version: Box<ModrinthVersion>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+/// Adopt a Modrinth App profile from its `profile.json`. Newer versions of the app keep their
+/// profiles in a database instead, which can not be adopted.
+pub fn from_modrinth_app(profile_dir: &Path) -> Result<Adopted> {
+    let path = profile_dir.join("profile.json");
+    if !path.is_file() {
+        return Err(Error::AdoptFailed(format!(
+            "{} has no profile.json, newer versions of the Modrinth App keep profiles in a \
+            database that can not be read",
+            profile_dir.display()
+        )));
+    }
+    let profile = serde_json::from_str::<ModrinthAppProfile>(&std::fs::read_to_string(&path)?)?;
+    let name = instance_name(profile_dir);
+    let loader = match profile.metadata.loader.as_str() {
+        "vanilla" => None,
+        x => Some(x.parse::<ModLoader>()?),
+    };
+    let mut adopted = Adopted {
+        origin: format!("the Modrinth App profile {name:?}"),
+        game_version: Some(MinecraftVersion::try_from(profile.metadata.game_version)?),
+        loader,
+        dot_minecraft: None,
+        instance: Some((Launcher::ModrinthApp, name)),
+        projects: vec![],
+        locked: vec![],
+        skipped: vec![],
+    };
+    for (file, entry) in profile.projects {
+        match entry.metadata {
+            _ if entry.disabled => adopted.skipped.push(format!("{file}, which is disabled")),
+            ModrinthAppProjectMetadata::Modrinth { project, version } => {
+                let (project, locked) = adopt_modrinth(loader, *project, *version);
+                adopted.projects.push(project);
+                adopted.locked.push(locked);
+            }
+            ModrinthAppProjectMetadata::Unknown => adopted
+                .skipped
+                .push(format!("{file}, which was not installed from Modrinth")),
+        }
+    }
+    Ok(adopted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const FERIUM: &str = r#"{
+        "active_profile": 1,
+        "profiles": [
+            {"name": "Old", "game_version": "1.20.1", "mod_loader": "Forge", "mods": []},
+            {
+                "name": "Default",
+                "output_dir": "/home/steve/.minecraft/mods",
+                "filters": [{"ModLoaderPrefer": ["Fabric", "Quilt"]}, {"GameVersionStrict": ["1.21.1"]}],
+                "mods": [
+                    {"name": "Sodium", "identifier": {"ModrinthProject": "AANobbMI"}},
+                    {"name": "Iris", "identifier": {"PinnedModrinthProject": ["YL57xq9U", "abc"]}},
+                    {"name": "JEI", "identifier": {"CurseForgeProject": 238222}},
+                    {"name": "Flashback", "identifier": {"GitHubRepository": ["Moulberry", "Flashback"]}}
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_from_ferium() {
+        let adopted = from_ferium(FERIUM, None).expect("Ferium profile shall be adopted");
+        assert_eq!(adopted.loader, Some(ModLoader::Fabric));
+        assert_eq!(adopted.game_version, Some("1.21.1".into()));
+        assert_eq!(
+            adopted.dot_minecraft,
+            Some(PathBuf::from("/home/steve/.minecraft")),
+            "The .minecraft directory shall be taken from the mods directory"
+        );
+        assert_eq!(adopted.projects.len(), 2);
+        assert_eq!(
+            adopted.skipped.len(),
+            2,
+            "Entries not on Modrinth shall be skipped"
+        );
+        let text = adopted
+            .to_toml(None, None)
+            .expect("Adopted profile shall be written");
+        let config = Config::loads(&text).expect("Adopted config shall load");
+        assert_eq!(config.projects().len(), 2);
+        assert!(text.contains("# - JEI, CurseForge project 238222, which is not on Modrinth"));
+        let old = from_ferium(FERIUM, Some("Old")).expect("Profiles shall be chosen by name");
+        assert_eq!(old.loader, Some(ModLoader::Forge));
+        assert!(
+            matches!(
+                from_ferium(FERIUM, Some("Missing")),
+                Err(Error::UnknownProfile { .. })
+            ),
+            "Adopting a missing profile shall fail"
+        );
+        let empty = from_ferium(r#"{"profiles": [{"name": "Empty"}]}"#, None)
+            .expect("Profiles without filters shall be adopted");
+        assert!(
+            matches!(empty.to_toml(None, None), Err(Error::AdoptFailed(_))),
+            "A config shall not be written without a game version"
+        );
+        empty
+            .to_toml(Some("1.21.1".into()), Some(ModLoader::Fabric))
+            .expect("A given game version and loader shall be used");
+    }
+
+    /// A Modrinth project and version as launchers record them
+    fn modrinth(slug: &str, loaders: &str) -> String {
+        format!(
+            r#""project": {{"id": "{slug}-id", "slug": "{slug}", "title": "{slug}"}},
+            "version": {{"id": "{slug}-v", "version_number": "1.0", "loaders": [{loaders}],
+                "files": [{{"url": "https://cdn.modrinth.com/{slug}.jar", "filename": "{slug}.jar",
+                    "size": 10, "hashes": {{"sha1": "aa"}}}}]}}"#
+        )
+    }
+
+    fn write_instance(dir: &Path, file: &str, text: &str) {
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).expect("Failure to create test path");
+        std::fs::write(dir.join(file), text).expect("Failure to write test manifest");
+    }
+
+    #[test]
+    fn test_from_atlauncher() {
+        let dir = PathBuf::from(".test/adopt/atlauncher/instances/Fabric Pack");
+        let atlauncher = |slug: &str, loaders: &str| {
+            modrinth(slug, loaders)
+                .replace("\"project\"", "\"modrinthProject\"")
+                .replace("\"version\"", "\"modrinthVersion\"")
+        };
+        let sodium = atlauncher("sodium", r#""fabric", "quilt""#);
+        let complementary = atlauncher("complementary", r#""iris", "optifine""#);
+        write_instance(
+            &dir,
+            "instance.json",
+            &format!(
+                r#"{{"id": "1.21.1", "launcher": {{"loaderVersion": {{"type": "Fabric"}}, "mods": [
+                    {{"name": "Sodium", {sodium}}},
+                    {{"name": "Complementary", {complementary}}},
+                    {{"name": "JEI", "curseForgeProjectId": 238222}},
+                    {{"name": "Old", "disabled": true}}
+                ]}}}}"#
+            ),
+        );
+        let adopted = from_atlauncher(&dir).expect("ATLauncher instance shall be adopted");
+        assert_eq!(
+            adopted.instance,
+            Some((Launcher::ATLauncher, "Fabric Pack".into())),
+            "The config shall target the instance"
+        );
+        assert_eq!(adopted.loader, Some(ModLoader::Fabric));
+        assert_eq!(adopted.skipped.len(), 2);
+        assert_eq!(
+            adopted.projects[1].loader,
+            Some(ModLoader::Iris),
+            "Projects for another loader shall keep the loader of their version"
+        );
+        let text = adopted
+            .to_toml(None, None)
+            .expect("Adopted instance shall be written");
+        assert!(text.contains("launcher = \"atlauncher\"\ninstance = \"Fabric Pack\""));
+        assert!(text.contains("complementary = { loader = \"iris\" }"));
+        let config = Config::loads(&text).expect("Adopted config shall load");
+        assert_eq!(config.projects().len(), 2);
+        let lock = adopted
+            .lockfile()
+            .expect("Installed versions shall be locked");
+        assert_eq!(lock.projects[1].slug, "sodium");
+        assert_eq!(lock.projects[1].version_id, "sodium-v");
+        assert_eq!(lock.projects[1].loader, Some(ModLoader::Fabric));
+        assert_eq!(lock.projects[1].files[0].name, "sodium.jar");
+    }
+
+    #[test]
+    fn test_from_modrinth_app() {
+        let dir = PathBuf::from(".test/adopt/modrinth/profiles/Quilt");
+        write_instance(
+            &dir,
+            "profile.json",
+            &format!(
+                r#"{{"metadata": {{"name": "Quilt", "game_version": "1.20.1", "loader": "quilt"}},
+                "projects": {{
+                    "mods/sodium.jar": {{"disabled": false, "metadata": {{"type": "modrinth", {}}}}},
+                    "mods/local.jar": {{"metadata": {{"type": "unknown"}}}}
+                }}}}"#,
+                modrinth("sodium", r#""fabric", "quilt""#)
+            ),
+        );
+        let adopted = from_modrinth_app(&dir).expect("Modrinth App profile shall be adopted");
+        assert_eq!(adopted.loader, Some(ModLoader::Quilt));
+        assert_eq!(adopted.game_version, Some("1.20.1".into()));
+        assert_eq!(adopted.projects[0].key, "sodium");
+        assert_eq!(adopted.projects[0].loader, None);
+        assert_eq!(
+            adopted.skipped,
+            vec!["mods/local.jar, which was not installed from Modrinth"]
+        );
+        std::fs::remove_file(dir.join("profile.json")).expect("Failure to remove test manifest");
+        assert!(
+            matches!(from_modrinth_app(&dir), Err(Error::AdoptFailed(_))),
+            "Profiles without a profile.json shall not be adopted"
+        );
+    }
+}
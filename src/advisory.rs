@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::labrinth;
+use crate::types::ModVersion;
+use crate::verify::FileHashes;
+
+/// The directories of an instance that audit scans for installed files
+const AUDIT_DIRS: [&str; 4] = ["mods", "resourcepacks", "shaderpacks", "datapacks"];
+
+/// A security advisory against projects, versions, or files, like a malware incident
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Advisory {
+    /// The identifier of the advisory, like `fractureiser`
+    pub id: String,
+    /// What the advisory is about
+    pub summary: String,
+    /// The ids of the affected projects
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+    /// The ids of the affected versions
+    #[serde(default)]
+    pub version_ids: Vec<String>,
+    /// The sha1 or sha512 hashes of the affected files
+    #[serde(default)]
+    pub hashes: Vec<String>,
+}
+
+impl Advisory {
+    /// Check if the advisory lists any of the hashes of a file
+    fn lists_hash<'a>(&self, mut hashes: impl Iterator<Item = &'a String>) -> bool {
+        hashes.any(|x| self.hashes.iter().any(|y| y.eq_ignore_ascii_case(x)))
+    }
+}
+
+/// The advisories of the feeds in `security.advisories`
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Advisories {
+    pub advisories: Vec<Advisory>,
+}
+
+/// Get the path a feed fetched from a url is kept at, to be used when it can not be fetched
+fn feed_cache_path(data_dir: &Path, url: &str) -> PathBuf {
+    let name = url
+        .chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect::<String>();
+    data_dir.join("advisories").join(format!("{name}.json"))
+}
+
+impl Advisories {
+    /// Load the advisories of feeds, each a url or a path. A feed fetched from a url is kept in the
+    /// data directory, and the kept copy is used if it can not be fetched again. A feed that can
+    /// not be loaded at all is an error, so a blocklist is never silently skipped.
+    pub fn load(client: &labrinth::Client, data_dir: &Path, feeds: &[String]) -> Result<Self> {
+        let mut result = Self::default();
+        for feed in feeds {
+            let text = if feed.starts_with("http://") || feed.starts_with("https://") {
+                let path = feed_cache_path(data_dir, feed);
+                match client.download_file(feed) {
+                    Ok(bytes) => {
+                        std::fs::create_dir_all(path.parent().expect("Feed path has a parent"))?;
+                        std::fs::write(&path, &bytes)?;
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    }
+                    Err(e) => std::fs::read_to_string(&path).map_err(|_| e)?,
+                }
+            } else {
+                std::fs::read_to_string(feed)?
+            };
+            let mut feed = serde_json::from_str::<Self>(&text)?;
+            result.advisories.append(&mut feed.advisories);
+        }
+        Ok(result)
+    }
+
+    /// Get the advisories against a version, its project, or any of its files
+    pub fn matching_version(&self, version: &ModVersion) -> Vec<&Advisory> {
+        self.advisories
+            .iter()
+            .filter(|x| {
+                x.project_ids.contains(&version.project_id.to_string())
+                    || x.version_ids.contains(&version.version_id.to_string())
+                    || version
+                        .files
+                        .iter()
+                        .any(|file| x.lists_hash(file.hashes.values()))
+            })
+            .collect()
+    }
+
+    /// Get the advisories against a file by its hashes
+    pub fn matching_file(&self, hashes: &FileHashes) -> Vec<&Advisory> {
+        self.advisories
+            .iter()
+            .filter(|x| x.lists_hash([&hashes.sha1, &hashes.sha512].into_iter()))
+            .collect()
+    }
+}
+
+/// List the files audit scans in an instance, in the directories that hold installed content
+pub fn audit_files(instance: &Path) -> Result<Vec<PathBuf>> {
+    let mut result = Vec::new();
+    let mut pending = AUDIT_DIRS
+        .iter()
+        .map(|x| instance.join(x))
+        .filter(|x| x.is_dir())
+        .collect::<Vec<_>>();
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                result.push(path);
+            }
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ModFile;
+    use crate::verify::hash_bytes;
+
+    #[test]
+    fn test_advisories() {
+        let dir = Path::new(".test/advisory");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir.join("instance/mods/nested")).expect("Failure to create dir");
+        let bad = hash_bytes(b"stage0");
+        let feed = dir.join("feed.json");
+        std::fs::write(
+            &feed,
+            serde_json::json!({"advisories": [
+                {"id": "stage0", "summary": "Malware", "hashes": [bad.sha1.to_uppercase()]},
+                {"id": "taken-over", "summary": "Compromised", "project_ids": ["EVIL"]},
+            ]})
+            .to_string(),
+        )
+        .expect("Failure to write feed");
+        let advisories = Advisories::load(
+            &labrinth::Client::new(),
+            &dir.join("data"),
+            &[feed.display().to_string()],
+        )
+        .expect("Feeds shall load from paths");
+        assert_eq!(advisories.advisories.len(), 2);
+        assert_eq!(
+            advisories.matching_file(&bad)[0].id,
+            "stage0",
+            "Hashes shall match regardless of case"
+        );
+        assert!(advisories.matching_file(&hash_bytes(b"fine")).is_empty());
+        let version = |project_id: &str, sha512: &str| ModVersion {
+            project_id: project_id.to_string().into(),
+            version_id: "V".to_string().into(),
+            name: "X".into(),
+            version_number: "1.0".into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![],
+            files: vec![ModFile {
+                url: String::new(),
+                name: "x.jar".into(),
+                hashes: [("sha512".to_string(), sha512.to_string())].into(),
+                size: 0,
+            }],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        };
+        assert_eq!(
+            advisories.matching_version(&version("EVIL", ""))[0].id,
+            "taken-over"
+        );
+        assert_eq!(
+            advisories
+                .matching_version(&version("GOOD", &bad.sha512))
+                .len(),
+            0,
+            "Only the listed hashes shall match"
+        );
+        assert!(
+            Advisories::load(
+                &labrinth::Client::new(),
+                &dir.join("data"),
+                &[dir.join("missing.json").display().to_string()],
+            )
+            .is_err(),
+            "A feed that can not be loaded shall be an error"
+        );
+        std::fs::write(dir.join("instance/mods/nested/a.jar"), "").expect("Failure to write");
+        std::fs::write(dir.join("instance/options.txt"), "").expect("Failure to write");
+        assert_eq!(
+            audit_files(&dir.join("instance")).expect("Files shall be listed"),
+            vec![dir.join("instance/mods/nested/a.jar")],
+            "Only content directories shall be audited"
+        );
+    }
+}
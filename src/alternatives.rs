@@ -0,0 +1,103 @@
+use crate::labrinth::SearchHit;
+
+/// How many alternatives found by searching are suggested
+pub const MAX_SEARCHED: usize = 3;
+
+/// Projects known to do the same job as others, for loaders or game versions the others do not
+/// support
+const EQUIVALENTS: &[(&str, &[&str])] = &[
+    ("optifine", &["sodium", "iris", "embeddium", "oculus"]),
+    ("optifabric", &["sodium", "iris"]),
+    ("rubidium", &["embeddium", "sodium"]),
+    ("oculus", &["iris"]),
+    ("embeddium", &["sodium"]),
+    ("sodium", &["embeddium"]),
+    ("iris", &["oculus"]),
+    ("phosphor", &["starlight"]),
+    ("okzoomer", &["zoomify"]),
+];
+
+/// Get the projects known to be equivalent to a project, best first
+pub fn equivalents(slug: &str) -> &'static [&'static str] {
+    EQUIVALENTS
+        .iter()
+        .find(|(x, _)| *x == slug)
+        .map_or(&[], |(_, x)| x)
+}
+
+/// Split a name into lowercase words, so names can be compared
+fn words(name: &str) -> Vec<String> {
+    name.split(|x: char| !x.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Rank the projects found by searching for alternatives to a project by how many categories
+/// they share with it, then by how many words their names share with its name. Ties keep the
+/// order of the search. The project itself and excluded slugs are left out.
+pub fn rank(
+    slug: &str,
+    name: &str,
+    categories: &[String],
+    hits: Vec<SearchHit>,
+    exclude: &[&str],
+) -> Vec<SearchHit> {
+    let name = words(name);
+    let mut ranked = hits
+        .into_iter()
+        .filter(|x| x.slug != slug && !exclude.contains(&x.slug.as_str()))
+        .map(|x| {
+            let shared = x
+                .categories
+                .iter()
+                .filter(|c| categories.contains(c))
+                .count();
+            let similar = words(&x.title).iter().filter(|w| name.contains(w)).count();
+            (shared, similar, x)
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|x| std::cmp::Reverse((x.0, x.1)));
+    ranked.into_iter().map(|(_, _, x)| x).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(slug: &str, title: &str, categories: &[&str]) -> SearchHit {
+        SearchHit {
+            project_id: slug.to_uppercase(),
+            slug: slug.into(),
+            title: title.into(),
+            description: String::new(),
+            project_type: "mod".into(),
+            downloads: 0,
+            categories: categories.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(equivalents("optifine")[..2], ["sodium", "iris"]);
+        assert!(equivalents("lithium").is_empty());
+        let ranked = rank(
+            "zoomer",
+            "Zoomer Deluxe",
+            &["utility".into(), "cursed".into()],
+            vec![
+                hit("zoomer", "Zoomer Deluxe", &["utility"]),
+                hit("spyglass", "Better Spyglass", &["utility"]),
+                hit("zoomplus", "Zoomer Plus", &["utility"]),
+                hit("logical-zoom", "Logical Zoom", &["utility", "cursed"]),
+                hit("zoomify", "Zoomify", &["utility"]),
+            ],
+            &["zoomify"],
+        );
+        assert_eq!(
+            ranked.iter().map(|x| x.slug.as_str()).collect::<Vec<_>>(),
+            vec!["logical-zoom", "zoomplus", "spyglass"],
+            "Alternatives shall be ranked by shared categories, then by similar names"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::tags;
+use crate::types::{MinecraftVersion, ModLoader, ModProject, ModVersion};
+
+/// A registry that projects and their versions are resolved from
+pub trait Backend: Send + Sync {
+    /// The name of the registry, as set in `defaults.registry` and recorded in the lockfile
+    fn name(&self) -> &str;
+
+    /// Get a project by its id or slug
+    fn get_project(&self, project: &str) -> Result<ModProject>;
+
+    /// Get a version by its id
+    fn get_version(&self, version: &str) -> Result<ModVersion>;
+
+    /// Get the versions of a project for any of the game versions and loaders
+    fn get_project_versions(
+        &self,
+        project: &str,
+        game_versions: &[MinecraftVersion],
+        loaders: &[ModLoader],
+    ) -> Result<Vec<ModVersion>>;
+
+    /// Get the official list of game versions, if the registry has its own instead of the one
+    /// fetched from the API
+    fn game_versions(&self) -> Option<tags::GameVersions> {
+        None
+    }
+
+    /// Get the table of mod loaders, if the registry has its own instead of the one fetched from
+    /// the API
+    fn loaders(&self) -> Option<tags::LoaderTable> {
+        None
+    }
+}
+
+/// The backends available at runtime, by name. The Modrinth backend is registered unless the
+/// `modrinth` feature is disabled, and more can be registered by embedders.
+#[derive(Default, Clone)]
+pub struct Backends {
+    backends: Vec<Arc<dyn Backend>>,
+}
+
+impl Backends {
+    /// Construct the backends built into this binary, configured by a config
+    pub fn builtin(mod_config: &Config) -> Self {
+        let mut result = Self::builtin_with(mod_config.client());
+        if let Some(x) = &mod_config.registry_fixture {
+            result.register(x.clone());
+        }
+        result
+    }
+
+    /// Construct the backends built into this binary, querying Modrinth with a client
+    #[cfg(feature = "modrinth")]
+    pub fn builtin_with(client: labrinth::Client) -> Self {
+        let mut result = Self::default();
+        result.register(Arc::new(client));
+        result
+    }
+
+    /// Construct the backends built into this binary, which has no Modrinth backend to use the
+    /// client with
+    #[cfg(not(feature = "modrinth"))]
+    pub fn builtin_with(_client: labrinth::Client) -> Self {
+        Self::default()
+    }
+
+    /// Add a backend, replacing any backend with the same name
+    pub fn register(&mut self, backend: Arc<dyn Backend>) {
+        self.backends.retain(|x| x.name() != backend.name());
+        self.backends.push(backend);
+    }
+
+    /// Get the names of the backends
+    pub fn names(&self) -> Vec<String> {
+        self.backends.iter().map(|x| x.name().to_string()).collect()
+    }
+
+    /// Get a backend by name
+    pub fn get(&self, name: &str) -> Result<Arc<dyn Backend>> {
+        self.backends
+            .iter()
+            .find(|x| x.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownBackend {
+                name: name.to_string(),
+                available: self.names(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend that has no projects
+    struct Empty(&'static str);
+
+    impl Backend for Empty {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn get_project(&self, project: &str) -> Result<ModProject> {
+            Err(Error::ProjectNotFound(project.to_string()))
+        }
+
+        fn get_version(&self, version: &str) -> Result<ModVersion> {
+            Err(Error::VersionNotFound {
+                project: version.to_string(),
+            })
+        }
+
+        fn get_project_versions(
+            &self,
+            _project: &str,
+            _game_versions: &[MinecraftVersion],
+            _loaders: &[ModLoader],
+        ) -> Result<Vec<ModVersion>> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn test_backends() {
+        let mut backends = Backends::default();
+        backends.register(Arc::new(Empty("local")));
+        backends.register(Arc::new(Empty("mirror")));
+        backends.register(Arc::new(Empty("local")));
+        assert_eq!(
+            backends.names(),
+            vec!["mirror", "local"],
+            "Registering a backend shall replace one with the same name"
+        );
+        assert_eq!(
+            backends
+                .get("local")
+                .expect("local shall be registered")
+                .name(),
+            "local"
+        );
+        assert!(
+            matches!(
+                backends.get("curseforge"),
+                Err(Error::UnknownBackend { available, .. }) if available.len() == 2
+            ),
+            "Getting a backend that is not registered shall fail"
+        );
+    }
+}
@@ -1,32 +1,254 @@
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::fsutil;
+use crate::hooks::Hooks;
+use crate::i18n::tr;
 use crate::labrinth;
+use crate::output::{self, status};
+use crate::tags;
 use crate::types::*;
+use crate::verify;
+use crate::zipdiff;
+
+/// The smallest zip file that is updated from a previous version instead of downloaded whole
+const MIN_UPDATE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// When a version in the cache was last used, so old versions can be removed
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct CacheEntry {
+    project_id: String,
+    version_id: String,
+    /// Seconds since the unix epoch when the version was last downloaded or installed
+    used: i64,
+}
+
+/// Choose the cached versions to remove, keeping the versions in use and the `keep` most recently
+/// used versions of each project
+fn select_garbage(entries: &[CacheEntry], keep: usize, in_use: &HashSet<&str>) -> Vec<CacheEntry> {
+    let mut by_project = HashMap::<&str, Vec<&CacheEntry>>::new();
+    for entry in entries {
+        by_project
+            .entry(entry.project_id.as_str())
+            .or_default()
+            .push(entry);
+    }
+    let mut result = Vec::<CacheEntry>::new();
+    for mut versions in by_project.into_values() {
+        versions.sort_by_key(|x| std::cmp::Reverse(x.used));
+        let mut kept = 0;
+        for entry in versions {
+            if in_use.contains(entry.version_id.as_str()) {
+                continue;
+            }
+            if kept < keep {
+                kept += 1;
+                continue;
+            }
+            result.push(entry.clone());
+        }
+    }
+    result.sort_by(|l, r| l.version_id.cmp(&r.version_id));
+    result
+}
 
 pub struct ModFileManager {
     data_dir: PathBuf,
-    dot_minecraft_dir: PathBuf,
+    /// The directory files are installed into, or None to only download into the cache, in which
+    /// case install paths are relative to the install directory
+    dot_minecraft_dir: Option<PathBuf>,
     client: labrinth::Client,
+    loaders: tags::LoaderTable,
+    copy_mode: fsutil::CopyMode,
+    shader_loader: Option<ModLoader>,
+    staging: Option<fsutil::StagingDir>,
+    hooks: Option<Arc<Hooks>>,
+    world_dir: Option<PathBuf>,
+    install_names: HashMap<ProjectId, String>,
+    full_hash: bool,
+    max_file_size: Option<u64>,
+    max_file_sizes: HashMap<ProjectId, u64>,
+    download_timeout: Option<std::time::Duration>,
+}
+
+/// Get the path of the sample hash kept next to a cached file
+fn sample_path(path: &std::path::Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sample");
+    path.with_file_name(name)
 }
 
 impl ModFileManager {
-    /// Construct a new mod file manager
-    pub fn new(data_dir: PathBuf, dot_minecraft_dir: PathBuf) -> Self {
+    /// Construct a new mod file manager, downloading with a client. Without an install directory
+    /// files are only downloaded into the cache.
+    pub fn new(
+        data_dir: PathBuf,
+        dot_minecraft_dir: Option<PathBuf>,
+        client: labrinth::Client,
+    ) -> Self {
         if !data_dir.is_dir() {
             std::fs::create_dir(&data_dir)
                 .unwrap_or_else(|e| panic!("{e:?}: Could not create {data_dir:?}"));
         }
-        if !dot_minecraft_dir.is_dir() {
-            panic!("{dot_minecraft_dir:?} does not exist");
+        if let Some(dir) = dot_minecraft_dir.as_ref().filter(|x| !x.is_dir()) {
+            panic!("{dir:?} does not exist");
         }
+        let loaders = tags::LoaderTable::load(&client, &data_dir);
         ModFileManager {
             data_dir,
             dot_minecraft_dir,
-            client: Default::default(),
+            client,
+            loaders,
+            copy_mode: fsutil::CopyMode::default(),
+            shader_loader: None,
+            staging: None,
+            hooks: None,
+            world_dir: None,
+            install_names: HashMap::new(),
+            full_hash: false,
+            max_file_size: None,
+            max_file_sizes: HashMap::new(),
+            download_timeout: None,
+        }
+    }
+
+    /// Set how files are copied from the cache when they are installed
+    pub fn with_copy_mode(mut self, copy_mode: fsutil::CopyMode) -> Self {
+        self.copy_mode = copy_mode;
+        self
+    }
+
+    /// Stage downloads and installs in a staging directory before moving them into place
+    pub fn with_staging(mut self, staging: fsutil::StagingDir) -> Self {
+        self.staging = Some(staging);
+        self
+    }
+
+    /// Let a hook script choose where files are installed
+    pub fn with_hooks(mut self, hooks: Option<Arc<Hooks>>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Install data packs into a world, given relative to the install directory, instead of the
+    /// `datapacks` directory
+    pub fn with_world_dir(mut self, world_dir: Option<PathBuf>) -> Self {
+        self.world_dir = world_dir;
+        self
+    }
+
+    /// Install the first file of projects under a name instead of its own, by project id
+    pub fn with_install_names(mut self, install_names: HashMap<ProjectId, String>) -> Self {
+        self.install_names = install_names;
+        self
+    }
+
+    /// Check cached files against their full hashes before they are used, instead of only their
+    /// size and sample hash
+    pub fn with_full_hash(mut self, full_hash: bool) -> Self {
+        self.full_hash = full_hash;
+        self
+    }
+
+    /// Refuse to download files larger than a size, or than the size set for their project by
+    /// project id
+    pub fn with_max_file_sizes(
+        mut self,
+        max_file_size: Option<u64>,
+        max_file_sizes: HashMap<ProjectId, u64>,
+    ) -> Self {
+        self.max_file_size = max_file_size;
+        self.max_file_sizes = max_file_sizes;
+        self
+    }
+
+    /// Fail downloads of files that take longer than a timeout
+    pub fn with_download_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    /// Get the directory of the world that data packs are installed into, if any
+    pub fn world_dir(&self) -> Option<PathBuf> {
+        self.world_dir.as_ref().map(|x| self.instance_path(x))
+    }
+
+    /// Join a path relative to the install directory onto the install directory, if there is one
+    fn instance_path(&self, path: &std::path::Path) -> PathBuf {
+        match &self.dot_minecraft_dir {
+            Some(x) => x.join(path),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Get the directory under the install directory that files for a loader are installed into
+    fn relative_install_dir(&self, loader: Option<ModLoader>) -> PathBuf {
+        let dir = self.loaders.install_dir(loader);
+        match &self.world_dir {
+            Some(x) if self.content_type(loader) == ProjectType::Datapack => x.join(dir),
+            _ => PathBuf::from(dir),
         }
     }
 
+    /// Set the shader loader that shaders are installed for
+    pub fn with_shader_loader(mut self, shader_loader: Option<ModLoader>) -> Self {
+        self.shader_loader = shader_loader;
+        self
+    }
+
+    /// Get the path of the index of when cached versions were used
+    fn index_path(&self) -> PathBuf {
+        self.data_dir.join("cache_index.json")
+    }
+
+    /// Load the index of cached versions, which is empty if it does not exist
+    fn load_index(&self) -> Vec<CacheEntry> {
+        std::fs::read_to_string(self.index_path())
+            .ok()
+            .and_then(|x| serde_json::from_str(&x).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record that the files of versions were used now
+    pub fn mark_used(&self, versions: &[&ModVersion]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let mut index = self.load_index();
+        for version in versions {
+            index.retain(|x| x.version_id != version.version_id.as_str());
+            index.push(CacheEntry {
+                project_id: version.project_id.to_string(),
+                version_id: version.version_id.to_string(),
+                used: now,
+            });
+        }
+        std::fs::write(self.index_path(), serde_json::to_string(&index)?)?;
+        Ok(())
+    }
+
+    /// Remove the cached files of old versions, keeping the versions in use and the `keep` most
+    /// recently used versions of each project. Versions that were never recorded are kept.
+    /// Returns the ids of the removed versions.
+    pub fn collect_garbage(&self, keep: usize, in_use: &[&VersionId]) -> Result<Vec<String>> {
+        let in_use = in_use.iter().map(|x| x.as_str()).collect::<HashSet<_>>();
+        let mut index = self.load_index();
+        let garbage = select_garbage(&index, keep, &in_use);
+        for entry in &garbage {
+            let version_id = VersionId::from(entry.version_id.clone());
+            let dir = self
+                .data_dir
+                .join(&version_id.as_str()[0..2])
+                .join(&version_id.as_str()[2..]);
+            if dir.is_dir() {
+                std::fs::remove_dir_all(fsutil::long_path(&dir))?;
+            }
+        }
+        index.retain(|x| !garbage.contains(x));
+        std::fs::write(self.index_path(), serde_json::to_string(&index)?)?;
+        Ok(garbage.into_iter().map(|x| x.version_id).collect())
+    }
+
     /// Construct the path to a cached download file
     fn cache_path(&self, version_id: &VersionId, filename: &String) -> PathBuf {
         self.data_dir
@@ -45,50 +267,548 @@ impl ModFileManager {
         if !path.is_file() { None } else { Some(path) }
     }
 
-    /// Download a file to the data cache directory
-    pub fn download_file(&self, version_id: &VersionId, mod_file: &ModFile) -> Result<PathBuf> {
-        let buffer = self.client.download_file(&mod_file.url)?;
-        let path = self.cache_path(version_id, &mod_file.name);
-        std::fs::create_dir_all(
+    /// Check that a cached file was not corrupted since it was downloaded. Its size is compared to
+    /// the size of the file, and its sample hash to the one kept when it was downloaded, or with
+    /// full hash checks its hashes to the hashes of the file.
+    fn is_intact(&self, path: &std::path::Path, mod_file: &ModFile) -> Result<bool> {
+        let size = std::fs::metadata(fsutil::long_path(path))?.len();
+        if mod_file.size > 0 && size != mod_file.size {
+            return Ok(false);
+        }
+        if self.full_hash {
+            return Ok(verify::hash_file(path)?.matches(&mod_file.hashes) != Some(false));
+        }
+        match std::fs::read_to_string(fsutil::long_path(&sample_path(path))) {
+            Ok(sample) => Ok(verify::sample_hash(path)? == sample.trim()),
+            Err(_) => Ok(true),
+        }
+    }
+
+    /// Return the location of a cached download file if it is intact. A corrupted file is
+    /// removed with a warning, so it is downloaded again.
+    pub fn find_intact_file(&self, version_id: &VersionId, mod_file: &ModFile) -> Option<PathBuf> {
+        let path = self.find_file(version_id, &mod_file.name)?;
+        if self.is_intact(&path, mod_file).unwrap_or(false) {
+            return Some(path);
+        }
+        let warning = tr!("cache-corrupt", file = &mod_file.name);
+        status!(
+            "  {}",
+            output::get().paint(output::Style::Warning, &warning)
+        );
+        let _ = std::fs::remove_file(fsutil::long_path(&path));
+        let _ = std::fs::remove_file(fsutil::long_path(&sample_path(&path)));
+        None
+    }
+
+    /// Find the cached zip file of the most recently used other version of a project
+    fn previous_zip(&self, version: &ModVersion) -> Option<PathBuf> {
+        let mut index = self.load_index();
+        index.retain(|x| {
+            x.project_id == version.project_id.as_str()
+                && x.version_id != version.version_id.as_str()
+        });
+        index.sort_by_key(|x| std::cmp::Reverse(x.used));
+        index.iter().find_map(|entry| {
+            let dir = self
+                .data_dir
+                .join(entry.version_id.get(0..2)?)
+                .join(entry.version_id.get(2..)?);
+            std::fs::read_dir(dir).ok()?.find_map(|x| {
+                let path = x.ok()?.path();
+                (path.extension()? == "zip").then_some(path)
+            })
+        })
+    }
+
+    /// Download a large zip file by reusing the unchanged entries of the cached file of a previous
+    /// version of its project, downloading only the changed entries with range requests. Returns
+    /// None if there is no previous file, the server does not support ranges, or the result does
+    /// not match the hashes of the file.
+    fn download_update(&self, version: &ModVersion, mod_file: &ModFile) -> Option<Vec<u8>> {
+        if mod_file.size < MIN_UPDATE_SIZE
+            || !mod_file.name.ends_with(".zip")
+            || mod_file.hashes.is_empty()
+        {
+            return None;
+        }
+        let old = std::fs::read(self.previous_zip(version)?).ok()?;
+        let (bytes, _) = zipdiff::update(&old, mod_file.size, |range| {
+            self.client.download_range(&mod_file.url, range)
+        })?;
+        (verify::hash_bytes(&bytes).matches(&mod_file.hashes) == Some(true)).then_some(bytes)
+    }
+
+    /// Download a file to the data cache directory. Large zip files are updated from the cached
+    /// file of a previous version when possible. Files larger than the size limit of their project
+    /// are refused, before downloading them if their size is known.
+    pub fn download_file(&self, version: &ModVersion, mod_file: &ModFile) -> Result<PathBuf> {
+        let limit = self
+            .max_file_sizes
+            .get(&version.project_id)
+            .copied()
+            .or(self.max_file_size);
+        let too_large = |limit| Error::FileTooLarge {
+            file: mod_file.name.clone(),
+            limit,
+        };
+        if let Some(limit) = limit
+            && mod_file.size > limit
+        {
+            return Err(too_large(limit));
+        }
+        let buffer = match self.download_update(version, mod_file) {
+            Some(x) => x,
+            None => self
+                .client
+                .download_limited(&mod_file.url, limit, self.download_timeout)
+                .map_err(|e| match e {
+                    Error::FileTooLarge { limit, .. } => too_large(limit),
+                    Error::DownloadTimeout { timeout, .. } => Error::DownloadTimeout {
+                        file: mod_file.name.clone(),
+                        timeout,
+                    },
+                    e => e,
+                })?,
+        };
+        if verify::hash_bytes(&buffer).matches(&mod_file.hashes) == Some(false) {
+            return Err(Error::HashMismatch(mod_file.name.clone()));
+        }
+        let path = self.cache_path(&version.version_id, &mod_file.name);
+        std::fs::create_dir_all(fsutil::long_path(
             path.parent()
                 .unwrap_or_else(|| panic!("{path:?} does not have parent")),
-        )?;
-        std::fs::write(&path, buffer)?;
+        ))?;
+        let sample = verify::sample_hash_bytes(&buffer);
+        match &self.staging {
+            Some(staging) => {
+                staging.write(&path, buffer)?;
+                staging.write(&sample_path(&path), sample.into_bytes())?;
+            }
+            None => {
+                std::fs::write(fsutil::long_path(&path), buffer)?;
+                std::fs::write(fsutil::long_path(&sample_path(&path)), sample)?;
+            }
+        }
         Ok(path)
     }
 
-    /// Get a file from the data cache, downloading it if necessary
-    pub fn get_file(&self, version_id: &VersionId, mod_file: &ModFile) -> Result<PathBuf> {
-        if let Some(path) = self.find_file(version_id, &mod_file.name) {
+    /// Get a file from the data cache, downloading it if it is missing or corrupted
+    pub fn get_file(&self, version: &ModVersion, mod_file: &ModFile) -> Result<PathBuf> {
+        if let Some(path) = self.find_intact_file(&version.version_id, mod_file) {
             return Ok(path);
         }
-        self.download_file(version_id, mod_file)
+        self.download_file(version, mod_file)
     }
 
-    fn install_path(&self, filename: &String, loader: Option<ModLoader>) -> PathBuf {
-        self.dot_minecraft_dir
-            .join(match loader {
-                Some(ModLoader::Minecraft) => "resourcepacks",
-                Some(ModLoader::Datapack) => "datapacks",
-                Some(ModLoader::Iris) | Some(ModLoader::Optifine) => "shaderpacks",
-                _ => "mods",
-            })
-            .join(filename)
+    /// Get the kind of content that files for a loader are
+    pub fn content_type(&self, loader: Option<ModLoader>) -> ProjectType {
+        self.loaders.content_type(loader)
+    }
+
+    /// Check if the files of a version can be installed. Shaders can only be installed for a
+    /// shader loader that loads them.
+    pub fn can_install(&self, version: &ModVersion) -> bool {
+        !self.loaders.is_shader(&version.loaders)
+            || self
+                .shader_loader
+                .is_some_and(|x| version.loaders.contains(&x))
     }
 
-    pub fn install_file(
+    /// Get the loader the files of a version are installed for. Shaders are installed for the
+    /// shader loader, and everything else for the loader it was resolved for, or else its first
+    /// loader.
+    pub fn install_loader(
         &self,
-        version_id: &VersionId,
-        mod_file: &ModFile,
-        loader: Option<ModLoader>,
-    ) -> Result<()> {
-        let src = self.get_file(version_id, mod_file)?;
-        let dst = self.install_path(&mod_file.name, loader);
-        std::fs::create_dir_all(
+        version: &ModVersion,
+        resolved: Option<ModLoader>,
+    ) -> Option<ModLoader> {
+        match self.shader_loader {
+            Some(x) if self.loaders.is_shader(&version.loaders) && version.loaders.contains(&x) => {
+                Some(x)
+            }
+            _ => resolved.or(version.loaders.first().copied()),
+        }
+    }
+
+    /// Construct the path a file is installed to
+    pub fn install_path(&self, filename: &String, loader: Option<ModLoader>) -> PathBuf {
+        self.instance_path(&self.relative_install_dir(loader).join(filename))
+    }
+
+    /// Plan where each file of the versions is installed, resolving files of different versions
+    /// that would be installed to the same path with a collision policy. `resolved` tells the
+    /// loader a version was resolved for, `slug` tells the slug of its project for the hook
+    /// script, and `is_pinned` tells if a version was chosen by the config rather than as a
+    /// dependency. The first file of a project with an install name is installed under that name.
+    pub fn plan_installs<'a, L, S, F>(
+        &self,
+        versions: &[&'a ModVersion],
+        policy: CollisionPolicy,
+        resolved: L,
+        slug: S,
+        is_pinned: F,
+    ) -> Result<Vec<Install<'a>>>
+    where
+        L: Fn(&ModVersion) -> Option<ModLoader>,
+        S: Fn(&ModVersion) -> String,
+        F: Fn(&ModVersion) -> bool,
+    {
+        let mut installs = Vec::new();
+        for version in versions {
+            let loader = self.install_loader(version, resolved(version));
+            let install_as = self.install_names.get(&version.project_id);
+            for (index, file) in version.files.iter().enumerate() {
+                let name = match install_as {
+                    Some(x) if index == 0 => x,
+                    _ => &file.name,
+                };
+                let mut path = self.relative_install_dir(loader).join(name);
+                if let Some(hooks) = &self.hooks
+                    && let Some(x) =
+                        hooks.install_path(&slug(version), version, &file.name, &path)?
+                {
+                    path = x;
+                }
+                installs.push(Install {
+                    version,
+                    file,
+                    path: self.instance_path(&path),
+                });
+            }
+        }
+        resolve_collisions(installs, policy, is_pinned)
+    }
+
+    /// Install a file from the cache, downloading it if necessary
+    pub fn install_file(&self, install: &Install) -> Result<()> {
+        let src = self.get_file(install.version, install.file)?;
+        let dst = &install.path;
+        std::fs::create_dir_all(fsutil::long_path(
             dst.parent()
                 .unwrap_or_else(|| panic!("{dst:?} does not have parent")),
-        )?;
-        std::fs::copy(src, dst)?;
-        Ok(())
+        ))?;
+        match &self.staging {
+            Some(staging) => staging.copy(&src, dst, self.copy_mode),
+            None => fsutil::copy_file(&src, dst, self.copy_mode),
+        }
+    }
+}
+
+/// What to do when files of different versions would be installed to the same path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollisionPolicy {
+    /// Fail without installing anything
+    #[default]
+    Error,
+    /// Install each file with the version number added to its name
+    Rename,
+    /// Install only the file of the version chosen by the config, failing if there is not exactly
+    /// one
+    PreferPinned,
+}
+
+/// A file of a version and the path it is installed to
+#[derive(Debug)]
+pub struct Install<'a> {
+    pub version: &'a ModVersion,
+    pub file: &'a ModFile,
+    pub path: PathBuf,
+}
+
+/// Add a version number to a file name, before its extension
+fn add_version_suffix(filename: &str, version_number: &str) -> String {
+    let suffix = fsutil::safe_file_name(version_number);
+    match filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{stem}-{suffix}.{extension}"),
+        _ => format!("{filename}-{suffix}"),
+    }
+}
+
+/// Resolve installs of different versions to the same path with a collision policy
+fn resolve_collisions<'a, F>(
+    installs: Vec<Install<'a>>,
+    policy: CollisionPolicy,
+    is_pinned: F,
+) -> Result<Vec<Install<'a>>>
+where
+    F: Fn(&ModVersion) -> bool,
+{
+    let mut by_path = HashMap::<PathBuf, Vec<Install<'a>>>::new();
+    for install in installs {
+        by_path
+            .entry(install.path.clone())
+            .or_default()
+            .push(install);
+    }
+    let mut result = Vec::<Install<'a>>::new();
+    for (path, mut group) in by_path {
+        if group.len() == 1 {
+            result.append(&mut group);
+            continue;
+        }
+        let collision = || Error::FileCollision {
+            path: path.clone(),
+            versions: group
+                .iter()
+                .map(|x| format!("{} {}", x.version.name, x.version.version_number))
+                .collect(),
+        };
+        match policy {
+            CollisionPolicy::Error => return Err(collision()),
+            CollisionPolicy::Rename => {
+                for mut install in group {
+                    let name =
+                        add_version_suffix(&install.file.name, &install.version.version_number);
+                    install.path.set_file_name(name);
+                    result.push(install);
+                }
+            }
+            CollisionPolicy::PreferPinned => {
+                let pinned = group.iter().filter(|x| is_pinned(x.version)).count();
+                if pinned != 1 {
+                    return Err(collision());
+                }
+                result.extend(group.into_iter().filter(|x| is_pinned(x.version)));
+            }
+        }
+    }
+    result.sort_by(|l, r| l.path.cmp(&r.path));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version_id: &str, version_number: &str, filename: &str) -> ModVersion {
+        ModVersion {
+            project_id: String::from("P").into(),
+            version_id: String::from(version_id).into(),
+            name: version_id.into(),
+            version_number: version_number.into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files: vec![ModFile {
+                url: String::new(),
+                name: filename.into(),
+                hashes: Default::default(),
+                size: 0,
+            }],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_collisions() {
+        let (a, b) = (
+            version("A", "1.0", "lib.jar"),
+            version("B", "2.0", "lib.jar"),
+        );
+        let installs = || {
+            [&a, &b]
+                .into_iter()
+                .map(|x| Install {
+                    version: x,
+                    file: &x.files[0],
+                    path: PathBuf::from("mods/lib.jar"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let paths = |x: Vec<Install>| x.into_iter().map(|x| x.path).collect::<Vec<_>>();
+        resolve_collisions(installs(), CollisionPolicy::Error, |_| false)
+            .expect_err("Collisions shall fail by default");
+        assert_eq!(
+            paths(resolve_collisions(installs(), CollisionPolicy::Rename, |_| false).unwrap()),
+            vec![
+                PathBuf::from("mods/lib-1.0.jar"),
+                PathBuf::from("mods/lib-2.0.jar")
+            ],
+            "Colliding files shall be renamed with their version"
+        );
+        let pinned = resolve_collisions(installs(), CollisionPolicy::PreferPinned, |x| {
+            x.version_id.as_str() == "B"
+        })
+        .unwrap();
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(
+            pinned[0].version.version_id.as_str(),
+            "B",
+            "The pinned version shall be installed"
+        );
+        resolve_collisions(installs(), CollisionPolicy::PreferPinned, |_| true)
+            .expect_err("Collisions between pinned versions shall fail");
+    }
+
+    #[test]
+    fn test_world_install_dir() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        let dir = PathBuf::from(".test/cache_world");
+        let minecraft = dir.join("minecraft");
+        std::fs::create_dir_all(&minecraft).expect("Failure to create .minecraft");
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(
+            dir.join("fixtures"),
+            FixtureMode::Replay,
+        )));
+        let manager = ModFileManager::new(dir.join("data"), Some(minecraft.clone()), client)
+            .with_world_dir(Some("saves/New World".into()));
+        assert_eq!(
+            manager.install_path(&"pack.zip".into(), Some(ModLoader::Datapack)),
+            minecraft.join("saves/New World/datapacks/pack.zip"),
+            "Data packs shall be installed into the world"
+        );
+        assert_eq!(
+            manager.install_path(&"sodium.jar".into(), Some(ModLoader::Fabric)),
+            minecraft.join("mods/sodium.jar"),
+            "Other files shall not be installed into the world"
+        );
+    }
+
+    #[test]
+    fn test_corrupt_cache() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        let dir = PathBuf::from(".test/cache_corrupt");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("minecraft")).expect("Failure to create .minecraft");
+        let bytes = (0..200_000u32).map(|x| x as u8).collect::<Vec<_>>();
+        let url = "https://cdn.example/big.jar";
+        Fixtures::new(dir.join("fixtures"), FixtureMode::Record)
+            .get(url, || Ok(bytes.clone()))
+            .expect("Fixtures shall record the file");
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(
+            dir.join("fixtures"),
+            FixtureMode::Replay,
+        )));
+        let manager = ModFileManager::new(dir.join("data"), Some(dir.join("minecraft")), client);
+        let mut version = version("ABCD", "1.0", "big.jar");
+        version.files[0].url = url.into();
+        version.files[0].size = bytes.len() as u64;
+        version.files[0].hashes = [("sha1".into(), verify::hash_bytes(&bytes).sha1)].into();
+        let file = &version.files[0];
+        let path = manager
+            .get_file(&version, file)
+            .expect("The file shall be downloaded");
+        assert_eq!(
+            verify::sample_hash(&path).expect("The file shall be sampled"),
+            verify::sample_hash_bytes(&bytes),
+            "Files and bytes shall have the same sample hash"
+        );
+        assert!(
+            manager
+                .find_intact_file(&version.version_id, file)
+                .is_some()
+        );
+        let mut corrupt = bytes.clone();
+        corrupt[199_999] ^= 1;
+        std::fs::write(&path, &corrupt).expect("Failure to corrupt file");
+        assert!(
+            manager
+                .find_intact_file(&version.version_id, file)
+                .is_none(),
+            "Corrupted files shall be found by their sample hash"
+        );
+        assert!(!path.exists(), "Corrupted files shall be removed");
+        manager
+            .get_file(&version, file)
+            .expect("The file shall be downloaded again");
+        std::fs::remove_file(sample_path(&path)).expect("Failure to remove sample");
+        corrupt[100_000] ^= 1;
+        std::fs::write(&path, &corrupt).expect("Failure to corrupt file");
+        assert!(
+            manager
+                .find_intact_file(&version.version_id, file)
+                .is_some(),
+            "Corruption outside the sample shall only be found with full hashes"
+        );
+        let manager = manager.with_full_hash(true);
+        assert!(
+            manager
+                .find_intact_file(&version.version_id, file)
+                .is_none(),
+            "Corrupted files shall be found by their full hash"
+        );
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        let dir = PathBuf::from(".test/cache_max_size");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("minecraft")).expect("Failure to create .minecraft");
+        let url = "https://cdn.example/huge.jar";
+        Fixtures::new(dir.join("fixtures"), FixtureMode::Record)
+            .get(url, || Ok(vec![0; 2048]))
+            .expect("Fixtures shall record the file");
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(
+            dir.join("fixtures"),
+            FixtureMode::Replay,
+        )));
+        let mut version = version("HUGE", "1.0", "huge.jar");
+        version.files[0].url = url.into();
+        let manager = ModFileManager::new(dir.join("data"), Some(dir.join("minecraft")), client)
+            .with_max_file_sizes(Some(1024), HashMap::from([("Q".to_string().into(), 4096)]));
+        let Err(Error::FileTooLarge { file, limit }) =
+            manager.download_file(&version, &version.files[0])
+        else {
+            panic!("Files larger than the limit shall be refused while downloading");
+        };
+        assert_eq!((file.as_str(), limit), ("huge.jar", 1024));
+        version.files[0].size = 1 << 20;
+        version.project_id = "Q".to_string().into();
+        assert!(
+            matches!(
+                manager.download_file(&version, &version.files[0]),
+                Err(Error::FileTooLarge { limit: 4096, .. })
+            ),
+            "Files shall be refused by the limit of their project before downloading"
+        );
+        version.files[0].size = 2048;
+        manager
+            .download_file(&version, &version.files[0])
+            .expect("Files within the limit of their project shall be downloaded");
+    }
+
+    #[test]
+    fn test_select_garbage() {
+        let entry = |project_id: &str, version_id: &str, used: i64| CacheEntry {
+            project_id: project_id.into(),
+            version_id: version_id.into(),
+            used,
+        };
+        let entries = vec![
+            entry("sodium", "s1", 1),
+            entry("sodium", "s2", 2),
+            entry("sodium", "s3", 3),
+            entry("sodium", "s4", 4),
+            entry("iris", "i1", 1),
+        ];
+        let garbage = select_garbage(&entries, 1, &HashSet::from(["s4"]));
+        assert_eq!(
+            garbage,
+            vec![entry("sodium", "s1", 1), entry("sodium", "s2", 2)],
+            "The versions in use and the most recent versions shall be kept"
+        );
+        assert_eq!(
+            select_garbage(&entries, 3, &HashSet::new()),
+            vec![entry("sodium", "s1", 1)],
+            "Only versions beyond the kept count shall be removed"
+        );
+        let keep = crate::config::ConfigCache::default().keep_versions;
+        let mut entries = entries;
+        entries.push(entry("sodium", "s0", 0));
+        assert_eq!(
+            select_garbage(&entries, keep, &HashSet::from(["s4"])),
+            vec![entry("sodium", "s0", 0)],
+            "By default, the resolved version and three others shall be kept"
+        );
+        assert_eq!(
+            select_garbage(&entries, keep, &HashSet::from(["s0"])),
+            vec![entry("sodium", "s1", 1)],
+            "Versions in use shall not count toward the kept versions"
+        );
     }
 }
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// In how many responses one is faulted
+const FAULT_ODDS: u64 = 4;
+/// The longest a slow response is delayed
+const MAX_DELAY_MS: u64 = 100;
+
+/// A fault injected into a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    /// The request fails
+    Fail,
+    /// The response is delayed
+    Slow(Duration),
+    /// A byte of the response is changed
+    Corrupt(usize),
+}
+
+/// Injects network failures, slow responses, and corrupt bytes into responses, to test that
+/// failed downloads, rollback, and resume work. The faults are chosen by a seed, so a run can be
+/// repeated.
+#[derive(Debug)]
+pub struct Chaos {
+    state: AtomicU64,
+}
+
+impl Chaos {
+    /// Construct chaos with a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    /// Get the next random number, with splitmix64 so it is safe to share between threads
+    fn next(&self) -> u64 {
+        let mut x = self
+            .state
+            .fetch_add(0x9e3779b97f4a7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e3779b97f4a7c15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+        x ^ (x >> 31)
+    }
+
+    /// Choose the fault of the next response, if any
+    fn fault(&self, len: usize) -> Option<Fault> {
+        if !self.next().is_multiple_of(FAULT_ODDS) {
+            return None;
+        }
+        match self.next() % 3 {
+            0 => Some(Fault::Fail),
+            1 => Some(Fault::Slow(Duration::from_millis(
+                self.next() % MAX_DELAY_MS,
+            ))),
+            _ if len == 0 => Some(Fault::Fail),
+            _ => Some(Fault::Corrupt(self.next() as usize % len)),
+        }
+    }
+
+    /// Inject a fault into the response to a request
+    pub fn apply(&self, key: &str, response: Result<Vec<u8>>) -> Result<Vec<u8>> {
+        let mut body = response?;
+        match self.fault(body.len()) {
+            None => {}
+            Some(Fault::Fail) => {
+                return Err(Error::IO(std::io::Error::other(format!(
+                    "chaos: injected failure for {key}"
+                ))));
+            }
+            Some(Fault::Slow(x)) => std::thread::sleep(x),
+            Some(Fault::Corrupt(x)) => body[x] ^= 0xff,
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaos() {
+        let faults = |seed| {
+            let chaos = Chaos::new(seed);
+            (0..64).map(|_| chaos.fault(8)).collect::<Vec<_>>()
+        };
+        assert_eq!(faults(7), faults(7), "Chaos shall repeat for a seed");
+        let faults = faults(7);
+        assert!(faults.contains(&None));
+        assert!(faults.contains(&Some(Fault::Fail)));
+        assert!(faults.iter().any(|x| matches!(x, Some(Fault::Corrupt(_)))));
+        let chaos = Chaos::new(7);
+        let results = (0..64)
+            .map(|_| chaos.apply("key", Ok(b"response".to_vec())))
+            .collect::<Vec<_>>();
+        assert!(
+            results
+                .iter()
+                .any(|x| x.as_ref().is_ok_and(|x| x != b"response")),
+            "Chaos shall corrupt responses"
+        );
+    }
+}
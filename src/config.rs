@@ -1,7 +1,23 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::error::Result;
-use crate::types::{MinecraftVersion, ModLoader, ProjectSlug};
+use crate::cache::CollisionPolicy;
+use crate::chaos::Chaos;
+use crate::error::{Error, Result};
+use crate::fixtures::Fixtures;
+use crate::fsutil::{CopyMode, StagingDir};
+use crate::hooks::Hooks;
+use crate::ignore::IgnoreRules;
+use crate::labrinth;
+use crate::launcher::{self, Launcher};
+use crate::metacache::MetadataCache;
+use crate::registry_fixture::FixtureRegistry;
+use crate::server;
+use crate::types::{
+    MinecraftVersion, ModDB, ModLoader, ProjectId, ProjectSlug, ProjectType, Registry, Side,
+    VersionId,
+};
 
 /// Configuration containing paths and projects to use
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -13,43 +29,285 @@ pub struct Config {
     #[serde(default)]
     pub paths: ConfigPaths,
 
+    /// Cache behavior
+    #[serde(default)]
+    pub cache: ConfigCache,
+
+    /// Install behavior
+    #[serde(default)]
+    pub install: ConfigInstall,
+
+    /// Limits on downloads
+    #[serde(default)]
+    pub download: ConfigDownload,
+
+    /// Update behavior
+    #[serde(default)]
+    pub update: ConfigUpdate,
+
+    /// A script applying custom policies
+    #[serde(default)]
+    pub hooks: ConfigHooks,
+
+    /// How a server directory is set up when installing for the server
+    #[serde(default)]
+    pub server: ConfigServer,
+
+    /// Checks that keep known malicious files out of the instance
+    #[serde(default)]
+    pub security: ConfigSecurity,
+
+    /// Where API responses are recorded or replayed. Set from the command line.
+    #[serde(skip)]
+    pub fixtures: Option<Fixtures>,
+
+    /// Faults injected into API responses. Set from the command line.
+    #[serde(skip)]
+    pub chaos: Option<Arc<Chaos>>,
+
+    /// The registry that projects are resolved from instead of the network. Set from the
+    /// command line.
+    #[serde(skip)]
+    pub registry_fixture: Option<Arc<FixtureRegistry>>,
+
+    /// Fail instead of fetching metadata that is not cached. Set from the command line.
+    #[serde(skip)]
+    pub frozen: bool,
+
     /// Projects that must be available
     projects: HashMap<String, OptionConfigProject>,
 
     /// Projects that may be available
     #[serde(default, rename = "optional-projects")]
     optional_projects: HashMap<String, OptionConfigProject>,
+
+    /// Groups of projects the player may choose to include, by name
+    #[serde(default)]
+    pub groups: BTreeMap<String, ConfigGroup>,
+
+    /// The names of the groups chosen to be included. If None, the default groups are included.
+    #[serde(skip)]
+    selected_groups: Option<Vec<String>>,
+
+    /// The game version that replaces the game versions of every project. Set from the command
+    /// line.
+    #[serde(skip)]
+    game_version_all: Option<MinecraftVersion>,
+}
+
+/// Values given for one invocation that take precedence over the config, without editing it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigOverrides {
+    /// Replaces `defaults.game_version`
+    pub game_version: Option<MinecraftVersion>,
+
+    /// Replaces `defaults.game_version` and the game versions set for each project
+    pub game_version_all: Option<MinecraftVersion>,
+
+    /// Replaces `defaults.loader`
+    pub loader: Option<ModLoader>,
+
+    /// Replaces `defaults.target`
+    pub target: Option<Side>,
+
+    /// Replaces `paths.data`
+    pub data_dir: Option<PathBuf>,
+
+    /// Replaces `paths.dot_minecraft`
+    pub minecraft_dir: Option<PathBuf>,
+
+    /// Replaces `paths.world`
+    pub world: Option<String>,
 }
 
 impl Config {
     /// Load the config from TOML text
     pub fn loads(text: &str) -> Result<Config> {
-        let result = toml::from_str::<Self>(text)?;
-        if !result.paths.dot_minecraft.is_dir() {
-            panic!("{:?}: directory does not exist", result.paths.dot_minecraft);
+        Ok(toml::from_str::<Self>(text)?)
+    }
+
+    /// Construct an API client using the fixtures of this config, or else the metadata cache, so
+    /// that recorded and replayed responses never come from the cache
+    pub fn client(&self) -> labrinth::Client {
+        let client = labrinth::Client::new()
+            .with_chaos(self.chaos.clone())
+            .with_frozen(self.frozen);
+        match &self.fixtures {
+            Some(_) => client.with_fixtures(self.fixtures.clone()),
+            None => client.with_cache(self.cache.metadata(&self.paths.data)),
         }
-        Ok(result)
     }
 
-    /// Get the projects, sorted by name
+    /// Apply the overrides of an invocation
+    pub fn apply_overrides(&mut self, overrides: &ConfigOverrides) {
+        if let Some(x) = overrides.game_version_all.or(overrides.game_version) {
+            self.defaults.game_version = x;
+        }
+        if let Some(x) = overrides.loader {
+            self.defaults.loader = x;
+        }
+        if let Some(x) = overrides.target {
+            self.defaults.target = x;
+        }
+        if let Some(x) = &overrides.data_dir {
+            self.paths.data = x.clone();
+        }
+        if let Some(x) = &overrides.minecraft_dir {
+            self.paths.set_minecraft_dir(x.clone());
+        }
+        if let Some(x) = &overrides.world {
+            self.paths.world = Some(x.clone());
+        }
+        self.game_version_all = overrides.game_version_all;
+    }
+
+    /// Replace `latest` and `latest-snapshot` in the game versions of the defaults and every
+    /// project. Resolve is only called when a keyword is used.
+    pub fn resolve_latest<F>(&mut self, mut resolve: F) -> Result<()>
+    where
+        F: FnMut(MinecraftVersion) -> Result<MinecraftVersion>,
+    {
+        let mut versions = vec![&mut self.defaults.game_version];
+        versions.extend(self.game_version_all.as_mut());
+        let projects = self
+            .projects
+            .values_mut()
+            .chain(self.optional_projects.values_mut())
+            .chain(
+                self.groups
+                    .values_mut()
+                    .flat_map(|x| x.projects.values_mut()),
+            );
+        for project in projects {
+            match &mut project.game_version {
+                Some(OneOrMany::One(x)) => versions.push(x),
+                Some(OneOrMany::Many(x)) => versions.extend(x.iter_mut()),
+                None => {}
+            }
+        }
+        for version in versions {
+            if version.is_latest() {
+                *version = resolve(*version)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a project with the defaults and overrides
+    fn resolve(&self, name: &String, project: &OptionConfigProject) -> ConfigProject {
+        let mut result = project.resolve(name, &self.defaults);
+        if let Some(x) = self.game_version_all {
+            result.game_versions = vec![x];
+        }
+        result
+    }
+
+    /// Choose the groups to include, failing if a name is not a group
+    pub fn select_groups(&mut self, names: Vec<String>) -> Result<()> {
+        if let Some(name) = names.iter().find(|x| !self.groups.contains_key(*x)) {
+            return Err(Error::UnknownGroup(name.clone()));
+        }
+        self.selected_groups = Some(names);
+        Ok(())
+    }
+
+    /// Check if a group is included
+    pub fn is_group_selected(&self, name: &str) -> bool {
+        match &self.selected_groups {
+            Some(x) => x.iter().any(|x| x == name),
+            None => self.groups.get(name).is_some_and(|x| x.default),
+        }
+    }
+
+    /// Get the projects, including those of the selected groups, sorted by name
     pub fn projects(&self) -> Vec<ConfigProject> {
         let mut result = Vec::<ConfigProject>::new();
-        for (name, project) in &self.projects {
-            result.push(project.resolve(name, &self.defaults))
+        let groups = self
+            .groups
+            .iter()
+            .filter(|(name, _)| self.is_group_selected(name))
+            .flat_map(|(_, group)| &group.projects);
+        for (name, project) in self.projects.iter().chain(groups) {
+            if project.side.includes(self.defaults.target) {
+                result.push(self.resolve(name, project))
+            }
         }
         result.sort_by(|l, r| l.name.as_str().cmp(r.name.as_str()));
         result
     }
 
+    /// Check if a project is in the config, as a project, an optional project, or in any group
+    pub fn is_configured(&self, name: &str) -> bool {
+        self.projects.contains_key(name)
+            || self.optional_projects.contains_key(name)
+            || self.groups.values().any(|x| x.projects.contains_key(name))
+    }
+
+    /// Get a project that is not in the config yet as it would be added, with the defaults
+    pub fn new_project(&self, name: &str) -> ConfigProject {
+        self.resolve(&name.to_string(), &OptionConfigProject::default())
+    }
+
     /// Get the optional projects, sorted by name
     pub fn optional_projects(&self) -> Vec<ConfigProject> {
         let mut result = Vec::<ConfigProject>::new();
         for (name, project) in &self.optional_projects {
-            result.push(project.resolve(name, &self.defaults))
+            if project.side.includes(self.defaults.target) {
+                result.push(self.resolve(name, project))
+            }
         }
         result.sort_by(|l, r| l.name.as_str().cmp(r.name.as_str()));
         result
     }
+
+    /// Get a hash of everything projects are resolved from: the defaults, the projects and
+    /// optional projects with the selected groups and overrides applied, the update policy, and
+    /// the hook script. A lockfile resolved from the same hash is up to date with the config.
+    pub fn resolution_hash(&self) -> String {
+        let (projects, optional_projects) = (self.projects(), self.optional_projects());
+        let inputs = ResolutionInputs {
+            hook_script: self.hooks.get().map(Hooks::source),
+            defaults: &self.defaults,
+            update: &self.update,
+            projects: projects.iter().map(ResolutionProject::from).collect(),
+            optional_projects: optional_projects
+                .iter()
+                .map(ResolutionProject::from)
+                .collect(),
+        };
+        let inputs = toml::to_string(&inputs).expect("Resolution inputs shall serialize");
+        crate::verify::hash_bytes(inputs.as_bytes()).sha1
+    }
+
+    /// Get the largest size of the files of projects that set their own, by the id of the project
+    /// each entry was resolved to
+    pub fn max_file_sizes(&self, mod_db: &ModDB) -> HashMap<ProjectId, u64> {
+        self.projects()
+            .into_iter()
+            .chain(self.optional_projects())
+            .filter_map(|project| {
+                let size = project.max_file_size?.bytes();
+                Some((project.resolved_id(mod_db)?, size))
+            })
+            .collect()
+    }
+
+    /// Get the names that files of projects are installed as, by the id of the project each entry
+    /// was resolved to. Only the file name of `install-as` is used, so it can not point outside the
+    /// install directory.
+    pub fn install_names(&self, mod_db: &ModDB) -> HashMap<ProjectId, String> {
+        self.projects()
+            .into_iter()
+            .chain(self.optional_projects())
+            .filter_map(|project| {
+                let name = Path::new(project.install_as.as_ref()?).file_name()?;
+                Some((
+                    project.resolved_id(mod_db)?,
+                    name.to_string_lossy().into_owned(),
+                ))
+            })
+            .collect()
+    }
 }
 
 /// Get the data directory for this program's data
@@ -59,18 +317,32 @@ fn default_data() -> PathBuf {
         .join("mcmod")
 }
 
-/// Get the .minecraft directory
-fn default_dot_minecraft() -> PathBuf {
-    if cfg!(windows) {
-        dirs::data_dir()
-    } else {
-        dirs::home_dir()
+/// Add a project with the defaults to the `[projects]` table of the text of a config, after the
+/// last project in the table, or in a new table at the end. The rest of the text, like comments
+/// and formatting, is kept as is.
+pub fn add_project_entry(text: &str, name: &str) -> Result<String> {
+    let mut document = text.parse::<toml_edit::DocumentMut>()?;
+    document
+        .entry("projects")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| Error::NotATable("projects".into()))?
+        .insert(name, toml_edit::value(toml_edit::InlineTable::new()));
+    Ok(document.to_string())
+}
+
+/// Remove a project from the `[projects]` or `[optional-projects]` table of the text of a config,
+/// whether it is a line of the table or a table of its own. The rest of the text is kept as is.
+/// Returns `None` if the project is not in either table.
+pub fn remove_project_entry(text: &str, name: &str) -> Result<Option<String>> {
+    let mut document = text.parse::<toml_edit::DocumentMut>()?;
+    let mut found = false;
+    for table in ["projects", "optional-projects"] {
+        if let Some(projects) = document.get_mut(table).and_then(|x| x.as_table_like_mut()) {
+            found |= projects.remove(name).is_some();
+        }
     }
-    .and_then(|x| {
-        let x = x.join(".minecraft");
-        if x.exists() { Some(x) } else { None }
-    })
-    .expect("Could not locate .minecraft, plese specify paths.dot_minecraft in config")
+    Ok(found.then(|| document.to_string()))
 }
 
 /// Get the temp directory for this program's data
@@ -79,16 +351,103 @@ fn default_temp() -> PathBuf {
 }
 
 /// Project information
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConfigProject {
     /// Name of the project
     pub name: ProjectSlug,
 
-    /// Target Minecraft version
-    pub game_version: MinecraftVersion,
+    /// Target Minecraft versions, in order of preference
+    pub game_versions: Vec<MinecraftVersion>,
 
-    /// Target mod loader
-    pub loader: ModLoader,
+    /// Target mod loaders, in order of preference
+    pub loaders: Vec<ModLoader>,
+
+    /// Configured projects that must be resolved for this project to be collected
+    pub requires: Vec<ProjectSlug>,
+
+    /// Why the project is in the config
+    pub note: Option<String>,
+
+    /// Keep the locked version instead of resolving the latest
+    pub frozen: bool,
+
+    /// Only choose versions the author marked as featured
+    pub featured_only: bool,
+
+    /// How many earlier patches of each game version to try when none of them has a version
+    pub game_version_tolerance: usize,
+
+    /// The name to install the file as instead of the name of the file in the version
+    pub install_as: Option<String>,
+
+    /// The largest size of a file of the project, instead of `download.max-file-size`
+    pub max_file_size: Option<ByteSize>,
+
+    /// The type of content to resolve and install the project as, for projects that publish
+    /// several
+    pub project_type: Option<ProjectType>,
+
+    /// The exact version to collect instead of looking up the latest. The name of the project is
+    /// then only a label.
+    pub version_id: Option<VersionId>,
+}
+
+impl ConfigProject {
+    /// Find the project this entry was resolved to, by its version id if it names one, or else by
+    /// its name as a slug or an id
+    pub fn resolved_id(&self, mod_db: &ModDB) -> Option<ProjectId> {
+        let project = match &self.version_id {
+            Some(version_id) => {
+                mod_db.get_project_by_id(&mod_db.get_version(version_id)?.project_id)
+            }
+            None => mod_db
+                .get_project_by_slug(&self.name)
+                .or_else(|| mod_db.get_project_by_id(&self.name.to_string().into())),
+        };
+        project.map(|x| x.project_id.clone())
+    }
+}
+
+/// The values projects are resolved from, serialized in a fixed order for the resolution hash
+#[derive(serde::Serialize)]
+struct ResolutionInputs<'a> {
+    hook_script: Option<&'a str>,
+    defaults: &'a ConfigDefaults,
+    update: &'a ConfigUpdate,
+    projects: Vec<ResolutionProject<'a>>,
+    optional_projects: Vec<ResolutionProject<'a>>,
+}
+
+/// The values a project is resolved from, serialized for the resolution hash
+#[derive(serde::Serialize)]
+struct ResolutionProject<'a> {
+    name: &'a str,
+    game_versions: &'a [MinecraftVersion],
+    loaders: &'a [ModLoader],
+    requires: Vec<&'a str>,
+    note: Option<&'a str>,
+    frozen: bool,
+    featured_only: bool,
+    game_version_tolerance: usize,
+    project_type: Option<ProjectType>,
+    version_id: Option<&'a str>,
+}
+
+impl<'a> From<&'a ConfigProject> for ResolutionProject<'a> {
+    fn from(value: &'a ConfigProject) -> Self {
+        Self {
+            name: value.name.as_str(),
+            game_versions: &value.game_versions,
+            loaders: &value.loaders,
+            requires: value.requires.iter().map(ProjectSlug::as_str).collect(),
+            note: value.note.as_deref(),
+            frozen: value.frozen,
+            featured_only: value.featured_only,
+            game_version_tolerance: value.game_version_tolerance,
+            project_type: value.project_type,
+            version_id: value.version_id.as_ref().map(VersionId::as_str),
+        }
+    }
 }
 
 /// Default targets for projects
@@ -99,6 +458,32 @@ pub struct ConfigDefaults {
 
     /// Mod loader
     pub loader: ModLoader,
+
+    /// The side to install projects for
+    #[serde(default = "default_target")]
+    pub target: Side,
+
+    /// Only choose versions the author marked as featured
+    #[serde(default, rename = "featured-only")]
+    pub featured_only: bool,
+
+    /// How many earlier patches of the game version to try when a project has no version for it
+    #[serde(default, rename = "game-version-tolerance")]
+    pub game_version_tolerance: usize,
+
+    /// The name of the registry backend that projects are resolved from
+    #[serde(default = "default_registry")]
+    pub registry: String,
+}
+
+/// Resolve projects from Modrinth unless told otherwise
+fn default_registry() -> String {
+    Registry::default().to_string()
+}
+
+/// Install for the client unless told otherwise
+fn default_target() -> Side {
+    Side::Client
 }
 
 /// Paths to use
@@ -108,33 +493,596 @@ pub struct ConfigPaths {
     #[serde(default = "default_data")]
     pub data: PathBuf,
 
-    /// .minecraft directory
-    #[serde(default = "default_dot_minecraft")]
-    pub dot_minecraft: PathBuf,
+    /// .minecraft directory. If omitted, it is detected from the launcher and instance
+    #[serde(default)]
+    pub dot_minecraft: Option<PathBuf>,
+
+    /// Launcher whose instance should be targeted
+    #[serde(default)]
+    pub launcher: Option<Launcher>,
+
+    /// Name of the launcher instance to target
+    #[serde(default)]
+    pub instance: Option<String>,
+
+    /// Names of launcher instances to install the same projects into, resolved once
+    #[serde(default)]
+    pub instances: Vec<String>,
+
+    /// Server directory, used when installing for the server
+    #[serde(default)]
+    pub server: Option<PathBuf>,
+
+    /// Name of the world under `saves` that data packs are installed into
+    #[serde(default)]
+    pub world: Option<String>,
 
     /// This program's temp directry
     #[serde(default = "default_temp")]
     pub temp: PathBuf,
 }
 
+/// Cache behavior
+#[derive(Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigCache {
+    /// Minutes that project and version metadata are used before they are fetched again
+    #[serde(default = "default_metadata_ttl")]
+    pub metadata_ttl: u64,
+
+    /// How many versions of each project are kept in the cache for rolling back, besides the
+    /// resolved version
+    #[serde(default = "default_keep_versions")]
+    pub keep_versions: usize,
+
+    /// Download the icons of resolved projects into the data directory, for frontends to show
+    #[serde(default)]
+    pub icons: bool,
+
+    /// A metadata cache directory shared with other configs, instead of the one in the data
+    /// directory
+    #[serde(default)]
+    pub shared_dir: Option<PathBuf>,
+
+    /// Fetch all metadata again, ignoring the TTL. Set from the command line.
+    #[serde(skip)]
+    pub refresh: bool,
+}
+
+impl Default for ConfigCache {
+    fn default() -> Self {
+        Self {
+            metadata_ttl: default_metadata_ttl(),
+            keep_versions: default_keep_versions(),
+            icons: false,
+            shared_dir: None,
+            refresh: false,
+        }
+    }
+}
+
+/// Use metadata for an hour before fetching it again
+fn default_metadata_ttl() -> u64 {
+    60
+}
+
+/// Keep the three most recently used versions of each project besides the resolved version
+fn default_keep_versions() -> usize {
+    3
+}
+
+impl ConfigCache {
+    /// Get the metadata cache in the shared directory, or else in a data directory
+    pub fn metadata(&self, data_dir: &std::path::Path) -> MetadataCache {
+        MetadataCache::new(
+            self.shared_dir
+                .clone()
+                .unwrap_or_else(|| data_dir.join("metadata")),
+            std::time::Duration::from_secs(self.metadata_ttl * 60),
+            self.refresh,
+        )
+    }
+}
+
+/// Update behavior
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigUpdate {
+    /// How long ago a version must have been published to be chosen
+    #[serde(default)]
+    pub min_age: Option<Age>,
+}
+
+/// How a server directory is set up when installing for the server
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigServer {
+    /// Values set in server.properties. Strings may use `{game_version}` and `{loader}`.
+    #[serde(default)]
+    pub properties: BTreeMap<String, toml::Value>,
+
+    /// Accept the Minecraft EULA in eula.txt. Set from the command line, because the player
+    /// must agree to it themselves.
+    #[serde(skip)]
+    pub accept_eula: bool,
+}
+
+/// Checks that keep known malicious files out of the instance
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigSecurity {
+    /// Urls or paths of JSON advisory feeds. Projects they list are not installed.
+    #[serde(default)]
+    pub advisories: Vec<String>,
+
+    /// Paths of lists of the hashes of files known to be safe to install
+    #[serde(default)]
+    pub allowlists: Vec<String>,
+
+    /// The url of a service that knows files by hash, with `{sha1}` or `{sha512}` in place of the
+    /// hash, or `modrinth`
+    #[serde(default)]
+    pub reputation: Option<String>,
+
+    /// Refuse to install files that no allowlist or reputation service knows
+    #[serde(default)]
+    pub require_known_hashes: bool,
+}
+
+impl ConfigSecurity {
+    /// Check if downloaded files are checked against allowlists or a reputation service before
+    /// they are installed
+    pub fn checks_hashes(&self) -> bool {
+        self.require_known_hashes || !self.allowlists.is_empty() || self.reputation.is_some()
+    }
+
+    /// Get the reputation service, which is Modrinth when known hashes are required but nothing
+    /// else is configured to know them
+    pub fn reputation(&self) -> Option<&str> {
+        match (&self.reputation, self.allowlists.is_empty()) {
+            (Some(x), _) => Some(x.as_str()),
+            (None, true) if self.require_known_hashes => Some("modrinth"),
+            (None, _) => None,
+        }
+    }
+}
+
+impl ConfigServer {
+    /// Get the values to set in server.properties, with the variables of the config replaced
+    pub fn properties(&self, defaults: &ConfigDefaults) -> BTreeMap<String, String> {
+        let vars = [
+            ("game_version", defaults.game_version.to_string()),
+            ("loader", defaults.loader.to_string()),
+        ];
+        self.properties
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    toml::Value::String(x) => server::expand(x, &vars),
+                    x => x.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// A script applying custom policies
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct ConfigHooks {
+    /// The path of the script, relative to the config
+    #[serde(default)]
+    pub script: Option<PathBuf>,
+
+    /// The compiled script. Loaded with `load`.
+    #[serde(skip)]
+    pub loaded: Option<Arc<Hooks>>,
+}
+
+impl ConfigHooks {
+    /// Compile the script, if any, resolving its path from the directory of the config
+    pub fn load(&mut self, config_dir: &Path) -> Result<()> {
+        if let Some(script) = &self.script {
+            self.loaded = Some(Arc::new(Hooks::load(&config_dir.join(script))?));
+        }
+        Ok(())
+    }
+
+    /// Get the compiled script, if any
+    pub fn get(&self) -> Option<&Hooks> {
+        self.loaded.as_deref()
+    }
+}
+
+/// A length of time written as a number of hours, days, weeks, months, or years, like `12h`,
+/// `3d`, `2w`, `18mo`, or `1y`. Months are 30 days and years are 365 days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Age {
+    /// The number of units
+    count: u32,
+    /// The unit, one of `h`, `d`, `w`, `m` for months, or `y`
+    unit: char,
+}
+
+impl Age {
+    /// Get the length of time, unless it is too long to represent
+    pub fn duration(&self) -> Option<chrono::TimeDelta> {
+        let count = i64::from(self.count);
+        match self.unit {
+            'h' => chrono::TimeDelta::try_hours(count),
+            'w' => chrono::TimeDelta::try_weeks(count),
+            'm' => chrono::TimeDelta::try_days(count * 30),
+            'y' => chrono::TimeDelta::try_days(count * 365),
+            _ => chrono::TimeDelta::try_days(count),
+        }
+    }
+
+    /// Get the time this long before a time
+    pub fn before(&self, time: chrono::NaiveDateTime) -> Result<chrono::NaiveDateTime> {
+        self.duration()
+            .and_then(|x| time.checked_sub_signed(x))
+            .ok_or_else(|| Error::InvalidAge(String::from(*self)))
+    }
+}
+
+impl TryFrom<String> for Age {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let invalid = || Error::InvalidAge(value.clone());
+        let (count, unit) = match value.strip_suffix("mo") {
+            Some(count) => (count, 'm'),
+            None => {
+                let unit = value.chars().last().ok_or_else(invalid)?;
+                if !matches!(unit, 'h' | 'd' | 'w' | 'y') {
+                    return Err(invalid());
+                }
+                (&value[..value.len() - 1], unit)
+            }
+        };
+        let count = count.parse().map_err(|_| invalid())?;
+        let age = Self { count, unit };
+        age.duration().ok_or_else(invalid)?;
+        Ok(age)
+    }
+}
+
+impl std::str::FromStr for Age {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s.to_string())
+    }
+}
+
+impl From<Age> for String {
+    fn from(value: Age) -> Self {
+        match value.unit {
+            'm' => format!("{}mo", value.count),
+            unit => format!("{}{unit}", value.count),
+        }
+    }
+}
+
+/// A number of bytes written with a unit, like `300MB` or `1.5GiB`. `KB`, `MB`, and `GB` are powers
+/// of 1000, and `KiB`, `MiB`, and `GiB` are powers of 1024. Units are not case sensitive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ByteSize(u64);
+
+/// The units of sizes, by lowercase name
+const SIZE_UNITS: [(&str, u64); 7] = [
+    ("b", 1),
+    ("kb", 1000),
+    ("mb", 1000 * 1000),
+    ("gb", 1000 * 1000 * 1000),
+    ("kib", 1 << 10),
+    ("mib", 1 << 20),
+    ("gib", 1 << 30),
+];
+
+impl ByteSize {
+    /// Get the number of bytes
+    pub fn bytes(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl TryFrom<String> for ByteSize {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let invalid = || Error::InvalidSize(value.clone());
+        let text = value.trim();
+        let split = text
+            .find(|x: char| !x.is_ascii_digit() && x != '.')
+            .unwrap_or(text.len());
+        let count = text[..split].parse::<f64>().map_err(|_| invalid())?;
+        let unit = text[split..].trim().to_lowercase();
+        let (_, scale) = SIZE_UNITS
+            .iter()
+            .find(|(name, _)| *name == unit || (unit.is_empty() && *name == "b"))
+            .ok_or_else(invalid)?;
+        Ok(Self((count * *scale as f64).round() as u64))
+    }
+}
+
+impl std::str::FromStr for ByteSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s.to_string())
+    }
+}
+
+impl From<ByteSize> for String {
+    fn from(value: ByteSize) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    /// Format the size in the largest binary unit it is a whole number of
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, scale) = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)]
+            .into_iter()
+            .find(|(_, scale)| self.0 >= *scale && self.0.is_multiple_of(*scale))
+            .unwrap_or(("B", 1));
+        write!(f, "{}{name}", self.0 / scale)
+    }
+}
+
+/// A group of projects the player may choose to include, like shaders or a minimap
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct ConfigGroup {
+    /// What the group adds, shown when choosing groups
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Whether the group is included unless the player chooses otherwise
+    #[serde(default)]
+    pub default: bool,
+
+    /// The projects of the group
+    #[serde(default)]
+    projects: HashMap<String, OptionConfigProject>,
+}
+
+/// Limits on downloads, so a huge file or a stalled transfer does not fill the disk or hang
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigDownload {
+    /// The largest size of a file. Projects can set their own with `max-file-size`.
+    #[serde(default)]
+    pub max_file_size: Option<ByteSize>,
+
+    /// Seconds that downloading a file may take
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl ConfigDownload {
+    /// Get how long downloading a file may take
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout.map(std::time::Duration::from_secs)
+    }
+}
+
+/// Install behavior
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigInstall {
+    /// How files are copied from the cache into the install directory
+    #[serde(default)]
+    pub mode: CopyMode,
+
+    /// What to do when files of different versions would be installed to the same path
+    #[serde(default)]
+    pub collisions: CollisionPolicy,
+
+    /// The shader loader that shaders are installed for, like `iris` or `canvas`. If omitted, it
+    /// is detected from the resolved mods.
+    #[serde(default)]
+    pub shader_loader: Option<ModLoader>,
+
+    /// Check cached files against their full hashes before they are installed, instead of only
+    /// their size and a sample of their contents
+    #[serde(default)]
+    pub full_hash: bool,
+
+    /// Write a page listing the installed projects into the install directory
+    #[serde(default)]
+    pub index_html: bool,
+
+    /// A directory of files copied into the install directory as they are, like configs and
+    /// options, relative to the config
+    #[serde(default)]
+    pub overrides: Option<PathBuf>,
+
+    /// Patterns in the format of `.gitignore` of files in the overrides not to copy
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl ConfigInstall {
+    /// Get the rules for which files in the overrides are not copied
+    pub fn ignore_rules(&self) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.extend(self.exclude.iter().map(String::as_str));
+        rules
+    }
+}
+
 impl Default for ConfigPaths {
     fn default() -> Self {
         Self {
-            dot_minecraft: default_dot_minecraft(),
+            dot_minecraft: None,
+            launcher: None,
+            instance: None,
+            instances: vec![],
+            server: None,
+            world: None,
             temp: default_temp(),
             data: default_data(),
         }
     }
 }
 
-/// Internal project information. Use [OptionConfigProject::resolve] to replace `None` at runtime.
+impl ConfigPaths {
+    /// Override paths with the MCMOD_DATA_DIR and MCMOD_MINECRAFT_DIR environment variables
+    pub fn apply_env(&mut self) {
+        self.apply_vars(|x| std::env::var_os(x));
+    }
+
+    /// Override paths with the variables returned by `var`
+    fn apply_vars<F>(&mut self, var: F)
+    where
+        F: Fn(&str) -> Option<std::ffi::OsString>,
+    {
+        if let Some(x) = var("MCMOD_DATA_DIR") {
+            self.data = x.into();
+        }
+        if let Some(x) = var("MCMOD_MINECRAFT_DIR") {
+            self.set_minecraft_dir(x.into());
+        }
+    }
+
+    /// Install into a .minecraft directory instead of the configured instances
+    pub fn set_minecraft_dir(&mut self, path: PathBuf) {
+        self.dot_minecraft = Some(path);
+        self.instances.clear();
+    }
+
+    /// Create a staging directory for this run inside the temp directory
+    pub fn staging(&self) -> Result<StagingDir> {
+        StagingDir::new(&self.temp)
+    }
+
+    /// Get the .minecraft directory, detecting it from the launcher and instance if not given
+    pub fn minecraft_dir(&self) -> Result<PathBuf> {
+        let path = match &self.dot_minecraft {
+            Some(x) => x.clone(),
+            None => launcher::find_game_dir(
+                self.launcher,
+                self.instance
+                    .as_deref()
+                    .or(self.instances.first().map(String::as_str)),
+            )?,
+        };
+        if !path.is_dir() {
+            return Err(Error::MissingDirectory(path));
+        }
+        Ok(path)
+    }
+
+    /// Get the directory to install files into for a target side
+    pub fn install_dir(&self, target: Side) -> Result<PathBuf> {
+        if target != Side::Server {
+            return self.minecraft_dir();
+        }
+        let path = self
+            .server
+            .clone()
+            .ok_or_else(|| Error::MissingDirectory("paths.server".into()))?;
+        if !path.is_dir() {
+            return Err(Error::MissingDirectory(path));
+        }
+        Ok(path)
+    }
+
+    /// Get every directory to install files into for a target side. The server has one, and the
+    /// client has the .minecraft directory and that of each instance in `instances`.
+    pub fn install_dirs(&self, target: Side) -> Result<Vec<PathBuf>> {
+        let mut result = vec![self.install_dir(target)?];
+        if target == Side::Server || self.dot_minecraft.is_some() {
+            return Ok(result);
+        }
+        for name in &self.instances {
+            let path = launcher::find_game_dir(self.launcher, Some(name))?;
+            if !path.is_dir() {
+                return Err(Error::MissingDirectory(path));
+            }
+            if !result.contains(&path) {
+                result.push(path);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// A config value that may be given as a single value or a list
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T: Clone> OneOrMany<T> {
+    /// Get the values as a list
+    fn to_vec(&self) -> Vec<T> {
+        match self {
+            OneOrMany::One(x) => vec![x.clone()],
+            OneOrMany::Many(x) => x.clone(),
+        }
+    }
+}
+
+/// Internal project information. Use [OptionConfigProject::resolve] to replace `None` at runtime.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
 struct OptionConfigProject {
-    /// Target Minecraft version
-    pub game_version: Option<MinecraftVersion>,
+    /// Target Minecraft versions, in order of preference
+    pub game_version: Option<OneOrMany<MinecraftVersion>>,
 
-    /// Target mod loader
-    pub loader: Option<ModLoader>,
+    /// Target mod loaders, in order of preference
+    pub loader: Option<OneOrMany<ModLoader>>,
+
+    /// The side the project is used on
+    #[serde(default)]
+    pub side: Side,
+
+    /// Configured projects that must be resolved for this project to be collected
+    #[serde(default)]
+    pub requires: Vec<String>,
+
+    /// Why the project is in the config
+    #[serde(default)]
+    pub note: Option<String>,
+
+    /// Keep the locked version instead of resolving the latest
+    #[serde(default)]
+    pub frozen: bool,
+
+    /// Only choose versions the author marked as featured
+    #[serde(default, rename = "featured-only")]
+    pub featured_only: Option<bool>,
+
+    /// How many earlier patches of the game versions to try
+    #[serde(default, rename = "game-version-tolerance")]
+    pub game_version_tolerance: Option<usize>,
+
+    /// The name to install the file as
+    #[serde(default, rename = "install-as")]
+    pub install_as: Option<String>,
+
+    /// The largest size of a file of the project
+    #[serde(default, rename = "max-file-size")]
+    pub max_file_size: Option<ByteSize>,
+
+    /// The type of content to resolve the project as
+    #[serde(default, rename = "type")]
+    pub project_type: Option<ProjectType>,
+
+    /// The exact version to collect
+    #[serde(default, rename = "version-id")]
+    pub version_id: Option<String>,
 }
 
 impl OptionConfigProject {
@@ -142,12 +1090,25 @@ impl OptionConfigProject {
     pub fn resolve(&self, name: &String, defaults: &ConfigDefaults) -> ConfigProject {
         ConfigProject {
             name: name.to_owned().into(),
-            game_version: self
+            game_versions: self
                 .game_version
                 .as_ref()
-                .unwrap_or(&defaults.game_version)
-                .to_owned(),
-            loader: self.loader.as_ref().unwrap_or(&defaults.loader).to_owned(),
+                .map_or_else(|| vec![defaults.game_version], OneOrMany::to_vec),
+            loaders: self
+                .loader
+                .as_ref()
+                .map_or_else(|| vec![defaults.loader], OneOrMany::to_vec),
+            requires: self.requires.iter().map(|x| x.clone().into()).collect(),
+            note: self.note.clone(),
+            frozen: self.frozen,
+            featured_only: self.featured_only.unwrap_or(defaults.featured_only),
+            game_version_tolerance: self
+                .game_version_tolerance
+                .unwrap_or(defaults.game_version_tolerance),
+            install_as: self.install_as.clone(),
+            max_file_size: self.max_file_size,
+            project_type: self.project_type,
+            version_id: self.version_id.clone().map(VersionId::from),
         }
     }
 }
@@ -165,6 +1126,32 @@ mod tests {
         .expect("Config shall be able to parse a toml.")
     }
 
+    /// Build the database of a lockfile of projects given by id, slug, and version id
+    fn locked_db(projects: &[(&str, &str, &str)]) -> ModDB {
+        let projects = projects
+            .iter()
+            .map(|(id, slug, version_id)| crate::lockfile::LockedProject {
+                project_id: id.to_string(),
+                slug: slug.to_string(),
+                name: slug.to_string(),
+                version_id: version_id.to_string(),
+                version_number: "1.0".into(),
+                loader: Some(ModLoader::Fabric),
+                license: None,
+                registry: "modrinth".into(),
+                reasons: vec!["configured".into()],
+                note: None,
+                icon_url: None,
+                files: vec![],
+            })
+            .collect();
+        crate::lockfile::Lockfile {
+            config_hash: None,
+            projects,
+        }
+        .to_db()
+    }
+
     fn create_test_paths() {
         let path = PathBuf::from(".test/.minecraft");
         if !path.exists() {
@@ -182,18 +1169,45 @@ mod tests {
         let expected_projects = Vec::from([
             ConfigProject {
                 name: "blazeandcaves-advancements-pack".into(),
-                game_version: expected_version,
-                loader: ModLoader::Datapack,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Datapack],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "faithful-32x".into(),
-                game_version: expected_version,
-                loader: ModLoader::Minecraft,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Minecraft],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "iris".into(),
-                game_version: expected_version,
-                loader: ModLoader::Fabric,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Fabric],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
         ]);
         assert_eq!(
@@ -212,18 +1226,45 @@ mod tests {
         let expected_projects = Vec::from([
             ConfigProject {
                 name: "blazeandcaves-advancements-pack".into(),
-                game_version: expected_version,
-                loader: ModLoader::Datapack,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Datapack],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "faithful-32x".into(),
-                game_version: expected_version,
-                loader: ModLoader::Minecraft,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Minecraft],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "iris".into(),
-                game_version: expected_version,
-                loader: ModLoader::NeoForge,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::NeoForge],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
         ]);
         assert_eq!(
@@ -241,18 +1282,45 @@ mod tests {
         let expected_projects = Vec::from([
             ConfigProject {
                 name: "blazeandcaves-advancements-pack".into(),
-                game_version: expected_version,
-                loader: ModLoader::Datapack,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Datapack],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "faithful-32x".into(),
-                game_version: expected_version,
-                loader: ModLoader::Minecraft,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Minecraft],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "iris".into(),
-                game_version: expected_version,
-                loader: ModLoader::Fabric,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Fabric],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
         ]);
         assert_eq!(
@@ -261,6 +1329,534 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_project_lists() {
+        let config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = { game_version = ["1.21.5", "1.21.4"], loader = ["fabric", "quilt"] }
+            "#,
+        )
+        .expect("Config shall be able to parse lists of game versions and loaders");
+        assert_eq!(
+            config.projects(),
+            vec![ConfigProject {
+                name: "sodium".into(),
+                game_versions: vec!["1.21.5".into(), "1.21.4".into()],
+                loaders: vec![ModLoader::Fabric, ModLoader::Quilt],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
+            }],
+            "Config shall keep the game versions and loaders in order"
+        );
+    }
+
+    #[test]
+    fn test_add_project_entry() {
+        let text = "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\n\
+            [projects] # The mods\nsodium = {} # Faster rendering\n\n# Later\n\n\
+            [optional-projects]\nzoomify = {}\n";
+        assert_eq!(
+            add_project_entry(text, "lithium").expect("Project shall be added"),
+            text.replace(
+                "sodium = {} # Faster rendering\n",
+                "sodium = {} # Faster rendering\nlithium = {}\n"
+            ),
+            "The project shall be added after the last project, keeping comments"
+        );
+        let text = "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"";
+        let edited = add_project_entry(text, "sodium").expect("Project shall be added");
+        assert_eq!(
+            edited,
+            format!("{text}\n\n[projects]\nsodium = {{}}\n"),
+            "A projects table shall be added if there is none"
+        );
+        let config = Config::loads(&edited).expect("Edited config shall parse");
+        assert!(config.is_configured("sodium"));
+        assert_eq!(
+            add_project_entry("[projects]\n", "odd.name").expect("Project shall be added"),
+            "[projects]\n\"odd.name\" = {}\n",
+            "Names that are not bare keys shall be quoted"
+        );
+        let text = "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\
+            [ projects ]\nsodium = {}\n[projects.lithium]\nnote = \"Faster ticks\"\n";
+        let edited = add_project_entry(text, "iris").expect("Project shall be added");
+        let config = Config::loads(&edited).expect("Edited config shall parse");
+        assert!(
+            ["sodium", "lithium", "iris"]
+                .iter()
+                .all(|x| config.is_configured(x)),
+            "A project shall be added to a projects table written in any style, got {edited}"
+        );
+        assert!(
+            matches!(
+                add_project_entry("projects = 1\n", "iris"),
+                Err(Error::NotATable(_))
+            ),
+            "A projects value that is not a table shall not be edited"
+        );
+    }
+
+    #[test]
+    fn test_remove_project_entry() {
+        let text = "[projects] # The mods\nsodium = {} # Faster rendering\n\"iris\" = {}\n\n\
+            [projects.lithium]\nnote = \"Faster ticks\"\n\n\
+            [optional-projects]\nzoomify = {}\n";
+        assert_eq!(
+            remove_project_entry(text, "sodium").expect("Config shall parse"),
+            Some(text.replace("sodium = {} # Faster rendering\n", "")),
+            "The line of the project shall be removed, keeping comments"
+        );
+        assert_eq!(
+            remove_project_entry(text, "iris").expect("Config shall parse"),
+            Some(text.replace("\"iris\" = {}\n", ""))
+        );
+        assert_eq!(
+            remove_project_entry(text, "lithium").expect("Config shall parse"),
+            Some(text.replace("[projects.lithium]\nnote = \"Faster ticks\"\n\n", "")),
+            "A table of the project shall be removed"
+        );
+        assert_eq!(
+            remove_project_entry(text, "zoomify").expect("Config shall parse"),
+            Some(text.replace("zoomify = {}\n", "")),
+            "Optional projects shall be removed"
+        );
+        assert_eq!(
+            remove_project_entry(text, "note").expect("Config shall parse"),
+            None
+        );
+        let text = "[projects]\nsodium = { note = \"\"\"\nFaster\nlithium = {}\n\"\"\" }\n";
+        let edited = remove_project_entry(text, "lithium").expect("Config shall parse");
+        assert_eq!(
+            edited, None,
+            "Text in a string that looks like a project shall not be removed"
+        );
+    }
+
+    #[test]
+    fn test_resolution_hash() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = {}
+            "#;
+        let hash = |text: &str| {
+            Config::loads(text)
+                .expect("Config shall parse")
+                .resolution_hash()
+        };
+        assert_eq!(
+            hash(text),
+            hash(&text.replace("[defaults]", "# A comment\n[defaults]")),
+            "Comments shall not change the resolution hash"
+        );
+        assert_ne!(
+            hash(text),
+            hash(&text.replace("1.21.5", "1.21.4")),
+            "The game version shall change the resolution hash"
+        );
+        assert_ne!(
+            hash(text),
+            hash(&format!("{text}\nlithium = {{}}")),
+            "The projects shall change the resolution hash"
+        );
+        let with_script = |script: &str| {
+            let mut config = Config::loads(text).expect("Config shall parse");
+            config.hooks.loaded = Some(Arc::new(
+                Hooks::compile(script).expect("Script shall compile"),
+            ));
+            config.resolution_hash()
+        };
+        assert_ne!(
+            with_script("fn filter_version(version) { true }"),
+            with_script("fn filter_version(version) { false }"),
+            "The hook script shall change the resolution hash"
+        );
+    }
+
+    #[test]
+    fn test_install_mode() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [install]
+            mode = "reflink"
+
+            [projects]
+            "#;
+        let config = Config::loads(text).expect("Config shall be able to parse install settings");
+        assert_eq!(config.install.mode, CopyMode::Reflink);
+        let text = text.replace("mode = \"reflink\"", "");
+        assert_eq!(
+            Config::loads(&text)
+                .expect("Config shall parse")
+                .install
+                .mode,
+            CopyMode::Auto,
+            "Config shall use reflinks where supported by default"
+        );
+    }
+
+    #[test]
+    fn test_update_filters() {
+        let config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            featured-only = true
+
+            [update]
+            min-age = "3d"
+
+            [projects]
+            sodium = {}
+            iris = { featured-only = false }
+            "#,
+        )
+        .expect("Config shall be able to parse update settings");
+        assert_eq!(
+            config.update.min_age.and_then(|x| x.duration()),
+            Some(chrono::TimeDelta::days(3))
+        );
+        assert_eq!(
+            config
+                .projects()
+                .iter()
+                .map(|x| x.featured_only)
+                .collect::<Vec<_>>(),
+            vec![false, true],
+            "Config shall let projects override featured-only"
+        );
+        assert_eq!(
+            Age::try_from(String::from("12h"))
+                .ok()
+                .and_then(|x| x.duration()),
+            Some(chrono::TimeDelta::hours(12))
+        );
+        Age::try_from(String::from("3")).expect_err("Age shall require a unit");
+        Age::try_from(String::from("d")).expect_err("Age shall require a count");
+        let months = "18mo".parse::<Age>().expect("Age shall accept months");
+        assert_eq!(months.duration(), Some(chrono::TimeDelta::days(540)));
+        assert_eq!(
+            String::from(months),
+            "18mo",
+            "Age shall be written as it was read"
+        );
+        assert_eq!(
+            "2y".parse::<Age>().ok().and_then(|x| x.duration()),
+            Some(chrono::TimeDelta::days(730))
+        );
+        "4000000000y"
+            .parse::<Age>()
+            .expect_err("Age shall reject a length too long to represent");
+        "300000y"
+            .parse::<Age>()
+            .expect("Age shall accept a representable length")
+            .before(chrono::Utc::now().naive_utc())
+            .expect_err("Age shall not reach before the earliest representable time");
+    }
+
+    #[test]
+    fn test_byte_size() {
+        let size = |x: &str| x.parse::<ByteSize>().ok().map(|x| x.bytes());
+        assert_eq!(size("300MB"), Some(300_000_000));
+        assert_eq!(
+            size("1.5 GiB"),
+            Some(3 << 29),
+            "Sizes shall accept fractions"
+        );
+        assert_eq!(
+            size("64kib"),
+            Some(65536),
+            "Units shall not be case sensitive"
+        );
+        assert_eq!(
+            size("512"),
+            Some(512),
+            "Sizes without a unit shall be bytes"
+        );
+        assert_eq!(size("300 parsecs"), None);
+        assert_eq!(size("MB"), None);
+        assert_eq!(ByteSize::from(3 << 29).to_string(), "1536MiB");
+        let config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [download]
+            max-file-size = "300MB"
+            timeout = 120
+
+            [projects]
+            sodium = {}
+            distant-horizons = { max-file-size = "1GiB" }
+            big-shaders = { version-id = "v2", max-file-size = "2GiB" }
+            "#,
+        )
+        .expect("Config shall parse download limits");
+        assert_eq!(
+            config.download.max_file_size.map(|x| x.bytes()),
+            Some(300_000_000)
+        );
+        assert_eq!(
+            config.download.timeout(),
+            Some(std::time::Duration::from_secs(120))
+        );
+        let mod_db = locked_db(&[
+            ("uCdwusMi", "distant-horizons", "v1"),
+            ("HVnmMxH1", "complementary", "v2"),
+        ]);
+        assert_eq!(
+            config.max_file_sizes(&mod_db),
+            HashMap::from([
+                ("uCdwusMi".to_string().into(), 1 << 30),
+                ("HVnmMxH1".to_string().into(), 2 << 30)
+            ]),
+            "Projects shall set their own size limit, by the project they were resolved to"
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = {}
+
+            [groups.minimap]
+            default = true
+            projects.xaeros-minimap = {}
+
+            [groups.shaders]
+            description = "Shader packs"
+            projects.iris = {}
+            "#,
+        )
+        .expect("Config shall be able to parse groups");
+        let names = |config: &Config| {
+            config
+                .projects()
+                .into_iter()
+                .map(|x| x.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&config),
+            vec!["sodium", "xaeros-minimap"],
+            "Config shall include the default groups"
+        );
+        config
+            .select_groups(vec!["shaders".into()])
+            .expect("Config shall select a group");
+        assert_eq!(names(&config), vec!["iris", "sodium"]);
+        config
+            .select_groups(vec!["maps".into()])
+            .expect_err("Config shall reject an unknown group");
+    }
+
+    #[test]
+    fn test_requires() {
+        let config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            iris = { frozen = true }
+            complementary-reimagined = { loader = "iris", requires = ["iris"], note = "Bundled with iris" }
+            "#,
+        )
+        .expect("Config shall be able to parse requirements");
+        assert_eq!(
+            config.projects()[0].note.as_deref(),
+            Some("Bundled with iris"),
+            "Config shall keep the note of a project"
+        );
+        assert_eq!(
+            config.projects()[0].requires,
+            vec![ProjectSlug::from("iris")],
+            "Config shall list the projects a project requires"
+        );
+        assert!(config.projects()[1].requires.is_empty());
+        assert!(
+            config.projects()[1].frozen,
+            "Config shall parse frozen projects"
+        );
+    }
+
+    #[test]
+    fn test_project_side() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = { side = "client" }
+            lithium = { side = "both" }
+            ledger = { side = "server" }
+            "#;
+        let names = |target: Side| {
+            let mut config = Config::loads(text).expect("Config shall be able to parse sides");
+            config.defaults.target = target;
+            config
+                .projects()
+                .into_iter()
+                .map(|x| x.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(Side::Client),
+            vec!["lithium", "sodium"],
+            "Config shall skip server projects for the client"
+        );
+        assert_eq!(
+            names(Side::Server),
+            vec!["ledger", "lithium"],
+            "Config shall skip client projects for the server"
+        );
+    }
+
+    #[test]
+    fn test_apply_vars() {
+        create_test_paths();
+        let mut config = load_test_config();
+        config.paths.instances = vec!["alice".into()];
+        config.paths.apply_vars(|x| match x {
+            "MCMOD_DATA_DIR" => Some("/data".into()),
+            "MCMOD_MINECRAFT_DIR" => Some("/minecraft".into()),
+            _ => None,
+        });
+        assert_eq!(
+            config.paths.data,
+            PathBuf::from("/data"),
+            "Config shall take the data directory from the environment"
+        );
+        assert_eq!(
+            config.paths.dot_minecraft,
+            Some(PathBuf::from("/minecraft")),
+            "Config shall take the .minecraft directory from the environment"
+        );
+        assert!(
+            config.paths.instances.is_empty(),
+            "Config shall install into the .minecraft directory from the environment"
+        );
+    }
+
+    #[test]
+    fn test_install_dirs() {
+        let dir = PathBuf::from(".test/install_dirs");
+        std::fs::create_dir_all(&dir).expect("Failure to create test path");
+        let mut config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [paths]
+            launcher = "prism"
+            instances = ["alice", "bob"]
+
+            [projects]
+            "#,
+        )
+        .expect("Config shall parse instances");
+        assert_eq!(config.paths.instances, vec!["alice", "bob"]);
+        config.apply_overrides(&ConfigOverrides {
+            minecraft_dir: Some(dir.clone()),
+            ..Default::default()
+        });
+        assert_eq!(
+            config
+                .paths
+                .install_dirs(Side::Client)
+                .expect("Install directories shall be found"),
+            vec![dir],
+            "Overriding the .minecraft directory shall install only into it"
+        );
+    }
+
+    #[test]
+    fn test_install_names() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = { install-as = "sodium.jar" }
+            gvQqBUqZ = { install-as = "../../lithium.jar" }
+            friends-zoomify = { version-id = "v3", install-as = "zoom.jar" }
+            iris = {}
+            "#;
+        let config = Config::loads(text).expect("Config shall parse");
+        let mod_db = locked_db(&[
+            ("AANobbMI", "sodium", "v1"),
+            ("gvQqBUqZ", "lithium", "v2"),
+            ("w7ThoJFB", "zoomify", "v3"),
+            ("YL57xq9U", "iris", "v4"),
+        ]);
+        assert_eq!(
+            config.install_names(&mod_db),
+            HashMap::from([
+                ("AANobbMI".to_string().into(), "sodium.jar".into()),
+                ("gvQqBUqZ".to_string().into(), "lithium.jar".into()),
+                ("w7ThoJFB".to_string().into(), "zoom.jar".into())
+            ]),
+            "Install names shall only be file names, by the project each entry was resolved to"
+        );
+    }
+
+    #[test]
+    fn test_server_properties() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+
+            [server.properties]
+            motd = "Modded {loader} {game_version}"
+            max-players = 8
+            pvp = false
+            "#;
+        let config = Config::loads(text).expect("Config shall parse");
+        assert_eq!(
+            config.server.properties(&config.defaults),
+            BTreeMap::from([
+                ("max-players".into(), "8".into()),
+                ("motd".into(), "Modded fabric 1.21.5".into()),
+                ("pvp".into(), "false".into()),
+            ]),
+            "Server properties shall be written as text with variables replaced"
+        );
+    }
+
     #[test]
     fn test_get_optional_projects() {
         create_test_paths();
@@ -270,13 +1866,31 @@ mod tests {
         let expected_projects = Vec::from([
             ConfigProject {
                 name: "camps_castles_carriages".into(),
-                game_version: expected_version,
-                loader: ModLoader::Fabric,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Fabric],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
             ConfigProject {
                 name: "lithium".into(),
-                game_version: expected_version,
-                loader: ModLoader::Fabric,
+                game_versions: vec![expected_version],
+                loaders: vec![ModLoader::Fabric],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: false,
+                game_version_tolerance: 0,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
             },
         ]);
         assert_eq!(
@@ -284,4 +1898,108 @@ mod tests {
             "Config shall return projects with the default game version and mod loader."
         );
     }
+
+    #[test]
+    fn test_overrides() {
+        let text = r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = {}
+            iris = { game_version = ["1.21.5", "1.21.4"], loader = "quilt" }
+            "#;
+        let mut config = Config::loads(text).expect("Config shall parse");
+        config.apply_overrides(&ConfigOverrides {
+            game_version: Some("1.20.1".into()),
+            loader: Some(ModLoader::NeoForge),
+            target: Some(Side::Server),
+            data_dir: Some(".test/overrides/data".into()),
+            minecraft_dir: Some(".test/overrides/.minecraft".into()),
+            ..Default::default()
+        });
+        assert_eq!(config.defaults.game_version, "1.20.1".into());
+        assert_eq!(config.defaults.target, Side::Server);
+        assert_eq!(
+            (
+                config.paths.data.as_path(),
+                config.paths.dot_minecraft.as_deref()
+            ),
+            (
+                std::path::Path::new(".test/overrides/data"),
+                Some(std::path::Path::new(".test/overrides/.minecraft"))
+            ),
+            "Config shall use the overridden directories"
+        );
+        let projects = config.projects();
+        assert_eq!(
+            (&projects[0].game_versions, &projects[0].loaders),
+            (
+                &vec!["1.21.5".into(), "1.21.4".into()],
+                &vec![ModLoader::Quilt]
+            ),
+            "Overriding the defaults shall keep the values set for a project"
+        );
+        assert_eq!(
+            (&projects[1].game_versions, &projects[1].loaders),
+            (&vec!["1.20.1".into()], &vec![ModLoader::NeoForge]),
+            "Overriding the defaults shall apply to projects without their own values"
+        );
+
+        let mut config = Config::loads(text).expect("Config shall parse");
+        config.apply_overrides(&ConfigOverrides {
+            game_version: Some("1.20.1".into()),
+            game_version_all: Some("1.21.1".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            config.defaults.game_version,
+            "1.21.1".into(),
+            "The game version for all projects shall take precedence"
+        );
+        assert!(
+            config
+                .projects()
+                .iter()
+                .all(|x| x.game_versions == vec!["1.21.1".into()]),
+            "The game version for all projects shall replace the values set for a project"
+        );
+    }
+
+    #[test]
+    fn test_resolve_latest() {
+        let text = r#"
+            [defaults]
+            game_version = "latest"
+            loader = "fabric"
+
+            [projects]
+            sodium = {}
+            iris = { game_version = ["latest-snapshot", "1.21.4"] }
+            "#;
+        let mut config = Config::loads(text).expect("Config shall parse");
+        let mut calls = 0;
+        config
+            .resolve_latest(|x| {
+                calls += 1;
+                Ok(match x {
+                    MinecraftVersion::LatestSnapshot => "25w15a".into(),
+                    _ => "1.21.5".into(),
+                })
+            })
+            .expect("Resolving shall not fail");
+        assert_eq!(calls, 2, "Only keywords shall be resolved");
+        assert_eq!(config.defaults.game_version, "1.21.5".into());
+        let mut projects = config.projects();
+        projects.sort_by_key(|x| x.name.to_string());
+        assert_eq!(
+            (&projects[0].game_versions, &projects[1].game_versions),
+            (
+                &vec!["25w15a".into(), "1.21.4".into()],
+                &vec!["1.21.5".into()]
+            ),
+            "Config shall replace the keywords of every project"
+        );
+    }
 }
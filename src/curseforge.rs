@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::labrinth;
+use crate::zipwrite;
+
+/// The url of the CurseForge API
+const CURSEFORGE_URL: &str = "https://api.curseforge.com";
+
+/// The id of Minecraft in the CurseForge API
+const MINECRAFT_GAME_ID: u32 = 432;
+
+/// The environment variable holding the key of the CurseForge API, which it requires
+pub const API_KEY_VAR: &str = "CURSEFORGE_API_KEY";
+
+/// The directory of a modpack whose files are copied into the instance as is
+const OVERRIDES: &str = "overrides";
+
+/// Compute the fingerprint CurseForge identifies a file by: MurmurHash2 with seed 1 of the bytes
+/// of the file without tabs, line breaks, and spaces
+pub fn fingerprint(bytes: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    let data = bytes
+        .iter()
+        .copied()
+        .filter(|x| !matches!(x, b'\t' | b'\n' | b'\r' | b' '))
+        .collect::<Vec<_>>();
+    let mut hash = 1 ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().expect("Chunk has 4 bytes"));
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        hash = hash.wrapping_mul(M) ^ k;
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (index, byte) in tail.iter().enumerate() {
+            hash ^= (*byte as u32) << (8 * index);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^ (hash >> 15)
+}
+
+/// A file on CurseForge, by the id of its project and its own id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurseFile {
+    pub project_id: u64,
+    pub file_id: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct FingerprintResponse {
+    data: FingerprintMatches,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FingerprintMatches {
+    exact_matches: Vec<FingerprintMatch>,
+}
+
+#[derive(serde::Deserialize)]
+struct FingerprintMatch {
+    /// The id of the project
+    id: u64,
+    file: MatchedFile,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchedFile {
+    id: u64,
+    file_fingerprint: u32,
+}
+
+/// Find the files on CurseForge that exactly match fingerprints, by fingerprint
+pub fn match_fingerprints(
+    client: &labrinth::Client,
+    api_key: &str,
+    fingerprints: &[u32],
+) -> Result<HashMap<u32, CurseFile>> {
+    let body = client.post_json(
+        &format!("{CURSEFORGE_URL}/v1/fingerprints/{MINECRAFT_GAME_ID}"),
+        &[("x-api-key", api_key)],
+        &serde_json::json!({ "fingerprints": fingerprints }),
+    )?;
+    let response = serde_json::from_slice::<FingerprintResponse>(&body)?;
+    Ok(response
+        .data
+        .exact_matches
+        .into_iter()
+        .map(|x| {
+            (
+                x.file.file_fingerprint,
+                CurseFile {
+                    project_id: x.id,
+                    file_id: x.file.id,
+                },
+            )
+        })
+        .collect())
+}
+
+/// A modpack in the format of CurseForge: a manifest of the files to download from CurseForge, and
+/// overrides that are copied into the instance
+#[derive(Debug, Default)]
+pub struct Modpack {
+    pub name: String,
+    pub version: String,
+    pub author: String,
+    pub game_version: String,
+    /// The mod loader and its version, like `fabric-0.16.14`
+    pub loader: String,
+    pub files: Vec<CurseFile>,
+    /// The files bundled in the overrides, by their path in the instance with `/` between
+    /// directories
+    pub overrides: Vec<(String, Vec<u8>)>,
+}
+
+impl Modpack {
+    /// Construct the manifest of the modpack
+    fn manifest(&self) -> serde_json::Value {
+        serde_json::json!({
+            "minecraft": {
+                "version": self.game_version,
+                "modLoaders": [{ "id": self.loader, "primary": true }],
+            },
+            "manifestType": "minecraftModpack",
+            "manifestVersion": 1,
+            "name": self.name,
+            "version": self.version,
+            "author": self.author,
+            "files": self.files.iter().map(|x| serde_json::json!({
+                "projectID": x.project_id,
+                "fileID": x.file_id,
+                "required": true,
+            })).collect::<Vec<_>>(),
+            "overrides": OVERRIDES,
+        })
+    }
+
+    /// Write the modpack as a zip archive
+    pub fn to_zip(&self) -> Result<Vec<u8>> {
+        let mut entries = vec![(
+            "manifest.json".to_string(),
+            serde_json::to_vec_pretty(&self.manifest())?,
+        )];
+        entries.extend(
+            self.overrides
+                .iter()
+                .map(|(path, bytes)| (format!("{OVERRIDES}/{path}"), bytes.clone())),
+        );
+        zipwrite::write_zip(&entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{FixtureMode, Fixtures};
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(b""), 1540447798);
+        assert_eq!(fingerprint(b"abcdefg"), 184182053);
+        assert_eq!(fingerprint(b"Hello, world!"), 2173663876);
+        assert_eq!(
+            fingerprint(b"Hello,\r\n world!\t"),
+            fingerprint(b"Hello, world!"),
+            "Whitespace shall not change the fingerprint"
+        );
+    }
+
+    #[test]
+    fn test_modpack() {
+        let dir = std::path::Path::new(".test/curseforge");
+        let _ = std::fs::remove_dir_all(dir);
+        let fingerprints = [fingerprint(b"sodium"), fingerprint(b"unknown")];
+        let url = format!("{CURSEFORGE_URL}/v1/fingerprints/{MINECRAFT_GAME_ID}");
+        let body = serde_json::to_vec(&serde_json::json!({ "fingerprints": fingerprints }))
+            .expect("JSON values always serialize");
+        Fixtures::new(dir.to_path_buf(), FixtureMode::Record)
+            .get(
+                &format!("{url}#{}", crate::verify::hash_bytes(&body).sha1),
+                || {
+                    Ok(serde_json::json!({"data": {"exactMatches": [
+                        {"id": 394468, "file": {"id": 6431050, "fileFingerprint": fingerprints[0]}}
+                    ]}})
+                    .to_string()
+                    .into_bytes())
+                },
+            )
+            .expect("Fixtures shall record the answer");
+        let client = labrinth::Client::new()
+            .with_fixtures(Some(Fixtures::new(dir.to_path_buf(), FixtureMode::Replay)));
+        let matches =
+            match_fingerprints(&client, "key", &fingerprints).expect("Fingerprints shall match");
+        assert_eq!(
+            matches,
+            HashMap::from([(
+                fingerprints[0],
+                CurseFile {
+                    project_id: 394468,
+                    file_id: 6431050
+                }
+            )]),
+            "Only files on CurseForge shall match"
+        );
+
+        let modpack = Modpack {
+            name: "Pack".into(),
+            version: "1.0.0".into(),
+            game_version: "1.21.5".into(),
+            loader: "fabric-0.16.14".into(),
+            files: matches.into_values().collect(),
+            overrides: vec![("mods/unknown.jar".into(), b"unknown".to_vec())],
+            ..Default::default()
+        };
+        let manifest = modpack.manifest();
+        assert_eq!(
+            manifest["minecraft"]["modLoaders"][0]["id"],
+            "fabric-0.16.14"
+        );
+        assert_eq!(
+            manifest["files"][0],
+            serde_json::json!({"projectID": 394468, "fileID": 6431050, "required": true})
+        );
+        let zip = modpack.to_zip().expect("Modpack shall be zipped");
+        assert!(
+            zip.windows(b"overrides/mods/unknown.jar".len())
+                .any(|x| x == b"overrides/mods/unknown.jar"),
+            "Files not on CurseForge shall be bundled in the overrides"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::Watcher;
+
+use crate::error::{Error, Result};
+use crate::fsutil::{self, CopyMode};
+use crate::i18n::tr;
+use crate::interrupt;
+use crate::output::status;
+
+/// How long a changed jar must stay unchanged before it is installed, so it is not copied while
+/// the build is still writing it
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// How often to check for Ctrl-C while waiting for changes
+const POLL: Duration = Duration::from_millis(200);
+
+/// The size and modification time of a file, to tell if it changed
+fn stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Jars built by a mod developer, installed into the mods directories of instances apart from
+/// the rest of the pack
+#[derive(Debug)]
+pub struct DevJars {
+    /// The jars, by their absolute path
+    jars: Vec<PathBuf>,
+    /// The directories the jars are installed into
+    mods_dirs: Vec<PathBuf>,
+    /// How the jars are copied into the mods directories
+    mode: CopyMode,
+    /// The stamp of each jar when it was last installed
+    installed: HashMap<PathBuf, (u64, SystemTime)>,
+}
+
+impl DevJars {
+    /// Construct the jars to install into mods directories. The jars must exist.
+    pub fn new(jars: &[PathBuf], mods_dirs: Vec<PathBuf>, mode: CopyMode) -> Result<Self> {
+        let jars = jars
+            .iter()
+            .map(|x| {
+                let name = x.file_name().ok_or_else(|| {
+                    Error::IO(std::io::Error::other(format!("{x:?} is not a file")))
+                })?;
+                let parent = match x.parent() {
+                    Some(p) if !p.as_os_str().is_empty() => p,
+                    _ => Path::new("."),
+                };
+                Ok(std::fs::canonicalize(parent)?.join(name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(x) = jars.iter().find(|x| !x.is_file()) {
+            return Err(Error::IO(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{x:?} does not exist"),
+            )));
+        }
+        Ok(Self {
+            jars,
+            mods_dirs,
+            mode,
+            installed: HashMap::new(),
+        })
+    }
+
+    /// Get the jars among the paths of a change
+    fn changed(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        self.jars
+            .iter()
+            .filter(|x| paths.contains(x))
+            .cloned()
+            .collect()
+    }
+
+    /// Install a jar into every mods directory, unless it is missing or did not change since it
+    /// was last installed. Returns whether it was installed.
+    fn install(&mut self, jar: &Path) -> Result<bool> {
+        let Some(stamp) = stamp(jar) else {
+            return Ok(false);
+        };
+        if self.installed.get(jar) == Some(&stamp) {
+            return Ok(false);
+        }
+        let name = jar.file_name().expect("Jars are files");
+        for dir in &self.mods_dirs {
+            std::fs::create_dir_all(dir)?;
+            let path = dir.join(name);
+            fsutil::copy_file(jar, &path, self.mode)?;
+            status!(
+                "{}",
+                tr!("dev-installed", path = path.display().to_string())
+            );
+        }
+        self.installed.insert(jar.to_path_buf(), stamp);
+        Ok(true)
+    }
+
+    /// Install the jars, then install each again whenever it changes, until Ctrl-C
+    pub fn watch(mut self) -> Result<()> {
+        for jar in self.jars.clone() {
+            self.install(&jar)?;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let mut dirs = self
+            .jars
+            .iter()
+            .filter_map(|x| x.parent())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+        }
+        status!("{}", tr!("dev-watching", count = self.jars.len()));
+        let mut pending = HashMap::<PathBuf, Instant>::new();
+        while interrupt::check().is_ok() {
+            match receiver.recv_timeout(POLL) {
+                Ok(event) => {
+                    for jar in self.changed(&event?.paths) {
+                        pending.insert(jar, Instant::now());
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            let settled = pending
+                .iter()
+                .filter(|(_, at)| at.elapsed() >= SETTLE)
+                .map(|(jar, _)| jar.clone())
+                .collect::<Vec<_>>();
+            for jar in settled {
+                pending.remove(&jar);
+                self.install(&jar)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install() {
+        let dir = Path::new(".test/dev");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir.join("build")).expect("Failure to create test directory");
+        let jar = dir.join("build/mymod.jar");
+        std::fs::write(&jar, "1").expect("Failure to write jar");
+        let mods = vec![dir.join("a/mods"), dir.join("b/mods")];
+        let mut jars = DevJars::new(std::slice::from_ref(&jar), mods.clone(), CopyMode::Copy)
+            .expect("Jar shall exist");
+        let jar = jars.jars[0].clone();
+        assert_eq!(
+            jars.changed(&[jar.clone(), jar.with_file_name("other.jar")]),
+            vec![jar.clone()],
+            "Only changes of the jars shall be noticed"
+        );
+        assert!(jars.install(&jar).expect("Jar shall be installed"));
+        for dir in &mods {
+            assert_eq!(
+                std::fs::read_to_string(dir.join("mymod.jar"))
+                    .ok()
+                    .as_deref(),
+                Some("1"),
+                "The jar shall be installed into every mods directory"
+            );
+        }
+        assert!(
+            !jars.install(&jar).expect("Jar shall be checked"),
+            "An unchanged jar shall not be installed again"
+        );
+        std::fs::write(&jar, "22").expect("Failure to write jar");
+        assert!(jars.install(&jar).expect("Jar shall be installed"));
+        assert_eq!(
+            std::fs::read_to_string(mods[0].join("mymod.jar"))
+                .ok()
+                .as_deref(),
+            Some("22"),
+            "A changed jar shall be installed again"
+        );
+        assert!(
+            DevJars::new(&[dir.join("build/missing.jar")], mods, CopyMode::Copy).is_err(),
+            "Jars that do not exist shall be rejected"
+        );
+    }
+}
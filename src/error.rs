@@ -7,6 +7,10 @@ pub enum Error {
     #[allow(dead_code)]
     TomlParse(toml::de::Error),
     #[allow(dead_code)]
+    TomlSerialize(toml::ser::Error),
+    #[allow(dead_code)]
+    TomlEdit(toml_edit::TomlError),
+    #[allow(dead_code)]
     JsonParse(serde_json::Error),
     #[allow(dead_code)]
     ChronoParse(chrono::ParseError),
@@ -20,6 +24,156 @@ pub enum Error {
     InvalidMinecraftVersion(String),
     #[allow(dead_code)]
     LocalCacheMiss { key: String, msg: String },
+    #[allow(dead_code)]
+    UnknownGameVersion(String),
+    #[allow(dead_code)]
+    NoCompatibleLoader {
+        project: String,
+        loaders: Vec<String>,
+    },
+    #[allow(dead_code)]
+    MissingDirectory(std::path::PathBuf),
+    #[allow(dead_code)]
+    FileLocked(std::path::PathBuf),
+    #[allow(dead_code)]
+    LauncherNotFound(String),
+    #[allow(dead_code)]
+    InstanceNotFound {
+        launcher: String,
+        instance: String,
+        available: Vec<String>,
+    },
+    #[allow(dead_code)]
+    Git(String),
+    #[allow(dead_code)]
+    FixtureNotFound(String),
+    #[allow(dead_code)]
+    Unresolved(Box<Failure>),
+    #[allow(dead_code)]
+    ProjectNotFound(String),
+    #[allow(dead_code)]
+    FileCollision {
+        path: std::path::PathBuf,
+        versions: Vec<String>,
+    },
+    #[allow(dead_code)]
+    UnknownGroup(String),
+    #[allow(dead_code)]
+    InvalidAge(String),
+    #[allow(dead_code)]
+    UnknownTemplate {
+        name: String,
+        available: Vec<String>,
+    },
+    #[allow(dead_code)]
+    InstallFailed(Vec<std::path::PathBuf>),
+    #[allow(dead_code)]
+    DownloadFailed(Vec<String>),
+    #[allow(dead_code)]
+    Interrupted,
+    #[allow(dead_code)]
+    RangeNotSupported(String),
+    #[allow(dead_code)]
+    HashMismatch(String),
+    #[allow(dead_code)]
+    NothingToResume(std::path::PathBuf),
+    #[allow(dead_code)]
+    InvalidWorld {
+        path: std::path::PathBuf,
+        reason: String,
+    },
+    #[allow(dead_code)]
+    Script(String),
+    #[allow(dead_code)]
+    HookRejected(Vec<String>),
+    #[allow(dead_code)]
+    UnknownBackend {
+        name: String,
+        available: Vec<String>,
+    },
+    #[allow(dead_code)]
+    UnknownProfile {
+        name: String,
+        available: Vec<String>,
+    },
+    #[allow(dead_code)]
+    AdoptFailed(String),
+    #[allow(dead_code)]
+    UnsupportedPlan(u64),
+    #[allow(dead_code)]
+    PlanMismatch(String),
+    #[allow(dead_code)]
+    Advised(Vec<String>),
+    #[allow(dead_code)]
+    UnknownHashes(Vec<String>),
+    #[allow(dead_code)]
+    InvalidChannel(String),
+    #[allow(dead_code)]
+    PromoteRejected(Vec<String>),
+    #[allow(dead_code)]
+    InvalidSize(String),
+    #[allow(dead_code)]
+    FileTooLarge { file: String, limit: u64 },
+    #[allow(dead_code)]
+    DownloadTimeout { file: String, timeout: u64 },
+    #[allow(dead_code)]
+    LockOutdated(std::path::PathBuf),
+    #[allow(dead_code)]
+    Frozen(String),
+    #[allow(dead_code)]
+    Watch(notify::Error),
+    #[allow(dead_code)]
+    RequirementsUnmet {
+        project: String,
+        requires: Vec<String>,
+    },
+    #[allow(dead_code)]
+    InvalidProjectId(String),
+    #[allow(dead_code)]
+    InvalidLockfile(String),
+    #[allow(dead_code)]
+    NotATable(String),
+}
+
+/// A tree explaining why something could not be resolved
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Failure {
+    /// What was being resolved, like a project or a dependency edge
+    pub subject: String,
+    /// Why each candidate was rejected
+    pub reasons: Vec<String>,
+    /// The failures that caused this one
+    pub causes: Vec<Failure>,
+}
+
+impl Failure {
+    /// Construct a failure with no reasons
+    pub fn new(subject: String) -> Self {
+        Self {
+            subject,
+            ..Default::default()
+        }
+    }
+
+    /// Format the failure and its causes, indented by depth
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(f, "{indent}{}", self.subject)?;
+        for reason in &self.reasons {
+            write!(f, "\n{indent}  - {reason}")?;
+        }
+        for cause in &self.causes {
+            writeln!(f)?;
+            cause.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
 }
 
 impl std::fmt::Display for Error {
@@ -27,6 +181,8 @@ impl std::fmt::Display for Error {
         match &self {
             Error::IO(error) => write!(f, "IO: {error:?}"),
             Error::TomlParse(error) => write!(f, "TOML: {error:?}"),
+            Error::TomlSerialize(error) => write!(f, "TOML: {error:?}"),
+            Error::TomlEdit(error) => write!(f, "TOML: {error:?}"),
             Error::JsonParse(error) => write!(f, "JSON: {error:?}"),
             Error::ChronoParse(error) => write!(f, "chrono: {error:?}"),
             Error::Request(error) => write!(f, "Request: {error:?}"),
@@ -34,6 +190,130 @@ impl std::fmt::Display for Error {
             Error::InvalidLoader(x) => write!(f, "Invalid loader {x:?}"),
             Error::InvalidMinecraftVersion(x) => write!(f, "Invalid minecraft version {x:?}"),
             Error::LocalCacheMiss { key, msg } => write!(f, "Not in local cache: {msg}: {key:?}"),
+            Error::UnknownGameVersion(x) => write!(f, "Unknown minecraft version {x:?}"),
+            Error::NoCompatibleLoader { project, loaders } => write!(
+                f,
+                "No compatible loader for {project:?}, it supports {loaders:?}"
+            ),
+            Error::MissingDirectory(x) => write!(f, "Directory does not exist {x:?}"),
+            Error::FileLocked(x) => write!(
+                f,
+                "File is in use by another program (a sync client like OneDrive?) {x:?}"
+            ),
+            Error::LauncherNotFound(x) => write!(f, "Could not locate the data of launcher {x:?}"),
+            Error::InstanceNotFound {
+                launcher,
+                instance,
+                available,
+            } => write!(
+                f,
+                "Could not find instance {instance:?} of {launcher}, available: {available:?}"
+            ),
+            Error::Git(x) => write!(f, "git: {x}"),
+            Error::FixtureNotFound(x) => write!(f, "No recorded response for {x:?}"),
+            Error::Unresolved(x) => write!(f, "Could not resolve {x}"),
+            Error::FileCollision { path, versions } => write!(
+                f,
+                "Files of {versions:?} would be installed to {path:?}, see install.collisions"
+            ),
+            Error::ProjectNotFound(x) => {
+                write!(f, "No project named {x:?}, is the slug spelled correctly?")
+            }
+            Error::UnknownGroup(x) => write!(f, "No group named {x:?} in the config"),
+            Error::InvalidSize(x) => write!(
+                f,
+                "Invalid size {x:?}, expected a number of bytes with a unit like \"300MB\" or \"1GiB\""
+            ),
+            Error::FileTooLarge { file, limit } => write!(
+                f,
+                "{file:?} is larger than the limit of {}",
+                crate::config::ByteSize::from(*limit)
+            ),
+            Error::DownloadTimeout { file, timeout } => {
+                write!(f, "Downloading {file:?} took longer than {timeout} seconds")
+            }
+            Error::LockOutdated(x) => write!(
+                f,
+                "Lockfile {x:?} is missing or not up to date with the config, resolve without --locked or --frozen to update it"
+            ),
+            Error::Frozen(x) => write!(
+                f,
+                "Metadata of {x:?} is not cached and --frozen forbids fetching it"
+            ),
+            Error::Watch(x) => write!(f, "Could not watch for changes: {x}"),
+            Error::InvalidProjectId(x) => write!(f, "Invalid project id {x:?}"),
+            Error::InvalidLockfile(x) => write!(f, "The locked projects have an unsafe {x}"),
+            Error::NotATable(x) => write!(f, "The config has a {x} that is not a table"),
+            Error::RequirementsUnmet { project, requires } => write!(
+                f,
+                "{project:?} requires {requires:?}, which were not resolved"
+            ),
+            Error::InvalidAge(x) => write!(
+                f,
+                "Invalid age {x:?}, expected a number of hours, days, weeks, months, or years like \"3d\" or \"18mo\""
+            ),
+            Error::UnknownTemplate { name, available } => {
+                write!(f, "No template named {name:?}, available: {available:?}")
+            }
+            Error::InstallFailed(x) => write!(f, "Could not install into {x:?}"),
+            Error::DownloadFailed(x) => write!(f, "Could not download {x:?}"),
+            Error::Interrupted => write!(f, "Interrupted"),
+            Error::HashMismatch(x) => {
+                write!(f, "The download of {x:?} does not match its hashes")
+            }
+            Error::RangeNotSupported(x) => {
+                write!(f, "The server does not support range requests for {x:?}")
+            }
+            Error::NothingToResume(x) => {
+                write!(f, "No interrupted run to resume, {x:?} does not exist")
+            }
+            Error::UnknownBackend { name, available } => write!(
+                f,
+                "No registry backend named {name:?} in this build, available: {available:?}"
+            ),
+            Error::InvalidWorld { path, reason } => {
+                write!(f, "Could not read the world {path:?}: {reason}")
+            }
+            Error::UnknownProfile { name, available } => {
+                write!(f, "No profile named {name:?}, available: {available:?}")
+            }
+            Error::AdoptFailed(x) => write!(f, "Could not adopt the config: {x}"),
+            Error::UnsupportedPlan(x) => write!(
+                f,
+                "The plan has format {x}, but this version of mcmod applies format {}",
+                crate::plan::PLAN_FORMAT
+            ),
+            Error::PlanMismatch(x) => write!(f, "The plan does not fit the config: {x}"),
+            Error::Script(x) => write!(f, "Hook script: {x}"),
+            Error::InvalidChannel(x) => write!(
+                f,
+                "Invalid channel {x:?}, use only letters, digits, `-`, and `_`"
+            ),
+            Error::UnknownHashes(x) => write!(
+                f,
+                "Refusing to install files that no allowlist or reputation service knows: {x:?}"
+            ),
+            Error::Advised(x) => {
+                write!(f, "Refusing to install projects with security advisories:")?;
+                for problem in x {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+            Error::PromoteRejected(x) => {
+                write!(f, "Refusing to promote the lockfile:")?;
+                for problem in x {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+            Error::HookRejected(x) => {
+                write!(f, "The hook script rejected the resolved projects:")?;
+                for problem in x {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -42,6 +322,16 @@ impl Error {
     pub fn invalid_loader(s: &str) -> Self {
         Error::InvalidLoader(s.to_string())
     }
+
+    /// Explain this error as the cause of failing to resolve a subject
+    pub fn context(self, subject: String) -> Self {
+        let mut failure = Failure::new(subject);
+        match self {
+            Error::Unresolved(cause) => failure.causes.push(*cause),
+            e => failure.reasons.push(e.to_string()),
+        }
+        Error::Unresolved(Box::new(failure))
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -56,12 +346,30 @@ impl From<toml::de::Error> for Error {
     }
 }
 
+impl From<toml::ser::Error> for Error {
+    fn from(value: toml::ser::Error) -> Self {
+        Error::TomlSerialize(value)
+    }
+}
+
+impl From<toml_edit::TomlError> for Error {
+    fn from(value: toml_edit::TomlError) -> Self {
+        Error::TomlEdit(value)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(value: reqwest::Error) -> Self {
         Error::Request(value)
     }
 }
 
+impl From<notify::Error> for Error {
+    fn from(value: notify::Error) -> Self {
+        Error::Watch(value)
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
         Error::JsonParse(value)
@@ -73,3 +381,30 @@ impl From<chrono::ParseError> for Error {
         Error::ChronoParse(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context() {
+        let mut failure = Failure::new("project lithium".into());
+        failure
+            .reasons
+            .push("1.21.5 with fabric: no versions".into());
+        let error = Error::Unresolved(Box::new(failure))
+            .context("dependency project gvQqBUqZ of Sodium 0.6".into())
+            .context("project sodium".into());
+        assert_eq!(
+            error.to_string(),
+            "Could not resolve project sodium\n  dependency project gvQqBUqZ of Sodium 0.6\n    \
+            project lithium\n      - 1.21.5 with fabric: no versions",
+            "Error shall explain each step of a failed resolution"
+        );
+        let Error::Unresolved(failure) = Error::invalid_loader("x").context("project x".into())
+        else {
+            panic!("Error shall become a failure with context");
+        };
+        assert_eq!(failure.reasons, vec!["Invalid loader \"x\"".to_string()]);
+    }
+}
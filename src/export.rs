@@ -0,0 +1,428 @@
+use std::collections::BTreeMap;
+
+use crate::lockfile::{LockedFile, LockedProject, Lockfile};
+use crate::types::ProjectStats;
+
+/// The formats the resolved projects can be exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// A CycloneDX software bill of materials in JSON
+    Sbom,
+    /// An inventory with a row for each file, for spreadsheets
+    Csv,
+    /// An inventory with an object for each file
+    Json,
+    /// A CurseForge modpack, a zip of a manifest and overrides
+    Curseforge,
+    /// A static HTML page listing the projects with their icons and links, for players
+    Html,
+}
+
+/// Export the locked projects in a format, with the stats of the projects by id where known.
+/// Formats that name the pack use `name`.
+pub fn export(
+    lock: &Lockfile,
+    stats: &BTreeMap<String, ProjectStats>,
+    format: ExportFormat,
+    name: &str,
+) -> String {
+    match format {
+        ExportFormat::Sbom => {
+            serde_json::to_string_pretty(&sbom(lock)).expect("JSON values always serialize")
+        }
+        ExportFormat::Csv => inventory_csv(&inventory(lock, stats)),
+        ExportFormat::Json => serde_json::to_string_pretty(&inventory(lock, stats))
+            .expect("JSON values always serialize"),
+        ExportFormat::Curseforge => unreachable!("CurseForge modpacks are zipped with their files"),
+        ExportFormat::Html => html(lock, name),
+    }
+}
+
+/// Escape text for HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Write a static page listing the locked projects by name, with their icons, versions, and
+/// links to their pages, for players to see what is installed
+pub fn html(lock: &Lockfile, title: &str) -> String {
+    let mut projects = lock.projects.iter().collect::<Vec<_>>();
+    projects.sort_by_key(|x| x.name.to_lowercase());
+    let mut rows = String::new();
+    for project in projects {
+        let icon = match &project.icon_url {
+            Some(url) => format!(
+                r#"<img src="{}" alt="" width="48" height="48">"#,
+                escape_html(url)
+            ),
+            None => String::new(),
+        };
+        let note = match &project.note {
+            Some(note) => format!(r#"<p class="note">{}</p>"#, escape_html(note)),
+            None => String::new(),
+        };
+        rows.push_str(&format!(
+            "<tr><td>{icon}</td><td><a href=\"{}\">{}</a>{note}</td><td><a href=\"{}\">{}</a></td><td>{}</td></tr>\n",
+            escape_html(&project.project_url()),
+            escape_html(&project.name),
+            escape_html(&project.changelog_url()),
+            escape_html(&project.version_number),
+            project.loader.map(|x| x.to_string()).unwrap_or_default(),
+        ));
+    }
+    let title = escape_html(title);
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td {{ padding: 0.4em; border-bottom: 1px solid #ddd; vertical-align: middle; }}
+img {{ border-radius: 8px; }}
+.note {{ margin: 0.2em 0 0; color: #666; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{} projects</p>
+<table>
+{rows}</table>
+</body>
+</html>
+"#,
+        lock.projects.len()
+    )
+}
+
+/// One file of a locked project in an inventory
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+struct InventoryRow<'a> {
+    slug: &'a str,
+    name: &'a str,
+    version: &'a str,
+    loader: String,
+    license: &'a str,
+    registry: &'a str,
+    file: &'a str,
+    size: u64,
+    url: &'a str,
+    sha1: &'a str,
+    sha512: &'a str,
+    reasons: String,
+    note: &'a str,
+    icon_url: &'a str,
+    downloads: Option<u64>,
+    followers: Option<u64>,
+    updated: String,
+}
+
+/// List every file of the locked projects, with missing values left empty
+fn inventory<'a>(
+    lock: &'a Lockfile,
+    stats: &'a BTreeMap<String, ProjectStats>,
+) -> Vec<InventoryRow<'a>> {
+    lock.projects
+        .iter()
+        .flat_map(|project| {
+            let stats = stats.get(&project.project_id);
+            project.files.iter().map(move |file| {
+                let hash = |alg: &str| file.hashes.get(alg).map_or("", String::as_str);
+                InventoryRow {
+                    slug: &project.slug,
+                    name: &project.name,
+                    version: &project.version_number,
+                    loader: project.loader.map(|x| x.to_string()).unwrap_or_default(),
+                    license: project.license.as_deref().unwrap_or_default(),
+                    registry: &project.registry,
+                    file: &file.name,
+                    size: file.size,
+                    url: &file.url,
+                    sha1: hash("sha1"),
+                    sha512: hash("sha512"),
+                    reasons: project.reasons.join("; "),
+                    note: project.note.as_deref().unwrap_or_default(),
+                    icon_url: project.icon_url.as_deref().unwrap_or_default(),
+                    downloads: stats.map(|x| x.downloads),
+                    followers: stats.map(|x| x.followers),
+                    updated: stats
+                        .and_then(|x| x.updated)
+                        .map(|x| x.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default(),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Write an inventory as CSV with a header row
+fn inventory_csv(rows: &[InventoryRow]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::<u8>::new());
+    for row in rows {
+        writer
+            .serialize(row)
+            .expect("Inventory rows always serialize");
+    }
+    let bytes = writer
+        .into_inner()
+        .expect("Writing to memory does not fail");
+    String::from_utf8(bytes).expect("Inventory rows are UTF-8")
+}
+
+/// Map a Modrinth hash algorithm to its CycloneDX name
+fn cyclonedx_alg(alg: &str) -> Option<&'static str> {
+    match alg {
+        "sha1" => Some("SHA-1"),
+        "sha256" => Some("SHA-256"),
+        "sha512" => Some("SHA-512"),
+        _ => None,
+    }
+}
+
+/// Describe one file of a project as a CycloneDX component
+fn sbom_component(project: &LockedProject, file: &LockedFile) -> serde_json::Value {
+    let hashes = file
+        .hashes
+        .iter()
+        .filter_map(|(alg, content)| {
+            cyclonedx_alg(alg).map(|alg| serde_json::json!({"alg": alg, "content": content}))
+        })
+        .collect::<Vec<_>>();
+    let licenses = project
+        .license
+        .iter()
+        .map(|x| {
+            if x.starts_with("LicenseRef-") {
+                serde_json::json!({"license": {"name": x}})
+            } else {
+                serde_json::json!({"license": {"id": x}})
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut properties = vec![
+        serde_json::json!({"name": "mcmod:file", "value": file.name}),
+        serde_json::json!({"name": "mcmod:project_id", "value": project.project_id}),
+        serde_json::json!({"name": "mcmod:version_id", "value": project.version_id}),
+    ];
+    if let Some(note) = &project.note {
+        properties.push(serde_json::json!({"name": "mcmod:note", "value": note}));
+    }
+    serde_json::json!({
+        "type": "library",
+        "bom-ref": format!("{}/{}", project.version_id, file.name),
+        "name": project.slug,
+        "version": project.version_number,
+        "description": project.name,
+        "hashes": hashes,
+        "licenses": licenses,
+        "properties": properties,
+        "externalReferences": [
+            {"type": "distribution", "url": file.url},
+            {"type": "website", "url": project.changelog_url()},
+        ],
+    })
+}
+
+/// Build a CycloneDX bill of materials listing every file of the locked projects
+fn sbom(lock: &Lockfile) -> serde_json::Value {
+    let components = lock
+        .projects
+        .iter()
+        .flat_map(|project| {
+            project
+                .files
+                .iter()
+                .map(move |file| sbom_component(project, file))
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sbom() {
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![LockedProject {
+                project_id: "AANobbMI".into(),
+                slug: "sodium".into(),
+                name: "Sodium".into(),
+                version_id: "abc".into(),
+                version_number: "0.6.13".into(),
+                loader: None,
+                license: Some("LicenseRef-Polyform-Shield".into()),
+                registry: "modrinth".into(),
+                reasons: vec![],
+                note: Some("Faster rendering".into()),
+                icon_url: None,
+                files: vec![LockedFile {
+                    name: "sodium.jar".into(),
+                    url: "https://cdn.modrinth.com/sodium.jar".into(),
+                    size: 1024,
+                    hashes: BTreeMap::from([
+                        ("sha1".into(), "aaaa".into()),
+                        ("sha512".into(), "bbbb".into()),
+                    ]),
+                }],
+            }],
+        };
+        let bom = sbom(&lock);
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        let component = &bom["components"][0];
+        assert_eq!(component["version"], "0.6.13");
+        assert_eq!(
+            component["hashes"][1],
+            serde_json::json!({"alg": "SHA-512", "content": "bbbb"}),
+            "SBOM shall list the hashes of each file"
+        );
+        assert_eq!(
+            component["licenses"][0]["license"]["name"], "LicenseRef-Polyform-Shield",
+            "SBOM shall name licenses that are not SPDX identifiers"
+        );
+        assert_eq!(
+            component["properties"][3],
+            serde_json::json!({"name": "mcmod:note", "value": "Faster rendering"}),
+            "SBOM shall keep the note of a project"
+        );
+        assert_eq!(
+            component["externalReferences"][0]["url"],
+            "https://cdn.modrinth.com/sodium.jar"
+        );
+    }
+
+    #[test]
+    fn test_inventory() {
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![LockedProject {
+                project_id: "AANobbMI".into(),
+                slug: "sodium".into(),
+                name: "Sodium, but faster".into(),
+                version_id: "abc".into(),
+                version_number: "0.6.13".into(),
+                loader: Some(crate::types::ModLoader::Fabric),
+                license: None,
+                registry: "modrinth".into(),
+                reasons: vec!["configured".into(), "dependency of iris".into()],
+                note: None,
+                icon_url: None,
+                files: vec![LockedFile {
+                    name: "sodium.jar".into(),
+                    url: "https://cdn.modrinth.com/sodium.jar".into(),
+                    size: 1024,
+                    hashes: BTreeMap::from([("sha1".into(), "aaaa".into())]),
+                }],
+            }],
+        };
+        let text = export(&lock, &BTreeMap::new(), ExportFormat::Csv, "Pack");
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "slug,name,version,loader,license,registry,file,size,url,sha1,sha512,reasons,note,\
+                icon_url,downloads,followers,updated"
+            ),
+            "CSV shall start with a header"
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "sodium,\"Sodium, but faster\",0.6.13,fabric,,modrinth,sodium.jar,1024,\
+                https://cdn.modrinth.com/sodium.jar,aaaa,,configured; dependency of iris,,,,,"
+            ),
+            "CSV shall have a row for each file, quoting values as needed"
+        );
+        let stats = BTreeMap::from([(
+            "AANobbMI".to_string(),
+            ProjectStats {
+                downloads: 5000,
+                followers: 20,
+                updated: chrono::NaiveDate::from_ymd_opt(2025, 3, 4)
+                    .and_then(|x| x.and_hms_opt(12, 0, 0)),
+            },
+        )]);
+        let json: serde_json::Value =
+            serde_json::from_str(&export(&lock, &stats, ExportFormat::Json, "Pack"))
+                .expect("JSON shall parse");
+        assert_eq!(json[0]["size"], 1024);
+        assert_eq!(
+            (&json[0]["downloads"], &json[0]["updated"]),
+            (&serde_json::json!(5000), &serde_json::json!("2025-03-04")),
+            "Exports shall include the stats of projects"
+        );
+        assert_eq!(
+            json[0]["license"], "",
+            "JSON shall leave missing values empty"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let project = |slug: &str, name: &str, icon_url: Option<&str>| LockedProject {
+            project_id: slug.into(),
+            slug: slug.into(),
+            name: name.into(),
+            version_id: "abc".into(),
+            version_number: "1.0".into(),
+            loader: Some(crate::types::ModLoader::Fabric),
+            license: None,
+            registry: "modrinth".into(),
+            reasons: vec![],
+            note: None,
+            icon_url: icon_url.map(String::from),
+            files: vec![],
+        };
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![
+                project(
+                    "sodium",
+                    "Sodium",
+                    Some("https://cdn.modrinth.com/icon.png"),
+                ),
+                project("iris", "Iris <Shaders>", None),
+            ],
+        };
+        let page = html(&lock, "Steve's server");
+        assert!(page.contains("<title>Steve&#39;s server</title>"));
+        assert!(
+            page.contains(r#"<a href="https://modrinth.com/project/sodium">Sodium</a>"#),
+            "Page shall link each project"
+        );
+        assert!(page.contains(r#"<img src="https://cdn.modrinth.com/icon.png""#));
+        assert!(
+            page.contains("Iris &lt;Shaders&gt;"),
+            "Page shall escape names"
+        );
+        assert!(
+            page.find("Iris").expect("Iris is listed")
+                < page.find("Sodium").expect("Sodium is listed"),
+            "Page shall list projects by name"
+        );
+    }
+}
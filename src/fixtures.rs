@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::fsutil;
+
+/// Whether responses are written to or read from the fixtures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Send requests and save each response
+    Record,
+    /// Answer requests from the saved responses without using the network
+    Replay,
+}
+
+/// A directory of recorded API responses, used for hermetic tests and reproducible bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+impl Fixtures {
+    /// Construct fixtures in a directory
+    pub fn new(dir: PathBuf, mode: FixtureMode) -> Self {
+        Self { dir, mode }
+    }
+
+    /// Get the path of the response to a request
+    fn path(&self, key: &str) -> PathBuf {
+        let key = key
+            .strip_prefix("https://")
+            .or_else(|| key.strip_prefix("http://"))
+            .unwrap_or(key);
+        self.dir.join(fsutil::safe_file_name(key))
+    }
+
+    /// Get the response to a request, sending it with `send` when recording
+    pub fn get<F>(&self, key: &str, send: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce() -> Result<Vec<u8>>,
+    {
+        let path = self.path(key);
+        match self.mode {
+            FixtureMode::Replay => {
+                std::fs::read(&path).map_err(|_| Error::FixtureNotFound(key.to_string()))
+            }
+            FixtureMode::Record => {
+                let body = send()?;
+                std::fs::create_dir_all(&self.dir)?;
+                std::fs::write(&path, &body)?;
+                Ok(body)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_replay() {
+        let dir = PathBuf::from(".test/fixtures");
+        let _ = std::fs::remove_dir_all(&dir);
+        let key = "https://api.modrinth.com/v2/project/sodium";
+        let record = Fixtures::new(dir.clone(), FixtureMode::Record);
+        record
+            .get(key, || Ok(b"{}".to_vec()))
+            .expect("Fixtures shall record a response");
+        let replay = Fixtures::new(dir, FixtureMode::Replay);
+        assert_eq!(
+            replay
+                .get(key, || panic!("Fixtures shall not send when replaying"))
+                .expect("Fixtures shall replay a recorded response"),
+            b"{}"
+        );
+        replay
+            .get("https://api.modrinth.com/v2/project/iris", || Ok(vec![]))
+            .expect_err("Fixtures shall fail to replay an unrecorded request");
+    }
+}
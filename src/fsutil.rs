@@ -0,0 +1,304 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// How many times to try an operation on a file that is locked by another process
+const LOCKED_ATTEMPTS: u32 = 5;
+
+/// How long to wait after the first failure on a locked file. Doubles after each attempt.
+const LOCKED_BACKOFF: Duration = Duration::from_millis(200);
+
+/// How old the staging directory of another run must be before it is removed as abandoned
+const STALE_STAGING: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The subdirectory of the temp directory that holds the staging directories
+const STAGING_DIR: &str = "mcmod-staging";
+
+/// How many staging directories this process has created, so each gets its own name
+static STAGING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Convert a path into a form that is not limited to MAX_PATH on Windows. Other platforms return
+/// the path unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let text = absolute.to_string_lossy();
+    if text.starts_with(r"\\?\") {
+        absolute
+    } else if let Some(unc) = text.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{unc}"))
+    } else {
+        PathBuf::from(format!(r"\\?\{text}"))
+    }
+}
+
+/// Convert a key like a url into a file name, replacing every character that is not safe in file
+/// names on all platforms
+pub fn safe_file_name(key: &str) -> String {
+    key.chars()
+        .map(|x| {
+            if x.is_ascii_alphanumeric() || matches!(x, '-' | '_' | '.') {
+                x
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Check if an IO error was caused by another process holding the file, such as a sync client
+/// like OneDrive or an antivirus scanner
+pub fn is_locked(error: &std::io::Error) -> bool {
+    if cfg!(windows) {
+        // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION, ERROR_CLOUD_FILE_IN_USE
+        matches!(error.raw_os_error(), Some(32) | Some(33) | Some(391))
+    } else {
+        matches!(error.kind(), std::io::ErrorKind::ResourceBusy)
+    }
+}
+
+/// Run a file operation, retrying with a backoff while the file is locked by another process
+fn retry_locked<T, F>(path: &Path, mut op: F) -> Result<T>
+where
+    F: FnMut() -> std::io::Result<T>,
+{
+    let mut delay = LOCKED_BACKOFF;
+    for attempt in 1..=LOCKED_ATTEMPTS {
+        match op() {
+            Ok(x) => return Ok(x),
+            Err(e) if is_locked(&e) && attempt < LOCKED_ATTEMPTS => {
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(e) if is_locked(&e) => {
+                return Err(Error::FileLocked(path.to_path_buf()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    unreachable!("The last attempt always returns")
+}
+
+/// How files are copied into place
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    serde::Deserialize,
+    serde::Serialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyMode {
+    /// Use a reflink when the file system supports it, otherwise copy
+    #[default]
+    Auto,
+    /// Always copy the contents
+    Copy,
+    /// Always use a reflink, failing if the file system does not support it
+    Reflink,
+}
+
+/// Copy a file into place. The file is first copied next to the destination and then renamed, so
+/// an interrupted copy never leaves a partial file at the destination. Locked files are retried.
+/// On file systems like Btrfs, XFS, APFS, and ReFS, a reflink shares the data of the source
+/// instead of copying it, which is instant and uses no extra space.
+pub fn copy_file(src: &Path, dst: &Path, mode: CopyMode) -> Result<()> {
+    let (src, dst) = (long_path(src), long_path(dst));
+    let mut partial = dst.clone().into_os_string();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+    let _ = std::fs::remove_file(&partial);
+    retry_locked(&dst, || match mode {
+        CopyMode::Auto => reflink_copy::reflink_or_copy(&src, &partial).map(|_| ()),
+        CopyMode::Copy => std::fs::copy(&src, &partial).map(|_| ()),
+        CopyMode::Reflink => reflink_copy::reflink(&src, &partial),
+    })
+    .inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial);
+    })?;
+    retry_locked(&dst, || std::fs::rename(&partial, &dst)).inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial);
+    })
+}
+
+/// Move a file into place. A file on another file system cannot be renamed, so it is copied next
+/// to the destination and renamed there instead. Locked files are retried.
+pub fn move_file(src: &Path, dst: &Path) -> Result<()> {
+    let (src, dst) = (long_path(src), long_path(dst));
+    let renamed = retry_locked(&dst, || match std::fs::rename(&src, &dst) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => Ok(false),
+        x => x.map(|_| true),
+    })?;
+    if !renamed {
+        copy_file(&src, &dst, CopyMode::Copy)?;
+        std::fs::remove_file(&src)?;
+    }
+    Ok(())
+}
+
+/// A directory for the staged work of one run, inside a subdirectory of the configured temp
+/// directory that only mcmod writes to. It is removed with everything in it when dropped.
+#[derive(Debug)]
+pub struct StagingDir {
+    path: PathBuf,
+    next: AtomicUsize,
+}
+
+impl StagingDir {
+    /// Create a staging directory inside `temp`, removing those of runs that were abandoned. The
+    /// temp directory may be shared with other programs, so only mcmod's subdirectory is cleaned.
+    pub fn new(temp: &Path) -> Result<Self> {
+        let temp = temp.join(STAGING_DIR);
+        let name = format!(
+            "run-{}-{}",
+            std::process::id(),
+            STAGING_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        if let Ok(entries) = std::fs::read_dir(long_path(&temp)) {
+            for entry in entries.flatten() {
+                let stale = entry
+                    .metadata()
+                    .and_then(|x| x.modified())
+                    .is_ok_and(|x| x.elapsed().is_ok_and(|x| x > STALE_STAGING));
+                if stale && entry.file_name().to_string_lossy().starts_with("run-") {
+                    let _ = std::fs::remove_dir_all(entry.path());
+                }
+            }
+        }
+        let path = temp.join(name);
+        std::fs::create_dir_all(long_path(&path))?;
+        Ok(Self {
+            path,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Get a new path to stage a file at
+    pub fn file(&self, name: &str) -> PathBuf {
+        let n = self.next.fetch_add(1, Ordering::Relaxed);
+        self.path.join(format!("{n}-{}", safe_file_name(name)))
+    }
+
+    /// Write a file in the staging directory and then move it into place, so an interrupted
+    /// write never leaves a partial file at the destination
+    pub fn write(&self, dst: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+        let name = dst.file_name().map(|x| x.to_string_lossy());
+        let staged = self.file(name.as_deref().unwrap_or("file"));
+        std::fs::write(long_path(&staged), contents)?;
+        move_file(&staged, dst).inspect_err(|_| {
+            let _ = std::fs::remove_file(long_path(&staged));
+        })
+    }
+
+    /// Copy a file into place. Copies are staged in this directory and moved into place, but a
+    /// reflink must be made on the file system of the destination, so it is staged next to it.
+    pub fn copy(&self, src: &Path, dst: &Path, mode: CopyMode) -> Result<()> {
+        if mode != CopyMode::Copy {
+            return copy_file(src, dst, mode);
+        }
+        let name = dst.file_name().map(|x| x.to_string_lossy());
+        let staged = self.file(name.as_deref().unwrap_or("file"));
+        let remove = |_: &Error| {
+            let _ = std::fs::remove_file(long_path(&staged));
+        };
+        retry_locked(src, || std::fs::copy(long_path(src), long_path(&staged)))
+            .inspect_err(remove)?;
+        move_file(&staged, dst).inspect_err(remove)
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(long_path(&self.path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_file() {
+        let dir = PathBuf::from(".test/fsutil");
+        std::fs::create_dir_all(&dir).expect("Failure to create test path");
+        let (src, dst) = (dir.join("src.txt"), dir.join("dst.txt"));
+        std::fs::write(&src, "contents").expect("Failure to write test file");
+        for mode in [CopyMode::Copy, CopyMode::Auto] {
+            copy_file(&src, &dst, mode).expect("Files shall be copied");
+            assert_eq!(
+                std::fs::read_to_string(&dst).expect("Failure to read test file"),
+                "contents",
+                "Copied files shall have the same contents"
+            );
+        }
+        assert!(
+            !dir.join("dst.txt.part").exists(),
+            "Copying shall not leave partial files"
+        );
+    }
+
+    #[test]
+    fn test_staging_dir() {
+        let dir = PathBuf::from(".test/fsutil-staging");
+        std::fs::create_dir_all(&dir).expect("Failure to create test path");
+        let temp = dir.join("temp");
+        std::fs::create_dir_all(temp.join("run-shared")).expect("Failure to create test path");
+        std::fs::write(temp.join("run-shared/file"), "").expect("Failure to write test file");
+        #[cfg(unix)]
+        std::fs::File::open(temp.join("run-shared"))
+            .and_then(|x| x.set_modified(std::time::SystemTime::now() - 2 * STALE_STAGING))
+            .expect("Failure to age test path");
+        let staging = StagingDir::new(&temp).expect("Staging shall be created");
+        let staged = staging.path.clone();
+        assert!(
+            staged.starts_with(temp.join(STAGING_DIR)),
+            "Staging shall be created in mcmod's own subdirectory"
+        );
+        assert!(
+            temp.join("run-shared/file").exists(),
+            "Staging shall not clean files outside mcmod's own subdirectory"
+        );
+        let (src, dst) = (dir.join("src.txt"), dir.join("dst.txt"));
+        staging
+            .write(&src, "contents")
+            .expect("Staged files shall be written");
+        staging
+            .copy(&src, &dst, CopyMode::Copy)
+            .expect("Staged files shall be copied");
+        assert_eq!(
+            std::fs::read_to_string(&dst).expect("Failure to read test file"),
+            "contents",
+            "Staged files shall be moved into place"
+        );
+        assert_ne!(
+            staging.file("a.jar"),
+            staging.file("a.jar"),
+            "Staged paths shall be unique"
+        );
+        drop(staging);
+        assert!(!staged.exists(), "Staging shall be removed when dropped");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_long_path() {
+        assert_eq!(
+            long_path(Path::new(r"C:\Users\alice")),
+            PathBuf::from(r"\\?\C:\Users\alice")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\server\share\dir")),
+            PathBuf::from(r"\\?\UNC\server\share\dir")
+        );
+    }
+}
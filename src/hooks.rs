@@ -0,0 +1,294 @@
+use std::path::{Component, Path, PathBuf};
+
+use rhai::{AST, Dynamic, Engine, Scope};
+
+use crate::error::{Error, Result};
+use crate::lockfile::Lockfile;
+use crate::types::ModVersion;
+
+/// How many operations a hook may run before it is stopped, so a script that never returns does
+/// not hang the program
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// How deeply expressions may nest at the top level and in functions. Debug builds of the engine
+/// default to half of this, so scripts are limited the same in every build.
+const MAX_EXPR_DEPTHS: (usize, usize) = (64, 32);
+
+/// A user script with functions that are called at defined points to apply custom policies. Every
+/// function is optional:
+///
+/// - `filter_version(version)` returns false to reject a candidate version of a project
+/// - `install_path(version, file, path)` returns the path a file is installed to, relative to the
+///   instance, or nothing to keep `path`
+/// - `validate(lock)` returns problems with the resolved projects, as a string or an array of
+///   strings, which fail the resolve
+pub struct Hooks {
+    engine: Engine,
+    ast: AST,
+    /// The text the script was compiled from
+    source: String,
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let functions = self
+            .ast
+            .iter_functions()
+            .map(|x| x.name)
+            .collect::<Vec<_>>();
+        f.debug_struct("Hooks")
+            .field("functions", &functions)
+            .finish()
+    }
+}
+
+/// Convert a value to the types of the script
+fn to_dynamic<T: serde::Serialize>(value: &T) -> Result<Dynamic> {
+    rhai::serde::to_dynamic(value).map_err(|e| Error::Script(e.to_string()))
+}
+
+impl Hooks {
+    /// Compile the text of a script
+    pub fn compile(text: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(MAX_EXPR_DEPTHS.0, MAX_EXPR_DEPTHS.1);
+        let ast = engine
+            .compile(text)
+            .map_err(|e| Error::Script(e.to_string()))?;
+        Ok(Self {
+            engine,
+            ast,
+            source: text.to_string(),
+        })
+    }
+
+    /// Get the text the script was compiled from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Read and compile a script
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::compile(&std::fs::read_to_string(path)?)
+            .map_err(|e| Error::Script(format!("{path:?}: {e}")))
+    }
+
+    /// Check if the script defines a function with a number of params
+    fn has(&self, name: &str, params: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|x| x.name == name && x.params.len() == params)
+    }
+
+    /// Call a function of the script
+    fn call(&self, name: &str, args: impl rhai::FuncArgs) -> Result<Dynamic> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| Error::Script(format!("{name}: {e}")))
+    }
+
+    /// Describe a version to the script
+    fn version(slug: &str, version: &ModVersion) -> Result<Dynamic> {
+        to_dynamic(&serde_json::json!({
+            "slug": slug,
+            "project_id": version.project_id.as_str(),
+            "version_id": version.version_id.as_str(),
+            "name": version.name,
+            "version_number": version.version_number,
+            "featured": version.featured,
+            "loaders": version.loaders.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+            "date_published": version.date_published.to_string(),
+        }))
+    }
+
+    /// Check if `filter_version` accepts a candidate version of a project
+    pub fn filter_version(&self, slug: &str, version: &ModVersion) -> Result<bool> {
+        if !self.has("filter_version", 1) {
+            return Ok(true);
+        }
+        let result = self.call("filter_version", (Self::version(slug, version)?,))?;
+        result
+            .as_bool()
+            .map_err(|x| Error::Script(format!("filter_version returned {x}, expected a bool")))
+    }
+
+    /// Get the path `install_path` chooses for a file, relative to the instance, if any. The path
+    /// must stay inside the instance.
+    pub fn install_path(
+        &self,
+        slug: &str,
+        version: &ModVersion,
+        file: &str,
+        path: &Path,
+    ) -> Result<Option<PathBuf>> {
+        if !self.has("install_path", 3) {
+            return Ok(None);
+        }
+        let args = (
+            Self::version(slug, version)?,
+            Dynamic::from(file.to_string()),
+            Dynamic::from(path.to_string_lossy().replace('\\', "/")),
+        );
+        let result = self.call("install_path", args)?;
+        if result.is_unit() {
+            return Ok(None);
+        }
+        let result =
+            PathBuf::from(result.into_string().map_err(|x| {
+                Error::Script(format!("install_path returned {x}, expected a string"))
+            })?);
+        if result.as_os_str().is_empty()
+            || !result
+                .components()
+                .all(|x| matches!(x, Component::Normal(_)))
+        {
+            return Err(Error::Script(format!(
+                "install_path returned {result:?}, which is not inside the instance"
+            )));
+        }
+        Ok(Some(result))
+    }
+
+    /// Get the problems `validate` finds with the resolved projects
+    pub fn validate(&self, lock: &Lockfile) -> Result<Vec<String>> {
+        if !self.has("validate", 1) {
+            return Ok(vec![]);
+        }
+        let result = self.call("validate", (to_dynamic(lock)?,))?;
+        if result.is_unit() {
+            return Ok(vec![]);
+        }
+        if result.is_array() {
+            return Ok(result
+                .into_array()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect());
+        }
+        Ok(vec![result.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ModLoader;
+
+    fn version(version_number: &str) -> ModVersion {
+        ModVersion {
+            project_id: String::from("AANobbMI").into(),
+            version_id: String::from("V").into(),
+            name: "Sodium".into(),
+            version_number: version_number.into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files: vec![],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_hooks() {
+        let hooks = Hooks::compile(
+            r#"
+            fn filter_version(version) { !version.version_number.contains("beta") }
+            fn install_path(version, file, path) {
+                if version.slug == "sodium" { `mods/renderers/${file}` }
+            }
+            fn validate(lock) {
+                let problems = [];
+                for project in lock.project {
+                    if project.license == () {
+                        problems.push(`${project.slug} has no license`);
+                    }
+                }
+                problems
+            }
+            "#,
+        )
+        .expect("The script shall compile");
+        assert!(
+            !hooks
+                .filter_version("sodium", &version("0.6.0-beta.2"))
+                .expect("filter_version shall not fail"),
+            "filter_version shall reject versions"
+        );
+        assert!(
+            hooks
+                .filter_version("sodium", &version("0.6.0"))
+                .expect("filter_version shall not fail"),
+            "filter_version shall accept versions"
+        );
+        assert_eq!(
+            hooks
+                .install_path(
+                    "sodium",
+                    &version("0.6.0"),
+                    "sodium.jar",
+                    Path::new("mods/sodium.jar")
+                )
+                .expect("install_path shall not fail"),
+            Some(PathBuf::from("mods/renderers/sodium.jar")),
+            "install_path shall choose the path"
+        );
+        assert_eq!(
+            hooks
+                .install_path(
+                    "lithium",
+                    &version("0.6.0"),
+                    "lithium.jar",
+                    Path::new("mods/lithium.jar")
+                )
+                .expect("install_path shall not fail"),
+            None,
+            "install_path shall keep the path when it returns nothing"
+        );
+        let lock: Lockfile = toml::from_str(
+            r#"
+            [[project]]
+            project_id = "AANobbMI"
+            slug = "sodium"
+            name = "Sodium"
+            version_id = "V"
+            version_number = "0.6.0"
+            "#,
+        )
+        .expect("The lock shall parse");
+        assert_eq!(
+            hooks.validate(&lock).expect("validate shall not fail"),
+            vec!["sodium has no license"]
+        );
+        let escape = Hooks::compile(r#"fn install_path(version, file, path) { "../" + file }"#)
+            .expect("The script shall compile");
+        assert!(
+            escape
+                .install_path(
+                    "sodium",
+                    &version("0.6.0"),
+                    "sodium.jar",
+                    Path::new("mods/sodium.jar")
+                )
+                .is_err(),
+            "install_path shall not leave the instance"
+        );
+        let empty = Hooks::compile("").expect("An empty script shall compile");
+        assert!(
+            empty
+                .filter_version("sodium", &version("0.6.0-beta.2"))
+                .expect("filter_version shall not fail"),
+            "A script without filter_version shall accept every version"
+        );
+        assert!(
+            empty
+                .validate(&lock)
+                .expect("validate shall not fail")
+                .is_empty(),
+            "A script without validate shall find no problems"
+        );
+    }
+}
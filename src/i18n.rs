@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use fluent::concurrent::FluentBundle;
+use fluent::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+/// The English messages, used for any message a translation does not have
+const ENGLISH: &str = include_str!("../locales/en-US.ftl");
+
+/// Bundles of messages, tried in order until one has the message
+pub struct Messages {
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+/// The messages of this run
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+/// Parse a bundle of messages. Messages with syntax errors are left out.
+fn bundle(lang: LanguageIdentifier, text: String) -> FluentBundle<FluentResource> {
+    let resource = FluentResource::try_new(text).unwrap_or_else(|(resource, errors)| {
+        eprintln!("Ignoring {} invalid messages for {lang}", errors.len());
+        resource
+    });
+    let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+    bundle.set_use_isolating(false);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+impl Messages {
+    /// Load a translation, falling back to English for the messages it does not have
+    pub fn new(translation: Option<(LanguageIdentifier, String)>) -> Self {
+        let english = "en-US".parse().expect("en-US is a valid language");
+        let mut bundles = Vec::new();
+        if let Some((lang, text)) = translation {
+            bundles.push(bundle(lang, text));
+        }
+        bundles.push(bundle(english, ENGLISH.to_string()));
+        Self { bundles }
+    }
+
+    /// Format a message with arguments. A message that does not exist is returned as its id.
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let args = args.iter().cloned().collect::<FluentArgs>();
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|x| x.value()) {
+                let mut errors = Vec::new();
+                return bundle
+                    .format_pattern(pattern, Some(&args), &mut errors)
+                    .into_owned();
+            }
+        }
+        id.to_string()
+    }
+}
+
+/// Get the language to show messages in, from the argument, or else LC_ALL, LC_MESSAGES, or LANG
+pub fn detect_language(lang: Option<&str>) -> Option<LanguageIdentifier> {
+    let value = lang.map(str::to_string).or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|x| std::env::var(x).ok().filter(|x| !x.is_empty()))
+    })?;
+    let tag = value.split(['.', '@']).next()?.replace('_', "-");
+    if tag == "C" || tag == "POSIX" {
+        return None;
+    }
+    tag.parse().ok()
+}
+
+/// Get the directory that translations are loaded from, MCMOD_LOCALES or else `locales` in the
+/// default data directory
+pub fn locales_dir() -> Option<PathBuf> {
+    std::env::var_os("MCMOD_LOCALES")
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_local_dir().map(|x| x.join("mcmod").join("locales")))
+}
+
+/// Read the translation for a language from a directory, trying the full tag like `pt-BR` and
+/// then the language like `pt`
+fn find_translation(dir: &Path, lang: &LanguageIdentifier) -> Option<String> {
+    [lang.to_string(), lang.language.to_string()]
+        .iter()
+        .find_map(|x| std::fs::read_to_string(dir.join(format!("{x}.ftl"))).ok())
+}
+
+/// Load the messages of this run in a language, or the detected language. Only the first call has
+/// an effect.
+pub fn init(lang: Option<&str>) {
+    let translation = detect_language(lang).and_then(|lang| {
+        let text = find_translation(&locales_dir()?, &lang)?;
+        Some((lang, text))
+    });
+    let _ = MESSAGES.set(Messages::new(translation));
+}
+
+/// Format a message of this run
+pub fn message(id: &str, args: &[(&str, FluentValue)]) -> String {
+    MESSAGES
+        .get_or_init(|| Messages::new(None))
+        .format(id, args)
+}
+
+/// Format a message by id, with named arguments like `tr!("collecting", name = project.name)`
+#[macro_export]
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::message($id, &[])
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::message(
+            $id,
+            &[$((stringify!($name), fluent::FluentValue::from($value))),+],
+        )
+    };
+}
+pub use crate::tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let messages = Messages::new(Some((
+            "de".parse().expect("de is a valid language"),
+            "collecting = Sammle { $name }".into(),
+        )));
+        assert_eq!(
+            messages.format("collecting", &[("name", "sodium".into())]),
+            "Sammle sodium",
+            "Messages shall be translated"
+        );
+        assert_eq!(
+            messages.format("found-projects", &[("count", 1.into())]),
+            "Found 1 project",
+            "Messages that are not translated shall be shown in English"
+        );
+        assert_eq!(messages.format("no-such-message", &[]), "no-such-message");
+        assert_eq!(
+            tr!("found-projects", count = 3),
+            "Found 3 projects",
+            "Messages shall choose the plural form"
+        );
+        assert_eq!(
+            tr!("download-progress", done = 2, failed = 0, total = 3),
+            "Downloaded 2 of 3 files"
+        );
+        assert_eq!(
+            tr!("download-progress", done = 2, failed = 1, total = 3),
+            "Downloaded 2 of 3 files, 1 failed"
+        );
+        assert_eq!(
+            detect_language(Some("pt_BR.UTF-8")).map(|x| x.to_string()),
+            Some("pt-BR".into()),
+            "Languages shall be read from locale names"
+        );
+        assert_eq!(detect_language(Some("C")), None);
+    }
+
+    #[test]
+    fn test_english_has_every_message() {
+        let messages = Messages::new(None);
+        let pattern = concat!("tr", "!(");
+        for entry in std::fs::read_dir("src").expect("Failure to read sources") {
+            let path = entry.expect("Failure to read sources").path();
+            let text = std::fs::read_to_string(&path).expect("Failure to read source");
+            for (index, _) in text.match_indices(pattern) {
+                let is_macro = text[..index]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|x| !x.is_alphanumeric() && x != '_');
+                let Some(rest) = text[index + pattern.len()..].trim_start().strip_prefix('"')
+                else {
+                    continue;
+                };
+                if !is_macro {
+                    continue;
+                }
+                let id = &rest[..rest.find('"').expect("Message ids shall be closed")];
+                assert_ne!(
+                    messages.format(id, &[]),
+                    id,
+                    "The English messages shall have {id:?}, used in {path:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::lockfile::Lockfile;
+
+/// The image extensions kept from icon urls. Others are saved as `png`.
+const ICON_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "webp", "gif", "svg"];
+
+/// The icon of a locked project, and where it is cached
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ProjectIcon {
+    /// The slug of the project
+    pub slug: String,
+    /// The url the icon is downloaded from
+    pub url: String,
+    /// The cached icon, if it was downloaded
+    pub path: Option<PathBuf>,
+    /// Why the icon could not be downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Get the path an icon is cached at in the data directory, keeping the extension of the url. A
+/// project id that is not a plain file name is rejected, so it can not point outside the cache.
+pub fn icon_path(data_dir: &Path, project_id: &str, url: &str) -> Result<PathBuf> {
+    if project_id.is_empty() || project_id.contains(['/', '\\']) || project_id.contains("..") {
+        return Err(Error::InvalidProjectId(project_id.to_string()));
+    }
+    let extension = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|x| x.rsplit_once('.'))
+        .map(|(_, x)| x.to_ascii_lowercase())
+        .filter(|x| ICON_EXTENSIONS.contains(&x.as_str()))
+        .unwrap_or_else(|| "png".into());
+    Ok(data_dir
+        .join("icons")
+        .join(format!("{project_id}.{extension}")))
+}
+
+/// Download the icon of a project into the data directory, unless it is already cached
+pub fn cache_icon(
+    client: &labrinth::Client,
+    data_dir: &Path,
+    project_id: &str,
+    url: &str,
+) -> Result<PathBuf> {
+    let path = icon_path(data_dir, project_id, url)?;
+    if path.is_file() {
+        return Ok(path);
+    }
+    let bytes = client.download_file(url)?;
+    std::fs::create_dir_all(path.parent().expect("Icon path has a parent"))?;
+    let part = path.with_extension("part");
+    std::fs::write(&part, bytes)?;
+    std::fs::rename(&part, &path)?;
+    Ok(path)
+}
+
+/// Cache the icons of the locked projects that have one. A failed download is recorded rather
+/// than stopping the others.
+pub fn cache_icons(
+    client: &labrinth::Client,
+    data_dir: &Path,
+    lock: &Lockfile,
+) -> Vec<ProjectIcon> {
+    lock.projects
+        .iter()
+        .filter_map(|project| {
+            let url = project.icon_url.as_ref()?;
+            let result = cache_icon(client, data_dir, &project.project_id, url);
+            Some(ProjectIcon {
+                slug: project.slug.clone(),
+                url: url.clone(),
+                error: result.as_ref().err().map(|x| x.to_string()),
+                path: result.ok(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{FixtureMode, Fixtures};
+    use crate::lockfile::LockedProject;
+
+    #[test]
+    fn test_cache_icons() {
+        let dir = PathBuf::from(".test/icons");
+        let _ = std::fs::remove_dir_all(&dir);
+        let url = "https://cdn.modrinth.com/data/AANobbMI/icon.webp";
+        Fixtures::new(dir.join("fixtures"), FixtureMode::Record)
+            .get(url, || Ok(b"RIFF".to_vec()))
+            .expect("Fixtures shall record the icon");
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(
+            dir.join("fixtures"),
+            FixtureMode::Replay,
+        )));
+        let project = |project_id: &str, slug: &str, icon_url: Option<&str>| LockedProject {
+            project_id: project_id.into(),
+            slug: slug.into(),
+            name: slug.into(),
+            version_id: "v1".into(),
+            version_number: "1.0".into(),
+            loader: None,
+            license: None,
+            registry: "modrinth".into(),
+            reasons: vec![],
+            note: None,
+            icon_url: icon_url.map(str::to_string),
+            files: vec![],
+        };
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![
+                project("AANobbMI", "sodium", Some(url)),
+                project("gvQqBUqZ", "lithium", None),
+                project("YL57xq9U", "iris", Some("https://cdn.modrinth.com/missing")),
+            ],
+        };
+        let data_dir = dir.join("data");
+        let icons = cache_icons(&client, &data_dir, &lock);
+        assert_eq!(icons.len(), 2, "Projects without an icon shall be skipped");
+        let cached = data_dir.join("icons").join("AANobbMI.webp");
+        assert_eq!(icons[0].path.as_ref(), Some(&cached));
+        assert_eq!(
+            std::fs::read(&cached).expect("Icon shall be cached"),
+            b"RIFF"
+        );
+        assert!(
+            icons[1].path.is_none() && icons[1].error.is_some(),
+            "A failed icon shall be recorded without stopping the others"
+        );
+        assert_eq!(
+            icon_path(&data_dir, "YL57xq9U", "https://cdn.modrinth.com/missing").ok(),
+            Some(data_dir.join("icons").join("YL57xq9U.png")),
+            "Icons without a known extension shall be saved as png"
+        );
+        for project_id in ["../config", "a/b", "a\\b", ""] {
+            icon_path(&data_dir, project_id, url)
+                .expect_err("Project ids that are not plain file names shall be rejected");
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+/// The name of the file in an overrides directory listing the files not to copy
+pub const IGNORE_FILE: &str = ".mcmodignore";
+
+/// The patterns of files that are never copied unless a rule includes them again, like editor
+/// backups and the metadata of operating systems
+const DEFAULT_PATTERNS: [&str; 7] = [
+    ".DS_Store",
+    "Thumbs.db",
+    "desktop.ini",
+    "*~",
+    "*.swp",
+    ".git/",
+    IGNORE_FILE,
+];
+
+/// A pattern of paths to exclude, or to include again if negated
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    pattern: Vec<char>,
+    /// Include the matching paths again instead of excluding them
+    negated: bool,
+    /// Only match directories
+    dir_only: bool,
+    /// Match the whole path from the root instead of only the name
+    anchored: bool,
+}
+
+impl Rule {
+    /// Parse a line in the format of `.gitignore`, ignoring blank lines and comments
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(x) => (true, x),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(x) => (true, x),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        (!line.is_empty()).then(|| Self {
+            pattern: line.chars().collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Check if the rule matches a path relative to the root, with `/` between directories
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let text = match self.anchored {
+            true => path,
+            false => path.rsplit('/').next().unwrap_or(path),
+        };
+        glob(&self.pattern, &text.chars().collect::<Vec<_>>())
+    }
+}
+
+/// Match text against a glob, where `*` and `?` do not match `/`, and `**` matches across
+/// directories
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => (0..=text.len())
+            .filter(|&i| i == 0 || text[i - 1] == '/')
+            .any(|i| glob(rest, &text[i..])),
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob(rest, &text[i..])),
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob(rest, &text[i..])),
+        ['?', rest @ ..] => text.first().is_some_and(|x| *x != '/') && glob(rest, &text[1..]),
+        [x, rest @ ..] => text.first() == Some(x) && glob(rest, &text[1..]),
+    }
+}
+
+/// Rules in the format of `.gitignore` for which files to exclude. Later rules take precedence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self {
+            rules: DEFAULT_PATTERNS
+                .iter()
+                .filter_map(|x| Rule::parse(x))
+                .collect(),
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Add rules, one per line
+    pub fn extend<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) {
+        self.rules.extend(lines.into_iter().filter_map(Rule::parse));
+    }
+
+    /// Check if a path relative to the root, with `/` between directories, is excluded
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|x| x.matches(path, is_dir))
+            .is_some_and(|x| !x.negated)
+    }
+
+    /// List the files under a directory that are not excluded, by their path relative to it with
+    /// `/` between directories, sorted. The rules in the `.mcmodignore` of the directory are added
+    /// to these rules. Files in excluded directories are excluded too.
+    pub fn collect_files(&self, root: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let mut rules = self.clone();
+        if let Ok(text) = std::fs::read_to_string(root.join(IGNORE_FILE)) {
+            rules.extend(text.lines());
+        }
+        let mut result = Vec::new();
+        let mut dirs = vec![(String::new(), root.to_path_buf())];
+        while let Some((prefix, dir)) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+                let is_dir = entry.file_type()?.is_dir();
+                if rules.is_ignored(&name, is_dir) {
+                    continue;
+                }
+                match is_dir {
+                    true => dirs.push((format!("{name}/"), entry.path())),
+                    false => result.push((name, entry.path())),
+                }
+            }
+        }
+        result.sort();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let mut rules = IgnoreRules::default();
+        rules.extend("# Backups\n*.bak\n/options.txt\nlogs/\nconfig/**/*.log\n!keep.bak\n".lines());
+        assert!(rules.is_ignored("config/.DS_Store", false));
+        assert!(rules.is_ignored("config/mod.json~", false));
+        assert!(rules.is_ignored("config/sodium.bak", false));
+        assert!(
+            !rules.is_ignored("config/keep.bak", false),
+            "Later negated rules shall include files again"
+        );
+        assert!(rules.is_ignored("options.txt", false));
+        assert!(
+            !rules.is_ignored("config/options.txt", false),
+            "Rules with a slash shall only match from the root"
+        );
+        assert!(rules.is_ignored("logs", true));
+        assert!(
+            !rules.is_ignored("logs", false),
+            "Rules ending with a slash shall only match directories"
+        );
+        assert!(rules.is_ignored("config/a/b/debug.log", false));
+        assert!(rules.is_ignored("config/debug.log", false));
+        assert!(!rules.is_ignored("config/debug.txt", false));
+        assert!(
+            !rules.is_ignored("# Backups", false),
+            "Comments shall not be rules"
+        );
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let root = Path::new(".test/ignore");
+        let _ = std::fs::remove_dir_all(root);
+        for path in [
+            "config/sodium.json",
+            "config/sodium.json~",
+            "logs/latest.log",
+            ".DS_Store",
+            "options.txt",
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().expect("Path has a parent"))
+                .expect("Failure to create test directory");
+            std::fs::write(path, "").expect("Failure to write test file");
+        }
+        std::fs::write(root.join(IGNORE_FILE), "logs/\n").expect("Failure to write test file");
+        let files = IgnoreRules::default()
+            .collect_files(root)
+            .expect("Files shall be collected")
+            .into_iter()
+            .map(|x| x.0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec!["config/sodium.json", "options.txt"],
+            "Files excluded by default or by the ignore file shall not be collected"
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{Error, Result};
+
+/// Whether the player asked to stop with Ctrl-C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The exit code of a run that was interrupted, as shells report for SIGINT
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Handle Ctrl-C by asking the run to stop after the current file, so it can clean up. A second
+/// Ctrl-C exits at once.
+pub fn install_handler() {
+    let result = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("Could not handle Ctrl-C: {e}");
+    }
+}
+
+/// Fail if the run was interrupted
+pub fn check() -> Result<()> {
+    match INTERRUPTED.load(Ordering::SeqCst) {
+        true => Err(Error::Interrupted),
+        false => Ok(()),
+    }
+}
@@ -1,52 +1,165 @@
+use crate::chaos::Chaos;
 use crate::error::{Error, Result};
+use crate::fixtures::Fixtures;
+use crate::metacache::MetadataCache;
+use crate::trace;
 use crate::types::{self, MinecraftVersion, ModLoader};
+use crate::verify::HashAlgorithm;
 use reqwest::blocking as rb;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
 
 const LABRINTH_URL: &str = "https://api.modrinth.com";
 
 #[derive(Default)]
 pub struct Client {
     client: rb::Client,
+    cache: Option<MetadataCache>,
+    fixtures: Option<Fixtures>,
+    chaos: Option<Arc<Chaos>>,
+    frozen: bool,
 }
 
 impl Client {
     pub fn new() -> Self {
-        Self {
-            client: rb::Client::new(),
+        Self::default()
+    }
+
+    /// Keep project and version metadata in a cache, apart from the metadata of other APIs
+    pub fn with_cache(mut self, cache: MetadataCache) -> Self {
+        self.cache = Some(cache.with_namespace(LABRINTH_URL));
+        self
+    }
+
+    /// Record responses to fixtures or replay them
+    pub fn with_fixtures(mut self, fixtures: Option<Fixtures>) -> Self {
+        self.fixtures = fixtures;
+        self
+    }
+
+    /// Inject faults into responses
+    pub fn with_chaos(mut self, chaos: Option<Arc<Chaos>>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    /// Fail instead of sending API requests, so only cached metadata and fixtures are answered.
+    /// Files are still downloaded.
+    pub fn with_frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Fail if API requests may not be sent
+    fn check_frozen(&self, key: &str) -> Result<()> {
+        match self.frozen {
+            true => Err(Error::Frozen(key.to_string())),
+            false => Ok(()),
         }
     }
 
-    fn get<U>(&self, url: U) -> Result<rb::Response>
+    /// Answer a request from the fixtures, or else send it, injecting chaos if there is any
+    fn respond<F>(&self, key: &str, send: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce() -> Result<Vec<u8>>,
+    {
+        let response = match &self.fixtures {
+            Some(fixtures) => fixtures.get(key, send),
+            None => send(),
+        };
+        match &self.chaos {
+            Some(chaos) => chaos.apply(key, response),
+            None => response,
+        }
+    }
+
+    /// Send a request and read its response with `read`, tracing both if HTTP tracing is on
+    fn execute<F>(&self, request: rb::Request, read: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce(rb::Response) -> Result<Vec<u8>>,
+    {
+        let Some(trace) = trace::get() else {
+            return read(self.client.execute(request)?);
+        };
+        let id = trace.request(&request);
+        let start = std::time::Instant::now();
+        let response = self.client.execute(request).inspect_err(|e| {
+            trace.failure(id, e, start.elapsed());
+        })?;
+        trace.response(id, &response, start.elapsed());
+        let body = read(response)?;
+        trace.body(id, &body);
+        Ok(body)
+    }
+
+    /// Send a request, or answer it from the fixtures
+    fn send<U, P>(&self, url: U, params: &P) -> Result<Vec<u8>>
+    where
+        U: reqwest::IntoUrl,
+        P: serde::Serialize + ?Sized,
+    {
+        let request = self.client.get(url).query(params).build()?;
+        let key = request.url().to_string();
+        let send = || -> Result<Vec<u8>> {
+            self.check_frozen(&key)?;
+            self.execute(request, |response| {
+                Ok(response.error_for_status()?.bytes()?.into())
+            })
+        };
+        self.respond(&key, send)
+    }
+
+    fn get<U>(&self, url: U) -> Result<String>
     where
         U: reqwest::IntoUrl,
     {
-        Ok(self.client.get(url).send()?.error_for_status()?)
+        self.get_form(url, &[] as &[(&str, &str)])
     }
 
-    fn get_form<U, P>(&self, url: U, params: &P) -> Result<rb::Response>
+    fn get_form<U, P>(&self, url: U, params: &P) -> Result<String>
     where
         U: reqwest::IntoUrl,
         P: serde::Serialize + ?Sized,
     {
-        Ok(self
-            .client
-            .get(url)
-            .query(&params)
-            .send()?
-            .error_for_status()?)
+        Ok(String::from_utf8_lossy(&self.send(url, params)?).into_owned())
+    }
+
+    /// Get a record through the metadata cache, if there is one
+    fn get_cached<T, F>(&self, key: &str, fetch: F) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce() -> Result<T>,
+    {
+        match &self.cache {
+            Some(cache) => cache.get(key, fetch),
+            None => fetch(),
+        }
     }
 
     /// Get a project from the database
     pub fn get_project(&self, project: &str) -> Result<types::ModProject> {
-        let response = self.get(format!("{LABRINTH_URL}/v2/project/{project}"))?;
-        let project = serde_json::from_str::<Project>(response.text()?.as_str())?;
+        let project = self
+            .get_cached(&format!("project/{project}"), || {
+                let text = self.get(format!("{LABRINTH_URL}/v2/project/{project}"))?;
+                Ok(serde_json::from_str::<Project>(text.as_str())?)
+            })
+            .map_err(|e| match e {
+                Error::Request(x) if x.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                    Error::ProjectNotFound(project.to_string())
+                }
+                e => e,
+            })?;
         Ok(project.into())
     }
 
     /// Get a version from the database
     pub fn get_version(&self, version: &str) -> Result<types::ModVersion> {
-        let response = self.get(format!("{LABRINTH_URL}/v2/version/{version}"))?;
-        let version = serde_json::from_str::<Version>(response.text()?.as_str())?;
+        let version = self.get_cached(&format!("version/{version}"), || {
+            let text = self.get(format!("{LABRINTH_URL}/v2/version/{version}"))?;
+            Ok(serde_json::from_str::<Version>(text.as_str())?)
+        })?;
         Ok(version.into())
     }
 
@@ -71,22 +184,26 @@ impl Client {
             ("game_versions", format!("[{game_versions}]")),
             ("loaders", format!("[{loaders}]")),
         ];
-        let response = self.get_form(
-            format!("{LABRINTH_URL}/v2/project/{project}/version"),
-            &params,
-        )?;
-        let versions = serde_json::from_str::<Vec<Version>>(response.text()?.as_str())?;
+        let key = format!("versions/{project}/{}/{}", params[0].1, params[1].1);
+        let versions = self.get_cached(&key, || {
+            let text = self.get_form(
+                format!("{LABRINTH_URL}/v2/project/{project}/version"),
+                &params,
+            )?;
+            Ok(serde_json::from_str::<Vec<Version>>(text.as_str())?)
+        })?;
         Ok(versions.into_iter().map(Version::into).collect())
     }
 
     /// Get the latest version of a project for the target Minecraft version and mod loader
+    #[cfg(test)]
     pub fn get_project_version_latest(
         &self,
         project: &str,
-        game_version: MinecraftVersion,
+        game_versions: &[MinecraftVersion],
         loader: types::ModLoader,
     ) -> Result<types::ModVersion> {
-        self.get_project_versions(project, &[game_version], &[loader])?
+        self.get_project_versions(project, game_versions, &[loader])?
             .into_iter()
             .max_by(|x, y| x.date_published.cmp(&y.date_published))
             .ok_or_else(|| Error::VersionNotFound {
@@ -94,9 +211,143 @@ impl Client {
             })
     }
 
+    /// Search for projects matching a query and the facets, most relevant first
+    pub fn search(
+        &self,
+        query: &str,
+        facets: &SearchFacets,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        let mut params = vec![("query", query.to_string()), ("limit", limit.to_string())];
+        if let Some(facets) = facets.to_param() {
+            params.push(("facets", facets));
+        }
+        let text = self.get_form(format!("{LABRINTH_URL}/v2/search"), &params)?;
+        Ok(serde_json::from_str::<SearchResponse>(text.as_str())?.hits)
+    }
+
+    /// Find the versions that published files, by the hash of each file that was found. Hashes
+    /// that no version published are left out.
+    pub fn get_versions_by_hashes(
+        &self,
+        hashes: &[&str],
+        algorithm: HashAlgorithm,
+    ) -> Result<HashMap<String, types::ModVersion>> {
+        let body = self.post_json(
+            &format!("{LABRINTH_URL}/v2/version_files"),
+            &[],
+            &serde_json::json!({ "hashes": hashes, "algorithm": algorithm.to_string() }),
+        )?;
+        let versions = serde_json::from_slice::<HashMap<String, Version>>(&body)?;
+        Ok(versions
+            .into_iter()
+            .map(|(hash, version)| (hash, version.into()))
+            .collect())
+    }
+
     /// Download a single file
     pub fn download_file(&self, file_url: &str) -> Result<Vec<u8>> {
-        Ok(self.get(file_url)?.bytes().map(|x| x.into())?)
+        self.send(file_url, &[] as &[(&str, &str)])
+    }
+
+    /// Send a JSON body to another API, with headers like its key, or answer it from the fixtures.
+    /// Recorded responses are told apart by a hash of the body.
+    pub fn post_json(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &serde_json::Value,
+    ) -> Result<Vec<u8>> {
+        let body = serde_json::to_vec(body)?;
+        let key = format!("{url}#{}", crate::verify::hash_bytes(&body).sha1);
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request.body(body).build()?;
+        let send = || -> Result<Vec<u8>> {
+            self.check_frozen(&key)?;
+            self.execute(request, |response| {
+                Ok(response.error_for_status()?.bytes()?.into())
+            })
+        };
+        self.respond(&key, send)
+    }
+
+    /// Download a file, failing if it is larger than the size limit or downloading it takes longer
+    /// than the timeout
+    pub fn download_limited(
+        &self,
+        file_url: &str,
+        max_size: Option<u64>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>> {
+        use std::io::Read;
+        let mut request = self.client.get(file_url);
+        if let Some(x) = timeout {
+            request = request.timeout(x);
+        }
+        let request = request.build()?;
+        let key = request.url().to_string();
+        let limit = max_size.unwrap_or(u64::MAX);
+        let too_large = || Error::FileTooLarge {
+            file: file_url.to_string(),
+            limit,
+        };
+        let send = || -> Result<Vec<u8>> {
+            self.execute(request, |response| {
+                let response = response.error_for_status()?;
+                if response.content_length().is_some_and(|x| x > limit) {
+                    return Err(too_large());
+                }
+                let mut bytes = Vec::new();
+                response
+                    .take(limit.saturating_add(1))
+                    .read_to_end(&mut bytes)?;
+                Ok(bytes)
+            })
+        };
+        let bytes = self.respond(&key, send).map_err(|e| match e {
+            Error::Request(x) if x.is_timeout() => Error::DownloadTimeout {
+                file: file_url.to_string(),
+                timeout: timeout.unwrap_or_default().as_secs(),
+            },
+            Error::IO(x) if x.kind() == std::io::ErrorKind::TimedOut => Error::DownloadTimeout {
+                file: file_url.to_string(),
+                timeout: timeout.unwrap_or_default().as_secs(),
+            },
+            e => e,
+        })?;
+        match bytes.len() as u64 > limit {
+            true => Err(too_large()),
+            false => Ok(bytes),
+        }
+    }
+
+    /// Download a range of the bytes of a file. Fails if the server does not support ranges.
+    pub fn download_range(&self, file_url: &str, range: Range<u64>) -> Result<Vec<u8>> {
+        let key = format!("{file_url}#bytes={}-{}", range.start, range.end);
+        let send = || -> Result<Vec<u8>> {
+            let request = self
+                .client
+                .get(file_url)
+                .header(
+                    reqwest::header::RANGE,
+                    format!("bytes={}-{}", range.start, range.end.saturating_sub(1)),
+                )
+                .build()?;
+            self.execute(request, |response| {
+                let response = response.error_for_status()?;
+                if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                    return Err(Error::RangeNotSupported(file_url.to_string()));
+                }
+                Ok(response.bytes()?.into())
+            })
+        };
+        self.respond(&key, send)
     }
 
     /// Download the files of a version into a list of tuples of the file info and the bytes
@@ -112,12 +363,26 @@ impl Client {
         Ok(result)
     }
 
+    /// Get the official list of Minecraft versions
+    pub fn get_game_versions(&self) -> Result<Vec<types::GameVersion>> {
+        let text = self.get(format!("{LABRINTH_URL}/v2/tag/game_version"))?;
+        Ok(serde_json::from_str::<Vec<types::GameVersion>>(
+            text.as_str(),
+        )?)
+    }
+
+    /// Get the list of mod loaders and the project types they support
+    pub fn get_loaders(&self) -> Result<Vec<types::LoaderInfo>> {
+        let text = self.get(format!("{LABRINTH_URL}/v2/tag/loader"))?;
+        Ok(serde_json::from_str::<Vec<types::LoaderInfo>>(
+            text.as_str(),
+        )?)
+    }
+
     /// Validate all internal enumerations are up to date
     pub fn validate_enums(&self) -> Result<Vec<Error>> {
         let mut result = Vec::<Error>::new();
-        let repsonse = self.get(format!("{LABRINTH_URL}/v2/tag/loader"))?;
-        let values = serde_json::from_str::<Vec<LoaderInfo>>(repsonse.text()?.as_str())?;
-        for v in values {
+        for v in self.get_loaders()? {
             if let Err(e) = ModLoader::try_from(v.name.as_str()) {
                 result.push(e)
             }
@@ -126,7 +391,31 @@ impl Client {
     }
 }
 
-#[derive(serde::Deserialize)]
+#[cfg(feature = "modrinth")]
+impl crate::backend::Backend for Client {
+    fn name(&self) -> &str {
+        "modrinth"
+    }
+
+    fn get_project(&self, project: &str) -> Result<types::ModProject> {
+        Client::get_project(self, project)
+    }
+
+    fn get_version(&self, version: &str) -> Result<types::ModVersion> {
+        Client::get_version(self, version)
+    }
+
+    fn get_project_versions(
+        &self,
+        project: &str,
+        game_versions: &[MinecraftVersion],
+        loaders: &[ModLoader],
+    ) -> Result<Vec<types::ModVersion>> {
+        Client::get_project_versions(self, project, game_versions, loaders)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
 struct Project {
     pub slug: String,
     pub title: String,
@@ -134,8 +423,27 @@ struct Project {
     pub project_id: String,
     // #[serde(rename = "versions")]
     // pub version_ids: Vec<String>,
-    // pub game_versions: Vec<MinecraftVersion>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub game_versions: Vec<MinecraftVersion>,
     pub loaders: Vec<ModLoader>,
+    #[serde(default)]
+    pub license: Option<License>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub downloads: u64,
+    #[serde(default)]
+    pub followers: u64,
+    #[serde(default)]
+    pub updated: Option<DatePublished>,
+    #[serde(default)]
+    pub client_side: SideSupport,
+    #[serde(default)]
+    pub server_side: SideSupport,
 }
 
 impl From<Project> for types::ModProject {
@@ -145,20 +453,119 @@ impl From<Project> for types::ModProject {
             name: value.title,
             slug: value.slug.into(),
             // version_ids: value.version_ids.into_iter().map(|x| x.into()).collect(),
-            // game_versions: value.game_versions,
+            description: value.description,
+            game_versions: value.game_versions,
             loaders: value.loaders,
+            license: value.license.map(|x| x.id),
+            icon_url: value.icon_url,
+            categories: value.categories,
+            stats: Some(types::ProjectStats {
+                downloads: value.downloads,
+                followers: value.followers,
+                updated: value.updated.map(|x| x.0),
+            }),
+            side: match (value.client_side, value.server_side) {
+                (SideSupport::Unsupported, SideSupport::Unsupported) => types::Side::Both,
+                (SideSupport::Unsupported, _) => types::Side::Server,
+                (_, SideSupport::Unsupported) => types::Side::Client,
+                _ => types::Side::Both,
+            },
         }
     }
 }
 
+/// What the projects found by a search must match. Unset facets match any project.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchFacets {
+    /// Projects with a version for this game version
+    pub game_version: Option<MinecraftVersion>,
+    /// Projects with a version for this loader
+    pub loader: Option<ModLoader>,
+    /// Projects of this type
+    pub project_type: Option<types::ProjectType>,
+    /// Projects in any of these categories
+    pub categories: Vec<String>,
+}
+
+impl SearchFacets {
+    /// Format the facets as the API takes them: a JSON list of groups that must all match, each
+    /// matching if any of its facets do
+    fn to_param(&self) -> Option<String> {
+        let mut groups = Vec::<Vec<String>>::new();
+        if let Some(x) = self.game_version {
+            groups.push(vec![format!("versions:{x}")]);
+        }
+        if let Some(x) = self.loader {
+            groups.push(vec![format!("categories:{x}")]);
+        }
+        if let Some(x) = self.project_type {
+            groups.push(vec![format!("project_type:{x}")]);
+        }
+        if !self.categories.is_empty() {
+            groups.push(
+                self.categories
+                    .iter()
+                    .map(|x| format!("categories:{x}"))
+                    .collect(),
+            );
+        }
+        (!groups.is_empty()).then(|| serde_json::Value::from(groups).to_string())
+    }
+}
+
+/// The projects found by a search
 #[derive(serde::Deserialize)]
+struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+}
+
+/// A project found by a search
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq)]
+pub struct SearchHit {
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub project_type: String,
+    #[serde(default)]
+    pub downloads: u64,
+    /// The categories and loaders of the project
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// The license of a project, as an SPDX identifier
+#[derive(serde::Deserialize, serde::Serialize)]
+struct License {
+    pub id: String,
+}
+
+/// Whether a project works on one side of the game
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+enum SideSupport {
+    Required,
+    Optional,
+    Unsupported,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
 struct Version {
     pub name: String,
+    pub version_number: String,
+    #[serde(default)]
+    pub changelog: Option<String>,
+    #[serde(default)]
+    pub featured: bool,
     #[serde(rename = "id")]
     pub version_id: String,
     pub project_id: String,
     pub dependencies: Vec<Dependency>,
-    #[cfg(test)]
+    #[serde(default)]
     pub game_versions: Vec<MinecraftVersion>,
     pub date_published: DatePublished,
     pub loaders: Vec<ModLoader>,
@@ -171,7 +578,9 @@ impl From<Version> for types::ModVersion {
             project_id: value.project_id.into(),
             version_id: value.version_id.into(),
             name: value.name,
-            #[cfg(test)]
+            version_number: value.version_number,
+            changelog: value.changelog.filter(|x| !x.trim().is_empty()),
+            featured: value.featured,
             game_versions: value.game_versions,
             loaders: value.loaders,
             dependencies: value
@@ -185,7 +594,20 @@ impl From<Version> for types::ModVersion {
     }
 }
 
-#[derive(serde::Deserialize)]
+/// Parse a project in the format of the API
+pub(crate) fn parse_project(value: serde_json::Value) -> Result<types::ModProject> {
+    Ok(serde_json::from_value::<Project>(value)?.into())
+}
+
+/// Parse a version in the format of the API, with the game versions it supports
+pub(crate) fn parse_version(
+    value: serde_json::Value,
+) -> Result<(Vec<MinecraftVersion>, types::ModVersion)> {
+    let version = serde_json::from_value::<Version>(value)?;
+    Ok((version.game_versions.clone(), version.into()))
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
 struct Dependency {
     pub version_id: Option<String>,
     pub project_id: Option<String>,
@@ -208,7 +630,7 @@ impl Dependency {
     }
 }
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 enum DependencyKind {
     Required,
@@ -217,10 +639,14 @@ enum DependencyKind {
     Embedded,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 struct FileLink {
     pub url: String,
     pub filename: String,
+    #[serde(default)]
+    pub hashes: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub size: u64,
 }
 
 impl From<FileLink> for types::ModFile {
@@ -228,14 +654,22 @@ impl From<FileLink> for types::ModFile {
         Self {
             url: value.url,
             name: value.filename,
+            hashes: value.hashes,
+            size: value.size,
         }
     }
 }
 
-#[derive(serde::Deserialize)]
-#[serde(try_from = "String")]
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(try_from = "String", into = "String")]
 struct DatePublished(chrono::NaiveDateTime);
 
+impl From<DatePublished> for String {
+    fn from(value: DatePublished) -> Self {
+        value.0.and_utc().to_rfc3339()
+    }
+}
+
 impl TryFrom<String> for DatePublished {
     type Error = Error;
     fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
@@ -245,11 +679,6 @@ impl TryFrom<String> for DatePublished {
     }
 }
 
-#[derive(serde::Deserialize, Debug)]
-struct LoaderInfo {
-    pub name: String,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,7 +689,7 @@ mod tests {
         let game_version = MinecraftVersion::from("1.21.2");
         let loader = ModLoader::Minecraft;
         let version = client
-            .get_project_version_latest("faithful-32x", game_version, loader)
+            .get_project_version_latest("faithful-32x", &[game_version], loader)
             .expect("Client should get a project version");
         if !version.game_versions.contains(&game_version) || !version.loaders.contains(&loader) {
             panic!("Client should get the latest project version for a specific target {version:?}")
@@ -273,7 +702,7 @@ mod tests {
         let game_version = MinecraftVersion::from("1.21.2");
         let loader = ModLoader::Fabric;
         let version = client
-            .get_project_version_latest("iris", game_version, loader)
+            .get_project_version_latest("iris", &[game_version], loader)
             .expect("Client should get a project version");
         if !version.game_versions.contains(&game_version) || !version.loaders.contains(&loader) {
             panic!("Client should get the latest project version for a specific target {version:?}")
@@ -290,4 +719,173 @@ mod tests {
             .validate_enums()
             .expect("Client shall be able to get and compare data");
     }
+
+    #[test]
+    fn test_project_side() {
+        let project = |client_side: &str, server_side: &str| -> types::ModProject {
+            serde_json::from_str::<Project>(&format!(
+                r#"{{"slug": "x", "title": "X", "id": "AAAA", "loaders": ["fabric"],
+                "client_side": "{client_side}", "server_side": "{server_side}"}}"#
+            ))
+            .expect("Project shall deserialize")
+            .into()
+        };
+        assert_eq!(project("required", "unsupported").side, types::Side::Client);
+        assert_eq!(project("unsupported", "required").side, types::Side::Server);
+        assert_eq!(project("optional", "required").side, types::Side::Both);
+        assert_eq!(project("unknown", "unsupported").side, types::Side::Client);
+    }
+
+    #[test]
+    fn test_project_stats() {
+        let project: types::ModProject = serde_json::from_str::<Project>(
+            r#"{"slug": "x", "title": "X", "id": "AAAA", "loaders": ["fabric"],
+            "downloads": 1200, "followers": 30, "updated": "2024-05-01T12:00:00.5Z"}"#,
+        )
+        .expect("Project shall deserialize")
+        .into();
+        let stats = project.stats.expect("Projects shall have stats");
+        assert_eq!((stats.downloads, stats.followers), (1200, 30));
+        assert_eq!(
+            stats.updated.map(|x| x.date()),
+            chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+        );
+    }
+
+    #[test]
+    fn test_project_details() {
+        let project: types::ModProject = serde_json::from_str::<Project>(
+            r#"{"slug": "x", "title": "X", "id": "AAAA", "loaders": ["fabric"],
+            "description": "Does things", "game_versions": ["1.21.4", "1.21.5"]}"#,
+        )
+        .expect("Project shall deserialize")
+        .into();
+        assert_eq!(project.description, "Does things");
+        assert_eq!(
+            project.game_versions,
+            vec![
+                MinecraftVersion::from("1.21.4"),
+                MinecraftVersion::from("1.21.5")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cached_project() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        use std::time::Duration;
+        let dir = std::path::Path::new(".test/labrinth_cache");
+        let _ = std::fs::remove_dir_all(dir);
+        Fixtures::new(dir.join("fixtures"), FixtureMode::Record)
+            .get(&format!("{LABRINTH_URL}/v2/project/x"), || {
+                Ok(
+                    br#"{"slug": "x", "title": "X", "id": "AAAA", "loaders": ["fabric"],
+                "updated": "2024-05-01T12:00:00Z", "client_side": "required",
+                "server_side": "unsupported"}"#
+                        .to_vec(),
+                )
+            })
+            .expect("Fixtures shall record the answer");
+        let cache = || MetadataCache::new(dir.join("cache"), Duration::from_secs(60), false);
+        let fetched = Client::new()
+            .with_fixtures(Some(Fixtures::new(
+                dir.join("fixtures"),
+                FixtureMode::Replay,
+            )))
+            .with_cache(cache())
+            .get_project("x")
+            .expect("Client shall get the project");
+        let cached = Client::new()
+            .with_fixtures(Some(Fixtures::new(dir.join("empty"), FixtureMode::Replay)))
+            .with_cache(cache())
+            .get_project("x")
+            .expect("Client shall get the project from the cache");
+        assert_eq!(
+            (
+                cached.slug,
+                cached.side,
+                cached.stats.and_then(|x| x.updated)
+            ),
+            (
+                fetched.slug,
+                fetched.side,
+                fetched.stats.and_then(|x| x.updated)
+            ),
+            "The cache shall keep the project as it was fetched"
+        );
+    }
+
+    #[test]
+    fn test_search_response() {
+        let response = serde_json::from_str::<SearchResponse>(
+            r#"{"hits": [{"project_id": "AANobbMI", "slug": "sodium", "title": "Sodium",
+            "description": "Fast", "project_type": "mod", "downloads": 100, "icon_url": null}],
+            "offset": 0, "limit": 10, "total_hits": 1}"#,
+        )
+        .expect("Search responses shall deserialize");
+        assert_eq!(response.hits[0].slug, "sodium");
+        assert_eq!(response.hits[0].downloads, 100);
+    }
+
+    #[test]
+    fn test_get_versions_by_hashes() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        let dir = std::path::Path::new(".test/labrinth_hashes");
+        let _ = std::fs::remove_dir_all(dir);
+        let known = crate::verify::hash_bytes(b"sodium").sha512;
+        let unknown = crate::verify::hash_bytes(b"unknown").sha512;
+        let hashes = [known.as_str(), unknown.as_str()];
+        let body =
+            serde_json::to_vec(&serde_json::json!({"hashes": hashes, "algorithm": "sha512"}))
+                .expect("JSON values always serialize");
+        let url = format!("{LABRINTH_URL}/v2/version_files");
+        Fixtures::new(dir.to_path_buf(), FixtureMode::Record)
+            .get(
+                &format!("{url}#{}", crate::verify::hash_bytes(&body).sha1),
+                || {
+                    Ok(serde_json::json!({known.clone(): {
+                        "id": "N3NwRJQx", "project_id": "AANobbMI", "name": "Sodium 0.6.13",
+                        "version_number": "mc1.21.5-0.6.13-fabric", "dependencies": [],
+                        "date_published": "2025-04-02T12:00:00Z", "loaders": ["fabric"],
+                        "files": []
+                    }})
+                    .to_string()
+                    .into_bytes())
+                },
+            )
+            .expect("Fixtures shall record the answer");
+        let client = Client::new()
+            .with_fixtures(Some(Fixtures::new(dir.to_path_buf(), FixtureMode::Replay)));
+        let versions = client
+            .get_versions_by_hashes(&hashes, HashAlgorithm::Sha512)
+            .expect("Versions shall be found by hash");
+        assert_eq!(
+            versions.keys().collect::<Vec<_>>(),
+            vec![&known],
+            "Only hashes of published files shall be found"
+        );
+        assert_eq!(versions[&known].version_id.as_str(), "N3NwRJQx");
+    }
+
+    #[test]
+    fn test_search_facets() {
+        assert_eq!(
+            SearchFacets::default().to_param(),
+            None,
+            "A search without facets shall not send any"
+        );
+        let facets = SearchFacets {
+            game_version: Some(MinecraftVersion::from("1.21.5")),
+            loader: Some(ModLoader::Fabric),
+            project_type: Some(types::ProjectType::Mod),
+            categories: vec!["optimization".into(), "utility".into()],
+        };
+        assert_eq!(
+            facets.to_param().as_deref(),
+            Some(
+                r#"[["versions:1.21.5"],["categories:fabric"],["project_type:mod"],["categories:optimization","categories:utility"]]"#
+            ),
+            "Each facet shall be a group that must match, and the categories one group"
+        );
+    }
 }
@@ -0,0 +1,355 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::prompt;
+
+/// Enumeration of the launchers whose directory layouts are understood
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Clone,
+    Copy,
+    clap::ValueEnum,
+    strum::Display,
+    strum::EnumIter,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Launcher {
+    /// The official launcher, using a single .minecraft directory
+    Vanilla,
+    /// Prism Launcher
+    Prism,
+    /// MultiMC
+    #[serde(rename = "multimc")]
+    #[strum(to_string = "multimc")]
+    #[value(name = "multimc")]
+    MultiMC,
+    /// ATLauncher
+    #[allow(clippy::enum_variant_names)]
+    #[serde(rename = "atlauncher")]
+    #[strum(to_string = "atlauncher")]
+    #[value(name = "atlauncher")]
+    ATLauncher,
+    /// The Modrinth App
+    ModrinthApp,
+}
+
+/// A launcher instance found on disk
+#[derive(Debug, PartialEq, Eq)]
+pub struct Instance {
+    /// The name of the instance directory
+    pub name: String,
+
+    /// The game directory of the instance, where mods, resourcepacks, etc are installed
+    pub game_dir: PathBuf,
+}
+
+impl Launcher {
+    /// Get the directories this launcher may keep its data in, in order of preference
+    fn candidate_roots(&self) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        let mut result = self.native_roots(&home, &dirs::data_dir());
+        if cfg!(target_os = "linux") {
+            result.append(&mut self.sandboxed_roots(&home));
+        }
+        result
+    }
+
+    /// Get the directories a natively installed launcher may keep its data in
+    fn native_roots(&self, home: &Option<PathBuf>, data: &Option<PathBuf>) -> Vec<PathBuf> {
+        let join = |base: &Option<PathBuf>, name: &str| base.as_ref().map(|x| x.join(name));
+        match self {
+            Launcher::Vanilla => {
+                if cfg!(windows) {
+                    vec![join(data, ".minecraft")]
+                } else if cfg!(target_os = "macos") {
+                    vec![join(data, "minecraft"), join(home, ".minecraft")]
+                } else {
+                    vec![join(home, ".minecraft")]
+                }
+            }
+            Launcher::Prism => vec![join(data, "PrismLauncher")],
+            Launcher::MultiMC => vec![join(data, "multimc"), join(data, "MultiMC")],
+            Launcher::ATLauncher => vec![join(data, "atlauncher"), join(data, "ATLauncher")],
+            Launcher::ModrinthApp => vec![join(data, "com.modrinth.theseus")],
+        }
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Get the directories a Flatpak or Snap installed launcher may keep its data in on Linux
+    fn sandboxed_roots(&self, home: &Option<PathBuf>) -> Vec<PathBuf> {
+        let Some(home) = home else {
+            return Vec::new();
+        };
+        let flatpak = home.join(".var/app");
+        let snap = home.join("snap");
+        match self {
+            Launcher::Vanilla => vec![
+                flatpak.join("com.mojang.Minecraft/.minecraft"),
+                snap.join("mc-installer/current/.minecraft"),
+            ],
+            Launcher::Prism => vec![
+                flatpak.join("org.prismlauncher.PrismLauncher/data/PrismLauncher"),
+                snap.join("prismlauncher/current/.local/share/PrismLauncher"),
+            ],
+            Launcher::MultiMC => vec![snap.join("multimc/current/.local/share/multimc")],
+            Launcher::ATLauncher => vec![
+                flatpak.join("com.atlauncher.ATLauncher/data/atlauncher"),
+                flatpak.join("com.atlauncher.ATLauncher/data/ATLauncher"),
+            ],
+            Launcher::ModrinthApp => {
+                vec![flatpak.join("com.modrinth.ModrinthApp/data/com.modrinth.theseus")]
+            }
+        }
+    }
+
+    /// Get every data directory of this launcher that exists, which may be several when it is
+    /// installed both natively and through Flatpak or Snap
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.candidate_roots()
+            .into_iter()
+            .filter(|x| x.is_dir())
+            .collect()
+    }
+
+    /// Get the data directory of this launcher, if it is installed. If several are found, the
+    /// user is asked which one to use.
+    pub fn root(&self) -> Option<PathBuf> {
+        let mut roots = self.roots();
+        if roots.len() <= 1 {
+            return roots.pop();
+        }
+        let question = tr!("multiple-roots", launcher = self.to_string());
+        let options = roots
+            .iter()
+            .map(|x| x.display().to_string())
+            .collect::<Vec<_>>();
+        let index = prompt::choose(&format!("root {self}"), &question, &options).unwrap_or(0);
+        Some(roots.swap_remove(index))
+    }
+
+    /// Get the launchers that are installed on this machine
+    pub fn detect() -> Vec<Launcher> {
+        <Launcher as strum::IntoEnumIterator>::iter()
+            .filter(|x| !x.roots().is_empty())
+            .collect()
+    }
+
+    /// Get the directory under the launcher root that holds the instances
+    fn instances_dir(&self, root: &Path) -> Option<PathBuf> {
+        match self {
+            Launcher::Vanilla => None,
+            Launcher::Prism | Launcher::MultiMC | Launcher::ATLauncher => {
+                Some(root.join("instances"))
+            }
+            Launcher::ModrinthApp => Some(root.join("profiles")),
+        }
+    }
+
+    /// Get the game directory of an instance directory
+    fn instance_game_dir(&self, instance_dir: &Path) -> PathBuf {
+        match self {
+            Launcher::Prism | Launcher::MultiMC => [".minecraft", "minecraft"]
+                .iter()
+                .map(|x| instance_dir.join(x))
+                .find(|x| x.is_dir())
+                .unwrap_or_else(|| instance_dir.join(".minecraft")),
+            _ => instance_dir.to_path_buf(),
+        }
+    }
+
+    /// List the instances under a launcher root directory, sorted by name
+    pub fn instances_in(&self, root: &Path) -> Result<Vec<Instance>> {
+        let Some(instances_dir) = self.instances_dir(root) else {
+            return Ok(vec![Instance {
+                name: self.to_string(),
+                game_dir: root.to_path_buf(),
+            }]);
+        };
+        if !instances_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut result = Vec::<Instance>::new();
+        for entry in instances_dir.read_dir()? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|x| x.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') || name.starts_with('_') {
+                continue;
+            }
+            result.push(Instance {
+                name: name.to_string(),
+                game_dir: self.instance_game_dir(&path),
+            });
+        }
+        result.sort_by(|l, r| l.name.cmp(&r.name));
+        Ok(result)
+    }
+
+    /// List the instances of this launcher, or nothing if it is not installed
+    pub fn instances(&self) -> Result<Vec<Instance>> {
+        match self.root() {
+            Some(root) => self.instances_in(&root),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Find the game directory of an instance of this launcher under a root directory. If no
+    /// name is given, the launcher must have exactly one instance.
+    pub fn find_instance_in(&self, root: &Path, name: Option<&str>) -> Result<PathBuf> {
+        let mut instances = self.instances_in(root)?;
+        let not_found = |instances: &[Instance]| Error::InstanceNotFound {
+            launcher: self.to_string(),
+            instance: name.unwrap_or_default().to_string(),
+            available: instances.iter().map(|x| x.name.clone()).collect(),
+        };
+        match name {
+            Some(name) if *self != Launcher::Vanilla => {
+                match instances.iter().position(|x| x.name == name) {
+                    Some(index) => Ok(instances.swap_remove(index).game_dir),
+                    None => Err(not_found(&instances)),
+                }
+            }
+            _ if instances.len() == 1 => Ok(instances.swap_remove(0).game_dir),
+            _ => Err(not_found(&instances)),
+        }
+    }
+
+    /// Find the game directory of an instance of this launcher
+    pub fn find_instance(&self, name: Option<&str>) -> Result<PathBuf> {
+        let root = self
+            .root()
+            .ok_or_else(|| Error::LauncherNotFound(self.to_string()))?;
+        self.find_instance_in(&root, name)
+    }
+}
+
+/// Find the game directory to install into. With no launcher, the vanilla .minecraft directory is
+/// used, unless an instance name is given, in which case every detected launcher is searched.
+pub fn find_game_dir(launcher: Option<Launcher>, instance: Option<&str>) -> Result<PathBuf> {
+    if let Some(launcher) = launcher {
+        return launcher.find_instance(instance);
+    }
+    let Some(instance) = instance else {
+        return Launcher::Vanilla.find_instance(None);
+    };
+    let mut available = Vec::<String>::new();
+    for launcher in Launcher::detect() {
+        if launcher == Launcher::Vanilla {
+            continue;
+        }
+        for each in launcher.instances()? {
+            if each.name == instance {
+                return Ok(each.game_dir);
+            }
+            available.push(format!("{launcher}/{}", each.name));
+        }
+    }
+    Err(Error::InstanceNotFound {
+        launcher: "any launcher".into(),
+        instance: instance.to_string(),
+        available,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_instances(root: &Path, dirs: &[&str]) {
+        if root.exists() {
+            std::fs::remove_dir_all(root).expect("Failure to clean test path");
+        }
+        for dir in dirs {
+            std::fs::create_dir_all(root.join(dir)).expect("Failure to create test path");
+        }
+    }
+
+    #[test]
+    fn test_prism_instances() {
+        let root = PathBuf::from(".test/launcher/prism");
+        create_instances(
+            &root,
+            &[
+                "instances/b/.minecraft",
+                "instances/a/minecraft",
+                "instances/_MMC_TEMP",
+            ],
+        );
+        let instances = Launcher::Prism
+            .instances_in(&root)
+            .expect("Launcher shall list instances");
+        assert_eq!(
+            instances,
+            vec![
+                Instance {
+                    name: "a".into(),
+                    game_dir: root.join("instances/a/minecraft"),
+                },
+                Instance {
+                    name: "b".into(),
+                    game_dir: root.join("instances/b/.minecraft"),
+                },
+            ],
+            "Launcher shall find the game directory of each instance"
+        );
+    }
+
+    #[test]
+    fn test_modrinth_app_find_instance() {
+        let root = PathBuf::from(".test/launcher/modrinth");
+        create_instances(&root, &["profiles/Fabric 1.21.5", "profiles/Other"]);
+        let game_dir = Launcher::ModrinthApp
+            .find_instance_in(&root, Some("Fabric 1.21.5"))
+            .expect("Launcher shall find an instance by name");
+        assert_eq!(game_dir, root.join("profiles/Fabric 1.21.5"));
+        Launcher::ModrinthApp
+            .find_instance_in(&root, None)
+            .expect_err("Launcher shall require a name when there are several instances");
+        Launcher::ModrinthApp
+            .find_instance_in(&root, Some("Missing"))
+            .expect_err("Launcher shall fail to find a missing instance");
+    }
+
+    #[test]
+    fn test_sandboxed_roots() {
+        let home = Some(PathBuf::from("/home/alice"));
+        assert!(
+            Launcher::Prism
+                .sandboxed_roots(&home)
+                .contains(&PathBuf::from(
+                    "/home/alice/.var/app/org.prismlauncher.PrismLauncher/data/PrismLauncher"
+                )),
+            "Launcher shall consider the Flatpak data directory"
+        );
+        assert!(
+            Launcher::Vanilla
+                .sandboxed_roots(&home)
+                .contains(&PathBuf::from(
+                    "/home/alice/snap/mc-installer/current/.minecraft"
+                )),
+            "Launcher shall consider the Snap data directory"
+        );
+    }
+
+    #[test]
+    fn test_vanilla_instance() {
+        let root = PathBuf::from(".test/launcher/vanilla/.minecraft");
+        create_instances(&root, &[""]);
+        let game_dir = Launcher::Vanilla
+            .find_instance_in(&root, None)
+            .expect("Launcher shall use the root as the vanilla game directory");
+        assert_eq!(game_dir, root);
+    }
+}
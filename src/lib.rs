@@ -0,0 +1,49 @@
+//! Resolve, download, and install Minecraft mods and other projects from Modrinth and other
+//! registries. The `mcmod` command line tool is built on this library, which other tools can use
+//! to resolve projects from their own registries by registering a [backend::Backend] with
+//! [solver::ModSolver::with_backend].
+
+pub mod adopt;
+pub mod advisory;
+pub mod alternatives;
+pub mod backend;
+pub mod cache;
+pub mod chaos;
+pub mod config;
+pub mod curseforge;
+pub mod dev;
+pub mod error;
+pub mod export;
+pub mod fixtures;
+pub mod fsutil;
+pub mod hooks;
+pub mod i18n;
+pub mod icons;
+pub mod ignore;
+pub mod interrupt;
+pub mod labrinth;
+pub mod launcher;
+pub mod lockfile;
+pub mod manifest;
+pub mod metacache;
+pub mod mojang;
+pub mod output;
+pub mod plan;
+pub mod prompt;
+pub mod registry_fixture;
+pub mod reputation;
+pub mod resume;
+pub mod serve;
+pub mod server;
+pub mod solver;
+pub mod summary;
+pub mod tags;
+pub mod templates;
+pub mod trace;
+pub mod tree;
+pub mod types;
+pub mod verify;
+pub mod workspace;
+pub mod world;
+pub mod zipdiff;
+pub mod zipwrite;
@@ -0,0 +1,832 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::types::{
+    ModDB, ModFile, ModLoader, ModProject, ModVersion, Reason, Registry, Side, VersionId,
+};
+
+/// How many lines of a changelog are quoted in release notes
+const CHANGELOG_EXCERPT_LINES: usize = 8;
+
+/// The resolved projects of a config, written next to it so installs can be reproduced and
+/// compared
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Lockfile {
+    /// The hash of the config values the projects were resolved from, so the lockfile is reused
+    /// while the config does not change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+    /// The locked projects, sorted by slug
+    #[serde(default, rename = "project")]
+    pub projects: Vec<LockedProject>,
+}
+
+/// A project and the version it was resolved to
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LockedProject {
+    /// The id of the project
+    pub project_id: String,
+    /// The slug of the project
+    pub slug: String,
+    /// The display name of the project
+    pub name: String,
+    /// The id of the version
+    pub version_id: String,
+    /// The version number given by the author
+    pub version_number: String,
+    /// The loader the version is installed for
+    pub loader: Option<ModLoader>,
+    /// The SPDX identifier of the license of the project
+    pub license: Option<String>,
+    /// The registry the version was found in
+    #[serde(default = "default_registry")]
+    pub registry: String,
+    /// Why the version was collected, like `configured` or `dependency of sodium`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    /// Why the config includes the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// The url of the project's icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// The files of the version
+    #[serde(default, rename = "file")]
+    pub files: Vec<LockedFile>,
+}
+
+/// A file of a locked version
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LockedFile {
+    /// The file name
+    pub name: String,
+    /// The download url
+    pub url: String,
+    /// The size of the file in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// The hashes of the file by algorithm
+    #[serde(default)]
+    pub hashes: BTreeMap<String, String>,
+}
+
+impl LockedProject {
+    /// Check if a config entry names this project, by its slug or its id
+    pub fn is_named(&self, name: &str) -> bool {
+        self.slug == name || self.project_id == name
+    }
+
+    /// Check if a config entry was resolved to this project, by the version id the entry names,
+    /// or else by its name
+    pub fn is_entry(&self, project: &config::ConfigProject) -> bool {
+        match &project.version_id {
+            Some(id) => self.version_id == id.as_str(),
+            None => self.is_named(project.name.as_str()),
+        }
+    }
+
+    /// Get the Modrinth page of the project
+    pub fn project_url(&self) -> String {
+        format!("https://modrinth.com/project/{}", self.slug)
+    }
+
+    /// Get the Modrinth page of the version, which shows its changelog
+    pub fn changelog_url(&self) -> String {
+        format!(
+            "https://modrinth.com/project/{}/version/{}",
+            self.slug, self.version_id
+        )
+    }
+}
+
+/// Lockfiles written before registries were recorded only used Modrinth
+fn default_registry() -> String {
+    Registry::default().to_string()
+}
+
+/// Describe why a version was collected as written in the lockfile, naming dependents by their
+/// project slug
+pub fn describe_reason(mod_db: &ModDB, reason: &Reason) -> String {
+    match reason {
+        Reason::Configured => "configured".into(),
+        Reason::Optional => "optional".into(),
+        Reason::Pinned => "pinned".into(),
+        Reason::DependencyOf(version_id) => {
+            format!("dependency of {}", dependent_name(mod_db, version_id))
+        }
+    }
+}
+
+/// Describe why a version was collected in the language of the run, to show to the user
+pub fn display_reason(mod_db: &ModDB, reason: &Reason) -> String {
+    match reason {
+        Reason::Configured => tr!("reason-configured"),
+        Reason::Optional => tr!("reason-optional"),
+        Reason::Pinned => tr!("reason-pinned"),
+        Reason::DependencyOf(version_id) => tr!(
+            "reason-dependency",
+            dependent = dependent_name(mod_db, version_id)
+        ),
+    }
+}
+
+/// Name the version a version is a dependency of by its project slug, or else by its id
+fn dependent_name(mod_db: &ModDB, version_id: &VersionId) -> String {
+    mod_db
+        .get_version(version_id)
+        .and_then(|x| mod_db.get_project_by_id(&x.project_id))
+        .map_or_else(|| version_id.to_string(), |x| x.slug.to_string())
+}
+
+/// Get the path of the lockfile for a config file, or of one of its channels, like
+/// `mcmod.beta.lock` for the `beta` channel
+pub fn lock_path(config_path: &Path, channel: Option<&str>) -> PathBuf {
+    match channel {
+        Some(x) => config_path.with_extension(format!("{x}.lock")),
+        None => config_path.with_extension("lock"),
+    }
+}
+
+/// Check that a channel name can be used in the name of a lockfile
+pub fn parse_channel(value: &str) -> Result<String> {
+    match !value.is_empty()
+        && value
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
+    {
+        true => Ok(value.to_string()),
+        false => Err(Error::InvalidChannel(value.to_string())),
+    }
+}
+
+impl Lockfile {
+    /// Construct a lockfile from the resolved versions
+    pub fn from_db(mod_db: &ModDB) -> Self {
+        let mut projects = mod_db
+            .get_versions()
+            .into_iter()
+            .map(|version| {
+                let project = mod_db.get_project_by_id(&version.project_id);
+                let provenance = mod_db
+                    .get_provenance(&version.version_id)
+                    .cloned()
+                    .unwrap_or_default();
+                LockedProject {
+                    project_id: version.project_id.to_string(),
+                    slug: project
+                        .map_or_else(|| version.project_id.to_string(), |x| x.slug.to_string()),
+                    name: project.map_or_else(|| version.name.clone(), |x| x.name.clone()),
+                    version_id: version.version_id.to_string(),
+                    version_number: version.version_number.clone(),
+                    loader: mod_db
+                        .get_loader(&version.version_id)
+                        .or(version.loaders.first().copied()),
+                    license: project.and_then(|x| x.license.clone()),
+                    registry: provenance.registry.to_string(),
+                    reasons: provenance
+                        .reasons
+                        .iter()
+                        .map(|x| describe_reason(mod_db, x))
+                        .collect(),
+                    note: provenance.note,
+                    icon_url: project.and_then(|x| x.icon_url.clone()),
+                    files: version
+                        .files
+                        .iter()
+                        .map(|x| LockedFile {
+                            name: x.name.clone(),
+                            url: x.url.clone(),
+                            size: x.size,
+                            hashes: x.hashes.clone(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        projects.sort_by(|l, r| l.slug.cmp(&r.slug));
+        Self {
+            config_hash: None,
+            projects,
+        }
+    }
+
+    /// Parse a lockfile from TOML text
+    pub fn loads(text: &str) -> Result<Self> {
+        let lock = toml::from_str::<Self>(text)?;
+        lock.check()?;
+        Ok(lock)
+    }
+
+    /// Check that the version ids and file names of the locked projects are safe to use in paths,
+    /// since a lockfile or plan may come from elsewhere. A version id must be at least two ASCII
+    /// letters, digits, `-`, `_`, or `.`, and a file name must not hold a path.
+    pub fn check(&self) -> Result<()> {
+        let invalid = |x: String| Err(Error::InvalidLockfile(x));
+        for project in &self.projects {
+            let version_id = &project.version_id;
+            if version_id.len() < 2
+                || version_id.contains("..")
+                || !version_id
+                    .chars()
+                    .all(|x| x.is_ascii_alphanumeric() || matches!(x, '-' | '_' | '.'))
+            {
+                return invalid(format!("version id {version_id:?} of {}", project.slug));
+            }
+            for file in &project.files {
+                let name = &file.name;
+                if name.is_empty()
+                    || name == "."
+                    || name.contains(['/', '\\'])
+                    || name.contains("..")
+                {
+                    return invalid(format!("file name {name:?} of {}", project.slug));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Load a lockfile
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::loads(std::fs::read_to_string(path)?.as_str())
+    }
+
+    /// Load the lockfile as it was committed at HEAD in the git repository containing it
+    pub fn load_git_head(path: &Path) -> Result<Self> {
+        let dir = match path.parent() {
+            Some(x) if !x.as_os_str().is_empty() => x,
+            _ => Path::new("."),
+        };
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::MissingDirectory(path.to_path_buf()))?
+            .to_string_lossy();
+        let output = std::process::Command::new("git")
+            .args(["show", &format!("HEAD:./{name}")])
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Self::loads(String::from_utf8_lossy(&output.stdout).as_ref())
+    }
+
+    /// Format the lockfile as it is written
+    pub fn dumps(&self) -> Result<String> {
+        let text = toml::to_string(self)?;
+        Ok(format!(
+            "# This file is generated by mcmod. Do not edit it by hand.\n\n{text}"
+        ))
+    }
+
+    /// Write the lockfile
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.dumps()?)?;
+        Ok(())
+    }
+
+    /// Find a project by its id and the loader it was resolved for, so each flavor of a project,
+    /// like a mod and a data pack, is found separately
+    pub fn get(&self, project_id: &str, loader: Option<ModLoader>) -> Option<&LockedProject> {
+        self.projects
+            .iter()
+            .find(|x| x.project_id == project_id && x.loader == loader)
+    }
+
+    /// Remove a project by its slug, id, or version id, and the dependencies that only removed
+    /// projects needed. Returns the removed projects, the named one first.
+    pub fn remove_project(&mut self, name: &str) -> Vec<LockedProject> {
+        let is_orphaned = |project: &LockedProject, removed: &[LockedProject]| {
+            !project.reasons.is_empty()
+                && project.reasons.iter().all(|reason| {
+                    reason
+                        .strip_prefix("dependency of ")
+                        .is_some_and(|dependent| {
+                            removed
+                                .iter()
+                                .any(|x| x.slug == dependent || x.version_id == dependent)
+                        })
+                })
+        };
+        let Some(index) = self
+            .projects
+            .iter()
+            .position(|x| x.is_named(name) || x.version_id == name)
+        else {
+            return vec![];
+        };
+        let mut removed = vec![self.projects.remove(index)];
+        while let Some(index) = self.projects.iter().position(|x| is_orphaned(x, &removed)) {
+            removed.push(self.projects.remove(index));
+        }
+        removed
+    }
+
+    /// Construct the resolved versions of this lockfile, so they can be downloaded and installed
+    /// without resolving them again. Only what the lockfile records is known, so versions have
+    /// only their locked loader and no dependencies.
+    pub fn to_db(&self) -> ModDB {
+        let mut mod_db = ModDB::default();
+        for project in &self.projects {
+            let loaders = project.loader.into_iter().collect::<Vec<_>>();
+            mod_db.add_project(ModProject {
+                project_id: project.project_id.clone().into(),
+                name: project.name.clone(),
+                slug: project.slug.clone().into(),
+                description: String::new(),
+                game_versions: vec![],
+                loaders: loaders.clone(),
+                side: Side::Both,
+                license: project.license.clone(),
+                icon_url: project.icon_url.clone(),
+                categories: vec![],
+                stats: None,
+            });
+            let version_id = VersionId::from(project.version_id.clone());
+            mod_db.add_version(ModVersion {
+                project_id: project.project_id.clone().into(),
+                version_id: version_id.clone(),
+                name: project.name.clone(),
+                version_number: project.version_number.clone(),
+                changelog: None,
+                featured: false,
+                game_versions: vec![],
+                loaders,
+                files: project
+                    .files
+                    .iter()
+                    .map(|x| ModFile {
+                        url: x.url.clone(),
+                        name: x.name.clone(),
+                        hashes: x.hashes.clone(),
+                        size: x.size,
+                    })
+                    .collect(),
+                dependencies: vec![],
+                date_published: chrono::NaiveDateTime::default(),
+            });
+            if let Some(loader) = project.loader {
+                mod_db.set_preferred_version(
+                    project.project_id.clone().into(),
+                    loader,
+                    version_id.clone(),
+                );
+            }
+            mod_db.set_registry(&version_id, project.registry.as_str().into());
+            if let Some(note) = &project.note {
+                mod_db.set_note(&version_id, note.clone());
+            }
+        }
+        for project in &self.projects {
+            let version_id = VersionId::from(project.version_id.clone());
+            for reason in &project.reasons {
+                if let Some(reason) = self.parse_reason(reason) {
+                    mod_db.add_reason(&version_id, reason);
+                }
+            }
+        }
+        mod_db
+    }
+
+    /// Parse a reason written by `describe_reason`
+    fn parse_reason(&self, reason: &str) -> Option<Reason> {
+        match reason {
+            "configured" => Some(Reason::Configured),
+            "optional" => Some(Reason::Optional),
+            "pinned" => Some(Reason::Pinned),
+            _ => {
+                let dependent = reason.strip_prefix("dependency of ")?;
+                self.projects
+                    .iter()
+                    .find(|x| x.slug == dependent || x.version_id == dependent)
+                    .map(|x| Reason::DependencyOf(x.version_id.clone().into()))
+            }
+        }
+    }
+
+    /// Compare this lockfile to a newer one
+    pub fn diff<'a>(&'a self, new: &'a Lockfile) -> LockDiff<'a> {
+        let mut result = LockDiff::default();
+        for project in &new.projects {
+            match self.get(&project.project_id, project.loader) {
+                None => result.added.push(project),
+                Some(old) if old.version_id != project.version_id => {
+                    result.changed.push((old, project))
+                }
+                Some(_) => {}
+            }
+        }
+        result.removed = self
+            .projects
+            .iter()
+            .filter(|x| new.get(&x.project_id, x.loader).is_none())
+            .collect();
+        result
+    }
+}
+
+/// The differences between two lockfiles
+#[derive(Debug, Default)]
+pub struct LockDiff<'a> {
+    /// Projects only in the new lockfile
+    pub added: Vec<&'a LockedProject>,
+    /// Projects only in the old lockfile
+    pub removed: Vec<&'a LockedProject>,
+    /// Projects whose version changed, as the old and new entries
+    pub changed: Vec<(&'a LockedProject, &'a LockedProject)>,
+}
+
+impl LockDiff<'_> {
+    /// Check if the lockfiles are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Compose a Markdown document of the changes, quoting the start of the changelog of each added
+    /// or updated version as given by `changelog`
+    pub fn release_notes<F>(&self, title: &str, mut changelog: F) -> String
+    where
+        F: FnMut(&LockedProject) -> Option<String>,
+    {
+        let mut text = format!("# {title}\n");
+        if self.is_empty() {
+            text.push_str("\nNo changes to the projects.\n");
+            return text;
+        }
+        let mut section = |heading: &str, entries: Vec<(String, Option<&LockedProject>)>| {
+            if entries.is_empty() {
+                return;
+            }
+            text.push_str(&format!("\n## {heading}\n\n"));
+            for (line, project) in entries {
+                text.push_str(&format!("- {line}\n"));
+                if let Some(excerpt) = project.and_then(&mut changelog).map(|x| excerpt(&x)) {
+                    text.push('\n');
+                    for line in excerpt.lines() {
+                        text.push_str(format!("  > {line}").trim_end());
+                        text.push('\n');
+                    }
+                    text.push('\n');
+                }
+            }
+        };
+        section(
+            "Added",
+            self.added
+                .iter()
+                .map(|x| {
+                    let line = format!("[{}]({}) {}", x.name, x.changelog_url(), x.version_number);
+                    (line, Some(*x))
+                })
+                .collect(),
+        );
+        section(
+            "Updated",
+            self.changed
+                .iter()
+                .map(|(old, new)| {
+                    let line = format!(
+                        "[{}]({}) {} -> {}",
+                        new.name,
+                        new.changelog_url(),
+                        old.version_number,
+                        new.version_number
+                    );
+                    (line, Some(*new))
+                })
+                .collect(),
+        );
+        section(
+            "Removed",
+            self.removed
+                .iter()
+                .map(|x| (format!("{} {}", x.name, x.version_number), None))
+                .collect(),
+        );
+        text
+    }
+}
+
+/// Shorten a changelog to its first lines
+fn excerpt(changelog: &str) -> String {
+    let lines = changelog.trim().lines().collect::<Vec<_>>();
+    let mut result = lines
+        .iter()
+        .take(CHANGELOG_EXCERPT_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > CHANGELOG_EXCERPT_LINES {
+        result.push_str("\n...");
+    }
+    result
+}
+
+impl std::fmt::Display for LockDiff<'_> {
+    /// Format the differences as a Markdown list
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        if !self.added.is_empty() {
+            writeln!(f, "Added:")?;
+            for x in &self.added {
+                writeln!(
+                    f,
+                    "- {} {} ({})",
+                    x.name,
+                    x.version_number,
+                    x.changelog_url()
+                )?;
+            }
+        }
+        if !self.removed.is_empty() {
+            writeln!(f, "Removed:")?;
+            for x in &self.removed {
+                writeln!(f, "- {} {}", x.name, x.version_number)?;
+            }
+        }
+        if !self.changed.is_empty() {
+            writeln!(f, "Updated:")?;
+            for (old, new) in &self.changed {
+                writeln!(
+                    f,
+                    "- {} {} -> {} ({})",
+                    new.name,
+                    old.version_number,
+                    new.version_number,
+                    new.changelog_url()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path() {
+        assert_eq!(
+            lock_path(Path::new("pack/mcmod.toml"), None),
+            PathBuf::from("pack/mcmod.lock")
+        );
+        assert_eq!(
+            lock_path(Path::new("pack/mcmod.toml"), Some("beta")),
+            PathBuf::from("pack/mcmod.beta.lock"),
+            "Channels shall have their own lockfile"
+        );
+        assert!(parse_channel("stable-2").is_ok());
+        assert!(
+            parse_channel("../stable").is_err(),
+            "Channels shall not name other paths"
+        );
+    }
+
+    fn locked(project_id: &str, version_id: &str, version_number: &str) -> LockedProject {
+        LockedProject {
+            project_id: project_id.into(),
+            slug: project_id.to_lowercase(),
+            name: project_id.into(),
+            version_id: version_id.into(),
+            version_number: version_number.into(),
+            loader: Some(ModLoader::Fabric),
+            license: Some("MIT".into()),
+            registry: "modrinth".into(),
+            reasons: vec!["configured".into()],
+            note: None,
+            icon_url: None,
+            files: vec![LockedFile {
+                name: format!("{project_id}.jar"),
+                url: format!("https://cdn.modrinth.com/{project_id}.jar"),
+                size: 1024,
+                hashes: BTreeMap::from([("sha1".into(), "da39a3ee".into())]),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![locked("Iris", "v1", "1.8.0")],
+        };
+        let text = toml::to_string(&lock).expect("Lockfile shall serialize");
+        assert_eq!(
+            Lockfile::loads(&text).expect("Lockfile shall deserialize"),
+            lock,
+            "Lockfile shall round trip through TOML"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let lock = |version_id: &str, file_name: &str| {
+            let mut project = locked("Iris", version_id, "1.8.0");
+            project.files[0].name = file_name.into();
+            Lockfile {
+                config_hash: None,
+                projects: vec![project],
+            }
+        };
+        lock("YL57xq9U", "iris-1.8.0.jar")
+            .check()
+            .expect("Lockfile shall accept plain ids and file names");
+        for version_id in ["", "a", "../../etc", "a/b", "é1"] {
+            assert!(
+                matches!(
+                    lock(version_id, "iris.jar").check(),
+                    Err(Error::InvalidLockfile(_))
+                ),
+                "Lockfile shall reject the version id {version_id:?}"
+            );
+        }
+        for file_name in ["", "../iris.jar", "mods/iris.jar", "mods\\iris.jar"] {
+            assert!(
+                matches!(
+                    lock("YL57xq9U", file_name).check(),
+                    Err(Error::InvalidLockfile(_))
+                ),
+                "Lockfile shall reject the file name {file_name:?}"
+            );
+        }
+        let text = toml::to_string(&lock("..", "iris.jar")).expect("Lockfile shall serialize");
+        assert!(
+            Lockfile::loads(&text).is_err(),
+            "Lockfile shall be checked when it is loaded"
+        );
+    }
+
+    #[test]
+    fn test_to_db() {
+        let mut sodium = locked("Sodium", "v2", "0.6.0");
+        sodium.note = Some("Faster rendering".into());
+        let mut indium = locked("Indium", "v3", "1.0.0");
+        indium.reasons = vec!["dependency of sodium".into()];
+        let lock = Lockfile {
+            config_hash: None,
+            projects: vec![indium, sodium],
+        };
+        let mod_db = lock.to_db();
+        assert_eq!(
+            Lockfile::from_db(&mod_db),
+            lock,
+            "A lockfile shall round trip through the resolved versions"
+        );
+        assert_eq!(
+            mod_db
+                .get_provenance(&VersionId::from(String::from("v3")))
+                .map(|x| x.reasons.clone()),
+            Some(vec![Reason::DependencyOf(String::from("v2").into())]),
+            "Dependents shall be found by their slug"
+        );
+    }
+
+    #[test]
+    fn test_remove_project() {
+        let with_reasons = |id: &str, version_id: &str, reasons: &[&str]| LockedProject {
+            reasons: reasons.iter().map(|x| x.to_string()).collect(),
+            ..locked(id, version_id, "1.0.0")
+        };
+        let mut lock = Lockfile {
+            config_hash: None,
+            projects: vec![
+                with_reasons("Iris", "v1", &["configured"]),
+                with_reasons("Sodium", "v2", &["dependency of iris"]),
+                with_reasons("Indium", "v3", &["dependency of sodium"]),
+                with_reasons(
+                    "FabricApi",
+                    "v4",
+                    &["dependency of v3", "dependency of modmenu"],
+                ),
+                with_reasons("ModMenu", "v5", &["configured"]),
+            ],
+        };
+        let removed = lock.remove_project("iris");
+        assert_eq!(
+            removed.iter().map(|x| x.slug.as_str()).collect::<Vec<_>>(),
+            vec!["iris", "sodium", "indium"],
+            "Dependencies only removed projects need shall be removed"
+        );
+        assert_eq!(
+            lock.projects
+                .iter()
+                .map(|x| x.slug.as_str())
+                .collect::<Vec<_>>(),
+            vec!["fabricapi", "modmenu"],
+            "Dependencies other projects need shall be kept"
+        );
+        assert!(lock.remove_project("iris").is_empty());
+        assert_eq!(
+            lock.remove_project("ModMenu")[0].slug,
+            "modmenu",
+            "A project shall be removed by its id"
+        );
+        assert_eq!(
+            lock.remove_project("v4")[0].slug,
+            "fabricapi",
+            "A project shall be removed by its version id"
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = Lockfile {
+            config_hash: None,
+            projects: vec![
+                locked("Iris", "v1", "1.8.0"),
+                locked("Lithium", "v2", "0.15"),
+            ],
+        };
+        let new = Lockfile {
+            config_hash: None,
+            projects: vec![locked("Iris", "v3", "1.8.1"), locked("Sodium", "v4", "0.6")],
+        };
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![&new.projects[1]], "Diff shall find added");
+        assert_eq!(
+            diff.removed,
+            vec![&old.projects[1]],
+            "Diff shall find removed"
+        );
+        assert_eq!(
+            diff.changed,
+            vec![(&old.projects[0], &new.projects[0])],
+            "Diff shall find updated"
+        );
+        assert!(old.diff(&old).is_empty(), "Diff shall find no changes");
+
+        let flavor = |version_id: &str, version_number: &str, loader: ModLoader| LockedProject {
+            loader: Some(loader),
+            ..locked("Terralith", version_id, version_number)
+        };
+        let old = Lockfile {
+            config_hash: None,
+            projects: vec![
+                flavor("v1", "2.5", ModLoader::Datapack),
+                flavor("v2", "2.5", ModLoader::Fabric),
+            ],
+        };
+        let new = Lockfile {
+            config_hash: None,
+            projects: vec![
+                flavor("v1", "2.5", ModLoader::Datapack),
+                flavor("v3", "2.6", ModLoader::Fabric),
+            ],
+        };
+        let diff = old.diff(&new);
+        assert!(
+            diff.added.is_empty() && diff.removed.is_empty(),
+            "Diff shall match each flavor of a project by its loader"
+        );
+        assert_eq!(
+            diff.changed,
+            vec![(&old.projects[1], &new.projects[1])],
+            "Diff shall find the updated flavor of a project"
+        );
+    }
+
+    #[test]
+    fn test_release_notes() {
+        let old = Lockfile {
+            config_hash: None,
+            projects: vec![
+                locked("Iris", "v1", "1.8.0"),
+                locked("Lithium", "v2", "0.15"),
+            ],
+        };
+        let new = Lockfile {
+            config_hash: None,
+            projects: vec![locked("Iris", "v3", "1.8.1")],
+        };
+        let changelog = (1..=10).map(|x| format!("Fix {x}")).collect::<Vec<_>>();
+        let notes = old
+            .diff(&new)
+            .release_notes("Pack 1.1", |_| Some(changelog.join("\n")));
+        assert!(
+            notes.starts_with("# Pack 1.1\n"),
+            "Release notes shall have a title"
+        );
+        assert!(
+            notes.contains(
+                "- [Iris](https://modrinth.com/project/iris/version/v3) 1.8.0 -> 1.8.1\n"
+            ),
+            "Release notes shall link updated versions"
+        );
+        assert!(
+            notes.contains("  > Fix 8\n  > ...\n") && !notes.contains("Fix 9"),
+            "Release notes shall quote an excerpt of the changelog"
+        );
+        assert!(
+            notes.contains("## Removed\n\n- Lithium 0.15\n"),
+            "Release notes shall list removed projects"
+        );
+    }
+}
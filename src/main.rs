@@ -1,31 +1,138 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use clap::Parser;
-use error::Result;
-
-use crate::types::*;
-
-mod cache;
-mod config;
-mod error;
-mod labrinth;
-mod solver;
-mod types;
+use mcmod::error::{Error, Result};
+use mcmod::i18n::tr;
+use mcmod::output::status;
+use mcmod::types::*;
+use mcmod::{
+    adopt, advisory, backend, cache, chaos, config, curseforge, dev, error, export, fixtures,
+    fsutil, i18n, icons, interrupt, labrinth, launcher, lockfile, manifest, mojang, output, plan,
+    prompt, registry_fixture, reputation, resume, serve, server, solver, summary, tags, templates,
+    trace, tree, types, verify, workspace, world,
+};
+use rayon::prelude::*;
 
 /// The options passed to the program through the command line interface
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 struct Cli {
     /// The config file to load. Defaults to ./mcmod.toml
+    #[arg(env = "MCMOD_CONFIG")]
     config: Option<PathBuf>,
 
     /// Override the default game version in the config
-    #[arg(long, short = 'v', value_parser = clap::value_parser!(MinecraftVersion))]
+    #[arg(long, short = 'v', env = "MCMOD_GAME_VERSION", value_parser = clap::value_parser!(MinecraftVersion))]
     game_version: Option<MinecraftVersion>,
 
+    /// Override the game version of every project, including those that set their own
+    #[arg(long, value_name = "GAME_VERSION", value_parser = clap::value_parser!(MinecraftVersion))]
+    game_version_all: Option<MinecraftVersion>,
+
     /// Override the default mod loader in the config
-    #[arg(long, short)]
+    #[arg(long, short, env = "MCMOD_LOADER")]
     loader: Option<ModLoader>,
 
+    /// Override the side to install projects for
+    #[arg(long, short)]
+    target: Option<Side>,
+
+    /// Install into this .minecraft directory instead of the one in the config
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["launcher", "instance"])]
+    minecraft_dir: Option<PathBuf>,
+
+    /// Download and cache files in this directory instead of the one in the config
+    #[arg(long, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Target an instance of this launcher instead of the default .minecraft directory
+    #[arg(long)]
+    launcher: Option<launcher::Launcher>,
+
+    /// Name of the launcher instance to target
+    #[arg(long)]
+    instance: Option<String>,
+
+    /// Name of the world under `saves` to install data packs into
+    #[arg(long, env = "MCMOD_WORLD")]
+    world: Option<String>,
+
+    /// Use the lockfile of this channel, like `beta` for mcmod.beta.lock, instead of mcmod.lock
+    #[arg(long, env = "MCMOD_CHANNEL", value_parser = parse_channel)]
+    channel: Option<String>,
+
+    /// Fetch all project and version metadata again instead of using the cache. Implies --update.
+    #[arg(long)]
+    refresh: bool,
+
+    /// Resolve the projects again for newer versions, even if the lockfile is up to date with the
+    /// config
+    #[arg(long, short = 'U')]
+    update: bool,
+
+    /// Ask the prompts again, like which groups to install, instead of using the answers
+    /// remembered from earlier runs
+    #[arg(long)]
+    reconfigure: bool,
+
+    /// Fail instead of resolving if the lockfile is missing or not up to date with the config
+    #[arg(long, conflicts_with_all = ["update", "refresh"])]
+    locked: bool,
+
+    /// Like --locked, and also fail instead of fetching metadata that is not cached. Files are
+    /// still downloaded.
+    #[arg(long, conflicts_with_all = ["update", "refresh"])]
+    frozen: bool,
+
+    /// Accept the Minecraft EULA (https://aka.ms/MinecraftEULA) in eula.txt when installing for
+    /// the server
+    #[arg(long)]
+    accept_eula: bool,
+
+    /// Refuse to install files that no allowlist or reputation service in the config knows, by
+    /// default those Modrinth knows
+    #[arg(long)]
+    require_known_hashes: bool,
+
+    /// Check cached files against their full hashes before installing them, instead of only their
+    /// size and a sample of their contents
+    #[arg(long)]
+    full_hash: bool,
+
+    /// Save every API response to this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "replay_fixtures")]
+    record_fixtures: Option<PathBuf>,
+
+    /// Answer API requests from responses saved with --record-fixtures instead of the network
+    #[arg(long, value_name = "DIR")]
+    replay_fixtures: Option<PathBuf>,
+
+    /// Resolve projects from the registry in this JSON file instead of the network, to test the
+    /// solver or reproduce a resolution bug
+    #[arg(long, value_name = "FILE")]
+    registry_fixture: Option<PathBuf>,
+
+    /// Print the metadata of every request and response, with tokens and passwords redacted, to
+    /// attach to a bug report. With a file, the trace and the bodies of the responses are also
+    /// written to it.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+    trace_http: Option<Option<PathBuf>>,
+
+    /// Inject network failures, slow responses, and corrupt bytes into API responses, chosen by
+    /// this seed, to test how failures are handled
+    #[arg(long, hide = true, value_name = "SEED")]
+    chaos: Option<u64>,
+
+    /// Number of threads used to hash files. Defaults to one per CPU.
+    #[arg(
+        long,
+        short,
+        env = "MCMOD_JOBS",
+        default_value_t = 0,
+        hide_default_value = true
+    )]
+    jobs: usize,
+
     /// Download the mod fles without installing them
     #[arg(long, short)]
     download: bool,
@@ -34,116 +141,2604 @@ struct Cli {
     #[arg(long, short)]
     install: bool,
 
+    /// Override how files are copied into the install directory
+    #[arg(long)]
+    install_mode: Option<fsutil::CopyMode>,
+
+    /// Include these groups of the config instead of choosing them interactively. Use `none` to
+    /// include no groups.
+    #[arg(long, value_name = "GROUPS", value_delimiter = ',')]
+    preset: Option<Vec<String>>,
+
+    /// When to color the output
+    #[arg(long, value_name = "WHEN", default_value = "auto", env = "MCMOD_COLOR")]
+    color: output::ColorChoice,
+
+    /// Only print ASCII characters
+    #[arg(long)]
+    ascii: bool,
+
+    /// The language to print messages in, like `de` or `pt-BR`. Defaults to the system language.
+    #[arg(long, env = "MCMOD_LANG")]
+    lang: Option<String>,
+
     /// Validate internal data types
     #[arg(long)]
     validate: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Cli {
+    /// Get the fixtures to record to or replay from
+    fn fixtures(&self) -> Option<fixtures::Fixtures> {
+        use fixtures::{FixtureMode, Fixtures};
+        match (&self.record_fixtures, &self.replay_fixtures) {
+            (Some(dir), _) => Some(Fixtures::new(dir.clone(), FixtureMode::Record)),
+            (_, Some(dir)) => Some(Fixtures::new(dir.clone(), FixtureMode::Replay)),
+            _ => None,
+        }
+    }
+
+    /// Get the values that override the config for this invocation
+    fn overrides(&self) -> config::ConfigOverrides {
+        config::ConfigOverrides {
+            game_version: self.game_version,
+            game_version_all: self.game_version_all,
+            loader: self.loader,
+            target: self.target,
+            data_dir: self.data_dir.clone(),
+            minecraft_dir: self.minecraft_dir.clone(),
+            world: self.world.clone(),
+        }
+    }
+}
+
+/// Commands to run after the projects are resolved
+#[derive(clap::Subcommand, Debug, Clone, PartialEq, Eq)]
+enum Command {
+    /// Write a new config from a template. Does not load an existing config.
+    Init {
+        /// The template to start from
+        #[arg(long, short, default_value = "basic")]
+        template: String,
+        /// Fetch the templates from a JSON index at this URL instead of the builtin templates
+        #[arg(long, value_name = "URL")]
+        index: Option<String>,
+        /// List the templates instead of writing a config
+        #[arg(long)]
+        list: bool,
+        /// Overwrite an existing config
+        #[arg(long)]
+        force: bool,
+    },
+    /// Add a project to the config, after checking that it resolves for the configured game
+    /// version and loader. Comments and formatting of the config are kept.
+    Add {
+        /// The slug of the project, like `sodium`
+        slug: String,
+    },
+    /// Remove a project from the config, and drop it and the dependencies no other project needs
+    /// from the lockfile
+    Remove {
+        /// The slug of the project, like `sodium`
+        slug: String,
+        /// Also remove the installed files of the removed projects
+        #[arg(long)]
+        uninstall: bool,
+    },
+    /// Search Modrinth for projects, narrowed to the game version and loader given by
+    /// --game-version and --loader. Does not load a config.
+    Search {
+        /// The words to search for
+        query: String,
+        /// Only find projects of this type
+        #[arg(long = "type", value_name = "TYPE")]
+        project_type: Option<ProjectType>,
+        /// The number of projects to find
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Print the hashes of files as Modrinth identifies them, in the format of sha512sum, or with
+    /// --lookup the project and version on Modrinth that published each. Does not load a config.
+    Hash {
+        /// The files to hash
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// The algorithm of the printed hashes
+        #[arg(long, value_enum, default_value_t)]
+        algorithm: verify::HashAlgorithm,
+        /// Look up the version on Modrinth that published each file
+        #[arg(long)]
+        lookup: bool,
+    },
+    /// Show the details of a project, and its latest version for the game version and loader of
+    /// the config, or of --game-version and --loader without a config
+    Info {
+        /// The slug of the project, like `sodium`
+        slug: String,
+    },
+    /// Write a config from the mod list of another tool, so its users can migrate. Does not load
+    /// an existing config.
+    AdoptConfig {
+        /// The tool to adopt the mod list of
+        #[arg(long, default_value = "ferium")]
+        from: adopt::AdoptSource,
+        /// The file or instance directory to read. Defaults to where the tool keeps its mod lists.
+        path: Option<PathBuf>,
+        /// The profile or instance to adopt. Defaults to the active profile, or the only instance.
+        #[arg(long)]
+        profile: Option<String>,
+        /// Overwrite an existing config
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the resolved projects
+    List {
+        /// Only list projects of this type
+        #[arg(long = "type")]
+        project_type: Option<ProjectType>,
+    },
+    /// Show the resolved projects as trees of the dependencies each required
+    Tree,
+    /// Explain why a project was resolved, by the chains of projects that required it
+    Why {
+        /// The slug of the project, like `fabric-api`
+        slug: String,
+    },
+    /// Check that the installed and cached files match their hashes. Exits
+    /// with 1 if any files differ, or 2 if they could not be checked.
+    #[command(alias = "status")]
+    Verify,
+    /// Check the locked projects and the installed files against the advisory feeds in
+    /// security.advisories. Exits with 1 if any are affected, or 2 if they could not be checked.
+    Audit {
+        /// Also flag configured projects that were not updated for this long, like `18mo`
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        stale: Option<config::Age>,
+    },
+    /// Remove old versions from the cache, keeping cache.keep-versions of each project
+    Gc,
+    /// Export the resolved projects in a machine readable format
+    Export {
+        /// The format to export
+        format: export::ExportFormat,
+        /// Write the export to this file instead of printing it. Required for `curseforge`.
+        #[arg(long, short, required_if_eq("format", "curseforge"))]
+        output: Option<PathBuf>,
+        /// The version of the mod loader, like `0.16.14`, for formats that name it. Required for
+        /// `curseforge`.
+        #[arg(long, required_if_eq("format", "curseforge"))]
+        loader_version: Option<String>,
+        /// The name of the modpack, for formats that name it. Defaults to the name of the
+        /// directory of the config.
+        #[arg(long)]
+        name: Option<String>,
+        /// The version of the modpack, for formats that name it
+        #[arg(long, default_value = "1.0.0")]
+        pack_version: String,
+        /// Bundle the files CurseForge does not know in the overrides of a `curseforge` export,
+        /// instead of leaving them out. Check that their licenses allow redistribution.
+        #[arg(long)]
+        bundle_unmatched: bool,
+    },
+    /// Show the projects added, removed, and updated between two lockfiles. Without arguments, the
+    /// lockfile of the config is compared to the one committed at git HEAD.
+    Diff {
+        /// The old lockfile
+        old: Option<PathBuf>,
+        /// The new lockfile. Defaults to the lockfile of the config.
+        new: Option<PathBuf>,
+    },
+    /// Write Markdown release notes of the changes between two lockfiles, quoting the changelog
+    /// of each new version. The lockfiles default as for diff.
+    ReleaseNotes {
+        /// The old lockfile
+        old: Option<PathBuf>,
+        /// The new lockfile. Defaults to the lockfile of the config.
+        new: Option<PathBuf>,
+        /// The title of the document
+        #[arg(long, default_value = "Release notes")]
+        title: String,
+        /// Write the document to this file instead of printing it
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Copy the lockfile of one channel into another after validating it, and print the release
+    /// notes of the changes, like `promote beta stable`
+    Promote {
+        /// The channel to promote
+        #[arg(value_parser = parse_channel)]
+        from: String,
+        /// The channel to replace
+        #[arg(value_parser = parse_channel)]
+        to: String,
+        /// The title of the release notes. Defaults to the name of the channel being replaced.
+        #[arg(long)]
+        title: Option<String>,
+        /// Write the release notes to this file instead of printing them
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Resolve, download, and install the changes, remove files that are no longer resolved, and
+    /// print what changed
+    Sync,
+
+    /// Continue the download or install that was interrupted, without resolving again
+    Resume,
+
+    /// Resolve the projects and download their files into the cache, without needing or touching
+    /// any install directory, e.g. to warm a cache in CI for later jobs
+    Fetch,
+
+    /// Install jars built locally into the mods directory of each instance, and install them
+    /// again whenever they change, until Ctrl-C. The rest of the pack is left as is.
+    Dev {
+        /// The jars to install, like `build/libs/mymod.jar`
+        #[arg(required = true)]
+        jars: Vec<PathBuf>,
+    },
+    /// Resolve the projects and write a JSON plan of the result, to be reviewed and applied with
+    /// apply, without downloading, installing, or changing the lockfile
+    Plan {
+        /// Write the plan to this file instead of printing it
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Download and install the projects of a plan written by plan, without resolving, and
+    /// replace the lockfile with the plan's
+    Apply {
+        /// The plan file
+        plan: PathBuf,
+    },
+
+    /// Answer JSON-RPC requests for frontends, one per line, as a long-lived process. Progress is
+    /// printed to stderr.
+    Serve {
+        /// Read requests from stdin and write responses to stdout
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
+    /// Run a command for every config of a workspace, sharing the metadata cache between them
+    Ws {
+        /// The workspace file listing the configs. Defaults to ./mcmod-workspace.toml
+        #[arg(long, short, value_name = "FILE", env = "MCMOD_WORKSPACE")]
+        workspace: Option<PathBuf>,
+
+        #[command(subcommand)]
+        command: WsCommand,
+    },
+}
+
+/// Commands run for every config of a workspace
+#[derive(clap::Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+enum WsCommand {
+    /// Show the projects that resolving again would add, remove, or update, without writing the
+    /// lockfiles. Exits with 1 if any config is out of date.
+    Outdated,
+    /// Resolve every config again and write its lockfile, downloading or installing with
+    /// --download or --install, and print what changed
+    Update,
+}
+
+/// Parse an age given on the command line, like `18mo`
+fn parse_age(value: &str) -> std::result::Result<config::Age, String> {
+    value.parse().map_err(|e: Error| e.to_string())
+}
+
+/// Parse a lock channel given on the command line
+fn parse_channel(value: &str) -> std::result::Result<String, String> {
+    lockfile::parse_channel(value).map_err(|e| e.to_string())
+}
+
+/// Get the path of the config file to load
+fn config_path(cli: &Cli) -> PathBuf {
+    cli.config
+        .to_owned()
+        .unwrap_or_else(|| PathBuf::from("./mcmod.toml"))
+}
+
+/// Get the path of the lockfile of the config, in the channel if one is given
+fn lock_path(cli: &Cli) -> PathBuf {
+    lockfile::lock_path(&config_path(cli), cli.channel.as_deref())
+}
+
+/// Load a config, overriding values as specified in cli
+fn load_config(cli: &Cli) -> Result<config::Config> {
+    let path = config_path(cli);
+    let mut mcmod = config::Config::loads(std::fs::read_to_string(&path)?.as_str())?;
+    let config_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    mcmod.hooks.load(config_dir)?;
+    if let Some(x) = &mut mcmod.install.overrides {
+        *x = config_dir.join(&x);
+    }
+    mcmod.paths.apply_env();
+    mcmod.apply_overrides(&cli.overrides());
+    mcmod.cache.refresh = cli.refresh;
+    mcmod.frozen = cli.frozen;
+    mcmod.server.accept_eula = cli.accept_eula;
+    mcmod.security.require_known_hashes |= cli.require_known_hashes;
+    mcmod.install.full_hash |= cli.full_hash;
+    cli.install_mode.inspect(|x| mcmod.install.mode = *x);
+    mcmod.fixtures = cli.fixtures();
+    mcmod.chaos = cli.chaos.map(|x| Arc::new(chaos::Chaos::new(x)));
+    if let Some(path) = &cli.registry_fixture {
+        mcmod.registry_fixture = Some(Arc::new(registry_fixture::FixtureRegistry::load(path)?));
+    }
+    prompt::remember_answers(&mcmod.paths.data, cli.reconfigure);
+    select_groups(cli, &mut mcmod)?;
+    let (client, data_dir) = (mcmod.client(), mcmod.paths.data.clone());
+    let mut manifest = None;
+    mcmod.resolve_latest(|x| {
+        if manifest.is_none() {
+            manifest = Some(mojang::VersionManifest::load(&client, &data_dir)?);
+        }
+        manifest
+            .as_ref()
+            .expect("Manifest was just loaded")
+            .resolve(x)
+    })?;
+    if cli.launcher.is_some() || cli.instance.is_some() {
+        mcmod.paths.dot_minecraft = None;
+        mcmod.paths.launcher = cli.launcher.or(mcmod.paths.launcher);
+        mcmod.paths.instance = cli.instance.clone().or(mcmod.paths.instance.take());
+        if cli.instance.is_some() {
+            mcmod.paths.instances.clear();
+        }
+    }
+    Ok(mcmod)
+}
+
+/// Choose the groups of the config to include, from the preset, or by asking when installing
+fn select_groups(cli: &Cli, mod_config: &mut config::Config) -> Result<()> {
+    if let Some(preset) = &cli.preset {
+        let names = preset.iter().filter(|x| *x != "none").cloned().collect();
+        return mod_config.select_groups(names);
+    }
+    if !cli.install || mod_config.groups.is_empty() {
+        return Ok(());
+    }
+    let names = mod_config.groups.keys().cloned().collect::<Vec<_>>();
+    let options = mod_config
+        .groups
+        .iter()
+        .map(|(name, group)| match &group.description {
+            Some(x) => format!("{name}: {x}"),
+            None => name.clone(),
+        })
+        .collect::<Vec<_>>();
+    let defaults = names
+        .iter()
+        .map(|x| mod_config.is_group_selected(x))
+        .collect::<Vec<_>>();
+    let key = format!(
+        "groups {}",
+        std::path::absolute(config_path(cli))?.display()
+    );
+    let chosen = prompt::choose_many(&key, &tr!("choose-groups"), &options, &defaults)?;
+    mod_config.select_groups(
+        names
+            .into_iter()
+            .zip(chosen)
+            .filter_map(|(name, chosen)| chosen.then_some(name))
+            .collect(),
+    )
+}
+
+fn solve_versions(mod_config: &config::Config, locked: lockfile::Lockfile) -> Result<types::ModDB> {
+    let solution = solver::ModSolver::new(mod_config)
+        .with_locked(locked)
+        .solve()
+        .inspect_err(|e| {
+            if !matches!(e, Error::Interrupted) {
+                summary::record(|x| {
+                    x.failed += 1;
+                    x.suggest(tr!("next-resolve-failed"));
+                });
+            }
+        })?;
+    let (mod_db, skipped) = (solution.mod_db, solution.skipped);
+    summary::record(|x| {
+        x.resolved += mod_db.get_versions().len();
+        x.skipped += skipped.len();
+        if !skipped.is_empty() {
+            x.suggest(tr!("next-skipped"));
+        }
+    });
+    if !skipped.is_empty() {
+        status!("{}", format_skipped(&skipped).trim_end());
+    }
+    if let Some(hooks) = mod_config.hooks.get() {
+        let problems = hooks.validate(&lockfile::Lockfile::from_db(&mod_db))?;
+        if !problems.is_empty() {
+            return Err(Error::HookRejected(problems));
+        }
+    }
+    Ok(mod_db)
+}
+
+/// Load the lockfile of the config, or an empty one if it has not been resolved yet. A channel
+/// without a lockfile starts from the lockfile of the config, so frozen projects stay frozen.
+fn load_lock(cli: &Cli) -> Result<lockfile::Lockfile> {
+    match find_lock(cli) {
+        Some(path) => lockfile::Lockfile::load(&path),
+        None => Ok(lockfile::Lockfile::default()),
+    }
+}
+
+/// Find the lockfile that load_lock reads, if there is one
+fn find_lock(cli: &Cli) -> Option<PathBuf> {
+    let default = lockfile::lock_path(&config_path(cli), None);
+    [lock_path(cli), default].into_iter().find(|x| x.is_file())
+}
+
+/// Warn that an explicit update keeps the frozen projects of the config at their locked versions
+fn frozen_warnings(mod_config: &config::Config, locked: &lockfile::Lockfile) -> Vec<String> {
+    mod_config
+        .projects()
+        .into_iter()
+        .chain(mod_config.optional_projects())
+        .filter(|x| x.frozen)
+        .filter_map(|project| {
+            let name = project.name.as_str();
+            let locked = locked.projects.iter().find(|x| x.is_entry(&project))?;
+            Some(tr!(
+                "frozen-not-updated",
+                name = name,
+                version = locked.version_number.as_str()
+            ))
+        })
+        .collect()
+}
+
+/// Resolve the projects of the config, keeping the locked versions of frozen projects, and write
+/// the lockfile. A lockfile resolved from the same config is used as is, without querying the
+/// registry, unless told to update.
+fn resolve(cli: &Cli, mod_config: &config::Config) -> Result<ModDB> {
+    let path = lock_path(cli);
+    let locked = load_lock(cli)?;
+    let config_hash = mod_config.resolution_hash();
+    let up_to_date = path.is_file() && locked.config_hash.as_ref() == Some(&config_hash);
+    if (cli.locked || cli.frozen) && !up_to_date {
+        return Err(Error::LockOutdated(path));
+    }
+    let (mod_db, lock) = if !cli.update && !cli.refresh && up_to_date {
+        status!(
+            "{}",
+            tr!("using-lockfile", path = path.display().to_string())
+        );
+        let mod_db = locked.to_db();
+        summary::record(|x| x.resolved += mod_db.get_versions().len());
+        (mod_db, locked)
+    } else {
+        if cli.update || cli.refresh {
+            for warning in frozen_warnings(mod_config, &locked) {
+                status!("{}", output::get().paint(output::Style::Warning, &warning));
+            }
+        }
+        let mod_db = solve_versions(mod_config, locked)?;
+        let mut lock = lockfile::Lockfile::from_db(&mod_db);
+        lock.config_hash = Some(config_hash);
+        lock.save(&path)?;
+        (mod_db, lock)
+    };
+    if mod_config.cache.icons {
+        let icons = icons::cache_icons(&mod_config.client(), &mod_config.paths.data, &lock);
+        for icon in &icons {
+            if let Some(error) = &icon.error {
+                status!(
+                    "{}",
+                    tr!(
+                        "icon-failed",
+                        name = icon.slug.as_str(),
+                        error = error.as_str()
+                    )
+                );
+            }
+        }
+    }
+    Ok(mod_db)
+}
+
+/// Answer a request of `serve`. The `config` and `preset` params replace those of the arguments.
+fn handle_request(
+    cli: &Cli,
+    method: &str,
+    params: serde_json::Value,
+) -> std::result::Result<serde_json::Value, serve::RpcError> {
+    let mut cli = cli.clone();
+    if let Some(config) = serve::param::<PathBuf>(&params, "config")? {
+        cli.config = Some(config);
+    }
+    if let Some(preset) = serve::param::<Vec<String>>(&params, "preset")? {
+        cli.preset = Some(preset);
+    }
+    match method {
+        "resolve" | "download" | "install" => {
+            let mod_config = load_config(&cli)?;
+            let mod_db = resolve(&cli, &mod_config)?;
+            if method != "resolve" {
+                prepare_files(&cli, &mod_config, &mod_db, method == "install")?;
+            }
+            Ok(serde_json::to_value(lockfile::Lockfile::from_db(&mod_db)).map_err(Error::from)?)
+        }
+        "icons" => {
+            let mod_config = load_config(&cli)?;
+            let lock = load_lock(&cli)?;
+            let icons = icons::cache_icons(&mod_config.client(), &mod_config.paths.data, &lock);
+            Ok(serde_json::to_value(icons).map_err(Error::from)?)
+        }
+        "hash" => {
+            let paths = serve::param::<Vec<PathBuf>>(&params, "paths")?
+                .ok_or_else(|| serve::RpcError::new(serve::INVALID_PARAMS, "paths is required"))?;
+            let files = match serve::param::<bool>(&params, "lookup")?.unwrap_or(false) {
+                true => identify_files(&cli, &paths)?,
+                false => verify::hash_files(&paths, cli.jobs)?
+                    .into_iter()
+                    .map(|x| (x, None))
+                    .collect(),
+            };
+            let files = paths
+                .iter()
+                .zip(files)
+                .map(|(path, (hashes, version))| {
+                    serde_json::json!({
+                        "path": path,
+                        "sha1": hashes.sha1,
+                        "sha512": hashes.sha512,
+                        "project_id": version.as_ref().map(|x| x.project_id.to_string()),
+                        "version_id": version.as_ref().map(|x| x.version_id.to_string()),
+                        "version_number": version.as_ref().map(|x| x.version_number.clone()),
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(serde_json::Value::from(files))
+        }
+        "search" => {
+            let query = serve::param::<String>(&params, "query")?
+                .ok_or_else(|| serve::RpcError::new(serve::INVALID_PARAMS, "query is required"))?;
+            let limit = serve::param::<usize>(&params, "limit")?.unwrap_or(10);
+            let facets = labrinth::SearchFacets {
+                game_version: serve::param(&params, "game_version")?,
+                loader: serve::param(&params, "loader")?,
+                project_type: serve::param(&params, "project_type")?,
+                ..Default::default()
+            };
+            let client = labrinth::Client::new().with_fixtures(cli.fixtures());
+            Ok(
+                serde_json::to_value(client.search(&query, &facets, limit)?)
+                    .map_err(Error::from)?,
+            )
+        }
+        _ => Err(serve::RpcError::new(
+            serve::METHOD_NOT_FOUND,
+            format!("No method {method:?}"),
+        )),
+    }
+}
+
+/// Summarize the optional projects that were skipped and why
+fn format_skipped(skipped: &[(ProjectSlug, error::Error)]) -> String {
+    let mut text = format!("{}\n", tr!("skipped-optional", count = skipped.len()));
+    for (name, error) in skipped {
+        text.push_str(&format!("  {name}:\n"));
+        for line in error.to_string().lines() {
+            text.push_str(&format!("    {line}\n"));
+        }
+    }
+    text
+}
+
+/// Get the shader loader to install shaders for, from the config or else the resolved mods named
+/// after a shader loader in the loader table
+fn shader_loader(mod_config: &config::Config, mod_db: &ModDB) -> Option<ModLoader> {
+    mod_config.install.shader_loader.or_else(|| {
+        let loaders = tags::LoaderTable::load(&mod_config.client(), &mod_config.paths.data);
+        mod_db.get_versions().into_iter().find_map(|version| {
+            let project = mod_db.get_project_by_id(&version.project_id)?;
+            loaders.shader_loader_of(project.slug.as_str())
+        })
+    })
+}
+
+/// Queue the files of a version that are not in the cache to be downloaded
+fn queue_version_files<'a>(
+    mod_manager: &cache::ModFileManager,
+    mod_db: &ModDB,
+    version: &'a ModVersion,
+    queue: &mut Vec<(&'a ModVersion, &'a ModFile)>,
+) {
+    let printed_name = mod_db
+        .get_project_by_id(&version.project_id)
+        .map(|x| x.name.as_str())
+        .unwrap_or(version.name.as_str());
+    status!(
+        "{}",
+        tr!(
+            "getting-files",
+            version = version.version_id.as_str(),
+            name = output::get().text(printed_name)
+        )
+    );
+    for mod_file in &version.files {
+        if mod_manager
+            .find_intact_file(&version.version_id, mod_file)
+            .is_some()
+        {
+            status!("  {}", tr!("using-cached", file = &mod_file.name));
+            summary::record(|x| x.cached += 1);
+        } else {
+            queue.push((version, mod_file));
+        }
+    }
+}
+
+/// The most files downloaded at once
+const PARALLEL_DOWNLOADS: usize = 4;
+
+/// The name of the page listing the installed projects, written with install.index-html
+const INDEX_PAGE: &str = "index.html";
+
+/// Download the queued files into the cache, several at once. A file that fails to download does
+/// not stop the others. Returns the names of the files that failed, or stops when interrupted.
+fn download_queue(
+    mod_manager: &cache::ModFileManager,
+    queue: &[(&ModVersion, &ModFile)],
+) -> Result<Vec<String>> {
+    let progress = output::Progress::new(queue.len(), |done, failed, total| {
+        tr!(
+            "download-progress",
+            done = done,
+            failed = failed,
+            total = total
+        )
+    });
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(PARALLEL_DOWNLOADS)
+        .build()
+        .map_err(|e| Error::IO(std::io::Error::other(e)))?;
+    let results = pool.install(|| {
+        queue
+            .par_iter()
+            .map(|(version, mod_file)| {
+                interrupt::check()?;
+                let task = tr!("downloading", file = &mod_file.name);
+                progress.start(&task);
+                let result = mod_manager.download_file(version, mod_file);
+                let lines = match &result {
+                    Ok(_) => vec![],
+                    Err(e) => {
+                        let error = tr!(
+                            "download-failed",
+                            file = &mod_file.name,
+                            error = e.to_string()
+                        );
+                        vec![format!(
+                            "  {}",
+                            output::get().paint(output::Style::Error, &error)
+                        )]
+                    }
+                };
+                progress.finish(&task, result.is_ok(), &lines);
+                Ok(result.is_err().then(|| mod_file.name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()
+    });
+    progress.end();
+    let failed = results?.into_iter().flatten().collect::<Vec<_>>();
+    summary::record(|x| {
+        x.downloaded += queue.len() - failed.len();
+        x.failed += failed.len();
+        if !failed.is_empty() {
+            x.suggest(tr!("retry-downloads"));
+        }
+    });
+    if !failed.is_empty() {
+        let summary = tr!(
+            "downloads-failed",
+            count = failed.len(),
+            total = queue.len()
+        );
+        status!("{}", output::get().paint(output::Style::Error, &summary));
+        for name in &failed {
+            status!("  {name}");
+        }
+    }
+    Ok(failed)
+}
+
+/// Construct the file manager for installing into an instance, or only downloading into the cache
+/// without one
+fn file_manager(
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    instance: Option<PathBuf>,
+) -> Result<cache::ModFileManager> {
+    let world_dir = instance.as_deref().and_then(|x| {
+        world::install_world_dir(
+            mod_config.defaults.target,
+            x,
+            mod_config.paths.world.as_deref(),
+        )
+    });
+    Ok(
+        cache::ModFileManager::new(mod_config.paths.data.clone(), instance, mod_config.client())
+            .with_copy_mode(mod_config.install.mode)
+            .with_shader_loader(shader_loader(mod_config, mod_db))
+            .with_staging(mod_config.paths.staging()?)
+            .with_hooks(mod_config.hooks.loaded.clone())
+            .with_world_dir(world_dir)
+            .with_install_names(mod_config.install_names(mod_db))
+            .with_full_hash(mod_config.install.full_hash)
+            .with_max_file_sizes(
+                mod_config.download.max_file_size.map(|x| x.bytes()),
+                mod_config.max_file_sizes(mod_db),
+            )
+            .with_download_timeout(mod_config.download.timeout()),
+    )
+}
+
+/// Download the files of the resolved versions and install them, saving the plan of the run so
+/// it can be resumed if it is interrupted
+fn prepare_files(
+    cli: &Cli,
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    install: bool,
+) -> Result<()> {
+    let plan = resume::RunPlan {
+        install,
+        prune: false,
+        instances: mod_config.paths.install_dirs(mod_config.defaults.target)?,
+        lock: lockfile::Lockfile::from_db(mod_db),
+    };
+    run_plan(cli, mod_config, mod_db, &plan)
+}
+
+/// Resolve against the lockfile, download and install the changes, remove the files that are no
+/// longer resolved, and print what changed
+fn sync(cli: &Cli) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let old = load_lock(cli)?;
+    let mod_db = resolve(cli, &mod_config)?;
+    let plan = resume::RunPlan {
+        install: true,
+        prune: true,
+        instances: mod_config.paths.install_dirs(mod_config.defaults.target)?,
+        lock: lockfile::Lockfile::from_db(&mod_db),
+    };
+    run_plan(cli, &mod_config, &mod_db, &plan)?;
+    print!("{}", old.diff(&plan.lock));
+    Ok(())
+}
+
+/// Resolve the projects and download their files into the cache. No install directory is needed,
+/// so the .minecraft directory and the server do not have to exist.
+fn fetch(cli: &Cli) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let mod_db = resolve(cli, &mod_config)?;
+    prepare_files_into(&mod_config, &mod_db, &[], false, false)
+}
+
+/// Resolve the projects and write the plan of what would change, without downloading, installing,
+/// or writing the lockfile. The plan is printed unless an output file is given.
+fn write_plan(cli: &Cli, output: Option<&PathBuf>) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let old = load_lock(cli)?;
+    let mod_db = solve_versions(&mod_config, old.clone())?;
+    let mut lock = lockfile::Lockfile::from_db(&mod_db);
+    lock.config_hash = Some(mod_config.resolution_hash());
+    let plan = plan::Plan::new(&mod_config.defaults, &old, lock);
+    let text = plan.to_json()?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            status!(
+                "{}",
+                tr!(
+                    "wrote-plan",
+                    path = path.display().to_string(),
+                    count = plan.changes.len()
+                )
+            );
+        }
+        None => println!("{text}"),
+    }
+    Ok(())
+}
+
+/// Download and install the projects of a plan without resolving, remove the files that are no
+/// longer planned, and replace the lockfile with the plan's
+fn apply_plan(cli: &Cli, path: &Path) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let plan = plan::Plan::load(path)?;
+    plan.check(&mod_config.defaults)?;
+    let old = load_lock(cli)?;
+    let run = resume::RunPlan {
+        install: true,
+        prune: true,
+        instances: mod_config.paths.install_dirs(mod_config.defaults.target)?,
+        lock: plan.lock,
+    };
+    run_plan(cli, &mod_config, &run.lock.to_db(), &run)?;
+    run.lock.save(&lock_path(cli))?;
+    print!("{}", old.diff(&run.lock));
+    Ok(())
+}
+
+/// Carry out the plan of a run, removing it once it finishes
+fn run_plan(
+    cli: &Cli,
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    plan: &resume::RunPlan,
+) -> Result<()> {
+    let path = resume::plan_path(&config_path(cli));
+    plan.save(&path)?;
+    prepare_files_into(
+        mod_config,
+        mod_db,
+        &plan.instances,
+        plan.install,
+        plan.prune,
+    )?;
+    resume::RunPlan::finish(&path)
+}
+
+/// Resume the run that was interrupted, without resolving again
+fn resume_run(cli: &Cli) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let plan = resume::RunPlan::load(&resume::plan_path(&config_path(cli)))?;
+    run_plan(cli, &mod_config, &plan.lock.to_db(), &plan)
+}
+
+/// Download the files of the resolved versions into the cache, and install them into each
+/// instance, pruning the files of the previous install if told to. Without instances the files are
+/// only downloaded.
+fn prepare_files_into(
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    instances: &[PathBuf],
+    install: bool,
+    prune: bool,
+) -> Result<()> {
+    check_advisories(mod_config, mod_db)?;
+    if install && mod_config.defaults.target == Side::Server {
+        set_up_server(mod_config, &instances[0])?;
+    }
+    let manager = file_manager(mod_config, mod_db, instances.first().cloned())?;
+    let versions = mod_db.get_versions();
+    let mut queue = Vec::new();
+    for version in &versions {
+        queue_version_files(&manager, mod_db, version, &mut queue);
+    }
+    let failed = download_queue(&manager, &queue)?;
+    if !failed.is_empty() {
+        return Err(Error::DownloadFailed(failed));
+    }
+    if install {
+        check_reputation(mod_config, &manager, &versions)?;
+    }
+    if install && instances.len() == 1 {
+        install_files(
+            mod_config,
+            mod_db,
+            &manager,
+            &instances[0],
+            &versions,
+            prune,
+        )?;
+    } else if install {
+        let results = instances
+            .par_iter()
+            .map(|instance| {
+                file_manager(mod_config, mod_db, Some(instance.clone())).and_then(|manager| {
+                    install_files(mod_config, mod_db, &manager, instance, &versions, prune)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut failed = Vec::<PathBuf>::new();
+        let mut interrupted = false;
+        for (instance, result) in instances.iter().zip(results) {
+            match result {
+                Err(Error::Interrupted) => interrupted = true,
+                Ok(count) => status!(
+                    "{}",
+                    tr!(
+                        "installed-into",
+                        count = count,
+                        path = instance.display().to_string()
+                    )
+                ),
+                Err(e) => {
+                    let path = instance.display().to_string();
+                    status!(
+                        "{}",
+                        tr!("install-failed", path = path, error = e.to_string())
+                    );
+                    failed.push(instance.clone());
+                }
+            }
+        }
+        if interrupted {
+            return Err(Error::Interrupted);
+        }
+        if !failed.is_empty() {
+            return Err(Error::InstallFailed(failed));
+        }
+    }
+    manager.mark_used(&versions)
+}
+
+/// Load the advisories of the feeds in the config
+fn load_advisories(mod_config: &config::Config) -> Result<advisory::Advisories> {
+    advisory::Advisories::load(
+        &mod_config.client(),
+        &mod_config.paths.data,
+        &mod_config.security.advisories,
+    )
+}
+
+/// Refuse to download or install the resolved versions if an advisory lists any of them, their
+/// projects, or their files
+fn check_advisories(mod_config: &config::Config, mod_db: &ModDB) -> Result<()> {
+    if mod_config.security.advisories.is_empty() {
+        return Ok(());
+    }
+    let advisories = load_advisories(mod_config)?;
+    let mut affected = Vec::<String>::new();
+    for version in mod_db.get_versions() {
+        let name = mod_db
+            .get_project_by_id(&version.project_id)
+            .map_or(version.name.as_str(), |x| x.slug.as_str());
+        for advisory in advisories.matching_version(version) {
+            affected.push(format!(
+                "{name} {}: {} ({})",
+                version.version_number, advisory.summary, advisory.id
+            ));
+        }
+    }
+    match affected.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Advised(affected)),
+    }
+}
+
+/// Check the hashes of the downloaded files against the allowlists and reputation service of the
+/// config before they are installed. Unknown files are refused if known hashes are required, and
+/// warned about otherwise.
+fn check_reputation(
+    mod_config: &config::Config,
+    manager: &cache::ModFileManager,
+    versions: &[&ModVersion],
+) -> Result<()> {
+    let security = &mod_config.security;
+    if !security.checks_hashes() {
+        return Ok(());
+    }
+    let reputation = reputation::Reputation::load(&security.allowlists, security.reputation())?;
+    let files = versions
+        .iter()
+        .flat_map(|version| {
+            version
+                .files
+                .iter()
+                .filter_map(|x| manager.find_file(&version.version_id, &x.name))
+        })
+        .collect::<Vec<_>>();
+    let hashes = verify::hash_files(&files, 0)?;
+    let client = mod_config.client();
+    let known = hashes
+        .par_iter()
+        .map(|x| reputation.is_known(&client, x))
+        .collect::<Result<Vec<_>>>()?;
+    let unknown = files
+        .iter()
+        .zip(known)
+        .filter(|(_, known)| !known)
+        .map(|(path, _)| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+    if security.require_known_hashes && !unknown.is_empty() {
+        return Err(Error::UnknownHashes(unknown));
+    }
+    for name in &unknown {
+        let warning = tr!("unknown-hash", file = name.as_str());
+        status!("{}", output::get().paint(output::Style::Warning, &warning));
+    }
+    Ok(())
+}
+
+/// Write the server.properties of the config into a server directory, and accept the EULA if told
+/// to, so the server is ready to start after installing
+fn set_up_server(mod_config: &config::Config, server_dir: &Path) -> Result<()> {
+    let properties = mod_config.server.properties(&mod_config.defaults);
+    if !properties.is_empty() && server::write_properties(server_dir, &properties)? {
+        let path = server_dir.join("server.properties");
+        status!(
+            "{}",
+            tr!("wrote-properties", path = path.display().to_string())
+        );
+    }
+    if server::is_eula_accepted(server_dir) {
+        return Ok(());
+    }
+    match mod_config.server.accept_eula {
+        true => {
+            server::accept_eula(server_dir)?;
+            status!("{}", tr!("accepted-eula", url = server::EULA_URL));
+        }
+        false => status!("{}", tr!("eula-not-accepted", url = server::EULA_URL)),
+    }
+    Ok(())
+}
+
+/// Check if a version was chosen by the config rather than only as a dependency
+fn is_pinned(mod_db: &ModDB, version: &ModVersion) -> bool {
+    mod_db.get_provenance(&version.version_id).is_some_and(|x| {
+        x.reasons
+            .iter()
+            .any(|x| !matches!(x, Reason::DependencyOf(_)))
+    })
+}
+
+/// Install the files of the versions into an instance, and return how many files were installed.
+/// When pruning, the files of the previous install that this one does not have are removed.
+fn install_files(
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    manager: &cache::ModFileManager,
+    instance: &Path,
+    versions: &[&ModVersion],
+    prune: bool,
+) -> Result<usize> {
+    let is_pinned = |version: &ModVersion| is_pinned(mod_db, version);
+    let mut installable = Vec::<&ModVersion>::new();
+    for version in versions {
+        if manager.can_install(version) {
+            installable.push(version);
+        } else {
+            let name = mod_db
+                .get_project_by_id(&version.project_id)
+                .map_or(version.name.as_str(), |x| x.name.as_str());
+            status!("{}", tr!("skipping-shader", name = name));
+            summary::record(|x| x.skipped += 1);
+        }
+    }
+    let installs = manager.plan_installs(
+        &installable,
+        mod_config.install.collisions,
+        |version| mod_db.get_loader(&version.version_id),
+        |version| {
+            mod_db
+                .get_project_by_id(&version.project_id)
+                .map_or(version.project_id.to_string(), |x| x.slug.to_string())
+        },
+        is_pinned,
+    )?;
+    if let Some(world) = manager.world_dir()
+        && installs.iter().any(|x| x.path.starts_with(&world))
+    {
+        check_world(mod_config, &world)?;
+    }
+    let mut changed = Vec::<(&Path, Option<PathBuf>)>::new();
+    for install in &installs {
+        let result = interrupt::check().and_then(|_| {
+            status!(
+                "{}",
+                tr!("installing", path = install.path.display().to_string())
+            );
+            let backup = match install.path.exists() {
+                true => {
+                    let backup = backup_path(&install.path);
+                    fsutil::move_file(&install.path, &backup)?;
+                    Some(backup)
+                }
+                false => None,
+            };
+            changed.push((&install.path, backup));
+            manager.install_file(install)
+        });
+        if let Err(e) = result {
+            let error = tr!("error", error = e.to_string());
+            status!("  {}", output::get().paint(output::Style::Error, &error));
+            if !matches!(e, Error::Interrupted) {
+                summary::record(|x| {
+                    x.failed += 1;
+                    x.suggest(tr!("next-install-failed"));
+                });
+            }
+            roll_back(&changed);
+            return Err(e);
+        }
+    }
+    for backup in changed.iter().filter_map(|(_, x)| x.as_ref()) {
+        let _ = std::fs::remove_file(fsutil::long_path(backup));
+    }
+    summary::record(|x| x.installed += installs.len());
+    let manifest = manifest::InstallManifest::from_installs(instance, &installs);
+    let previous = manifest::InstallManifest::load(instance)?;
+    manifest.save(instance)?;
+    if mod_config.install.index_html {
+        let installed = installs
+            .iter()
+            .map(|x| x.version.version_id.as_str())
+            .collect::<std::collections::HashSet<_>>();
+        let mut lock = lockfile::Lockfile::from_db(mod_db);
+        lock.projects
+            .retain(|x| installed.contains(x.version_id.as_str()));
+        let path = instance.join(INDEX_PAGE);
+        std::fs::write(&path, export::html(&lock, &tr!("index-title")))?;
+        status!("{}", tr!("wrote-index", path = path.display().to_string()));
+    }
+    install_overrides(mod_config, instance)?;
+    if let Some(previous) = &previous {
+        for (from, to) in previous.carry_settings(instance, &manifest)? {
+            status!(
+                "{}",
+                tr!(
+                    "kept-settings",
+                    from = from.display().to_string(),
+                    to = to.display().to_string()
+                )
+            );
+        }
+    }
+    if let Some(previous) = previous.filter(|_| prune) {
+        let (removed, kept) = previous.prune(instance, &manifest)?;
+        for path in removed {
+            status!("{}", tr!("pruned", path = path.display().to_string()));
+        }
+        for path in kept {
+            status!("{}", tr!("pruned-kept", path = path.display().to_string()));
+        }
+    }
+    Ok(installs.len())
+}
+
+/// Copy the files of the overrides directory into the install directory, except those excluded by
+/// install.exclude and the .mcmodignore of the directory. Files that are already the same are not
+/// copied again.
+fn install_overrides(mod_config: &config::Config, instance: &Path) -> Result<()> {
+    let Some(dir) = &mod_config.install.overrides else {
+        return Ok(());
+    };
+    if !dir.is_dir() {
+        return Err(Error::MissingDirectory(dir.clone()));
+    }
+    let files = mod_config.install.ignore_rules().collect_files(dir)?;
+    let mut copied = 0;
+    for (name, src) in &files {
+        let dst = instance.join(name);
+        if std::fs::read(&dst).is_ok_and(|x| std::fs::read(src).is_ok_and(|y| x == y)) {
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        fsutil::copy_file(src, &dst, mod_config.install.mode)?;
+        copied += 1;
+    }
+    status!(
+        "{}",
+        tr!(
+            "copied-overrides",
+            count = copied,
+            unchanged = files.len() - copied,
+            path = dir.display().to_string()
+        )
+    );
+    Ok(())
+}
+
+/// Warn if a world was last saved with a version older than the game version, so it can not read
+/// data packs made for the game version until it is opened in that version
+fn check_world(mod_config: &config::Config, world: &Path) -> Result<()> {
+    if mod_config.defaults.target == Side::Server && !world.join("level.dat").is_file() {
+        return Ok(());
+    }
+    let world_version = world::load_version(world)?;
+    let game_version = mod_config.defaults.game_version;
+    let is_older = world_version.is_older(&game_version).or_else(|| {
+        let name = world_version.game_version()?;
+        mojang::VersionManifest::load(&mod_config.client(), &mod_config.paths.data)
+            .ok()?
+            .is_older(&name, &game_version)
+    });
+    if is_older == Some(true) {
+        let warning = tr!(
+            "world-too-old",
+            world = world.display().to_string(),
+            world_version = world_version
+                .name
+                .unwrap_or_else(|| tr!("before-data-versions")),
+            game_version = game_version.to_string(),
+        );
+        status!("{}", output::get().paint(output::Style::Warning, &warning));
+    }
+    Ok(())
+}
+
+/// Get the path a file replaced by an install is kept at until the install finishes
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".mcmod-backup");
+    PathBuf::from(backup)
+}
+
+/// Remove the files an install added or replaced before it stopped, and restore the replaced
+/// files from their backups, leaving the instance as it was
+fn roll_back(changed: &[(&Path, Option<PathBuf>)]) {
+    if !changed.is_empty() {
+        status!("{}", tr!("rolling-back", count = changed.len()));
+    }
+    for (path, backup) in changed.iter().rev() {
+        let _ = std::fs::remove_file(fsutil::long_path(path));
+        if let Some(backup) = backup {
+            let _ = fsutil::move_file(backup, path);
+        }
+    }
+}
+
+/// Remove old versions from the cache, keeping the resolved versions
+fn collect_garbage(mod_config: &config::Config, mod_db: &ModDB) -> Result<()> {
+    let manager = cache::ModFileManager::new(
+        mod_config.paths.data.clone(),
+        Some(mod_config.paths.install_dir(mod_config.defaults.target)?),
+        mod_config.client(),
+    );
+    let in_use = mod_db
+        .get_versions()
+        .into_iter()
+        .map(|x| &x.version_id)
+        .collect::<Vec<_>>();
+    let removed = manager.collect_garbage(mod_config.cache.keep_versions, &in_use)?;
+    for version_id in &removed {
+        println!("{}", tr!("removed-version", version = version_id.as_str()));
+    }
+    println!("{}", tr!("removed-count", count = removed.len()));
+    Ok(())
+}
+
+/// Print rows of text as a table with aligned columns, fit to the width of the output
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    print!("{}", output::get().table(header, rows));
+}
+
+/// Print why each resolved version of a project was resolved, by the chains of projects that
+/// required it
+fn explain_project(mod_db: &ModDB, slug: &str) -> Result<()> {
+    let project = mod_db
+        .get_project_by_slug(&slug.into())
+        .ok_or_else(|| Error::ProjectNotFound(slug.to_string()))?;
+    let mut versions = mod_db
+        .get_versions()
+        .into_iter()
+        .filter(|x| x.project_id == project.project_id)
+        .collect::<Vec<_>>();
+    if versions.is_empty() {
+        return Err(Error::ProjectNotFound(slug.to_string()));
+    }
+    versions.sort_by(|a, b| a.version_number.cmp(&b.version_number));
+    for version in versions {
+        print!("{}", tree::explain(mod_db, version, output::get().ascii));
+    }
+    Ok(())
+}
+
+/// Print the resolved versions sorted by project name, with where they are installed and whether
+/// they are cached or installed
+fn list_versions(
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    project_type: Option<ProjectType>,
+) -> Result<()> {
+    let manager = cache::ModFileManager::new(
+        mod_config.paths.data.clone(),
+        Some(mod_config.paths.install_dir(mod_config.defaults.target)?),
+        mod_config.client(),
+    )
+    .with_shader_loader(shader_loader(mod_config, mod_db));
+    let mut rows = Vec::<Vec<String>>::new();
+    for version in mod_db.get_versions() {
+        let loader = manager.install_loader(version, mod_db.get_loader(&version.version_id));
+        if project_type.is_some_and(|x| x != manager.content_type(loader)) {
+            continue;
+        }
+        let project = mod_db.get_project_by_id(&version.project_id);
+        let name = project.map_or(version.name.as_str(), |x| x.name.as_str());
+        let stats = project.and_then(|x| x.stats.as_ref());
+        let is_installed = version
+            .files
+            .iter()
+            .all(|x| manager.install_path(&x.name, loader).is_file());
+        let is_cached = version
+            .files
+            .iter()
+            .all(|x| manager.find_file(&version.version_id, &x.name).is_some());
+        let state = if is_installed {
+            tr!("state-installed")
+        } else if is_cached {
+            tr!("state-cached")
+        } else {
+            tr!("state-missing")
+        };
+        let directory = version.files.first().map_or_else(String::new, |x| {
+            manager
+                .install_path(&x.name, loader)
+                .parent()
+                .map(|x| x.display().to_string())
+                .unwrap_or_default()
+        });
+        let provenance = mod_db.get_provenance(&version.version_id);
+        rows.push(vec![
+            name.to_string(),
+            version.version_number.clone(),
+            version
+                .files
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            provenance
+                .and_then(|x| {
+                    x.reasons
+                        .iter()
+                        .find(|x| !matches!(x, Reason::DependencyOf(_)))
+                        .or(x.reasons.first())
+                })
+                .map(|x| lockfile::display_reason(mod_db, x))
+                .unwrap_or_default(),
+            version.date_published.format("%Y-%m-%d").to_string(),
+            loader.map(|x| x.to_string()).unwrap_or_default(),
+            directory,
+            state,
+            stats.map_or_else(String::new, |x| x.downloads.to_string()),
+            stats.map_or_else(String::new, |x| x.followers.to_string()),
+            stats
+                .and_then(|x| x.updated)
+                .map_or_else(String::new, |x| x.format("%Y-%m-%d").to_string()),
+            provenance.and_then(|x| x.note.clone()).unwrap_or_default(),
+        ]);
+    }
+    for project in unresolved_projects(mod_config, mod_db) {
+        if project_type.is_some_and(|x| project.project_type != Some(x)) {
+            continue;
+        }
+        let blank = String::new;
+        rows.push(vec![
+            project.name.to_string(),
+            blank(),
+            blank(),
+            tr!("reason-configured"),
+            blank(),
+            blank(),
+            blank(),
+            tr!("state-unresolved"),
+            blank(),
+            blank(),
+            blank(),
+            project.note.unwrap_or_default(),
+        ]);
+    }
+    rows.sort_by(|l, r| l[0].to_lowercase().cmp(&r[0].to_lowercase()));
+    let header = [
+        tr!("column-project"),
+        tr!("column-version"),
+        tr!("column-file"),
+        tr!("column-reason"),
+        tr!("column-published"),
+        tr!("column-loader"),
+        tr!("column-directory"),
+        tr!("column-state"),
+        tr!("column-downloads"),
+        tr!("column-followers"),
+        tr!("column-updated"),
+        tr!("column-note"),
+    ];
+    print_table(
+        &header.iter().map(String::as_str).collect::<Vec<_>>(),
+        &rows,
+    );
+    Ok(())
+}
+
+/// Get the configured projects, including the optional ones, that were not resolved. Projects may
+/// be named by slug or id.
+fn unresolved_projects(mod_config: &config::Config, mod_db: &ModDB) -> Vec<config::ConfigProject> {
+    mod_config
+        .projects()
+        .into_iter()
+        .chain(mod_config.optional_projects())
+        .filter(|x| x.resolved_id(mod_db).is_none())
+        .collect()
+}
+
+/// Get the stats of the resolved projects that their registry reported, by project id
+fn project_stats(mod_db: &ModDB) -> std::collections::BTreeMap<String, ProjectStats> {
+    mod_db
+        .get_versions()
+        .into_iter()
+        .filter_map(|version| {
+            let project = mod_db.get_project_by_id(&version.project_id)?;
+            Some((project.project_id.to_string(), project.stats.clone()?))
+        })
+        .collect()
+}
+
+/// Get the default name of a modpack, the name of the directory of the config
+fn pack_name(cli: &Cli) -> String {
+    std::path::absolute(config_path(cli))
+        .ok()
+        .and_then(|x| Some(x.parent()?.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "mcmod".into())
+}
+
+/// Export the resolved projects as a CurseForge modpack. The files CurseForge knows by their
+/// fingerprint are downloaded from CurseForge, and the others are bundled in the overrides.
+fn export_curseforge(
+    mod_config: &config::Config,
+    mod_db: &ModDB,
+    mut modpack: curseforge::Modpack,
+    output: &Path,
+    bundle_unmatched: bool,
+) -> Result<()> {
+    let manager = file_manager(mod_config, mod_db, None)?;
+    let versions = mod_db
+        .get_versions()
+        .into_iter()
+        .filter(|x| manager.can_install(x))
+        .collect::<Vec<_>>();
+    let installs = manager.plan_installs(
+        &versions,
+        mod_config.install.collisions,
+        |version| mod_db.get_loader(&version.version_id),
+        |version| {
+            mod_db
+                .get_project_by_id(&version.project_id)
+                .map_or(version.project_id.to_string(), |x| x.slug.to_string())
+        },
+        |version| is_pinned(mod_db, version),
+    )?;
+    let mut files = Vec::new();
+    for install in &installs {
+        let bytes = std::fs::read(manager.get_file(install.version, install.file)?)?;
+        files.push((install, curseforge::fingerprint(&bytes), bytes));
+    }
+    let matches = match std::env::var(curseforge::API_KEY_VAR) {
+        Ok(key) => curseforge::match_fingerprints(
+            &mod_config.client(),
+            &key,
+            &files.iter().map(|x| x.1).collect::<Vec<_>>(),
+        )?,
+        Err(_) => {
+            let text = tr!("curseforge-no-key", var = curseforge::API_KEY_VAR);
+            status!("{}", output::get().paint(output::Style::Warning, &text));
+            std::collections::HashMap::new()
+        }
+    };
+    let mut unmatched = Vec::new();
+    for (install, fingerprint, bytes) in files {
+        match matches.get(&fingerprint) {
+            Some(x) => modpack.files.push(*x),
+            None if !bundle_unmatched => unmatched.push(install.file.name.clone()),
+            None => {
+                let path = install
+                    .path
+                    .components()
+                    .map(|x| x.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                unmatched.push(install.file.name.clone());
+                modpack.overrides.push((path, bytes));
+            }
+        }
+    }
+    if let Some(dir) = &mod_config.install.overrides {
+        for (path, file) in mod_config.install.ignore_rules().collect_files(dir)? {
+            modpack.overrides.push((path, std::fs::read(file)?));
+        }
+    }
+    mod_config
+        .paths
+        .staging()?
+        .write(output, modpack.to_zip()?)?;
+    status!(
+        "{}",
+        tr!(
+            "curseforge-exported",
+            path = output.display().to_string(),
+            referenced = modpack.files.len(),
+            unmatched = unmatched.len()
+        )
+    );
+    if !unmatched.is_empty() {
+        let text = match bundle_unmatched {
+            true => tr!("curseforge-bundled", files = unmatched.join(", ")),
+            false => tr!("curseforge-left-out", files = unmatched.join(", ")),
+        };
+        status!("{}", output::get().paint(output::Style::Warning, &text));
+    }
+    Ok(())
+}
+
+/// Load an old and a new lockfile to compare. A missing new lockfile is the lockfile of the config,
+/// and a missing old lockfile is read from git HEAD.
+fn load_lockfiles(
+    cli: &Cli,
+    old: Option<&PathBuf>,
+    new: Option<&PathBuf>,
+) -> Result<(lockfile::Lockfile, lockfile::Lockfile)> {
+    let new = match new {
+        Some(x) => lockfile::Lockfile::load(x)?,
+        None => load_lock(cli)?,
+    };
+    let old = match old {
+        Some(x) => lockfile::Lockfile::load(x)?,
+        None => {
+            lockfile::Lockfile::load_git_head(&find_lock(cli).unwrap_or_else(|| lock_path(cli)))?
+        }
+    };
+    Ok((old, new))
+}
+
+/// Fail if a config would be overwritten without being told to
+fn check_overwrite(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            ),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Add a project to the config after resolving it and its dependencies for the configured game
+/// version and loader, so a project that does not exist or has no compatible version is not added
+fn add_project(cli: &Cli, slug: &str) -> Result<()> {
+    let path = config_path(cli);
+    let mod_config = load_config(cli)?;
+    if mod_config.is_configured(slug) {
+        status!("{}", tr!("already-configured", name = slug));
+        return Ok(());
+    }
+    status!("{}", tr!("collecting", name = slug));
+    let found = solver::ModSolver::new(&mod_config)
+        .collect_project_and_dependencies(&mod_config.new_project(slug), Reason::Configured)?;
+    status!("  {}", tr!("found-projects", count = found.len()));
+    let text = std::fs::read_to_string(&path)?;
+    let edited = config::add_project_entry(&text, slug)?;
+    config::Config::loads(&edited)?;
+    mod_config.paths.staging()?.write(&path, edited)?;
+    status!(
+        "{}",
+        tr!(
+            "added-project",
+            name = slug,
+            path = path.display().to_string()
+        )
+    );
+    Ok(())
+}
+
+/// Print the projects on Modrinth matching a query, most relevant first
+fn search_projects(
+    cli: &Cli,
+    query: &str,
+    project_type: Option<ProjectType>,
+    limit: usize,
+) -> Result<()> {
+    let facets = labrinth::SearchFacets {
+        game_version: cli.game_version,
+        loader: cli.loader,
+        project_type,
+        ..Default::default()
+    };
+    let hits = labrinth::Client::new()
+        .with_fixtures(cli.fixtures())
+        .search(query, &facets, limit)?;
+    if hits.is_empty() {
+        status!("{}", tr!("no-search-results", query = query));
+        return Ok(());
+    }
+    let rows = hits
+        .into_iter()
+        .map(|x| vec![x.slug, x.description, x.downloads.to_string()])
+        .collect::<Vec<_>>();
+    print_table(&["Project", "Description", "Downloads"], &rows);
+    Ok(())
+}
+
+/// Hash files, and find the versions that published them, by the sha512 of each file. Files of
+/// the lockfile are identified by their locked hashes, and only the others are looked up on
+/// Modrinth.
+fn identify_files(
+    cli: &Cli,
+    files: &[PathBuf],
+) -> Result<Vec<(verify::FileHashes, Option<ModVersion>)>> {
+    let hashes = verify::hash_files(files, cli.jobs)?;
+    let locked = load_lock(cli)?.to_db();
+    let unknown = hashes
+        .iter()
+        .filter(|x| locked.get_version_by_hash(&x.sha512).is_none())
+        .map(|x| x.sha512.as_str())
+        .collect::<Vec<_>>();
+    let mut versions = match unknown.is_empty() {
+        true => std::collections::HashMap::new(),
+        false => labrinth::Client::new()
+            .with_fixtures(cli.fixtures())
+            .get_versions_by_hashes(&unknown, verify::HashAlgorithm::Sha512)?,
+    };
+    Ok(hashes
+        .into_iter()
+        .map(|x| {
+            let version = locked
+                .get_version_by_hash(&x.sha512)
+                .cloned()
+                .or_else(|| versions.remove(&x.sha512));
+            (x, version)
+        })
+        .collect())
+}
+
+/// Print the hashes of files in the format of sha512sum, or with `lookup` a table of the project
+/// and version on Modrinth that published each file
+fn print_hashes(
+    cli: &Cli,
+    files: &[PathBuf],
+    algorithm: verify::HashAlgorithm,
+    lookup: bool,
+) -> Result<()> {
+    if !lookup {
+        for (path, hashes) in files.iter().zip(verify::hash_files(files, cli.jobs)?) {
+            println!("{}  {}", hashes.get(algorithm), path.display());
+        }
+        return Ok(());
+    }
+    let client = labrinth::Client::new().with_fixtures(cli.fixtures());
+    let mut rows = Vec::new();
+    for (path, (hashes, version)) in files.iter().zip(identify_files(cli, files)?) {
+        let (project, version) = match version {
+            Some(x) => (
+                client
+                    .get_project(x.project_id.as_str())
+                    .map_or(x.project_id.to_string(), |x| x.slug.to_string()),
+                x.version_number,
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        rows.push(vec![
+            path.display().to_string(),
+            project,
+            version,
+            hashes.get(algorithm).to_string(),
+        ]);
+    }
+    print_table(&["File", "Project", "Version", "Hash"], &rows);
+    Ok(())
+}
+
+/// Describe the game versions a project supports by the oldest and newest, and how many there are
+fn describe_game_versions(game_versions: &[MinecraftVersion]) -> String {
+    match game_versions {
+        [] => "-".to_string(),
+        [x] => x.to_string(),
+        [first, .., last] => format!("{first} - {last} ({} versions)", game_versions.len()),
+    }
+}
+
+/// Print the details of a project from the registry of the config, or from Modrinth without a
+/// config, with its latest version for the game version and loader of the config
+fn show_info(cli: &Cli, slug: &str) -> Result<()> {
+    let (backend, game_version, loader) = match config_path(cli).is_file() {
+        true => {
+            let mod_config = load_config(cli)?;
+            (
+                backend::Backends::builtin(&mod_config).get(&mod_config.defaults.registry)?,
+                Some(mod_config.defaults.game_version),
+                Some(mod_config.defaults.loader),
+            )
+        }
+        false => {
+            let client = labrinth::Client::new().with_fixtures(cli.fixtures());
+            (
+                backend::Backends::builtin_with(client).get("modrinth")?,
+                cli.game_version_all.or(cli.game_version),
+                cli.loader,
+            )
+        }
+    };
+    let project = backend.get_project(slug)?;
+    let join = |x: &[String]| match x.is_empty() {
+        true => "-".to_string(),
+        false => x.join(", "),
+    };
+    let stats = project.stats.as_ref();
+    let mut rows = vec![
+        ("Project".to_string(), project.name.clone()),
+        ("Slug".to_string(), project.slug.to_string()),
+        (
+            "Description".to_string(),
+            match project.description.trim() {
+                "" => "-".to_string(),
+                x => x.to_string(),
+            },
+        ),
+        (
+            "Loaders".to_string(),
+            join(
+                &project
+                    .loaders
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        (
+            "Game versions".to_string(),
+            describe_game_versions(&project.game_versions),
+        ),
+        (
+            "License".to_string(),
+            project.license.clone().unwrap_or_else(|| "-".into()),
+        ),
+        ("Categories".to_string(), join(&project.categories)),
+    ];
+    if let Some(stats) = stats {
+        rows.push(("Downloads".to_string(), stats.downloads.to_string()));
+        rows.push(("Followers".to_string(), stats.followers.to_string()));
+        if let Some(updated) = stats.updated {
+            rows.push((
+                "Updated".to_string(),
+                updated.format("%Y-%m-%d").to_string(),
+            ));
+        }
+    }
+    if let (Some(game_version), Some(loader)) = (game_version, loader) {
+        let loaders = match project.loaders.contains(&loader) {
+            true => vec![loader],
+            false => project.loaders.clone(),
+        };
+        let latest = backend
+            .get_project_versions(slug, &[game_version], &loaders)?
+            .into_iter()
+            .max_by_key(|x| x.date_published);
+        rows.push((
+            format!("Latest for {game_version} {loader}"),
+            latest.map_or_else(
+                || "-".to_string(),
+                |x| {
+                    format!(
+                        "{} ({}, {})",
+                        x.version_number,
+                        x.version_id,
+                        x.date_published.format("%Y-%m-%d")
+                    )
+                },
+            ),
+        ));
+    }
+    let width = rows.iter().map(|x| x.0.chars().count()).max().unwrap_or(0);
+    for (label, value) in rows {
+        let label = format!("{label:width$}");
+        println!(
+            "{}  {value}",
+            output::get().paint(output::Style::Heading, &label)
+        );
+    }
+    Ok(())
+}
+
+/// Install jars built locally into the mods directory of each install directory of the config,
+/// and install them again whenever they change, until Ctrl-C
+fn watch_dev_jars(cli: &Cli, jars: &[PathBuf]) -> Result<()> {
+    let mod_config = load_config(cli)?;
+    let mods_dirs = mod_config
+        .paths
+        .install_dirs(mod_config.defaults.target)?
+        .into_iter()
+        .map(|x| x.join("mods"))
+        .collect();
+    dev::DevJars::new(jars, mods_dirs, mod_config.install.mode)?.watch()
+}
+
+/// Remove a project from the config, and drop it and the dependencies that no other project needs
+/// from the lockfile, optionally removing their installed files. A lockfile that was up to date
+/// with the config stays up to date, so nothing is resolved again.
+fn remove_project(cli: &Cli, slug: &str, uninstall: bool) -> Result<()> {
+    let path = config_path(cli);
+    let text = std::fs::read_to_string(&path)?;
+    let Some(edited) = config::remove_project_entry(&text, slug)? else {
+        status!("{}", tr!("not-configured", name = slug));
+        return Ok(());
+    };
+    config::Config::loads(&edited)?;
+    let old_config = load_config(cli)?;
+    let locked_name = old_config
+        .projects()
+        .into_iter()
+        .chain(old_config.optional_projects())
+        .find(|x| x.name.as_str() == slug)
+        .and_then(|x| x.version_id)
+        .map_or_else(|| slug.to_string(), |x| x.to_string());
+    old_config.paths.staging()?.write(&path, edited)?;
+    status!(
+        "{}",
+        tr!(
+            "removed-project",
+            name = slug,
+            path = path.display().to_string()
+        )
+    );
+    let mod_config = load_config(cli)?;
+    let mut lock = load_lock(cli)?;
+    let removed = lock.remove_project(&locked_name);
+    for project in removed.iter().skip(1) {
+        status!(
+            "{}",
+            tr!("removed-dependency", name = project.slug.as_str())
+        );
+    }
+    if lock.config_hash == Some(old_config.resolution_hash()) {
+        lock.config_hash = Some(mod_config.resolution_hash());
+    }
+    if !removed.is_empty() {
+        lock.save(&lock_path(cli))?;
+    }
+    if !uninstall {
+        return Ok(());
+    }
+    let project_ids = removed
+        .iter()
+        .map(|x| x.project_id.as_str())
+        .collect::<Vec<_>>();
+    for instance in mod_config.paths.install_dirs(mod_config.defaults.target)? {
+        let Some(mut manifest) = manifest::InstallManifest::load(&instance)? else {
+            continue;
+        };
+        let (removed, kept) = manifest.remove_projects(&instance, &project_ids)?;
+        manifest.save(&instance)?;
+        for path in removed {
+            status!("{}", tr!("pruned", path = path.display().to_string()));
+        }
+        for path in kept {
+            status!("{}", tr!("pruned-kept", path = path.display().to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Write a config from the mod list of another tool, and a lockfile of the versions installed in
+/// a launcher instance. Modrinth project ids are replaced with slugs where they can be looked up.
+fn adopt_config(
+    cli: &Cli,
+    source: adopt::AdoptSource,
+    path: Option<&PathBuf>,
+    profile: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    let source_path = match path {
+        Some(x) => x.clone(),
+        None => source.locate(profile)?,
+    };
+    let mut adopted = source.load(&source_path, profile)?;
+    adopted.use_slugs(&labrinth::Client::new().with_fixtures(cli.fixtures()));
+    let text = adopted.to_toml(cli.game_version_all.or(cli.game_version), cli.loader)?;
+    config::Config::loads(&text)?;
+    let path = config_path(cli);
+    check_overwrite(&path, force)?;
+    std::fs::write(&path, &text)?;
+    if let Some(lock) = adopted.lockfile() {
+        lock.save(&lockfile::lock_path(&path, cli.channel.as_deref()))?;
+    }
+    println!(
+        "{}",
+        tr!(
+            "adopted-config",
+            path = path.display().to_string(),
+            origin = adopted.origin.as_str(),
+            count = adopted.projects.len()
+        )
+    );
+    for entry in &adopted.skipped {
+        println!("  {}", tr!("adopt-skipped", entry = entry.as_str()));
+    }
+    Ok(())
+}
+
+/// Write a new config from a template, or list the templates
+fn init_config(
+    cli: &Cli,
+    template: &str,
+    index: Option<&str>,
+    list: bool,
+    force: bool,
+) -> Result<()> {
+    let templates = match index {
+        Some(url) => {
+            let client = labrinth::Client::new().with_fixtures(cli.fixtures());
+            templates::load_index(&client, url)?
+        }
+        None => templates::builtin(),
+    };
+    if list {
+        let rows = templates
+            .iter()
+            .map(|x| vec![x.name.clone(), x.description.clone()])
+            .collect::<Vec<_>>();
+        print_table(&["Template", "Description"], &rows);
+        return Ok(());
+    }
+    let template = templates::find(templates, template)?;
+    config::Config::loads(&template.config)?;
+    let path = config_path(cli);
+    check_overwrite(&path, force)?;
+    std::fs::write(&path, &template.config)?;
+    println!(
+        "{}",
+        tr!(
+            "wrote-template",
+            path = path.display().to_string(),
+            template = &template.name
+        )
+    );
+    Ok(())
+}
+
+/// Print the differences between two lockfiles
+fn diff_lockfiles(cli: &Cli, old: Option<&PathBuf>, new: Option<&PathBuf>) -> Result<()> {
+    let (old, new) = load_lockfiles(cli, old, new)?;
+    print!("{}", old.diff(&new));
+    Ok(())
 }
 
-/// Load a config, overriding values as specified in cli
-fn load_config(cli: &Cli) -> Result<config::Config> {
-    let config_path = cli
-        .config
-        .to_owned()
-        .unwrap_or_else(|| PathBuf::from("./mcmod.toml"));
-    let mut mcmod = config::Config::loads(std::fs::read_to_string(config_path)?.as_str())?;
-    cli.game_version
-        .inspect(|x| mcmod.defaults.game_version = *x);
-    cli.loader.inspect(|x| mcmod.defaults.loader = *x);
-    Ok(mcmod)
+/// Compose release notes for the differences between two lockfiles, fetching the changelogs of
+/// the new versions
+fn release_notes(
+    cli: &Cli,
+    old: &lockfile::Lockfile,
+    new: &lockfile::Lockfile,
+    title: &str,
+) -> String {
+    let client = labrinth::Client::new().with_fixtures(cli.fixtures());
+    old.diff(new).release_notes(title, |project| {
+        client
+            .get_version(&project.version_id)
+            .inspect_err(|e| {
+                let name = project.name.as_str();
+                eprintln!(
+                    "{}",
+                    tr!("changelog-failed", name = name, error = e.to_string())
+                )
+            })
+            .ok()
+            .and_then(|x| x.changelog)
+    })
 }
 
-fn solve_versions(mod_config: &config::Config) -> Result<types::ModDB> {
-    let mut mod_solver = solver::ModSolver::new(mod_config);
+/// Find the problems that keep a lockfile from being promoted: configured projects it does not
+/// lock, frozen projects whose version differs from the replaced lockfile, problems found by the
+/// hook script, and locked files that are not cached or do not match their hashes
+fn promotion_problems(
+    cli: &Cli,
+    mod_config: &config::Config,
+    lock: &lockfile::Lockfile,
+    replaced: &lockfile::Lockfile,
+) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
     for project in mod_config.projects() {
-        println!("Collecting {}", project.name);
-        mod_solver
-            .collect_project_and_dependencies(&project)
-            .inspect(|x| println!("  Found {} projects", x.len()))
-            .inspect_err(|e| println!("  Error: {e}"))?;
+        if !project.requires.is_empty() {
+            continue;
+        }
+        let name = project.name.as_str();
+        let Some(locked) = lock.projects.iter().find(|x| x.is_entry(&project)) else {
+            problems.push(format!("{name} is configured but not locked"));
+            continue;
+        };
+        if project.frozen
+            && let Some(old) = replaced.projects.iter().find(|x| x.is_entry(&project))
+            && old.version_id != locked.version_id
+        {
+            problems.push(format!(
+                "{name} is frozen at {}, but would change to {}",
+                old.version_number, locked.version_number
+            ));
+        }
     }
-    for project in mod_config.optional_projects() {
-        println!("Collecting {} (optional)", project.name);
-        let _ = mod_solver
-            .collect_project_and_dependencies(&project)
-            .inspect(|x| println!("  Found {} projects", x.len()))
-            .inspect_err(|e| println!("  Error: {e}"));
+    if let Some(hooks) = mod_config.hooks.get() {
+        problems.extend(hooks.validate(lock)?);
     }
-    mod_solver.solve()
+    let mut checks = Vec::new();
+    let manager = mod_config
+        .paths
+        .install_dir(mod_config.defaults.target)
+        .ok()
+        .filter(|x| x.is_dir() && mod_config.paths.data.is_dir())
+        .map(|instance| {
+            cache::ModFileManager::new(
+                mod_config.paths.data.clone(),
+                Some(instance),
+                mod_config.client(),
+            )
+        });
+    for project in &lock.projects {
+        let version_id = VersionId::from(project.version_id.clone());
+        for file in &project.files {
+            match manager
+                .as_ref()
+                .and_then(|x| x.find_file(&version_id, &file.name))
+            {
+                Some(path) => checks.push(verify::FileCheck {
+                    path,
+                    expected: file.hashes.clone(),
+                }),
+                None => problems.push(format!(
+                    "{} of {} is not cached, so its hashes could not be verified",
+                    file.name, project.slug
+                )),
+            }
+        }
+    }
+    let states = verify::check_files(&checks, cli.jobs)?;
+    for (check, state) in checks.iter().zip(states) {
+        if state == verify::FileState::Modified {
+            problems.push(format!(
+                "{} does not match its hashes",
+                check.path.display()
+            ));
+        }
+    }
+    Ok(problems)
 }
 
-/// Install the files from src into dot_minecraft, deleting any previous files in datapacks, mods,
-/// and resourcepacks.
-fn prepare_version_files(
-    mod_manager: &cache::ModFileManager,
-    mod_db: &ModDB,
-    version: &ModVersion,
-    install: bool,
+/// Replace the lockfile of a channel with that of another after validating it, and write the
+/// release notes of the changes
+fn promote(
+    cli: &Cli,
+    from: &str,
+    to: &str,
+    title: Option<&str>,
+    output: Option<&PathBuf>,
 ) -> Result<()> {
-    let printed_name = mod_db
-        .get_project_by_id(&version.project_id)
-        .map(|x| x.name.as_str())
-        .unwrap_or(version.name.as_str());
-    println!(
-        "Getting files for {} : {}",
-        version.version_id, printed_name
+    let mod_config = load_config(cli)?;
+    let config_path = config_path(cli);
+    let (from_path, to_path) = (
+        lockfile::lock_path(&config_path, Some(from)),
+        lockfile::lock_path(&config_path, Some(to)),
     );
-    for mod_file in &version.files {
-        if mod_manager
-            .find_file(&version.version_id, &mod_file.name)
-            .is_some()
-        {
-            println!("  Using cached file {}", mod_file.name);
-        } else {
-            println!("  Downloading file {}", mod_file.name);
-            mod_manager
-                .download_file(&version.version_id, mod_file)
-                .expect("Failure to get file");
-        }
-        if install {
-            println!("  Installing");
-            mod_manager
-                .install_file(
-                    &version.version_id,
-                    mod_file,
-                    version.loaders.first().copied(),
-                )
-                .expect("Failure to get file");
-        }
+    let lock = lockfile::Lockfile::load(&from_path)?;
+    let replaced = match to_path.is_file() {
+        true => lockfile::Lockfile::load(&to_path)?,
+        false => lockfile::Lockfile::default(),
+    };
+    let problems = promotion_problems(cli, &mod_config, &lock, &replaced)?;
+    if !problems.is_empty() {
+        return Err(Error::PromoteRejected(problems));
+    }
+    let notes = release_notes(cli, &replaced, &lock, title.unwrap_or(to));
+    let diff = replaced.diff(&lock);
+    let count = diff.added.len() + diff.removed.len() + diff.changed.len();
+    lock.save(&to_path)?;
+    status!("{}", tr!("promoted", from = from, to = to, count = count));
+    match output {
+        Some(path) => std::fs::write(path, notes)?,
+        None => print!("{notes}"),
     }
     Ok(())
 }
 
-fn prepare_files(mod_config: &config::Config, mod_db: &ModDB, install: bool) -> Result<()> {
-    let manager = cache::ModFileManager::new(
-        mod_config.paths.data.clone(),
-        mod_config.paths.dot_minecraft.clone(),
-    );
-    for version in mod_db.get_versions() {
-        prepare_version_files(&manager, mod_db, version, install)?;
+/// Write release notes for the differences between two lockfiles, fetching the changelogs of the
+/// new versions
+fn write_release_notes(
+    cli: &Cli,
+    old: Option<&PathBuf>,
+    new: Option<&PathBuf>,
+    title: &str,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let (old, new) = load_lockfiles(cli, old, new)?;
+    let notes = release_notes(cli, &old, &new, title);
+    match output {
+        Some(path) => std::fs::write(path, notes)?,
+        None => print!("{notes}"),
     }
     Ok(())
 }
 
+/// Run a command for every config of a workspace. Configs without their own shared metadata cache
+/// use the cache of the workspace, so a project fetched for one config is not fetched again for
+/// the next. Returns the number of configs that are out of date or were changed.
+fn run_workspace(cli: &Cli, path: Option<&PathBuf>, command: WsCommand) -> Result<usize> {
+    let path = path
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(workspace::DEFAULT_PATH));
+    let workspace = workspace::Workspace::load(&path)?;
+    let mut cache_dir = workspace.cache_dir.clone();
+    let mut changed = 0;
+    for config in &workspace.configs {
+        interrupt::check()?;
+        status!(
+            "{}",
+            tr!("workspace-config", config = config.display().to_string())
+        );
+        let cli = Cli {
+            config: Some(config.clone()),
+            update: true,
+            ..cli.clone()
+        };
+        let mut mod_config = load_config(&cli)?;
+        let shared = mod_config
+            .cache
+            .shared_dir
+            .get_or_insert_with(|| {
+                cache_dir
+                    .clone()
+                    .unwrap_or_else(|| mod_config.paths.data.join("metadata"))
+            })
+            .clone();
+        cache_dir.get_or_insert(shared);
+        let old = load_lock(&cli)?;
+        let new = match command {
+            WsCommand::Outdated => {
+                lockfile::Lockfile::from_db(&solve_versions(&mod_config, load_lock(&cli)?)?)
+            }
+            WsCommand::Update => {
+                let mod_db = resolve(&cli, &mod_config)?;
+                if cli.download || cli.install {
+                    prepare_files(&cli, &mod_config, &mod_db, cli.install)?;
+                }
+                lockfile::Lockfile::from_db(&mod_db)
+            }
+        };
+        let diff = old.diff(&new);
+        if !diff.is_empty() {
+            changed += 1;
+        }
+        print!("{diff}");
+    }
+    Ok(changed)
+}
+
+/// Print the summary of the run once it finished or failed, with the next steps for what was
+/// skipped or failed, and return its result
+fn summarize<T>(result: Result<T>) -> Result<T> {
+    summary::take().print();
+    result
+}
+
+/// Exit with a hint to resume if a step was interrupted, otherwise return its result
+fn exit_if_interrupted<T>(result: Result<T>) -> Result<T> {
+    if let Err(Error::Interrupted) = result {
+        eprintln!("{}", tr!("interrupted"));
+        std::process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    result
+}
+
+/// The exit code of verify when files differ from the lockfile, of audit when anything is
+/// affected by an advisory, and of ws outdated when any config is out of date
+const EXIT_DRIFT: i32 = 1;
+
+/// The exit code of verify and audit when they could not check the files
+const EXIT_ERROR: i32 = 2;
+
+/// Hash the installed and cached files in parallel, and print each file that does not match.
+/// Installed files are checked against the manifest in the instance if it has one, else the
+/// lockfile. Cached files are checked against the lockfile when the data directory exists. Nothing
+/// is resolved or written. Returns the number of problems found.
+fn verify_files(cli: &Cli) -> Result<usize> {
+    let mod_config = load_config(cli)?;
+    let instance = mod_config.paths.install_dir(mod_config.defaults.target)?;
+    let manifest = manifest::InstallManifest::load(&instance)?;
+    let lock = match find_lock(cli) {
+        Some(_) => Some(load_lock(cli)?),
+        None if manifest.is_none() => Some(lockfile::Lockfile::load(&lock_path(cli))?),
+        None => None,
+    };
+    let mut checks = manifest
+        .as_ref()
+        .map(|x| x.checks(&instance))
+        .unwrap_or_default();
+    if let Some(lock) = &lock
+        && (manifest.is_none() || mod_config.paths.data.is_dir())
+    {
+        let manager = cache::ModFileManager::new(
+            mod_config.paths.data.clone(),
+            Some(instance.clone()),
+            mod_config.client(),
+        );
+        for project in &lock.projects {
+            let version_id = VersionId::from(project.version_id.clone());
+            for file in &project.files {
+                if manifest.is_none() {
+                    checks.push(verify::FileCheck {
+                        path: manager.install_path(&file.name, project.loader),
+                        expected: file.hashes.clone(),
+                    });
+                }
+                if let Some(path) = manager.find_file(&version_id, &file.name) {
+                    checks.push(verify::FileCheck {
+                        path,
+                        expected: file.hashes.clone(),
+                    });
+                }
+            }
+        }
+    }
+    let states = verify::check_files(&checks, cli.jobs)?;
+    let mut problems = 0;
+    for (check, state) in checks.iter().zip(states) {
+        if state != verify::FileState::Ok {
+            problems += 1;
+            let style = match state {
+                verify::FileState::Missing => output::Style::Error,
+                _ => output::Style::Warning,
+            };
+            let state = output::get().paint(style, &format!("{state:>8}"));
+            println!("{state}  {}", check.path.display());
+        }
+    }
+    let style = match problems {
+        0 => output::Style::Good,
+        _ => output::Style::Error,
+    };
+    let summary = tr!("verified", count = checks.len(), problems = problems);
+    println!("{}", output::get().paint(style, &summary));
+    Ok(problems)
+}
+
+/// Check the locked projects and the files installed in each instance against the advisory feeds,
+/// and the configured projects for staleness if told to, and print each that is affected. Nothing
+/// is resolved or written. Returns the number affected.
+fn audit(cli: &Cli, stale: Option<config::Age>) -> Result<usize> {
+    let mod_config = load_config(cli)?;
+    if mod_config.security.advisories.is_empty() && stale.is_none() {
+        status!("{}", tr!("no-advisories"));
+    }
+    let advisories = load_advisories(&mod_config)?;
+    // The id of the advisory or check, what it affects, and why
+    let mut affected = Vec::<(String, String, String)>::new();
+    let lock = load_lock(cli)?;
+    let mod_db = lock.to_db();
+    let versions = mod_db.get_versions();
+    for version in &versions {
+        let name = mod_db
+            .get_project_by_id(&version.project_id)
+            .map_or(version.name.as_str(), |x| x.slug.as_str());
+        for advisory in advisories.matching_version(version) {
+            affected.push((
+                advisory.id.clone(),
+                format!("{name} {}", version.version_number),
+                advisory.summary.clone(),
+            ));
+        }
+    }
+    let mut files = Vec::<PathBuf>::new();
+    for instance in mod_config.paths.install_dirs(mod_config.defaults.target)? {
+        files.append(&mut advisory::audit_files(&instance)?);
+    }
+    let hashes = verify::hash_files(&files, cli.jobs)?;
+    for (path, hashes) in files.iter().zip(&hashes) {
+        for advisory in advisories.matching_file(hashes) {
+            affected.push((
+                advisory.id.clone(),
+                path.display().to_string(),
+                advisory.summary.clone(),
+            ));
+        }
+    }
+    if let Some(stale) = stale {
+        let cutoff = stale.before(chrono::Utc::now().naive_utc())?;
+        let client = mod_config.client();
+        let configured = lock
+            .projects
+            .iter()
+            .filter(|x| x.registry == "modrinth" && x.reasons.iter().any(|x| x == "configured"));
+        for project in configured {
+            let updated = client
+                .get_project(&project.project_id)?
+                .stats
+                .and_then(|x| x.updated);
+            if let Some(updated) = updated.filter(|x| *x < cutoff) {
+                let reason = tr!(
+                    "stale-project",
+                    updated = updated.format("%Y-%m-%d").to_string(),
+                    age = String::from(stale)
+                );
+                affected.push(("stale".into(), project.slug.clone(), reason));
+            }
+        }
+    }
+    for (id, subject, reason) in &affected {
+        let id = output::get().paint(output::Style::Error, id);
+        println!("{id}  {subject}: {reason}");
+    }
+    let style = match affected.len() {
+        0 => output::Style::Good,
+        _ => output::Style::Error,
+    };
+    let summary = tr!(
+        "audited",
+        count = versions.len() + files.len(),
+        affected = affected.len()
+    );
+    println!("{}", output::get().paint(style, &summary));
+    Ok(affected.len())
+}
+
 fn main() {
     let cli = Cli::parse();
+    output::init(output::Output {
+        status_to_stderr: matches!(
+            cli.command,
+            Some(Command::Serve { .. }) | Some(Command::Plan { output: None })
+        ),
+        ..output::Output::detect(cli.color, cli.ascii)
+    });
+    i18n::init(cli.lang.as_deref());
+    if let Some(file) = &cli.trace_http {
+        trace::init(file.as_deref()).expect("Failure to open HTTP trace file");
+    }
+    match &cli.command {
+        Some(Command::Diff { old, new }) => {
+            diff_lockfiles(&cli, old.as_ref(), new.as_ref()).expect("Failure to diff lockfiles");
+            return;
+        }
+        Some(Command::Init {
+            template,
+            index,
+            list,
+            force,
+        }) => {
+            init_config(&cli, template, index.as_deref(), *list, *force)
+                .expect("Failure to initialize config");
+            return;
+        }
+        Some(Command::Add { slug }) => {
+            add_project(&cli, slug).expect("Failure to add project");
+            return;
+        }
+        Some(Command::Remove { slug, uninstall }) => {
+            remove_project(&cli, slug, *uninstall).expect("Failure to remove project");
+            return;
+        }
+        Some(Command::Search {
+            query,
+            project_type,
+            limit,
+        }) => {
+            search_projects(&cli, query, *project_type, *limit)
+                .expect("Failure to search projects");
+            return;
+        }
+        Some(Command::Hash {
+            files,
+            algorithm,
+            lookup,
+        }) => {
+            print_hashes(&cli, files, *algorithm, *lookup).expect("Failure to hash files");
+            return;
+        }
+        Some(Command::Info { slug }) => {
+            show_info(&cli, slug).expect("Failure to show project");
+            return;
+        }
+        Some(Command::AdoptConfig {
+            from,
+            path,
+            profile,
+            force,
+        }) => {
+            adopt_config(&cli, *from, path.as_ref(), profile.as_deref(), *force)
+                .expect("Failure to adopt config");
+            return;
+        }
+        Some(Command::Verify) => {
+            let code = match verify_files(&cli) {
+                Ok(0) => 0,
+                Ok(_) => EXIT_DRIFT,
+                Err(e) => {
+                    eprintln!("{}", tr!("verify-failed", error = e.to_string()));
+                    EXIT_ERROR
+                }
+            };
+            std::process::exit(code);
+        }
+        Some(Command::Audit { stale }) => {
+            let code = match audit(&cli, *stale) {
+                Ok(0) => 0,
+                Ok(_) => EXIT_DRIFT,
+                Err(e) => {
+                    eprintln!("{}", tr!("audit-failed", error = e.to_string()));
+                    EXIT_ERROR
+                }
+            };
+            std::process::exit(code);
+        }
+        Some(Command::ReleaseNotes {
+            old,
+            new,
+            title,
+            output,
+        }) => {
+            write_release_notes(&cli, old.as_ref(), new.as_ref(), title, output.as_ref())
+                .expect("Failure to write release notes");
+            return;
+        }
+        Some(Command::Promote {
+            from,
+            to,
+            title,
+            output,
+        }) => {
+            promote(&cli, from, to, title.as_deref(), output.as_ref()).expect("Failure to promote");
+            return;
+        }
+        Some(Command::Sync) => {
+            interrupt::install_handler();
+            exit_if_interrupted(summarize(sync(&cli))).expect("Failure to sync");
+            return;
+        }
+        Some(Command::Plan { output }) => {
+            write_plan(&cli, output.as_ref()).expect("Failure to plan");
+            return;
+        }
+        Some(Command::Apply { plan }) => {
+            interrupt::install_handler();
+            exit_if_interrupted(summarize(apply_plan(&cli, plan))).expect("Failure to apply plan");
+            return;
+        }
+        Some(Command::Resume) => {
+            interrupt::install_handler();
+            exit_if_interrupted(summarize(resume_run(&cli))).expect("Failure to resume");
+            return;
+        }
+        Some(Command::Fetch) => {
+            interrupt::install_handler();
+            exit_if_interrupted(summarize(fetch(&cli))).expect("Failure to fetch");
+            return;
+        }
+        Some(Command::Dev { jars }) => {
+            interrupt::install_handler();
+            watch_dev_jars(&cli, jars).expect("Failure to install dev jars");
+            return;
+        }
+        Some(Command::Ws { workspace, command }) => {
+            interrupt::install_handler();
+            let changed =
+                exit_if_interrupted(summarize(run_workspace(&cli, workspace.as_ref(), *command)))
+                    .expect("Failure to run workspace");
+            if *command == WsCommand::Outdated && changed > 0 {
+                std::process::exit(EXIT_DRIFT);
+            }
+            return;
+        }
+        Some(Command::Serve { .. }) => {
+            let stdin = std::io::stdin().lock();
+            serve::serve(stdin, std::io::stdout(), |method, params| {
+                handle_request(&cli, method, params)
+            })
+            .expect("Failure to serve requests");
+            return;
+        }
+        _ => {}
+    }
+    interrupt::install_handler();
     let mod_config = load_config(&cli).expect("Failure to load config");
     if cli.validate {
-        let client = labrinth::Client::new();
+        let client = mod_config.client();
         let errors = client.validate_enums().expect("Failed to compare data");
         if !errors.is_empty() {
             println!("{errors:?}")
         }
     }
 
-    let mod_db = solve_versions(&mod_config).expect("Failure to resolve projects");
+    let resolved = resolve(&cli, &mod_config).inspect_err(|_| summary::take().print());
+    let mod_db = exit_if_interrupted(resolved).expect("Failure to resolve projects");
     if cli.download || cli.install {
-        prepare_files(&mod_config, &mod_db, cli.install).expect("Failure to prepare files");
+        let prepared = summarize(prepare_files(&cli, &mod_config, &mod_db, cli.install));
+        exit_if_interrupted(prepared).expect("Failure to prepare files");
+    } else {
+        summary::take().print();
+    }
+    match cli.command {
+        Some(Command::List { project_type }) => {
+            list_versions(&mod_config, &mod_db, project_type).expect("Failure to list projects")
+        }
+        Some(Command::Tree) => print!("{}", tree::render(&mod_db, output::get().ascii)),
+        Some(Command::Why { ref slug }) => {
+            explain_project(&mod_db, slug).expect("Failure to explain project")
+        }
+        Some(Command::Gc) => {
+            collect_garbage(&mod_config, &mod_db).expect("Failure to clean the cache")
+        }
+        Some(Command::Export {
+            format: export::ExportFormat::Curseforge,
+            ref output,
+            ref loader_version,
+            ref name,
+            ref pack_version,
+            bundle_unmatched,
+        }) => {
+            let modpack = curseforge::Modpack {
+                name: name.clone().unwrap_or_else(|| pack_name(&cli)),
+                version: pack_version.clone(),
+                game_version: mod_config.defaults.game_version.to_string(),
+                loader: format!(
+                    "{}-{}",
+                    mod_config.defaults.loader,
+                    loader_version
+                        .as_deref()
+                        .expect("Loader version is required for curseforge")
+                ),
+                ..Default::default()
+            };
+            let output = output
+                .as_deref()
+                .expect("Output is required for curseforge");
+            export_curseforge(&mod_config, &mod_db, modpack, output, bundle_unmatched)
+                .expect("Failure to export CurseForge modpack")
+        }
+        Some(Command::Export {
+            format,
+            ref output,
+            ref name,
+            ..
+        }) => {
+            let text = export::export(
+                &lockfile::Lockfile::from_db(&mod_db),
+                &project_stats(&mod_db),
+                format,
+                &name.clone().unwrap_or_else(|| pack_name(&cli)),
+            );
+            match output {
+                Some(path) => mod_config
+                    .paths
+                    .staging()
+                    .and_then(|x| x.write(path, text))
+                    .expect("Failure to write export"),
+                None => println!("{text}"),
+            }
+        }
+        Some(Command::Init { .. })
+        | Some(Command::Add { .. })
+        | Some(Command::Remove { .. })
+        | Some(Command::AdoptConfig { .. })
+        | Some(Command::Verify)
+        | Some(Command::Audit { .. })
+        | Some(Command::Diff { .. })
+        | Some(Command::ReleaseNotes { .. })
+        | Some(Command::Promote { .. })
+        | Some(Command::Search { .. })
+        | Some(Command::Hash { .. })
+        | Some(Command::Info { .. })
+        | Some(Command::Sync)
+        | Some(Command::Resume)
+        | Some(Command::Fetch)
+        | Some(Command::Dev { .. })
+        | Some(Command::Plan { .. })
+        | Some(Command::Apply { .. })
+        | Some(Command::Serve { .. })
+        | Some(Command::Ws { .. }) => {
+            unreachable!("Commands without resolving return early")
+        }
+        None => {}
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mcmod::ignore;
     use std::fs;
 
     #[test]
@@ -153,8 +2748,8 @@ mod tests {
         assert_eq!(cli.config, None, "Cli shall set falsy defaults");
         assert_eq!(cli.game_version, None, "Cli shall set falsy defaults");
         assert_eq!(cli.loader, None, "Cli shall set falsy defaults");
-        assert_eq!(cli.download, false, "Cli shall set falsy defaults");
-        assert_eq!(cli.install, false, "Cli shall set falsy defaults");
+        assert!(!cli.download, "Cli shall set falsy defaults");
+        assert!(!cli.install, "Cli shall set falsy defaults");
     }
 
     #[test]
@@ -185,8 +2780,8 @@ mod tests {
             Some(ModLoader::Minecraft),
             "Cli shall read the input mod loader"
         );
-        assert_eq!(cli.download, true, "Cli shall set the download flag");
-        assert_eq!(cli.install, true, "Cli shall set the install flag");
+        assert!(cli.download, "Cli shall set the download flag");
+        assert!(cli.install, "Cli shall set the install flag");
     }
 
     #[test]
@@ -217,8 +2812,319 @@ mod tests {
             Some(ModLoader::Minecraft),
             "Cli shall read the input mod loader"
         );
-        assert_eq!(cli.download, true, "Cli shall set the install flag");
-        assert_eq!(cli.install, true, "Cli shall set the install flag");
+        assert!(cli.download, "Cli shall set the install flag");
+        assert!(cli.install, "Cli shall set the install flag");
+    }
+
+    #[test]
+    fn test_cli_parse_launcher() {
+        let cli = Cli::try_parse_from(["exe", "--launcher", "prism", "--instance", "Pack"])
+            .expect("Cli shall accept a launcher and instance");
+        assert_eq!(
+            cli.launcher,
+            Some(launcher::Launcher::Prism),
+            "Cli shall read the input launcher"
+        );
+        assert_eq!(
+            cli.instance,
+            Some("Pack".into()),
+            "Cli shall read the input instance"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_list() {
+        let cli = Cli::try_parse_from(["exe", "config", "list", "--type", "resourcepack"])
+            .expect("Cli shall accept the list command after a config");
+        assert_eq!(cli.config, Some(PathBuf::from("config")));
+        assert_eq!(
+            cli.command,
+            Some(Command::List {
+                project_type: Some(ProjectType::Resourcepack)
+            }),
+            "Cli shall read the list command"
+        );
+        let cli = Cli::try_parse_from(["exe", "list"]).expect("Cli shall accept a bare command");
+        assert_eq!(
+            cli.config, None,
+            "Cli shall not read a command as the config"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_search() {
+        let cli =
+            Cli::try_parse_from(["exe", "-l", "fabric", "search", "minimap", "--type", "mod"])
+                .expect("Cli shall accept the search command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Search {
+                query: "minimap".into(),
+                project_type: Some(ProjectType::Mod),
+                limit: 10
+            }),
+            "Cli shall read the query and filters of a search"
+        );
+        assert_eq!(cli.loader, Some(ModLoader::Fabric));
+    }
+
+    #[test]
+    fn test_cli_parse_diff() {
+        let cli = Cli::try_parse_from(["exe", "diff", "old.lock", "new.lock"])
+            .expect("Cli shall accept the diff command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Diff {
+                old: Some(PathBuf::from("old.lock")),
+                new: Some(PathBuf::from("new.lock"))
+            }),
+            "Cli shall read both lockfiles"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_init() {
+        let cli = Cli::try_parse_from(["exe", "init", "--template", "performance-fabric"])
+            .expect("Cli shall accept the init command");
+        assert!(
+            matches!(cli.command, Some(Command::Init { template, .. }) if template == "performance-fabric"),
+            "Cli shall read the template name"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_plan() {
+        let cli = Cli::try_parse_from(["exe", "plan", "-o", "plan.json"])
+            .expect("Cli shall accept the plan command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Plan {
+                output: Some("plan.json".into())
+            })
+        );
+        let cli = Cli::try_parse_from(["exe", "apply", "plan.json"])
+            .expect("Cli shall accept the apply command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Apply {
+                plan: "plan.json".into()
+            }),
+            "Cli shall read the plan to apply"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_audit() {
+        let cli = Cli::try_parse_from(["exe", "audit", "--stale", "18mo"])
+            .expect("Cli shall accept the audit command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Audit {
+                stale: Some("18mo".parse().expect("Age shall parse"))
+            })
+        );
+        assert!(
+            Cli::try_parse_from(["exe", "audit", "--stale", "soon"]).is_err(),
+            "Cli shall reject invalid ages"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_channel() {
+        let cli =
+            Cli::try_parse_from(["exe", "--channel", "beta"]).expect("Cli shall accept a channel");
+        assert_eq!(lock_path(&cli), PathBuf::from("./mcmod.beta.lock"));
+        assert!(
+            Cli::try_parse_from(["exe", "--channel", "beta/../x"]).is_err(),
+            "Cli shall reject channels that are not file names"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_trace_http() {
+        let cli = Cli::try_parse_from(["exe", "--trace-http", "pack.toml"])
+            .expect("Cli shall accept tracing without a file");
+        assert_eq!(cli.trace_http, Some(None));
+        assert_eq!(
+            config_path(&cli),
+            PathBuf::from("pack.toml"),
+            "The config shall not be taken as the trace file"
+        );
+        let cli = Cli::try_parse_from(["exe", "--trace-http=trace.log"])
+            .expect("Cli shall accept tracing to a file");
+        assert_eq!(cli.trace_http, Some(Some(PathBuf::from("trace.log"))));
+    }
+
+    #[test]
+    fn test_resolve_locked() {
+        let dir = Path::new(".test/locked");
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).expect("Failure to create test path");
+        let config = dir.join("mcmod.toml");
+        let text = "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\n[projects]\nsodium = {}\n";
+        fs::write(&config, text).expect("Failure to write config");
+        let cli = |flags: &[&str]| {
+            let mut args = vec!["exe", config.to_str().expect("Path is UTF-8")];
+            args.extend(["--registry-fixture", "fixtures/registry.json"]);
+            args.extend(flags);
+            Cli::try_parse_from(args).expect("Cli shall parse")
+        };
+        let run = |cli: &Cli| resolve(cli, &load_config(cli).expect("Config shall load"));
+        assert!(
+            matches!(run(&cli(&["--locked"])), Err(Error::LockOutdated(_))),
+            "--locked shall fail without a lockfile"
+        );
+        run(&cli(&[])).expect("Projects shall resolve");
+        run(&cli(&["--locked"])).expect("--locked shall use the lockfile");
+        run(&cli(&["--frozen"])).expect("--frozen shall use the lockfile");
+        fs::write(&config, format!("{text}lithium = {{}}\n")).expect("Failure to write config");
+        assert!(
+            matches!(run(&cli(&["--locked"])), Err(Error::LockOutdated(_))),
+            "--locked shall fail when the config changed"
+        );
+        assert!(
+            Cli::try_parse_from(["exe", "--locked", "--update"]).is_err(),
+            "Cli shall not accept --locked with --update"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_export_curseforge() {
+        assert!(
+            Cli::try_parse_from(["exe", "export", "curseforge", "-o", "pack.zip"]).is_err(),
+            "Cli shall require the loader version for curseforge"
+        );
+        let cli = Cli::try_parse_from([
+            "exe",
+            "export",
+            "curseforge",
+            "-o",
+            "pack.zip",
+            "--loader-version",
+            "0.16.14",
+        ])
+        .expect("Cli shall accept a curseforge export");
+        assert!(matches!(
+            cli.command,
+            Some(Command::Export { loader_version: Some(x), pack_version, bundle_unmatched: false, .. })
+                if x == "0.16.14" && pack_version == "1.0.0"
+        ));
+        assert!(
+            Cli::try_parse_from(["exe", "export", "csv"]).is_ok(),
+            "Cli shall not require a loader version for other formats"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_promote() {
+        let cli = Cli::try_parse_from(["exe", "promote", "beta", "stable"])
+            .expect("Cli shall accept the promote command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Promote {
+                from: "beta".into(),
+                to: "stable".into(),
+                title: None,
+                output: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_ws() {
+        let cli = Cli::try_parse_from(["exe", "ws", "-w", "servers.toml", "outdated"])
+            .expect("Cli shall accept the ws command");
+        assert_eq!(
+            cli.command,
+            Some(Command::Ws {
+                workspace: Some(PathBuf::from("servers.toml")),
+                command: WsCommand::Outdated
+            })
+        );
+        assert!(
+            Cli::try_parse_from(["exe", "ws"]).is_err(),
+            "Cli shall require a workspace command"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_status() {
+        let cli = Cli::try_parse_from(["exe", "status"]).expect("Cli shall accept status");
+        assert_eq!(
+            cli.command,
+            Some(Command::Verify),
+            "Cli shall accept status as verify"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_overrides() {
+        let cli = Cli::try_parse_from([
+            "exe",
+            "--game-version-all",
+            "1.21.1",
+            "-l",
+            "quilt",
+            "-t",
+            "server",
+            "--minecraft-dir",
+            "scratch",
+            "--world",
+            "New World",
+        ])
+        .expect("Cli shall accept overrides");
+        assert_eq!(
+            cli.overrides(),
+            config::ConfigOverrides {
+                game_version: None,
+                game_version_all: Some(MinecraftVersion::from("1.21.1")),
+                loader: Some(ModLoader::Quilt),
+                target: Some(Side::Server),
+                data_dir: None,
+                minecraft_dir: Some(PathBuf::from("scratch")),
+                world: Some("New World".into()),
+            },
+            "Cli shall pass its overrides to the config"
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_fixtures() {
+        let cli = Cli::try_parse_from(["exe", "--replay-fixtures", "fixtures"])
+            .expect("Cli shall accept a fixtures directory");
+        assert_eq!(
+            cli.fixtures(),
+            Some(fixtures::Fixtures::new(
+                PathBuf::from("fixtures"),
+                fixtures::FixtureMode::Replay
+            ))
+        );
+        Cli::try_parse_from(["exe", "--record-fixtures", "a", "--replay-fixtures", "b"])
+            .expect_err("Cli shall not record and replay at once");
+    }
+
+    #[test]
+    fn test_format_skipped() {
+        let skipped = vec![
+            (
+                "lithum".into(),
+                error::Error::ProjectNotFound("lithum".into()),
+            ),
+            (
+                "iris".into(),
+                error::Error::VersionNotFound {
+                    project: "iris".into(),
+                }
+                .context("project iris".into()),
+            ),
+        ];
+        assert_eq!(
+            format_skipped(&skipped),
+            "Skipped 2 optional projects:\n  lithum:\n    No project named \"lithum\", is the \
+            slug spelled correctly?\n  iris:\n    Could not resolve project iris\n      - \
+            Response empty for \"iris\"\n",
+            "Skipped projects shall be listed with their reasons"
+        );
     }
 
     #[test]
@@ -269,15 +3175,387 @@ mod tests {
         );
     }
 
+    /// Construct a file manager in a test directory that can download the files given a body and
+    /// no other file, through a client that injects faults if given chaos, and a version with
+    /// all the files
+    fn fixture_downloads(
+        dir: &Path,
+        files: &[(String, Option<Vec<u8>>)],
+        chaos: Option<chaos::Chaos>,
+    ) -> (cache::ModFileManager, ModVersion) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir.join("minecraft")).expect("Failure to create test dir");
+        let fixtures = fixtures::Fixtures::new(dir.join("fixtures"), fixtures::FixtureMode::Record);
+        let files = files
+            .iter()
+            .map(|(name, body)| {
+                let url = format!("https://cdn.example.com/{name}");
+                let mut file = ModFile {
+                    url: url.clone(),
+                    name: name.clone(),
+                    hashes: Default::default(),
+                    size: 0,
+                };
+                if let Some(body) = body {
+                    fixtures
+                        .get(&url, || Ok(body.clone()))
+                        .expect("Failure to record fixture");
+                    file.hashes = [("sha1".into(), verify::hash_bytes(body).sha1)].into();
+                    file.size = body.len() as u64;
+                }
+                file
+            })
+            .collect::<Vec<_>>();
+        let fixtures = fixtures::Fixtures::new(dir.join("fixtures"), fixtures::FixtureMode::Replay);
+        let manager = cache::ModFileManager::new(
+            dir.join("data"),
+            Some(dir.join("minecraft")),
+            labrinth::Client::new()
+                .with_fixtures(Some(fixtures))
+                .with_chaos(chaos.map(Arc::new)),
+        );
+        let version = ModVersion {
+            project_id: String::from("P").into(),
+            version_id: String::from("VVVV").into(),
+            name: "Test".into(),
+            version_number: "1.0".into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files,
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        };
+        (manager, version)
+    }
+
+    /// Set up downloads where `ok.jar` succeeds and `unreachable.jar` fails
+    fn failing_downloads(dir: &Path) -> (cache::ModFileManager, ModVersion) {
+        let files = [
+            ("ok.jar".to_string(), Some(b"ok".to_vec())),
+            ("unreachable.jar".to_string(), None),
+        ];
+        fixture_downloads(dir, &files, None)
+    }
+
+    #[test]
+    fn test_download_queue() {
+        let (manager, version) = failing_downloads(Path::new(".test/download_queue"));
+        let queue = version
+            .files
+            .iter()
+            .map(|x| (&version, x))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            download_queue(&manager, &queue).expect("Downloads shall not be interrupted"),
+            vec!["unreachable.jar"],
+            "Downloads shall report the files that failed"
+        );
+        assert!(
+            manager
+                .find_file(&version.version_id, &"ok.jar".to_string())
+                .is_some(),
+            "A failed download shall not stop the others"
+        );
+    }
+
+    /// Set up a version of files downloaded through a client that injects faults
+    fn chaotic_downloads(dir: &Path, seed: u64) -> (cache::ModFileManager, ModVersion) {
+        let files = (0..16)
+            .map(|index| {
+                let body = format!("contents of file {index}").into_bytes();
+                (format!("file{index}.jar"), Some(body))
+            })
+            .collect::<Vec<_>>();
+        fixture_downloads(dir, &files, Some(chaos::Chaos::new(seed)))
+    }
+
+    #[test]
+    fn test_download_chaos() {
+        let (manager, version) = chaotic_downloads(Path::new(".test/download_chaos"), 11);
+        let mut rounds_with_failures = 0;
+        for _ in 0..20 {
+            let queue = version
+                .files
+                .iter()
+                .filter(|x| manager.find_file(&version.version_id, &x.name).is_none())
+                .map(|x| (&version, x))
+                .collect::<Vec<_>>();
+            if queue.is_empty() {
+                break;
+            }
+            let failed = download_queue(&manager, &queue).expect("Downloads shall not stop");
+            rounds_with_failures += usize::from(!failed.is_empty());
+        }
+        assert!(rounds_with_failures > 0, "Chaos shall make downloads fail");
+        for file in &version.files {
+            let path = manager
+                .find_file(&version.version_id, &file.name)
+                .expect("Resuming shall download every file in the end");
+            assert_eq!(
+                verify::hash_file(&path)
+                    .expect("Failure to hash file")
+                    .matches(&file.hashes),
+                Some(true),
+                "A corrupt download shall never be cached"
+            );
+        }
+    }
+
+    #[test]
+    fn test_install_chaos() {
+        let dir = Path::new(".test/install_chaos");
+        let (manager, version) = chaotic_downloads(dir, 5);
+        let mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n[projects]\n",
+        )
+        .expect("Failure to load config");
+        let instance = dir.join("minecraft");
+        let mods = instance.join("mods");
+        fs::create_dir_all(&mods).expect("Failure to create test dir");
+        for name in ["file3.jar", "file9.jar", "other.jar"] {
+            fs::write(mods.join(name), "old").expect("Failure to write test file");
+        }
+        let listing = || {
+            let mut files = fs::read_dir(&mods)
+                .expect("Failure to list mods")
+                .map(|x| x.expect("Failure to list mods").path())
+                .map(|x| (x.clone(), fs::read_to_string(x).unwrap_or_default()))
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        let before = listing();
+        let mut rounds_with_failures = 0;
+        for _ in 0..40 {
+            match install_files(
+                &mod_config,
+                &ModDB::default(),
+                &manager,
+                &instance,
+                &[&version],
+                false,
+            ) {
+                Ok(_) => break,
+                Err(_) => rounds_with_failures += 1,
+            }
+            assert_eq!(
+                listing(),
+                before,
+                "A failed install shall leave the instance as it was"
+            );
+            assert!(
+                manifest::InstallManifest::load(&instance)
+                    .expect("Failure to load manifest")
+                    .is_none(),
+                "A failed install shall not write a manifest"
+            );
+        }
+        assert!(rounds_with_failures > 0, "Chaos shall make installs fail");
+        for file in &version.files {
+            assert_eq!(
+                verify::hash_file(&mods.join(&file.name))
+                    .expect("Retrying shall install every file in the end")
+                    .matches(&file.hashes),
+                Some(true),
+                "A corrupt download shall never be installed"
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(mods.join("other.jar")).ok().as_deref(),
+            Some("old"),
+            "Files the install does not manage shall be left alone"
+        );
+    }
+
+    #[test]
+    fn test_install_roll_back() {
+        let dir = Path::new(".test/install_roll_back");
+        let (manager, version) = failing_downloads(dir);
+        let mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n[projects]\n",
+        )
+        .expect("Failure to load config");
+        let instance = dir.join("minecraft");
+        let mods = instance.join("mods");
+        fs::create_dir_all(&mods).expect("Failure to create test dir");
+        fs::write(mods.join("unreachable.jar"), "old").expect("Failure to write test file");
+        let result = install_files(
+            &mod_config,
+            &ModDB::default(),
+            &manager,
+            &instance,
+            &[&version],
+            false,
+        );
+        assert!(result.is_err(), "The install shall fail");
+        assert!(
+            !mods.join("ok.jar").exists(),
+            "A failed install shall remove the files it added"
+        );
+        assert_eq!(
+            fs::read_to_string(mods.join("unreachable.jar"))
+                .ok()
+                .as_deref(),
+            Some("old"),
+            "A failed install shall restore the files it replaced"
+        );
+        assert!(
+            !backup_path(&mods.join("unreachable.jar")).exists(),
+            "A failed install shall not leave backups behind"
+        );
+        assert!(
+            manifest::InstallManifest::load(&instance)
+                .expect("Failure to load manifest")
+                .is_none(),
+            "A failed install shall not write a manifest"
+        );
+    }
+
+    #[test]
+    fn test_frozen_warnings() {
+        let mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\
+            [projects]\nsodium = { frozen = true }\nlithium = {}\niris = { frozen = true }\n\
+            P7dR8mSH = { frozen = true }\nold-zoomify = { version-id = \"dddd\", frozen = true }\n",
+        )
+        .expect("Failure to load config");
+        let locked = lockfile::Lockfile::loads(
+            "[[project]]\nproject_id = \"AANobbMI\"\nslug = \"sodium\"\nname = \"Sodium\"\n\
+            version_id = \"aaaa\"\nversion_number = \"0.6.0\"\n\
+            [[project]]\nproject_id = \"gvQqBUqZ\"\nslug = \"lithium\"\nname = \"Lithium\"\n\
+            version_id = \"bbbb\"\nversion_number = \"0.14.0\"\n\
+            [[project]]\nproject_id = \"P7dR8mSH\"\nslug = \"fabric-api\"\nname = \"Fabric API\"\n\
+            version_id = \"cccc\"\nversion_number = \"0.120.0\"\n\
+            [[project]]\nproject_id = \"w7ThoJFB\"\nslug = \"zoomify\"\nname = \"Zoomify\"\n\
+            version_id = \"dddd\"\nversion_number = \"2.14.2\"\n",
+        )
+        .expect("Failure to load lockfile");
+        assert_eq!(
+            frozen_warnings(&mod_config, &locked),
+            vec![
+                "Not updating P7dR8mSH, it is frozen at 0.120.0",
+                "Not updating old-zoomify, it is frozen at 2.14.2",
+                "Not updating sodium, it is frozen at 0.6.0"
+            ],
+            "Only locked frozen projects shall be warned about, named by slug, id, or version id"
+        );
+    }
+
+    #[test]
+    fn test_promotion_problems() {
+        let mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\
+            [paths]\ndata = \".test/promotion_problems\"\n\
+            [projects]\nAANobbMI = { frozen = true }\nlithium = {}\n",
+        )
+        .expect("Failure to load config");
+        let lock = |version_id: &str| {
+            lockfile::Lockfile::loads(&format!(
+                "[[project]]\nproject_id = \"AANobbMI\"\nslug = \"sodium\"\nname = \"Sodium\"\n\
+                version_id = \"{version_id}\"\nversion_number = \"{version_id}\"\n\
+                [[project.file]]\nname = \"sodium.jar\"\nurl = \"https://example.com\"\n"
+            ))
+            .expect("Failure to load lockfile")
+        };
+        let cli = Cli::try_parse_from(["exe"]).expect("Cli shall parse");
+        let problems = promotion_problems(&cli, &mod_config, &lock("bbbb"), &lock("aaaa"))
+            .expect("Promotion shall be checked");
+        assert_eq!(
+            problems,
+            vec![
+                "AANobbMI is frozen at aaaa, but would change to bbbb",
+                "lithium is configured but not locked",
+                "sodium.jar of sodium is not cached, so its hashes could not be verified",
+            ],
+            "Projects named by id shall be found, and uncached files shall not pass"
+        );
+    }
+
+    #[test]
+    fn test_unresolved_projects() {
+        let mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\
+            [projects]\nsodium = {}\nAANobbMI = {}\n[optional-projects]\nmodmenu = {}\n",
+        )
+        .expect("Failure to load config");
+        let mut mod_db = ModDB::default();
+        mod_db.add_project(ModProject {
+            project_id: "AANobbMI".to_string().into(),
+            name: "Sodium".into(),
+            slug: "sodium".into(),
+            description: String::new(),
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            side: Side::Both,
+            license: None,
+            icon_url: None,
+            categories: vec![],
+            stats: None,
+        });
+        let names = unresolved_projects(&mod_config, &mod_db)
+            .into_iter()
+            .map(|x| x.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["modmenu"],
+            "Only configured projects that were not resolved by slug or id shall be listed as \
+            unresolved"
+        );
+    }
+
+    #[test]
+    fn test_install_overrides() {
+        let dir = Path::new(".test/install_overrides");
+        let _ = std::fs::remove_dir_all(dir);
+        let overrides = dir.join("overrides");
+        std::fs::create_dir_all(overrides.join("config")).expect("Failure to create overrides");
+        std::fs::write(overrides.join("config/sodium.json"), "{}").expect("Failure to write");
+        std::fs::write(overrides.join("config/sodium.json.bak"), "").expect("Failure to write");
+        std::fs::write(overrides.join("notes.md"), "").expect("Failure to write");
+        std::fs::write(overrides.join(ignore::IGNORE_FILE), "*.md\n").expect("Failure to write");
+        let mut mod_config = config::Config::loads(
+            "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n[projects]\n\
+            [install]\nexclude = [\"*.bak\"]\n",
+        )
+        .expect("Failure to load config");
+        mod_config.install.overrides = Some(overrides);
+        let instance = dir.join("minecraft");
+        install_overrides(&mod_config, &instance).expect("Overrides shall be installed");
+        assert!(instance.join("config/sodium.json").is_file());
+        assert!(
+            !instance.join("config/sodium.json.bak").exists(),
+            "Files excluded by the config shall not be copied"
+        );
+        assert!(
+            !instance.join("notes.md").exists(),
+            "Files excluded by the ignore file shall not be copied"
+        );
+    }
+
     #[test]
     fn test_action_install() {
         create_test_paths();
         let mod_config = load_test_config();
         let mod_solver = solver::ModSolver::new(&mod_config);
-        let mod_db = mod_solver.solve().expect("Failure to resolve versions");
-        prepare_files(&mod_config, &mod_db, false).expect("Failure to download files");
-        prepare_files(&mod_config, &mod_db, true).expect("Failure to install files");
-        let minecraft = &mod_config.paths.dot_minecraft;
+        let mod_db = mod_solver
+            .solve()
+            .expect("Failure to resolve versions")
+            .mod_db;
+        let instances = mod_config
+            .paths
+            .install_dirs(mod_config.defaults.target)
+            .expect("Failure to find install directories");
+        prepare_files_into(&mod_config, &mod_db, &instances, false, false)
+            .expect("Failure to download files");
+        prepare_files_into(&mod_config, &mod_db, &instances, true, false)
+            .expect("Failure to install files");
+        let minecraft = &mod_config
+            .paths
+            .minecraft_dir()
+            .expect("Failure to find .minecraft");
         check_children_count(&minecraft.join("datapacks"), 1);
         check_children_count(&minecraft.join("mods"), 3);
         check_children_count(&minecraft.join("resourcepacks"), 1);
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cache::Install;
+use crate::error::Result;
+use crate::fsutil;
+use crate::verify::{FileCheck, hash_file};
+
+/// The name of the manifest in the instance directory
+const MANIFEST_NAME: &str = ".mcmod-manifest.json";
+
+/// The directories of packs that keep their settings next to them, in `<file name>.txt`
+const SETTINGS_DIRS: [&str; 2] = ["shaderpacks", "resourcepacks"];
+
+/// The files written by the last install, kept in the instance so it can be verified on machines
+/// without the data directory or lockfile
+#[derive(Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct InstallManifest {
+    /// The version of mcmod that installed the files
+    pub mcmod_version: String,
+    /// When the files were installed, in RFC 3339
+    pub installed: String,
+    /// The installed files
+    pub files: Vec<ManifestFile>,
+}
+
+/// A file written by an install
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ManifestFile {
+    /// The path of the file relative to the instance, separated by `/`
+    pub path: String,
+    /// The hashes of the file by algorithm
+    pub hashes: BTreeMap<String, String>,
+    /// The project the file belongs to, so an updated file can be matched with the one it replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// The name of the file in its version, if it was installed under another name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+/// Get the path of the manifest of an instance
+pub fn manifest_path(instance: &Path) -> PathBuf {
+    instance.join(MANIFEST_NAME)
+}
+
+impl InstallManifest {
+    /// Describe the installed files, with paths relative to the instance
+    pub fn from_installs(instance: &Path, installs: &[Install]) -> Self {
+        let files = installs
+            .iter()
+            .map(|install| ManifestFile {
+                path: install
+                    .path
+                    .strip_prefix(instance)
+                    .unwrap_or(&install.path)
+                    .components()
+                    .map(|x| x.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                hashes: install.file.hashes.clone(),
+                project_id: Some(install.version.project_id.to_string()),
+                file_name: install
+                    .path
+                    .file_name()
+                    .is_none_or(|x| x.to_string_lossy() != install.file.name)
+                    .then(|| install.file.name.clone()),
+            })
+            .collect();
+        Self {
+            mcmod_version: env!("CARGO_PKG_VERSION").to_string(),
+            installed: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            files,
+        }
+    }
+
+    /// Load the manifest of an instance, if it has one
+    pub fn load(instance: &Path) -> Result<Option<Self>> {
+        let path = manifest_path(instance);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// Save the manifest into an instance
+    pub fn save(&self, instance: &Path) -> Result<()> {
+        std::fs::write(manifest_path(instance), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Get the checks of the installed files
+    pub fn checks(&self, instance: &Path) -> Vec<FileCheck> {
+        self.files
+            .iter()
+            .map(|file| FileCheck {
+                path: instance.join(&file.path),
+                expected: file.hashes.clone(),
+            })
+            .collect()
+    }
+
+    /// Rename the settings files of the shader and resource packs that a newer install updated,
+    /// like `shaderpacks/<file name>.txt`, to match the new file name, so the settings are not
+    /// lost. Settings already written for the new file are left alone. Returns the old and new
+    /// paths of the renamed files.
+    pub fn carry_settings(&self, instance: &Path, newer: &Self) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let dir = |x: &ManifestFile| x.path.rsplit_once('/').map(|(dir, _)| dir.to_string());
+        let mut renamed = Vec::new();
+        for file in &self.files {
+            let Some(project_id) = &file.project_id else {
+                continue;
+            };
+            if !dir(file).is_some_and(|x| SETTINGS_DIRS.contains(&x.as_str())) {
+                continue;
+            }
+            let Some(update) = newer.files.iter().find(|x| {
+                x.project_id.as_ref() == Some(project_id)
+                    && x.path != file.path
+                    && dir(x) == dir(file)
+            }) else {
+                continue;
+            };
+            let from = instance.join(format!("{}.txt", file.path));
+            let to = instance.join(format!("{}.txt", update.path));
+            if from.is_file() && !to.exists() {
+                std::fs::rename(fsutil::long_path(&from), fsutil::long_path(&to))?;
+                renamed.push((from, to));
+            }
+        }
+        Ok(renamed)
+    }
+
+    /// Remove the installed files of projects, by project id, and their entries. Files that were
+    /// modified since they were installed are kept. Returns the removed and kept paths.
+    pub fn remove_projects(
+        &mut self,
+        instance: &Path,
+        project_ids: &[&str],
+    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let newer = Self {
+            files: self
+                .files
+                .iter()
+                .filter(|x| {
+                    x.project_id
+                        .as_deref()
+                        .is_none_or(|x| !project_ids.contains(&x))
+                })
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        let result = self.prune(instance, &newer)?;
+        self.files = newer.files;
+        Ok(result)
+    }
+
+    /// Remove the files of this manifest that a newer install no longer has. Files that were
+    /// modified since they were installed are kept. A copy of a file the newer install renamed,
+    /// left under its original name like one installed before `install-as` was set, is removed
+    /// too. Returns the removed and kept paths.
+    pub fn prune(&self, instance: &Path, newer: &Self) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let (mut removed, mut kept) = (Vec::new(), Vec::new());
+        for file in &self.files {
+            if newer.files.iter().any(|x| x.path == file.path) {
+                continue;
+            }
+            let path = instance.join(&file.path);
+            if !path.is_file() {
+                continue;
+            }
+            if hash_file(&path)?.matches(&file.hashes) == Some(false) {
+                kept.push(path);
+            } else {
+                std::fs::remove_file(fsutil::long_path(&path))?;
+                removed.push(path);
+            }
+        }
+        for file in &newer.files {
+            let Some(file_name) = &file.file_name else {
+                continue;
+            };
+            let original = match file.path.rsplit_once('/') {
+                Some((dir, _)) => format!("{dir}/{file_name}"),
+                None => file_name.clone(),
+            };
+            let path = instance.join(&original);
+            if newer.files.iter().any(|x| x.path == original) || !path.is_file() {
+                continue;
+            }
+            if hash_file(&path)?.matches(&file.hashes) == Some(true) {
+                std::fs::remove_file(fsutil::long_path(&path))?;
+                removed.push(path);
+            }
+        }
+        Ok((removed, kept))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ModFile, ModVersion};
+
+    #[test]
+    fn test_round_trip() {
+        let instance = PathBuf::from(".test/manifest");
+        std::fs::create_dir_all(&instance).expect("Failure to create test path");
+        let _ = std::fs::remove_file(manifest_path(&instance));
+        assert_eq!(
+            InstallManifest::load(&instance).expect("A missing manifest shall not fail"),
+            None
+        );
+        let file = ModFile {
+            name: "sodium.jar".into(),
+            url: "https://cdn.modrinth.com/sodium.jar".into(),
+            hashes: BTreeMap::from([("sha1".into(), "aaaa".into())]),
+            size: 1024,
+        };
+        let version = ModVersion {
+            project_id: String::from("AANobbMI").into(),
+            version_id: String::from("abc").into(),
+            name: "Sodium".into(),
+            version_number: "0.6.13".into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![],
+            files: vec![file.clone()],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        };
+        let installs = [Install {
+            version: &version,
+            file: &file,
+            path: instance.join("mods").join("sodium.jar"),
+        }];
+        let manifest = InstallManifest::from_installs(&instance, &installs);
+        assert_eq!(
+            manifest.files[0].path, "mods/sodium.jar",
+            "InstallManifest shall store paths relative to the instance"
+        );
+        assert_eq!(manifest.files[0].file_name, None);
+        let renamed = InstallManifest::from_installs(
+            &instance,
+            &[Install {
+                path: instance.join("mods").join("renamed.jar"),
+                ..installs[0]
+            }],
+        );
+        assert_eq!(
+            renamed.files[0].file_name.as_deref(),
+            Some("sodium.jar"),
+            "InstallManifest shall record the name of files installed under another name"
+        );
+        manifest.save(&instance).expect("Manifest shall be saved");
+        let loaded = InstallManifest::load(&instance)
+            .expect("Manifest shall be loaded")
+            .expect("Manifest shall exist after saving");
+        assert_eq!(loaded, manifest);
+        assert_eq!(
+            loaded.checks(&instance)[0].path,
+            instance.join("mods/sodium.jar")
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let instance = PathBuf::from(".test/manifest_prune");
+        let _ = std::fs::remove_dir_all(&instance);
+        std::fs::create_dir_all(instance.join("mods")).expect("Failure to create test path");
+        let file = |path: &str, contents: &str| {
+            std::fs::write(instance.join(path), contents).expect("Failure to write test file");
+            let hashes = hash_file(&instance.join(path)).expect("Failure to hash test file");
+            ManifestFile {
+                path: path.into(),
+                hashes: BTreeMap::from([("sha1".into(), hashes.sha1)]),
+                project_id: None,
+                file_name: None,
+            }
+        };
+        let old = InstallManifest {
+            files: vec![
+                file("mods/kept.jar", "kept"),
+                file("mods/stale.jar", "stale"),
+                file("mods/modified.jar", "original"),
+            ],
+            ..Default::default()
+        };
+        std::fs::write(instance.join("mods/modified.jar"), "changed").expect("Failure to write");
+        let new = InstallManifest {
+            files: vec![old.files[0].clone()],
+            ..Default::default()
+        };
+        let (removed, kept) = old.prune(&instance, &new).expect("Pruning shall not fail");
+        assert_eq!(removed, vec![instance.join("mods/stale.jar")]);
+        assert_eq!(
+            kept,
+            vec![instance.join("mods/modified.jar")],
+            "Pruning shall keep files modified since they were installed"
+        );
+        assert!(instance.join("mods/kept.jar").is_file());
+        assert!(!instance.join("mods/stale.jar").exists());
+
+        let copy = file("mods/sodium-0.6.jar", "sodium");
+        let renamed = ManifestFile {
+            path: "mods/sodium.jar".into(),
+            file_name: Some("sodium-0.6.jar".into()),
+            ..copy
+        };
+        let newer = InstallManifest {
+            files: vec![renamed],
+            ..Default::default()
+        };
+        let (removed, _) = new
+            .prune(&instance, &newer)
+            .expect("Pruning shall not fail");
+        assert_eq!(
+            removed,
+            vec![
+                instance.join("mods/kept.jar"),
+                instance.join("mods/sodium-0.6.jar")
+            ],
+            "Pruning shall remove copies of renamed files left under their original name"
+        );
+    }
+
+    #[test]
+    fn test_carry_settings() {
+        let instance = PathBuf::from(".test/manifest_settings");
+        let _ = std::fs::remove_dir_all(&instance);
+        std::fs::create_dir_all(instance.join("shaderpacks")).expect("Failure to create test path");
+        let file = |path: &str, project_id: &str| ManifestFile {
+            path: path.into(),
+            hashes: BTreeMap::new(),
+            project_id: Some(project_id.into()),
+            file_name: None,
+        };
+        let old = InstallManifest {
+            files: vec![
+                file("shaderpacks/Complementary_r5.1.zip", "HVnmMxH1"),
+                file("shaderpacks/BSL_v8.2.zip", "Q1vvjJYV"),
+                file("mods/sodium-0.6.12.jar", "AANobbMI"),
+            ],
+            ..Default::default()
+        };
+        let new = InstallManifest {
+            files: vec![
+                file("shaderpacks/Complementary_r5.2.zip", "HVnmMxH1"),
+                file("shaderpacks/BSL_v8.2.zip", "Q1vvjJYV"),
+                file("mods/sodium-0.6.13.jar", "AANobbMI"),
+            ],
+            ..Default::default()
+        };
+        for path in [
+            "shaderpacks/Complementary_r5.1.zip.txt",
+            "shaderpacks/BSL_v8.2.zip.txt",
+            "mods/sodium-0.6.12.jar.txt",
+        ] {
+            std::fs::create_dir_all(instance.join(path).parent().unwrap())
+                .expect("Failure to create test path");
+            std::fs::write(instance.join(path), "shadowMapResolution=2048")
+                .expect("Failure to write settings");
+        }
+        let renamed = old
+            .carry_settings(&instance, &new)
+            .expect("Carrying settings shall not fail");
+        assert_eq!(
+            renamed,
+            vec![(
+                instance.join("shaderpacks/Complementary_r5.1.zip.txt"),
+                instance.join("shaderpacks/Complementary_r5.2.zip.txt")
+            )],
+            "The settings of updated shader packs shall be renamed to match"
+        );
+        assert!(instance.join("shaderpacks/BSL_v8.2.zip.txt").is_file());
+        assert!(
+            instance.join("mods/sodium-0.6.12.jar.txt").is_file(),
+            "Files of mods shall not be renamed"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::fsutil;
+
+/// A cached record, like a project or version, and when it was fetched
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Entry<T> {
+    /// Seconds since the unix epoch when the record was fetched
+    fetched: i64,
+    /// The record
+    value: T,
+}
+
+/// The entries this process fetched again while refreshing, so each is only fetched once per run
+static REFRESHED: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Check if this process already fetched an entry again while refreshing
+fn is_refreshed(path: &Path) -> bool {
+    REFRESHED
+        .lock()
+        .expect("Refreshed entries are not poisoned")
+        .contains(path)
+}
+
+/// Record that this process fetched an entry again while refreshing
+fn set_refreshed(path: &Path) {
+    REFRESHED
+        .lock()
+        .expect("Refreshed entries are not poisoned")
+        .insert(path.to_path_buf());
+}
+
+impl<T> Entry<T> {
+    /// Check if the entry is younger than the TTL
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        let age = chrono::Utc::now().timestamp() - self.fetched;
+        age >= 0 && (age as u64) < ttl.as_secs()
+    }
+}
+
+/// How long to wait for another process fetching the same entry before fetching it anyway
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long between checks of whether another process finished fetching an entry
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// How old a lock must be to be considered abandoned by a process that exited while fetching
+const LOCK_STALE: Duration = Duration::from_secs(120);
+
+/// A lock on fetching an entry, held by creating a file next to it and released by removing it
+struct EntryLock(PathBuf);
+
+impl EntryLock {
+    /// Take the lock on an entry, waiting while another process holds it. Returns None if the
+    /// lock could not be taken before the timeout.
+    fn acquire(path: &Path) -> Option<Self> {
+        let mut name = path.file_name()?.to_os_string();
+        name.push(".lock");
+        let lock = path.with_file_name(name);
+        let started = Instant::now();
+        loop {
+            match std::fs::File::create_new(&lock) {
+                Ok(_) => return Some(Self(lock)),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let abandoned = std::fs::metadata(&lock)
+                        .and_then(|x| x.modified())
+                        .is_ok_and(|x| x.elapsed().is_ok_and(|x| x > LOCK_STALE));
+                    if abandoned {
+                        let _ = std::fs::remove_file(&lock);
+                        continue;
+                    }
+                }
+                Err(_) => return None,
+            }
+            if started.elapsed() > LOCK_TIMEOUT {
+                return None;
+            }
+            std::thread::sleep(LOCK_POLL);
+        }
+    }
+}
+
+impl Drop for EntryLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Persisted project and version metadata from the API, one record per entry. Each entry records
+/// when it was fetched, and is served until it is older than the TTL. The directory can be shared
+/// by several configs and processes: entries are namespaced by API, written atomically, and
+/// fetched by one process at a time while the others wait to read the result.
+pub struct MetadataCache {
+    dir: PathBuf,
+    namespace: String,
+    ttl: Duration,
+    refresh: bool,
+}
+
+impl MetadataCache {
+    /// Construct a cache in a directory. With refresh set, every entry is fetched again the first
+    /// time this process uses it.
+    pub fn new(dir: PathBuf, ttl: Duration, refresh: bool) -> Self {
+        Self {
+            dir,
+            namespace: String::new(),
+            ttl,
+            refresh,
+        }
+    }
+
+    /// Keep the entries in a namespace, so APIs with the same keys do not share entries
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = fsutil::safe_file_name(namespace);
+        self
+    }
+
+    /// Get the path of the entry for a key
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(&self.namespace)
+            .join(format!("{}.json", fsutil::safe_file_name(key)))
+    }
+
+    /// Write an entry by moving a complete file into place, so readers never see a partial entry
+    fn write<T: serde::Serialize>(path: &Path, entry: &Entry<T>) -> Result<()> {
+        std::fs::create_dir_all(path.parent().expect("Entry path has a parent"))?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(format!(".{}.part", std::process::id()));
+        std::fs::write(&partial, serde_json::to_string(entry)?)?;
+        std::fs::rename(&partial, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&partial);
+        })?;
+        Ok(())
+    }
+
+    /// Read an entry, ignoring a missing or corrupt file, or one written in another format
+    fn read<T: serde::de::DeserializeOwned>(path: &Path) -> Option<Entry<T>> {
+        serde_json::from_str(std::fs::read_to_string(path).ok()?.as_str()).ok()
+    }
+
+    /// Get the record for a key, fetching it if the entry is missing, stale, or not refreshed yet.
+    /// If another process is fetching the entry, its result is used once it is done. If it can not
+    /// be fetched, a stale entry is used.
+    pub fn get<T, F>(&self, key: &str, fetch: F) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce() -> Result<T>,
+    {
+        let path = self.entry_path(key);
+        let refresh = self.refresh && !is_refreshed(&path);
+        let before = Self::read::<T>(&path);
+        let fetched_before = before.as_ref().map(|x| x.fetched);
+        if let Some(entry) = before
+            && !refresh
+            && entry.is_fresh(self.ttl)
+        {
+            return Ok(entry.value);
+        }
+        std::fs::create_dir_all(path.parent().expect("Entry path has a parent"))?;
+        let _lock = EntryLock::acquire(&path);
+        let cached = Self::read::<T>(&path);
+        let fetched_since = fetched_before != cached.as_ref().map(|x| x.fetched);
+        if let Some(entry) = cached
+            && entry.is_fresh(self.ttl)
+            && (!refresh || fetched_since)
+        {
+            if refresh {
+                set_refreshed(&path);
+            }
+            return Ok(entry.value);
+        }
+        match fetch() {
+            Ok(value) => {
+                let entry = Entry {
+                    fetched: chrono::Utc::now().timestamp(),
+                    value,
+                };
+                Self::write(&path, &entry)?;
+                if refresh {
+                    set_refreshed(&path);
+                }
+                Ok(entry.value)
+            }
+            Err(e) => Self::read::<T>(&path).map(|x| x.value).ok_or(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_cache_ttl() {
+        let dir = PathBuf::from(".test/metacache");
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = MetadataCache::new(dir.clone(), Duration::from_secs(60), false);
+        let get = |cache: &MetadataCache, key: &str, value: &str| {
+            cache
+                .get(key, || Ok(value.to_string()))
+                .expect("MetadataCache shall get an entry")
+        };
+        assert_eq!(get(&cache, "project/a", "1"), "1");
+        assert_eq!(
+            get(&cache, "project/a", "2"),
+            "1",
+            "MetadataCache shall serve a fresh entry"
+        );
+        let refresh = MetadataCache::new(dir.clone(), Duration::from_secs(60), true);
+        assert_eq!(
+            get(&refresh, "project/a", "3"),
+            "3",
+            "MetadataCache shall fetch when refreshing"
+        );
+        assert_eq!(
+            get(&refresh, "project/a", "4"),
+            "3",
+            "MetadataCache shall only fetch an entry again once per run when refreshing"
+        );
+        assert_eq!(
+            cache
+                .get("project/a", || Ok(vec![1, 2]))
+                .expect("MetadataCache shall get an entry"),
+            vec![1, 2],
+            "MetadataCache shall fetch an entry holding another kind of record"
+        );
+        get(&cache, "project/a", "3");
+        let expired = MetadataCache::new(dir.clone(), Duration::ZERO, false);
+        assert_eq!(
+            expired
+                .get::<String, _>("project/a", || Err(Error::InvalidLoader("offline".into())))
+                .expect("MetadataCache shall fall back to a stale entry"),
+            "3",
+            "MetadataCache shall use a stale entry when fetching fails"
+        );
+        expired
+            .get::<String, _>("project/b", || Err(Error::InvalidLoader("offline".into())))
+            .expect_err("MetadataCache shall fail without an entry");
+        let other = MetadataCache::new(dir.clone(), Duration::from_secs(60), false)
+            .with_namespace("https://api.example");
+        assert_eq!(
+            get(&other, "project/a", "4"),
+            "4",
+            "MetadataCache shall not share entries between namespaces"
+        );
+    }
+
+    #[test]
+    fn test_cache_shared() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let dir = PathBuf::from(".test/metacache_shared");
+        let _ = std::fs::remove_dir_all(&dir);
+        let fetches = AtomicUsize::new(0);
+        let fetch = || {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(200));
+            Ok("shared".to_string())
+        };
+        std::thread::scope(|scope| {
+            let threads = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        MetadataCache::new(dir.clone(), Duration::from_secs(60), false)
+                            .with_namespace("modrinth")
+                            .get("project/a", fetch)
+                    })
+                })
+                .collect::<Vec<_>>();
+            for thread in threads {
+                assert_eq!(
+                    thread
+                        .join()
+                        .expect("The thread shall not panic")
+                        .expect("MetadataCache shall get the shared entry"),
+                    "shared"
+                );
+            }
+        });
+        assert_eq!(
+            fetches.load(Ordering::SeqCst),
+            1,
+            "MetadataCache shall fetch an entry once for every process sharing it"
+        );
+        let entry = dir.join("modrinth/project_a.json");
+        let lock = std::fs::File::create(entry.with_extension("json.lock"))
+            .expect("The abandoned lock shall be created");
+        lock.set_modified(std::time::SystemTime::now() - LOCK_STALE * 2)
+            .expect("The abandoned lock shall be backdated");
+        let refresh = MetadataCache::new(dir.clone(), Duration::from_secs(60), true)
+            .with_namespace("modrinth");
+        assert_eq!(
+            refresh
+                .get("project/a", || Ok("new".to_string()))
+                .expect("MetadataCache shall get the entry"),
+            "new",
+            "MetadataCache shall take over abandoned locks"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::tags;
+use crate::types::MinecraftVersion;
+
+/// Where Mojang publishes every version of the game
+const VERSION_MANIFEST_URL: &str =
+    "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+/// Mojang's list of every version of the game, newest first, with when each was released
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct VersionManifest {
+    /// The ids of the latest release and snapshot
+    latest: LatestVersions,
+    /// The versions, newest first
+    versions: Vec<ManifestVersion>,
+}
+
+/// The ids of the latest release and snapshot
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct LatestVersions {
+    release: String,
+    snapshot: String,
+}
+
+/// A version of the game in the manifest
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct ManifestVersion {
+    /// The version, like `1.21.5` or `25w14a`
+    id: String,
+    /// The kind of version, like `release`, `snapshot`, `old_beta`, or `old_alpha`
+    #[serde(rename = "type")]
+    version_type: String,
+    /// When the version was released, in RFC 3339
+    #[serde(rename = "releaseTime")]
+    release_time: String,
+}
+
+impl VersionManifest {
+    /// Parse the JSON of the manifest
+    pub fn loads(text: &str) -> Result<Self> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Load the manifest, using the cache in the data directory when it is fresh
+    pub fn load(client: &labrinth::Client, data_dir: &Path) -> Result<Self> {
+        tags::load_cached(data_dir, "version_manifest", || {
+            Self::loads(&String::from_utf8_lossy(
+                &client.download_file(VERSION_MANIFEST_URL)?,
+            ))
+        })
+    }
+
+    /// Find a version in the manifest, treating `1.21` and `1.21.0` as equal
+    fn find(&self, version: &MinecraftVersion) -> Option<(usize, &ManifestVersion)> {
+        self.versions.iter().enumerate().find(|(_, x)| {
+            MinecraftVersion::try_from(x.id.clone()).is_ok_and(|x| x.is_equivalent(version))
+        })
+    }
+
+    /// Replace `latest` and `latest-snapshot` with the version they stand for. Other versions
+    /// are returned unchanged.
+    pub fn resolve(&self, version: MinecraftVersion) -> Result<MinecraftVersion> {
+        let id = match version {
+            MinecraftVersion::LatestRelease => &self.latest.release,
+            MinecraftVersion::LatestSnapshot => &self.latest.snapshot,
+            _ => return Ok(version),
+        };
+        MinecraftVersion::try_from(id.clone())
+            .map_err(|_| Error::UnknownGameVersion(format!("{version} ({id})")))
+    }
+
+    /// Get when a version was released
+    pub fn release_time(
+        &self,
+        version: &MinecraftVersion,
+    ) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let (_, x) = self.find(&self.resolve(*version).ok()?)?;
+        chrono::DateTime::parse_from_rfc3339(&x.release_time).ok()
+    }
+
+    /// Order two versions by when they were released, so snapshots fall between the releases
+    /// around them. Returns None if either is not in the manifest.
+    pub fn compare(&self, left: &MinecraftVersion, right: &MinecraftVersion) -> Option<Ordering> {
+        let time = |x| self.release_time(x);
+        match (time(left), time(right)) {
+            (Some(l), Some(r)) if l != r => Some(l.cmp(&r)),
+            _ => {
+                let (l, _) = self.find(&self.resolve(*left).ok()?)?;
+                let (r, _) = self.find(&self.resolve(*right).ok()?)?;
+                Some(r.cmp(&l))
+            }
+        }
+    }
+
+    /// Check if a version was released before another. Returns None if either is not in the
+    /// manifest.
+    pub fn is_older(&self, version: &MinecraftVersion, than: &MinecraftVersion) -> Option<bool> {
+        self.compare(version, than).map(|x| x == Ordering::Less)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+        "latest": {"release": "1.21.5", "snapshot": "25w15a"},
+        "versions": [
+            {"id": "25w15a", "type": "snapshot", "releaseTime": "2025-04-08T12:00:00+00:00"},
+            {"id": "1.21.5", "type": "release", "releaseTime": "2025-03-25T12:00:00+00:00"},
+            {"id": "25w10a", "type": "snapshot", "releaseTime": "2025-03-05T12:00:00+00:00"},
+            {"id": "1.21.4", "type": "release", "releaseTime": "2024-12-03T10:00:00+00:00"},
+            {"id": "1.21", "type": "release", "releaseTime": "2024-06-13T08:00:00+00:00"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_version_manifest() {
+        let manifest = VersionManifest::loads(MANIFEST).expect("The manifest shall parse");
+        assert_eq!(
+            manifest
+                .resolve(MinecraftVersion::LatestRelease)
+                .expect("latest shall resolve"),
+            MinecraftVersion::from("1.21.5")
+        );
+        assert_eq!(
+            manifest
+                .resolve(MinecraftVersion::LatestSnapshot)
+                .expect("latest-snapshot shall resolve"),
+            MinecraftVersion::from("25w15a")
+        );
+        let snapshot = MinecraftVersion::from("25w10a");
+        assert_eq!(
+            manifest.compare(&snapshot, &MinecraftVersion::from("1.21.5")),
+            Some(Ordering::Less),
+            "Snapshots shall be ordered by when they were released"
+        );
+        assert_eq!(
+            manifest.is_older(&MinecraftVersion::from("1.21.4"), &snapshot),
+            Some(true)
+        );
+        assert_eq!(
+            manifest.is_older(
+                &MinecraftVersion::from("1.21.0"),
+                &MinecraftVersion::from("1.21.4")
+            ),
+            Some(true),
+            "The manifest shall treat a missing patch as zero"
+        );
+        assert_eq!(
+            manifest.compare(&MinecraftVersion::from("1.8.9"), &snapshot),
+            None,
+            "Versions that are not in the manifest shall not be ordered"
+        );
+    }
+}
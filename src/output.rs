@@ -0,0 +1,397 @@
+use std::io::{IsTerminal, Write};
+use std::sync::{Mutex, OnceLock};
+
+/// When to color the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when printing to a terminal, unless NO_COLOR is set or TERM is dumb
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color, for logs captured by systemd or CI
+    Never,
+}
+
+/// How a piece of text is emphasized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Table headers
+    Heading,
+    /// Things that went well, like files that match
+    Good,
+    /// Things that were skipped
+    Warning,
+    /// Things that failed
+    Error,
+}
+
+impl Style {
+    /// The ANSI escape code that starts the style
+    fn ansi(&self) -> &'static str {
+        match self {
+            Style::Heading => "\x1b[1m",
+            Style::Good => "\x1b[32m",
+            Style::Warning => "\x1b[33m",
+            Style::Error => "\x1b[31m",
+        }
+    }
+}
+
+/// How text is written to the terminal
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Output {
+    /// Whether to color text with ANSI escape codes
+    pub color: bool,
+    /// Whether to replace characters outside of ASCII
+    pub ascii: bool,
+    /// The width that tables are fit into, if any
+    pub width: Option<usize>,
+    /// Whether progress is printed to stderr, so stdout can carry a protocol
+    pub status_to_stderr: bool,
+    /// Whether stdout is a terminal, so progress can be redrawn in place
+    pub is_terminal: bool,
+}
+
+/// The output settings of this run
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+/// Set the output settings of this run. Only the first call has an effect.
+pub fn init(output: Output) {
+    let _ = OUTPUT.set(output);
+}
+
+/// Get the output settings of this run
+pub fn get() -> &'static Output {
+    OUTPUT.get_or_init(|| Output::detect(ColorChoice::Auto, false))
+}
+
+/// Print a line of progress
+pub fn print_status(line: &str) {
+    match get().status_to_stderr {
+        true => eprintln!("{line}"),
+        false => println!("{line}"),
+    }
+}
+
+/// Print a formatted line of progress, like `println!`
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        $crate::output::print_status(&format!($($arg)*))
+    };
+}
+pub use crate::status;
+
+/// The tasks of a [Progress] that are running and finished
+#[derive(Debug, Default)]
+struct ProgressState {
+    /// The lines of the running tasks, in the order they started
+    active: Vec<String>,
+    /// How many tasks finished
+    done: usize,
+    /// How many of the finished tasks failed
+    failed: usize,
+    /// How many lines are drawn below the cursor's line, to be cleared before drawing again
+    drawn: usize,
+}
+
+/// Progress of tasks running in parallel. On a terminal it is a live display of one line per
+/// running task and a summary line, redrawn as tasks start and finish. Otherwise each task is
+/// printed as a plain line when it starts, so logs stay readable.
+pub struct Progress {
+    /// How many tasks will run
+    total: usize,
+    /// Formats the summary line from the finished, failed, and total tasks
+    summary: fn(usize, usize, usize) -> String,
+    live: bool,
+    state: Mutex<ProgressState>,
+}
+
+impl Progress {
+    /// Start the progress of a number of tasks, with a function to format the summary line
+    pub fn new(total: usize, summary: fn(usize, usize, usize) -> String) -> Self {
+        Self {
+            total,
+            summary,
+            live: get().is_live(),
+            state: Mutex::new(ProgressState::default()),
+        }
+    }
+
+    /// Get the lines of the live display
+    fn lines(&self, state: &ProgressState) -> Vec<String> {
+        let output = get();
+        let summary = (self.summary)(state.done, state.failed, self.total);
+        state
+            .active
+            .iter()
+            .map(|x| format!("  {x}"))
+            .chain([summary])
+            .map(|x| match output.width {
+                Some(width) => output.truncate(&x, width.saturating_sub(1)),
+                None => x,
+            })
+            .collect()
+    }
+
+    /// Clear the live display and print lines above it, then draw it again
+    fn redraw(&self, state: &mut ProgressState, above: &[String]) {
+        let mut stdout = std::io::stdout().lock();
+        if state.drawn > 0 {
+            let _ = write!(stdout, "\x1b[{}F\x1b[J", state.drawn);
+        }
+        for line in above {
+            let _ = writeln!(stdout, "{line}");
+        }
+        let lines = self.lines(state);
+        for line in &lines {
+            let _ = writeln!(stdout, "{line}");
+        }
+        state.drawn = lines.len();
+        let _ = stdout.flush();
+    }
+
+    /// Show that a task started
+    pub fn start(&self, task: &str) {
+        let mut state = self.state.lock().expect("Progress lock is not poisoned");
+        if !self.live {
+            print_status(task);
+            return;
+        }
+        state.active.push(task.to_string());
+        self.redraw(&mut state, &[]);
+    }
+
+    /// Show that a task finished, printing lines about it, like why it failed
+    pub fn finish(&self, task: &str, ok: bool, lines: &[String]) {
+        let mut state = self.state.lock().expect("Progress lock is not poisoned");
+        state.done += 1;
+        if !ok {
+            state.failed += 1;
+        }
+        if !self.live {
+            lines.iter().for_each(|x| print_status(x));
+            return;
+        }
+        if let Some(index) = state.active.iter().position(|x| x == task) {
+            state.active.remove(index);
+        }
+        self.redraw(&mut state, lines);
+    }
+
+    /// Leave the summary line as the last line printed
+    pub fn end(self) {
+        let mut state = self.state.lock().expect("Progress lock is not poisoned");
+        if self.live {
+            state.active.clear();
+            self.redraw(&mut state, &[]);
+        }
+    }
+}
+
+/// The narrowest a column is shrunk to when fitting a table
+const MIN_COLUMN_WIDTH: usize = 6;
+
+impl Output {
+    /// Detect the output settings of the terminal. Tables are fit to the COLUMNS variable, or
+    /// else the terminal, and are not fit when the output is not a terminal.
+    pub fn detect(color: ColorChoice, ascii: bool) -> Self {
+        let is_terminal = std::io::stdout().is_terminal();
+        let color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal
+                    && std::env::var_os("NO_COLOR").is_none_or(|x| x.is_empty())
+                    && std::env::var_os("TERM").is_none_or(|x| x != "dumb")
+            }
+        };
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .or_else(|| {
+                is_terminal
+                    .then(terminal_size::terminal_size)
+                    .flatten()
+                    .map(|(w, _)| w.0 as usize)
+            });
+        Self {
+            color,
+            ascii,
+            width,
+            status_to_stderr: false,
+            is_terminal,
+        }
+    }
+
+    /// Check if progress is redrawn in place rather than printed line by line
+    pub fn is_live(&self) -> bool {
+        self.is_terminal && !self.status_to_stderr
+    }
+
+    /// Emphasize text with a style, if coloring
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        match self.color {
+            true => format!("{}{text}\x1b[0m", style.ansi()),
+            false => text.to_string(),
+        }
+    }
+
+    /// Replace the characters of text outside of ASCII, if ASCII only
+    pub fn text(&self, text: &str) -> String {
+        if !self.ascii || text.is_ascii() {
+            return text.to_string();
+        }
+        text.chars()
+            .map(|x| match x {
+                _ if x.is_ascii() => x.to_string(),
+                '\u{2018}' | '\u{2019}' => "'".into(),
+                '\u{201C}' | '\u{201D}' => "\"".into(),
+                '\u{2013}' | '\u{2014}' => "-".into(),
+                '\u{2026}' => "...".into(),
+                '\u{2192}' => "->".into(),
+                _ => "?".into(),
+            })
+            .collect()
+    }
+
+    /// Shorten text to a number of characters, marking that it was cut
+    fn truncate(&self, text: &str, width: usize) -> String {
+        if text.chars().count() <= width {
+            return text.to_string();
+        }
+        let marker = if self.ascii { "..." } else { "\u{2026}" };
+        let keep = width.saturating_sub(marker.chars().count());
+        text.chars().take(keep).chain(marker.chars()).collect()
+    }
+
+    /// Format rows of text as a table with aligned columns. When the table is wider than the
+    /// output, the widest columns are shrunk and their cells are cut short.
+    pub fn table(&self, header: &[&str], rows: &[Vec<String>]) -> String {
+        let header = header.iter().map(|x| self.text(x)).collect::<Vec<_>>();
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().map(|x| self.text(x)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut widths = header.iter().map(|x| x.chars().count()).collect::<Vec<_>>();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        if let Some(limit) = self.width {
+            let separators = 2 * widths.len().saturating_sub(1);
+            while widths.iter().sum::<usize>() + separators > limit {
+                let Some(widest) = widths
+                    .iter_mut()
+                    .filter(|x| **x > MIN_COLUMN_WIDTH)
+                    .max_by_key(|x| **x)
+                else {
+                    break;
+                };
+                *widest -= 1;
+            }
+        }
+        let format_row = |cells: &[String], style: Option<Style>| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", self.truncate(cell, *width)))
+                .collect::<Vec<_>>()
+                .join("  ");
+            let line = line.trim_end();
+            match style {
+                Some(x) => self.paint(x, line),
+                None => line.to_string(),
+            }
+        };
+        let mut result = format_row(&header, Some(Style::Heading));
+        result.push('\n');
+        for row in &rows {
+            result.push_str(&format_row(row, None));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let output = Output {
+            width: Some(24),
+            ascii: true,
+            ..Default::default()
+        };
+        let rows = vec![
+            vec!["Sodium".into(), "A very fast renderer".into()],
+            vec!["Lithium".into(), "Fast".into()],
+        ];
+        assert_eq!(
+            output.table(&["Project", "Description"], &rows),
+            "Project  Description\nSodium   A very fast ...\nLithium  Fast\n",
+            "Tables shall be fit into the width"
+        );
+        let output = Output::default();
+        assert_eq!(
+            output.table(&["Project", "Description"], &rows),
+            "Project  Description\nSodium   A very fast renderer\nLithium  Fast\n",
+            "Tables shall not be cut without a width"
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        let progress = Progress::new(3, |done, failed, total| {
+            format!("{done} of {total}, {failed} failed")
+        });
+        let state = ProgressState {
+            active: vec!["Downloading sodium.jar".into()],
+            done: 2,
+            failed: 1,
+            drawn: 0,
+        };
+        assert_eq!(
+            progress.lines(&state),
+            vec!["  Downloading sodium.jar", "2 of 3, 1 failed"],
+            "Progress shall show a line per running task and a summary line"
+        );
+        progress.start("Downloading sodium.jar");
+        progress.finish("Downloading sodium.jar", true, &[]);
+        assert_eq!(
+            progress
+                .state
+                .lock()
+                .expect("Progress lock is not poisoned")
+                .done,
+            1
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let output = Output {
+            ascii: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            output.text("Sodium \u{2014} \u{201C}fast\u{201D} \u{1F680}"),
+            "Sodium - \"fast\" ?",
+            "ASCII output shall replace other characters"
+        );
+        assert_eq!(
+            output.paint(Style::Error, "missing"),
+            "missing",
+            "Output shall not color unless told to"
+        );
+        let output = Output {
+            color: true,
+            ..Default::default()
+        };
+        assert_eq!(output.paint(Style::Good, "ok"), "\x1b[32mok\x1b[0m");
+    }
+}
@@ -0,0 +1,221 @@
+use std::path::Path;
+
+use crate::config::ConfigDefaults;
+use crate::error::{Error, Result};
+use crate::lockfile::Lockfile;
+use crate::types::{MinecraftVersion, ModLoader, Side};
+
+/// The format of plan files this version writes and reads, raised when their meaning changes
+pub const PLAN_FORMAT: u32 = 1;
+
+/// The outcome of resolving a config, written by `plan` so it can be reviewed and then applied by
+/// `apply`, possibly on another machine, without resolving again
+#[derive(Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Plan {
+    /// The format of the plan file
+    pub format: u32,
+    /// The game version the projects were resolved for
+    pub game_version: MinecraftVersion,
+    /// The loader the projects were resolved for
+    pub loader: ModLoader,
+    /// The side the projects were resolved for
+    pub target: Side,
+    /// What changes from the lockfile the plan was made against, for review
+    #[serde(default)]
+    pub changes: Vec<PlanChange>,
+    /// The resolved projects
+    pub lock: Lockfile,
+}
+
+/// A project whose version changes when a plan is applied
+#[derive(Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct PlanChange {
+    /// The slug of the project
+    pub slug: String,
+    /// The version number before, if the project was locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// The version number after, if the project is still resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+impl Plan {
+    /// Construct the plan of resolving a config to a lockfile, against the lockfile it replaces
+    pub fn new(defaults: &ConfigDefaults, old: &Lockfile, lock: Lockfile) -> Self {
+        let diff = old.diff(&lock);
+        let change = |slug: &str, from: Option<&str>, to: Option<&str>| PlanChange {
+            slug: slug.into(),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+        };
+        let mut changes = diff
+            .added
+            .iter()
+            .map(|x| change(&x.slug, None, Some(&x.version_number)))
+            .chain(
+                diff.removed
+                    .iter()
+                    .map(|x| change(&x.slug, Some(&x.version_number), None)),
+            )
+            .chain(diff.changed.iter().map(|(old, new)| {
+                change(
+                    &new.slug,
+                    Some(&old.version_number),
+                    Some(&new.version_number),
+                )
+            }))
+            .collect::<Vec<_>>();
+        changes.sort_by(|l, r| l.slug.cmp(&r.slug));
+        Self {
+            format: PLAN_FORMAT,
+            game_version: defaults.game_version,
+            loader: defaults.loader,
+            target: defaults.target,
+            changes,
+            lock,
+        }
+    }
+
+    /// Load a plan, failing if it was written in a format this version does not read
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let format = serde_json::from_str::<serde_json::Value>(&text)?
+            .get("format")
+            .and_then(|x| x.as_u64())
+            .unwrap_or_default();
+        if format != PLAN_FORMAT as u64 {
+            return Err(Error::UnsupportedPlan(format));
+        }
+        let plan = serde_json::from_str::<Self>(&text)?;
+        plan.lock.check()?;
+        Ok(plan)
+    }
+
+    /// Write the plan as JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Check that the plan was made for the game version, loader, and side of a config, so it is
+    /// not applied to an instance it does not fit
+    pub fn check(&self, defaults: &ConfigDefaults) -> Result<()> {
+        let planned = (self.game_version, self.loader, self.target);
+        let configured = (defaults.game_version, defaults.loader, defaults.target);
+        if planned != configured {
+            return Err(Error::PlanMismatch(format!(
+                "the plan is for {} {} on the {}, the config is for {} {} on the {}",
+                planned.1, planned.0, planned.2, configured.1, configured.0, configured.2
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::lockfile::LockedProject;
+
+    fn locked(slug: &str, version_number: &str) -> LockedProject {
+        LockedProject {
+            project_id: slug.to_uppercase(),
+            slug: slug.into(),
+            name: slug.into(),
+            version_id: format!("{slug}-{version_number}"),
+            version_number: version_number.into(),
+            loader: Some(ModLoader::Fabric),
+            license: None,
+            registry: "modrinth".into(),
+            reasons: vec!["configured".into()],
+            note: None,
+            icon_url: None,
+            files: vec![],
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let config = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            "#,
+        )
+        .expect("Config shall parse");
+        let old = Lockfile {
+            config_hash: None,
+            projects: vec![locked("lithium", "0.1"), locked("sodium", "0.5")],
+        };
+        let new = Lockfile {
+            config_hash: None,
+            projects: vec![locked("iris", "1.8"), locked("sodium", "0.6")],
+        };
+        let plan = Plan::new(&config.defaults, &old, new);
+        assert_eq!(
+            plan.changes,
+            vec![
+                PlanChange {
+                    slug: "iris".into(),
+                    from: None,
+                    to: Some("1.8".into())
+                },
+                PlanChange {
+                    slug: "lithium".into(),
+                    from: Some("0.1".into()),
+                    to: None
+                },
+                PlanChange {
+                    slug: "sodium".into(),
+                    from: Some("0.5".into()),
+                    to: Some("0.6".into())
+                },
+            ],
+            "Plans shall list what changes for review"
+        );
+        let dir = Path::new(".test/plan");
+        std::fs::create_dir_all(dir).expect("Failure to create test dir");
+        let path = dir.join("plan.json");
+        std::fs::write(&path, plan.to_json().expect("Plan shall serialize"))
+            .expect("Failure to write plan");
+        let loaded = Plan::load(&path).expect("Plan shall load");
+        assert_eq!(loaded, plan);
+        loaded
+            .check(&config.defaults)
+            .expect("Plan shall fit the config it was made from");
+        let other = Config::loads(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "quilt"
+
+            [projects]
+            "#,
+        )
+        .expect("Config shall parse");
+        assert!(
+            matches!(loaded.check(&other.defaults), Err(Error::PlanMismatch(_))),
+            "Plans shall not be applied for another loader"
+        );
+        std::fs::write(&path, r#"{"format": 99}"#).expect("Failure to write plan");
+        assert!(
+            matches!(Plan::load(&path), Err(Error::UnsupportedPlan(99))),
+            "Plans of an unknown format shall not be applied"
+        );
+        let mut unsafe_plan = Plan::new(&config.defaults, &old, Lockfile::default());
+        unsafe_plan.lock.projects.push(LockedProject {
+            version_id: "../data".into(),
+            ..locked("iris", "1.8")
+        });
+        std::fs::write(&path, unsafe_plan.to_json().expect("Plan shall serialize"))
+            .expect("Failure to write plan");
+        assert!(
+            matches!(Plan::load(&path), Err(Error::InvalidLockfile(_))),
+            "Plans with version ids that are not safe in paths shall not be applied"
+        );
+    }
+}
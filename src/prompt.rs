@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::Result;
+use crate::i18n::tr;
+use crate::output::{self, status};
+
+/// The name of the file in the data directory that answers to prompts are remembered in
+const ANSWERS_NAME: &str = "answers.json";
+
+/// An answer to a prompt, with the options it was chosen from
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct Answer {
+    options: Vec<String>,
+    chosen: Vec<String>,
+}
+
+/// The answers to prompts by a key naming the prompt, remembered so later runs do not ask again
+#[derive(Debug, Default)]
+struct Answers {
+    /// The file the answers are saved to, if they are remembered
+    path: Option<PathBuf>,
+    /// Ask every prompt again, replacing the remembered answers
+    reconfigure: bool,
+    answers: BTreeMap<String, Answer>,
+}
+
+impl Answers {
+    /// Load the answers saved in a file. A missing or unreadable file has no answers.
+    fn load(path: PathBuf, reconfigure: bool) -> Self {
+        let answers = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|x| serde_json::from_str(&x).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            reconfigure,
+            answers,
+        }
+    }
+
+    /// Get the options chosen last time for a prompt, if it had the same options
+    fn recall(&self, key: &str, options: &[String]) -> Option<&[String]> {
+        if self.reconfigure {
+            return None;
+        }
+        self.answers
+            .get(key)
+            .filter(|x| x.options == options)
+            .map(|x| x.chosen.as_slice())
+    }
+
+    /// Remember the options chosen for a prompt, and save the answers
+    fn record(&mut self, key: &str, options: &[String], chosen: Vec<String>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let answer = Answer {
+            options: options.to_vec(),
+            chosen,
+        };
+        self.answers.insert(key.to_string(), answer);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&self.answers)?)?;
+        Ok(())
+    }
+}
+
+/// The answers of this run
+static ANSWERS: Mutex<Answers> = Mutex::new(Answers {
+    path: None,
+    reconfigure: false,
+    answers: BTreeMap::new(),
+});
+
+/// Get the answers of this run
+fn answers() -> std::sync::MutexGuard<'static, Answers> {
+    ANSWERS.lock().expect("Answers lock is not poisoned")
+}
+
+/// Remember the answers to prompts in the data directory, so each prompt is only asked once.
+/// With `reconfigure`, every prompt is asked again and the new answers replace the old.
+pub fn remember_answers(data_dir: &Path, reconfigure: bool) {
+    *answers() = Answers::load(data_dir.join(ANSWERS_NAME), reconfigure);
+}
+
+/// Tell the user which remembered answer is used for a prompt
+fn print_recalled(question: &str, chosen: &[String]) {
+    let choice = match chosen.is_empty() {
+        true => "-".to_string(),
+        false => chosen.join(", "),
+    };
+    status!(
+        "{}",
+        tr!(
+            "remembered-answer",
+            question = question,
+            choice = output::get().text(&choice)
+        )
+    );
+}
+
+/// Ask the user to choose one of several options, and return the index of the choice. An answer
+/// remembered for the same `key` and options is used without asking. When stdin is not a
+/// terminal, the first option is chosen without asking.
+pub fn choose(key: &str, question: &str, options: &[String]) -> Result<usize> {
+    let recalled = answers()
+        .recall(key, options)
+        .and_then(|x| options.iter().position(|option| x.first() == Some(option)));
+    if let Some(index) = recalled {
+        print_recalled(question, &options[index..=index]);
+        return Ok(index);
+    }
+    status!("{question}");
+    for (index, option) in options.iter().enumerate() {
+        status!("  {}) {}", index + 1, output::get().text(option));
+    }
+    if !std::io::stdin().is_terminal() {
+        let choice = options.first().map_or("", |x| x);
+        status!("  {}", tr!("not-interactive", choice = choice));
+        return Ok(0);
+    }
+    let mut line = String::new();
+    loop {
+        print!("{}", tr!("choose-one", count = options.len()));
+        std::io::stdout().flush()?;
+        line.clear();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(0);
+        }
+        match parse_choice(&line, options.len()) {
+            Some(index) => {
+                answers().record(key, options, vec![options[index].clone()])?;
+                return Ok(index);
+            }
+            None => status!("  {}", tr!("invalid-choice", choice = line.trim())),
+        }
+    }
+}
+
+/// Ask the user to choose any of several options, and return whether each is chosen. The
+/// `defaults` are marked, and are kept if the answer is empty. An answer remembered for the same
+/// `key` and options is used without asking. When stdin is not a terminal, the defaults are
+/// chosen without asking.
+pub fn choose_many(
+    key: &str,
+    question: &str,
+    options: &[String],
+    defaults: &[bool],
+) -> Result<Vec<bool>> {
+    if let Some(chosen) = answers().recall(key, options) {
+        print_recalled(question, chosen);
+        return Ok(options.iter().map(|x| chosen.contains(x)).collect());
+    }
+    status!("{question}");
+    for (index, (option, chosen)) in options.iter().zip(defaults).enumerate() {
+        let mark = if *chosen { "x" } else { " " };
+        status!("  [{mark}] {}) {}", index + 1, output::get().text(option));
+    }
+    if !std::io::stdin().is_terminal() {
+        status!("  {}", tr!("not-interactive-defaults"));
+        return Ok(defaults.to_vec());
+    }
+    let mut line = String::new();
+    loop {
+        print!("{}", tr!("choose-any", count = options.len()));
+        std::io::stdout().flush()?;
+        line.clear();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(defaults.to_vec());
+        }
+        match parse_choices(&line, defaults) {
+            Some(chosen) => {
+                let names = options
+                    .iter()
+                    .zip(&chosen)
+                    .filter(|(_, chosen)| **chosen)
+                    .map(|(option, _)| option.clone())
+                    .collect();
+                answers().record(key, options, names)?;
+                return Ok(chosen);
+            }
+            None => status!("  {}", tr!("invalid-choice", choice = line.trim())),
+        }
+    }
+}
+
+/// Parse one-based choices separated by commas or spaces into whether each option is chosen. An
+/// empty line keeps the defaults, and `-` chooses none.
+fn parse_choices(line: &str, defaults: &[bool]) -> Option<Vec<bool>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Some(defaults.to_vec());
+    }
+    let mut chosen = vec![false; defaults.len()];
+    if line == "-" {
+        return Some(chosen);
+    }
+    for choice in line.split([',', ' ']).filter(|x| !x.is_empty()) {
+        match choice.parse::<usize>() {
+            Ok(x) if (1..=defaults.len()).contains(&x) => chosen[x - 1] = true,
+            _ => return None,
+        }
+    }
+    Some(chosen)
+}
+
+/// Parse a one-based choice into an index, or None if it is not one of `count` options
+fn parse_choice(line: &str, count: usize) -> Option<usize> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Some(0);
+    }
+    match line.parse::<usize>() {
+        Ok(x) if (1..=count).contains(&x) => Some(x - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(
+            parse_choice("2\n", 3),
+            Some(1),
+            "Choices shall be one-based"
+        );
+        assert_eq!(
+            parse_choice("\n", 3),
+            Some(0),
+            "An empty choice shall be the first"
+        );
+        assert_eq!(parse_choice("4", 3), None, "Choices shall be in range");
+        assert_eq!(parse_choice("zero", 3), None, "Choices shall be numbers");
+    }
+
+    #[test]
+    fn test_answers() {
+        let path = PathBuf::from(".test/prompt/answers.json");
+        let _ = std::fs::remove_file(&path);
+        let options = vec!["shaders".to_string(), "minimap".to_string()];
+        let mut answers = Answers::load(path.clone(), false);
+        assert_eq!(answers.recall("groups", &options), None);
+        answers
+            .record("groups", &options, vec!["minimap".into()])
+            .expect("Answers shall be saved");
+        let answers = Answers::load(path.clone(), false);
+        assert_eq!(
+            answers.recall("groups", &options),
+            Some(["minimap".to_string()].as_slice()),
+            "Answers shall be remembered across runs"
+        );
+        assert_eq!(
+            answers.recall("groups", &options[..1]),
+            None,
+            "Answers to a prompt with other options shall not be used"
+        );
+        assert_eq!(
+            Answers::load(path, true).recall("groups", &options),
+            None,
+            "Answers shall not be used when reconfiguring"
+        );
+    }
+
+    #[test]
+    fn test_parse_choices() {
+        let defaults = [true, false, false];
+        assert_eq!(
+            parse_choices("2, 3\n", &defaults),
+            Some(vec![false, true, true]),
+            "Choices shall be one-based"
+        );
+        assert_eq!(
+            parse_choices("\n", &defaults),
+            Some(defaults.to_vec()),
+            "An empty choice shall keep the defaults"
+        );
+        assert_eq!(parse_choices("-", &defaults), Some(vec![false; 3]));
+        assert_eq!(
+            parse_choices("1 4", &defaults),
+            None,
+            "Choices shall be in range"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::path::Path;
+
+use crate::backend::Backend;
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::tags;
+use crate::types::{GameVersion, LoaderInfo, MinecraftVersion, ModLoader, ModProject, ModVersion};
+
+/// A registry in a file, in the format of the API
+#[derive(serde::Deserialize)]
+struct RegistryFile {
+    /// The name of the registry. Defaults to `modrinth`, which it stands in for.
+    #[serde(default = "default_name")]
+    name: String,
+    /// The official list of game versions
+    game_versions: Vec<GameVersion>,
+    /// The mod loaders and the project types they can load. Defaults to the builtin table.
+    #[serde(default)]
+    loaders: Vec<LoaderInfo>,
+    projects: Vec<serde_json::Value>,
+    versions: Vec<serde_json::Value>,
+}
+
+fn default_name() -> String {
+    "modrinth".into()
+}
+
+/// A registry of projects and versions loaded from a file, so that projects can be resolved
+/// without the network. Used to test the solver and to reproduce resolution bugs.
+#[derive(Debug)]
+pub struct FixtureRegistry {
+    name: String,
+    game_versions: Vec<GameVersion>,
+    loaders: Vec<LoaderInfo>,
+    projects: Vec<ModProject>,
+    /// Each version and the game versions it supports
+    versions: Vec<(Vec<MinecraftVersion>, ModVersion)>,
+}
+
+impl FixtureRegistry {
+    /// Load a registry from JSON text
+    pub fn loads(text: &str) -> Result<Self> {
+        let file = serde_json::from_str::<RegistryFile>(text)?;
+        Ok(Self {
+            name: file.name,
+            game_versions: file.game_versions,
+            loaders: file.loaders,
+            projects: file
+                .projects
+                .into_iter()
+                .map(labrinth::parse_project)
+                .collect::<Result<_>>()?,
+            versions: file
+                .versions
+                .into_iter()
+                .map(labrinth::parse_version)
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Load a registry from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::loads(std::fs::read_to_string(path)?.as_str())
+    }
+}
+
+impl Backend for FixtureRegistry {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_project(&self, project: &str) -> Result<ModProject> {
+        self.projects
+            .iter()
+            .find(|x| x.project_id.as_str() == project || x.slug.as_str() == project)
+            .cloned()
+            .ok_or_else(|| Error::ProjectNotFound(project.to_string()))
+    }
+
+    fn get_version(&self, version: &str) -> Result<ModVersion> {
+        self.versions
+            .iter()
+            .map(|(_, x)| x)
+            .find(|x| x.version_id.as_str() == version)
+            .cloned()
+            .ok_or_else(|| Error::VersionNotFound {
+                project: version.to_string(),
+            })
+    }
+
+    /// Get the versions of a project for any of the game versions and loaders, newest first like
+    /// the API
+    fn get_project_versions(
+        &self,
+        project: &str,
+        game_versions: &[MinecraftVersion],
+        loaders: &[ModLoader],
+    ) -> Result<Vec<ModVersion>> {
+        let project_id = self.get_project(project)?.project_id;
+        let mut result = self
+            .versions
+            .iter()
+            .filter(|(supported, x)| {
+                x.project_id == project_id
+                    && supported.iter().any(|x| game_versions.contains(x))
+                    && x.loaders.iter().any(|x| loaders.contains(x))
+            })
+            .map(|(_, x)| x.clone())
+            .collect::<Vec<_>>();
+        result.sort_by_key(|x| std::cmp::Reverse(x.date_published));
+        Ok(result)
+    }
+
+    fn game_versions(&self) -> Option<tags::GameVersions> {
+        Some(tags::GameVersions::new(self.game_versions.clone()))
+    }
+
+    fn loaders(&self) -> Option<tags::LoaderTable> {
+        Some(match self.loaders.is_empty() {
+            true => tags::LoaderTable::builtin(),
+            false => tags::LoaderTable::new(self.loaders.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::lockfile::Lockfile;
+    use crate::solver::ModSolver;
+    use std::sync::Arc;
+
+    /// The registry bundled for tests
+    const REGISTRY: &str = "fixtures/registry.json";
+
+    /// The configs resolved against the bundled registry, each next to its expected lockfile
+    const GOLDEN_DIR: &str = "fixtures/golden";
+
+    #[test]
+    fn test_fixture_registry() {
+        let registry = FixtureRegistry::load(Path::new(REGISTRY)).expect("Registry shall load");
+        assert_eq!(registry.name(), "modrinth");
+        let versions = registry
+            .get_project_versions("sodium", &["1.21.5".into()], &[ModLoader::Fabric])
+            .expect("Versions shall be listed");
+        assert_eq!(
+            versions
+                .iter()
+                .map(|x| x.version_number.as_str())
+                .collect::<Vec<_>>(),
+            vec!["mc1.21.5-0.6.13-fabric"],
+            "Only versions for the game versions and loaders shall be listed"
+        );
+        let versions = registry
+            .get_project_versions(
+                "P7dR8mSH",
+                &["1.21.4".into(), "1.21.5".into()],
+                &[ModLoader::Fabric],
+            )
+            .expect("Versions shall be listed by project id");
+        assert_eq!(
+            versions[0].version_number, "0.120.0+1.21.5",
+            "Versions shall be listed newest first"
+        );
+        assert!(matches!(
+            registry.get_project("optifine"),
+            Err(Error::ProjectNotFound(_))
+        ));
+        assert!(
+            registry
+                .game_versions()
+                .is_some_and(|x| x.contains(&"1.21".into())),
+            "The registry shall have its own game versions"
+        );
+    }
+
+    /// Resolve each config in the golden directory against the bundled registry and compare the
+    /// lockfile with the one next to it. Set MCMOD_BLESS to write the lockfiles instead, after a
+    /// change to the solver that is meant to change them.
+    #[test]
+    fn test_golden_resolutions() {
+        let registry =
+            Arc::new(FixtureRegistry::load(Path::new(REGISTRY)).expect("Registry shall load"));
+        let mut configs = std::fs::read_dir(GOLDEN_DIR)
+            .expect("Golden dir shall be listed")
+            .map(|x| x.expect("Golden dir entry shall be read").path())
+            .filter(|x| x.extension().is_some_and(|x| x == "toml"))
+            .collect::<Vec<_>>();
+        configs.sort();
+        assert!(!configs.is_empty(), "There shall be golden configs");
+        let bless = std::env::var_os("MCMOD_BLESS").is_some();
+        for path in configs {
+            let mut config = Config::loads(
+                std::fs::read_to_string(&path)
+                    .expect("Golden config shall be read")
+                    .as_str(),
+            )
+            .expect("Golden config shall parse");
+            config.paths.data = ".test/golden".into();
+            config.registry_fixture = Some(registry.clone());
+            let mod_db = ModSolver::new(&config)
+                .solve()
+                .unwrap_or_else(|e| panic!("{} shall resolve: {e}", path.display()))
+                .mod_db;
+            let actual = Lockfile::from_db(&mod_db)
+                .dumps()
+                .expect("Lockfile shall format");
+            let lock_path = path.with_extension("lock");
+            if bless {
+                std::fs::write(&lock_path, actual).expect("Golden lockfile shall be written");
+                continue;
+            }
+            let expected = std::fs::read_to_string(&lock_path).unwrap_or_default();
+            assert_eq!(
+                actual,
+                expected,
+                "Resolving {} shall match its golden lockfile. Run the tests with MCMOD_BLESS=1 \
+                 if the change is intended.",
+                path.display()
+            );
+        }
+    }
+}
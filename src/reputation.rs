@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::verify::FileHashes;
+
+/// The service used for `security.reputation = "modrinth"`, which knows every file published on
+/// Modrinth
+const MODRINTH_SERVICE: &str = "https://api.modrinth.com/v2/version_file/{sha512}?algorithm=sha512";
+
+/// The hashes of files known to be safe, from allowlists and a reputation service
+#[derive(Debug, Default)]
+pub struct Reputation {
+    /// The hashes in the allowlists, in lowercase
+    known: HashSet<String>,
+    /// The url of the service, with `{sha1}` or `{sha512}` in place of the hash
+    service: Option<String>,
+}
+
+/// Read the hashes of an allowlist, one per line. Only the first word of a line is read, so the
+/// output of `sha512sum` can be used as is. Blank lines and lines starting with `#` are skipped.
+pub fn parse_allowlist(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.lines()
+        .filter_map(|x| x.split_whitespace().next())
+        .filter(|x| !x.starts_with('#'))
+        .map(str::to_ascii_lowercase)
+}
+
+impl Reputation {
+    /// Load the allowlists at paths, and the reputation service by url or `modrinth`
+    pub fn load(allowlists: &[String], service: Option<&str>) -> Result<Self> {
+        let mut known = HashSet::new();
+        for path in allowlists {
+            known.extend(parse_allowlist(&std::fs::read_to_string(path)?));
+        }
+        let service = service.map(|x| match x {
+            "modrinth" => MODRINTH_SERVICE.to_string(),
+            x => x.to_string(),
+        });
+        Ok(Self { known, service })
+    }
+
+    /// Check if a file is known, by an allowlist or else by asking the service. The service knows
+    /// a file if it answers its url successfully, and does not if it answers 404.
+    pub fn is_known(&self, client: &labrinth::Client, hashes: &FileHashes) -> Result<bool> {
+        if self.known.contains(&hashes.sha1) || self.known.contains(&hashes.sha512) {
+            return Ok(true);
+        }
+        let Some(service) = &self.service else {
+            return Ok(false);
+        };
+        let url = service
+            .replace("{sha1}", &hashes.sha1)
+            .replace("{sha512}", &hashes.sha512);
+        match client.download_file(&url) {
+            Ok(_) => Ok(true),
+            Err(Error::Request(x)) if x.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{FixtureMode, Fixtures};
+    use crate::verify::hash_bytes;
+
+    #[test]
+    fn test_reputation() {
+        let dir = std::path::Path::new(".test/reputation");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).expect("Failure to create test dir");
+        let allowed = hash_bytes(b"allowed");
+        let listed = hash_bytes(b"listed");
+        let unknown = hash_bytes(b"unknown");
+        let allowlist = dir.join("allowlist.txt");
+        std::fs::write(
+            &allowlist,
+            format!(
+                "# Reviewed by the admins\n{}  mods/allowed.jar\n\n",
+                allowed.sha512.to_uppercase()
+            ),
+        )
+        .expect("Failure to write allowlist");
+        let url = format!("https://reputation.example/{}", listed.sha1);
+        Fixtures::new(dir.join("fixtures"), FixtureMode::Record)
+            .get(&url, || Ok(b"{}".to_vec()))
+            .expect("Fixtures shall record the answer");
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(
+            dir.join("fixtures"),
+            FixtureMode::Replay,
+        )));
+        let reputation = Reputation::load(
+            &[allowlist.display().to_string()],
+            Some("https://reputation.example/{sha1}"),
+        )
+        .expect("Allowlists shall load");
+        assert!(
+            reputation
+                .is_known(&client, &allowed)
+                .expect("Allowed files shall be checked"),
+            "Files in an allowlist shall be known without asking the service"
+        );
+        assert!(
+            reputation
+                .is_known(&client, &listed)
+                .expect("Listed files shall be checked"),
+            "Files the service answers for shall be known"
+        );
+        let offline = Reputation::load(&[allowlist.display().to_string()], None)
+            .expect("Allowlists shall load");
+        assert!(
+            !offline
+                .is_known(&client, &unknown)
+                .expect("Unknown files shall be checked"),
+            "Files in no allowlist shall be unknown without a service"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::lockfile::Lockfile;
+
+/// The actions of a run that downloads or installs files, saved before they start so that an
+/// interrupted run can be resumed without resolving again. Files that finished downloading are
+/// in the cache, so only the rest are downloaded when resuming.
+#[derive(Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct RunPlan {
+    /// Whether the files are installed, or only downloaded
+    pub install: bool,
+    /// Whether the files of the previous install that are no longer resolved are removed
+    #[serde(default)]
+    pub prune: bool,
+    /// The directories the files are installed into
+    pub instances: Vec<PathBuf>,
+    /// The resolved projects
+    pub lock: Lockfile,
+}
+
+/// Get the path of the plan of a config file
+pub fn plan_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("resume.json")
+}
+
+impl RunPlan {
+    /// Load the plan saved next to a config, failing if there is none to resume
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(Error::NothingToResume(path.to_path_buf()));
+        }
+        let plan = serde_json::from_str::<Self>(&std::fs::read_to_string(path)?)?;
+        plan.lock.check()?;
+        Ok(plan)
+    }
+
+    /// Save the plan
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Remove the plan of a run that finished
+    pub fn finish(path: &Path) -> Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_plan() {
+        let dir = PathBuf::from(".test/resume");
+        std::fs::create_dir_all(&dir).expect("Failure to create test dir");
+        let path = plan_path(&dir.join("mcmod.toml"));
+        assert_eq!(path, dir.join("mcmod.resume.json"));
+        RunPlan::finish(&path).expect("Finishing without a plan shall not fail");
+        assert!(
+            matches!(RunPlan::load(&path), Err(Error::NothingToResume(_))),
+            "Resuming without a plan shall fail"
+        );
+        let plan = RunPlan {
+            install: true,
+            prune: true,
+            instances: vec![dir.join("minecraft")],
+            lock: Lockfile::default(),
+        };
+        plan.save(&path).expect("Failure to save plan");
+        assert_eq!(RunPlan::load(&path).expect("Failure to load plan"), plan);
+        RunPlan::finish(&path).expect("Failure to finish plan");
+        assert!(!path.exists(), "Finishing shall remove the plan");
+    }
+}
@@ -0,0 +1,167 @@
+use std::io::{BufRead, Write};
+use std::panic::AssertUnwindSafe;
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// The JSON-RPC error code of a line that is not JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON-RPC error code of JSON that is not a request
+pub const INVALID_REQUEST: i64 = -32600;
+/// The JSON-RPC error code of an unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code of missing or mistyped params
+pub const INVALID_PARAMS: i64 = -32602;
+/// The error code of an operation that failed
+pub const OPERATION_FAILED: i64 = -32000;
+
+/// An error answered to a request
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(value: Error) -> Self {
+        Self::new(OPERATION_FAILED, value.to_string())
+    }
+}
+
+/// A JSON-RPC request. Requests without an id are notifications, which are not answered.
+#[derive(Debug, serde::Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Get an optional named param of a request
+pub fn param<T>(params: &Value, name: &str) -> std::result::Result<Option<T>, RpcError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(x) => serde_json::from_value(x.clone())
+            .map(Some)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("{name}: {e}"))),
+    }
+}
+
+/// Describe why a request panicked
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|x| x.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "The operation panicked".into())
+}
+
+/// Answer JSON-RPC 2.0 requests read one per line, writing one response per line, until the input
+/// ends or a `shutdown` request. A request that fails or panics is answered with an error and the
+/// next request is read.
+pub fn serve<R, W, F>(input: R, mut output: W, mut handle: F) -> Result<()>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(&str, Value) -> std::result::Result<Value, RpcError>,
+{
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = serde_json::from_str::<Value>(&line)
+            .map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))
+            .and_then(|x| {
+                serde_json::from_value::<Request>(x)
+                    .map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))
+            });
+        let (id, result, stop) = match request {
+            Err(e) => (Some(Value::Null), Err(e), false),
+            Ok(request) if request.method == "shutdown" => (request.id, Ok(Value::Null), true),
+            Ok(request) => {
+                let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    handle(&request.method, request.params)
+                }))
+                .unwrap_or_else(|x| Err(RpcError::new(OPERATION_FAILED, panic_message(x))));
+                (request.id, result, false)
+            }
+        };
+        if let Some(id) = id {
+            let response = match result {
+                Ok(x) => serde_json::json!({"jsonrpc": "2.0", "id": id, "result": x}),
+                Err(e) => serde_json::json!({"jsonrpc": "2.0", "id": id, "error": e}),
+            };
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+        if stop {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let input = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "echo", "params": {"text": "hi"}}"#,
+            r#"{"jsonrpc": "2.0", "method": "echo", "params": {"text": "notified"}}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "missing"}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "panic"}"#,
+            "not json",
+            r#"{"jsonrpc": "2.0", "id": 4, "method": "shutdown"}"#,
+            r#"{"jsonrpc": "2.0", "id": 5, "method": "echo"}"#,
+        ]
+        .join("\n");
+        let mut output = Vec::<u8>::new();
+        let mut handled = Vec::<String>::new();
+        serve(input.as_bytes(), &mut output, |method, params| {
+            handled.push(method.to_string());
+            match method {
+                "echo" => Ok(param::<String>(&params, "text")?.into()),
+                "panic" => panic!("Broken"),
+                _ => Err(RpcError::new(METHOD_NOT_FOUND, method)),
+            }
+        })
+        .expect("Serving shall not fail");
+        let responses = String::from_utf8(output)
+            .expect("Responses shall be UTF-8")
+            .lines()
+            .map(|x| serde_json::from_str::<Value>(x).expect("Responses shall be JSON"))
+            .collect::<Vec<_>>();
+        assert_eq!(responses.len(), 5, "Notifications shall not be answered");
+        assert_eq!(responses[0]["result"], "hi");
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(
+            responses[2]["error"]["message"], "Broken",
+            "A request that panics shall be answered with an error"
+        );
+        assert_eq!(responses[3]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[3]["id"], Value::Null);
+        assert_eq!(responses[4]["id"], 4);
+        assert_eq!(
+            handled,
+            vec!["echo", "echo", "missing", "panic"],
+            "Serving shall stop after shutdown"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Where the Minecraft EULA can be read
+pub const EULA_URL: &str = "https://aka.ms/MinecraftEULA";
+
+/// Check if the EULA was accepted in a server directory
+pub fn is_eula_accepted(server_dir: &Path) -> bool {
+    std::fs::read_to_string(server_dir.join("eula.txt")).is_ok_and(|text| {
+        text.lines()
+            .filter_map(|x| x.split_once('='))
+            .any(|(key, value)| key.trim() == "eula" && value.trim() == "true")
+    })
+}
+
+/// Write the `eula.txt` that accepts the EULA, as the server writes it
+pub fn accept_eula(server_dir: &Path) -> Result<()> {
+    let text = format!(
+        "#By changing the setting below to TRUE you are indicating your agreement to our EULA \
+        ({EULA_URL}).\n#{}\neula=true\n",
+        chrono::Local::now().format("%a %b %d %H:%M:%S %Z %Y")
+    );
+    std::fs::write(server_dir.join("eula.txt"), text)?;
+    Ok(())
+}
+
+/// Replace `{name}` in a value with the variables, like `{game_version}`
+pub fn expand(value: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(value.to_string(), |value, (name, x)| {
+        value.replace(&format!("{{{name}}}"), x)
+    })
+}
+
+/// Set properties in the text of a server.properties, replacing the values of keys it has and
+/// adding the others at the end. Comments and other keys are kept.
+pub fn render_properties(text: &str, properties: &BTreeMap<String, String>) -> String {
+    let mut remaining = properties.clone();
+    let mut lines = text
+        .lines()
+        .map(|line| {
+            let key = line
+                .split_once('=')
+                .map(|(key, _)| key.trim())
+                .filter(|_| !line.trim_start().starts_with(['#', '!']));
+            match key.and_then(|x| remaining.remove_entry(x)) {
+                Some((key, value)) => format!("{key}={value}"),
+                None => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+    lines.extend(
+        remaining
+            .iter()
+            .map(|(key, value)| format!("{key}={value}")),
+    );
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Set properties in the server.properties of a server directory, creating it if needed. Returns
+/// whether the file changed.
+pub fn write_properties(server_dir: &Path, properties: &BTreeMap<String, String>) -> Result<bool> {
+    let path = server_dir.join("server.properties");
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let rendered = render_properties(&text, properties);
+    if rendered == text {
+        return Ok(false);
+    }
+    std::fs::write(&path, rendered)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_properties() {
+        let properties = BTreeMap::from([
+            ("motd".to_string(), "Modded fabric 1.21.1".to_string()),
+            ("level-name".to_string(), "survival".to_string()),
+        ]);
+        assert_eq!(
+            render_properties(
+                "#Minecraft server properties\nmotd=A Minecraft Server\npvp=true\n",
+                &properties
+            ),
+            "#Minecraft server properties\nmotd=Modded fabric 1.21.1\npvp=true\nlevel-name=survival\n",
+            "Properties shall be replaced in place and added at the end"
+        );
+        assert_eq!(
+            expand(
+                "Modded {loader} {game_version}",
+                &[
+                    ("game_version", "1.21.1".into()),
+                    ("loader", "fabric".into())
+                ]
+            ),
+            "Modded fabric 1.21.1"
+        );
+    }
+
+    #[test]
+    fn test_accept_eula() {
+        let dir = Path::new(".test/server_eula");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).expect("Failure to create test path");
+        assert!(!is_eula_accepted(dir));
+        std::fs::write(dir.join("eula.txt"), "eula=false\n").expect("Failure to write eula.txt");
+        assert!(
+            !is_eula_accepted(dir),
+            "The EULA shall not be accepted by the server's default eula.txt"
+        );
+        accept_eula(dir).expect("The EULA shall be accepted");
+        assert!(is_eula_accepted(dir));
+        let properties = BTreeMap::from([("motd".to_string(), "Hi".to_string())]);
+        assert!(write_properties(dir, &properties).expect("Properties shall be written"));
+        assert!(
+            !write_properties(dir, &properties).expect("Properties shall be written"),
+            "Properties that did not change shall not be written again"
+        );
+    }
+}
@@ -1,75 +1,392 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::alternatives;
+use crate::backend::{Backend, Backends};
 use crate::config;
-use crate::error::{Error, Result};
+use crate::error::{Error, Failure, Result};
+use crate::i18n::tr;
+use crate::interrupt;
 use crate::labrinth;
-use crate::types::{self, ModLink, ModLoader, ProjectId, ProjectSlug, VersionId};
+use crate::lockfile::{Lockfile, describe_reason};
+use crate::output::{self, status};
+use crate::tags;
+use crate::types::{self, ModLink, ModLoader, ProjectId, ProjectSlug, Reason, Side, VersionId};
+
+/// How many older versions of a configured project are tried when the dependencies of its latest
+/// version conflict with those of the other projects
+const MAX_OLDER_VERSIONS: usize = 5;
+
+/// A specific version of a project that a collected version requires
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Requirement {
+    /// The version that requires it, or the version itself if the config pins it
+    dependent: VersionId,
+    /// The required version
+    version: VersionId,
+}
+
+/// The projects and versions a config was solved to
+pub struct Solution {
+    /// The collected projects and versions
+    pub mod_db: types::ModDB,
+    /// The optional projects that were skipped, with the reason
+    pub skipped: Vec<(ProjectSlug, Error)>,
+}
 
 /// Collects all mods and their dependencies according to the config
 pub struct ModSolver<'a> {
     client: labrinth::Client,
+    backends: Backends,
     mod_config: &'a config::Config,
     mod_db: types::ModDB,
+    game_versions: Option<tags::GameVersions>,
+    loaders: Option<tags::LoaderTable>,
+    locked: Lockfile,
+    /// The specific versions of each project that collected versions require
+    requirements: HashMap<ProjectId, Vec<Requirement>>,
+    /// Whether the last failure was versions requiring different versions of a project, which
+    /// an older version of a configured project may avoid
+    conflicted: bool,
+    /// The versions fetched by id, so a dependency is only fetched once
+    fetched: HashMap<VersionId, types::ModVersion>,
 }
 
 impl<'a> ModSolver<'a> {
     /// Construct a new mod solver for a config
     pub fn new(mod_config: &'a config::Config) -> Self {
         ModSolver {
-            client: labrinth::Client::new(),
+            client: mod_config.client(),
+            backends: Backends::builtin(mod_config),
             mod_config,
             mod_db: types::ModDB::default(),
+            game_versions: None,
+            loaders: None,
+            locked: Lockfile::default(),
+            requirements: HashMap::new(),
+            conflicted: false,
+            fetched: HashMap::new(),
+        }
+    }
+
+    /// Set the lockfile that frozen projects keep their versions from
+    pub fn with_locked(mut self, locked: Lockfile) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Register a backend, replacing a built in backend with the same name
+    pub fn with_backend(mut self, backend: Arc<dyn Backend>) -> Self {
+        self.backends.register(backend);
+        self
+    }
+
+    /// Get the backend that projects are resolved from, chosen by `defaults.registry`
+    fn backend(&self) -> Result<Arc<dyn Backend>> {
+        self.backends.get(&self.mod_config.defaults.registry)
+    }
+
+    /// Get a version by its id from the backend, reusing the version if it was fetched before
+    fn fetch_version(&mut self, version_id: &VersionId) -> Result<types::ModVersion> {
+        if let Some(x) = self.fetched.get(version_id) {
+            return Ok(x.clone());
         }
+        let version = self.backend()?.get_version(version_id.as_str())?;
+        self.fetched.insert(version_id.clone(), version.clone());
+        Ok(version)
     }
 
-    /// Solve all the dependencies of the config, consuming self
-    pub fn solve(mut self) -> Result<types::ModDB> {
+    /// Add a version found in the backend to the database
+    fn add_version(&mut self, version: types::ModVersion) {
+        let version_id = version.version_id.clone();
+        self.mod_db.add_version(version);
+        self.mod_db.set_registry(
+            &version_id,
+            self.mod_config.defaults.registry.as_str().into(),
+        );
+    }
+
+    /// Get the table of mod loaders, loading it on first use
+    fn loaders(&mut self) -> &tags::LoaderTable {
+        if self.loaders.is_none() {
+            let loaders = self
+                .backend()
+                .ok()
+                .and_then(|x| x.loaders())
+                .unwrap_or_else(|| {
+                    tags::LoaderTable::load(&self.client, &self.mod_config.paths.data)
+                });
+            self.loaders = Some(loaders);
+        }
+        self.loaders.as_ref().expect("Loaders were just loaded")
+    }
+
+    /// Get the official list of game versions, loading it on first use
+    fn game_versions(&mut self) -> Result<&tags::GameVersions> {
+        if self.game_versions.is_none() {
+            let game_versions = match self.backend().ok().and_then(|x| x.game_versions()) {
+                Some(x) => x,
+                None => tags::GameVersions::load(&self.client, &self.mod_config.paths.data)?,
+            };
+            self.game_versions = Some(game_versions);
+        }
+        Ok(self
+            .game_versions
+            .as_ref()
+            .expect("Game versions were just loaded"))
+    }
+
+    /// Solve all the dependencies of the config, consuming self. Optional projects that can not
+    /// be resolved are skipped and returned with the reason.
+    pub fn solve(mut self) -> Result<Solution> {
         self.collect_required_projects()?;
-        self.collect_optional_projects();
-        Ok(self.mod_db)
+        let mut skipped = self.collect_optional_projects()?;
+        for (name, error) in self.collect_gated_projects()? {
+            if let Error::RequirementsUnmet { .. } = error {
+                status!("{}", tr!("skipping-requires", name = name.as_str()));
+            }
+            skipped.push((name, error));
+        }
+        Ok(Solution {
+            mod_db: self.mod_db,
+            skipped,
+        })
+    }
+
+    /// List the projects a config project requires that have not been resolved
+    pub fn unmet_requirements(&self, project: &config::ConfigProject) -> Vec<ProjectSlug> {
+        project
+            .requires
+            .iter()
+            .filter(|slug| {
+                !self
+                    .mod_db
+                    .get_project_by_slug(slug)
+                    .is_some_and(|x| self.mod_db.has_preferred(&x.project_id))
+            })
+            .cloned()
+            .collect()
     }
 
-    /// Collect all the required versions from the config
-    fn collect_required_projects(&mut self) -> Result<Vec<VersionId>> {
-        let mut versions = Vec::<VersionId>::new();
+    /// Collect the projects that require other projects, once those are resolved. Requirements
+    /// may be chained, so projects are collected until no more are met. Optional projects that
+    /// can not be resolved, or whose requirements are never met, are skipped and returned with
+    /// the reason. A configured project whose requirements are never met fails.
+    pub fn collect_gated_projects(&mut self) -> Result<Vec<(ProjectSlug, Error)>> {
+        let mut pending = self
+            .mod_config
+            .projects()
+            .into_iter()
+            .map(|x| (x, Reason::Configured))
+            .chain(
+                self.mod_config
+                    .optional_projects()
+                    .into_iter()
+                    .map(|x| (x, Reason::Optional)),
+            )
+            .filter(|(x, _)| !x.requires.is_empty())
+            .collect::<Vec<_>>();
+        let mut skipped = Vec::new();
+        loop {
+            let (ready, waiting) = pending
+                .into_iter()
+                .partition::<Vec<_>, _>(|(x, _)| self.unmet_requirements(x).is_empty());
+            pending = waiting;
+            if ready.is_empty() {
+                break;
+            }
+            for (project, reason) in ready {
+                match self.collect_project_and_dependencies(&project, reason.clone()) {
+                    Err(e) if reason == Reason::Optional => skipped.push((project.name, e)),
+                    x => {
+                        x?;
+                    }
+                }
+            }
+        }
+        for (project, reason) in pending {
+            let error = Error::RequirementsUnmet {
+                project: project.name.to_string(),
+                requires: self
+                    .unmet_requirements(&project)
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect(),
+            };
+            if reason != Reason::Optional {
+                return Err(error);
+            }
+            skipped.push((project.name, error));
+        }
+        Ok(skipped)
+    }
+
+    /// Collect all the required projects from the config, except those that require other
+    /// projects
+    fn collect_required_projects(&mut self) -> Result<()> {
         for project in self.mod_config.projects() {
-            let mut collected = self.collect_project_and_dependencies(&project)?;
-            versions.append(&mut collected);
+            if !project.requires.is_empty() {
+                continue;
+            }
+            interrupt::check()?;
+            status!("{}", tr!("collecting", name = project.name.as_str()));
+            self.collect_project_and_dependencies(&project, Reason::Configured)
+                .inspect(|x| status!("  {}", tr!("found-projects", count = x.len())))
+                .inspect_err(|e| {
+                    let error = tr!("error", error = e.to_string());
+                    status!("  {}", output::get().paint(output::Style::Error, &error));
+                })?;
         }
-        Ok(versions)
+        Ok(())
     }
 
-    /// Collect all the optional versions from the config
-    fn collect_optional_projects(&mut self) -> Vec<VersionId> {
-        let mut versions = Vec::<VersionId>::new();
+    /// Collect all the optional projects from the config, except those that require other
+    /// projects. Projects that can not be resolved are skipped and returned with the reason.
+    fn collect_optional_projects(&mut self) -> Result<Vec<(ProjectSlug, Error)>> {
+        let mut skipped = Vec::new();
         for project in self.mod_config.optional_projects() {
-            let mut collected = match self.collect_project_and_dependencies(&project) {
-                Ok(x) => x,
-                Err(_) => continue,
-            };
-            versions.append(&mut collected);
+            if !project.requires.is_empty() {
+                continue;
+            }
+            interrupt::check()?;
+            status!(
+                "{}",
+                tr!("collecting-optional", name = project.name.as_str())
+            );
+            match self.collect_project_and_dependencies(&project, Reason::Optional) {
+                Ok(x) => status!("  {}", tr!("found-projects", count = x.len())),
+                Err(e) => {
+                    status!("  {}", tr!("skipping-unresolved"));
+                    skipped.push((project.name, e));
+                }
+            }
         }
-        versions
+        Ok(skipped)
     }
 
-    /// Collect a config project and its dependencies
+    /// Collect a config project and its dependencies. Nothing is added to the database unless
+    /// the project and all of its dependencies are collected. If the dependencies of the latest
+    /// version require other versions of a project than those already collected, older versions
+    /// of the project are tried, so the whole pack agrees on one version of each shared project.
     pub fn collect_project_and_dependencies(
         &mut self,
         project: &config::ConfigProject,
+        reason: Reason,
     ) -> Result<Vec<VersionId>> {
-        let base_id = self.collect_config_project(project)?;
-        let mut deps = self.collect_dependencies(&base_id).inspect_err(|_| {
-            self.mod_db
-                .remove(&types::ModLink::VersionId(base_id.clone()))
-        })?;
+        let project = &self.typed_project(project)?;
+        self.conflicted = false;
+        let error = match self
+            .transaction(|solver| solver.collect_with_dependencies(project, &reason, None))
+        {
+            Ok(x) => return Ok(x),
+            Err(e) => e,
+        };
+        if !std::mem::take(&mut self.conflicted)
+            || project.frozen
+            || project.version_id.is_some()
+            || self.mod_db.get_project_by_slug(&project.name).is_some()
+        {
+            return Err(error);
+        }
+        let Ok((candidates, loader)) = self.find_project_versions(project) else {
+            return Err(error);
+        };
+        let mut tried = 0;
+        for version in candidates.into_iter().skip(1).take(MAX_OLDER_VERSIONS) {
+            tried += 1;
+            status!(
+                "  {}",
+                tr!("trying-older", version = version.version_number.as_str())
+            );
+            let chosen = Some((version, loader));
+            if let Ok(x) = self
+                .transaction(|solver| solver.collect_with_dependencies(project, &reason, chosen))
+            {
+                return Ok(x);
+            }
+            self.conflicted = false;
+        }
+        match error {
+            Error::Unresolved(mut failure) if tried > 0 => {
+                failure.reasons.push(format!(
+                    "tried {tried} older versions of {}, the search is limited to {MAX_OLDER_VERSIONS} \
+                    older versions of the project collected last, and does not change the versions \
+                    collected before it",
+                    project.name
+                ));
+                Err(Error::Unresolved(failure))
+            }
+            e => Err(e),
+        }
+    }
+
+    /// Collect a config project, or the chosen version of it, and its dependencies
+    fn collect_with_dependencies(
+        &mut self,
+        project: &config::ConfigProject,
+        reason: &Reason,
+        chosen: Option<(types::ModVersion, ModLoader)>,
+    ) -> Result<Vec<VersionId>> {
+        let base_id = self.collect_config_project(project, chosen)?;
+        self.mod_db.add_reason(&base_id, reason.clone());
+        if let Some(note) = &project.note {
+            self.mod_db.set_note(&base_id, note.clone());
+        }
+        let mut deps = self
+            .collect_dependencies(&base_id)
+            .map_err(|e| e.context(format!("project {}", project.name)))?;
         deps.push(base_id);
         Ok(deps)
     }
 
+    /// Run a step of the resolution against a staging copy of the database. The staged database
+    /// replaces the committed one only if the step succeeds, so a failure never leaves a partial
+    /// resolution behind.
+    fn transaction<T, F>(&mut self, step: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let staging = (self.mod_db.clone(), self.requirements.clone());
+        let committed = std::mem::replace(&mut self.mod_db, staging.0);
+        let requirements = std::mem::replace(&mut self.requirements, staging.1);
+        let result = step(self);
+        if result.is_err() {
+            self.mod_db = committed;
+            self.requirements = requirements;
+        }
+        result
+    }
+
+    /// Record that a version requires a specific version of a project
+    fn require(&mut self, project_id: &ProjectId, dependent: &VersionId, version: &VersionId) {
+        let requirement = Requirement {
+            dependent: dependent.clone(),
+            version: version.clone(),
+        };
+        let requirements = self.requirements.entry(project_id.clone()).or_default();
+        if !requirements.contains(&requirement) {
+            requirements.push(requirement);
+        }
+    }
+
+    /// Describe a version by its project and version number, like `sodium 0.6.13`
+    fn describe_version(&self, version_id: &VersionId) -> String {
+        let Some(version) = self.mod_db.get_version(version_id) else {
+            return version_id.to_string();
+        };
+        let name = self
+            .mod_db
+            .get_project_by_id(&version.project_id)
+            .map_or_else(|| version.name.clone(), |x| x.slug.to_string());
+        format!("{name} {}", version.version_number)
+    }
+
     /// Collect one project by its id
     fn collect_project_by_id(&mut self, project_id: &ProjectId) -> Result<ProjectId> {
         if let Some(project) = &mut self.mod_db.get_project_by_id(project_id) {
             return Ok(project.project_id.clone());
         }
-        let project = self.client.get_project(project_id.as_str())?;
+        let project = self.backend()?.get_project(project_id.as_str())?;
         let project_id = project.project_id.clone();
         self.mod_db.add_project(project);
         Ok(project_id)
@@ -80,51 +397,537 @@ impl<'a> ModSolver<'a> {
         if let Some(project) = &mut self.mod_db.get_project_by_slug(project_slug) {
             return Ok(project.project_id.clone());
         }
-        let project = self.client.get_project(project_slug.as_str())?;
+        let project = self.backend()?.get_project(project_slug.as_str())?;
         let project_id = project.project_id.clone();
         self.mod_db.add_project(project);
         Ok(project_id)
     }
 
-    /// Collect one version by its id
-    fn collect_version(&mut self, version_id: &VersionId) -> Result<VersionId> {
-        if let Some(version) = &mut self.mod_db.get_version(version_id) {
-            return Ok(version.version_id.clone());
+    /// Collect one version by its id, as required by a dependent version. If another version of
+    /// its project was already collected, the two are reconciled.
+    fn collect_version(
+        &mut self,
+        version_id: &VersionId,
+        dependent: &VersionId,
+    ) -> Result<VersionId> {
+        if let Some(version) = self.mod_db.get_version(version_id) {
+            let (project_id, version_id) = (version.project_id.clone(), version.version_id.clone());
+            self.require(&project_id, dependent, &version_id);
+            return Ok(version_id);
+        }
+        let version = self.fetch_version(version_id)?;
+        let project_id = self.collect_project_by_id(&version.project_id)?;
+        self.require(&project_id, dependent, &version.version_id);
+        let loader = self.choose_loader(&project_id, &version.loaders)?;
+        if let Some(existing) = self.mod_db.get_preferred(&project_id, loader).cloned() {
+            return self.reconcile_versions(existing, version);
         }
-        let version = self.client.get_version(version_id.as_str())?;
         let version_id = version.version_id.clone();
-        self.mod_db.add_version(version);
+        self.add_version(version);
+        self.mod_db
+            .set_preferred_version(project_id, loader, version_id.clone());
         Ok(version_id)
     }
 
-    /// Collect one project and a version by a project id
-    fn collect_config_project(&mut self, project: &config::ConfigProject) -> Result<VersionId> {
+    /// Choose between a collected version of a project and another version required by a
+    /// dependency. The required version replaces the collected one if no other version requires
+    /// the collected one, they have a loader in common, and the required one supports the game
+    /// versions the project targets. Otherwise the error explains what requires each version.
+    fn reconcile_versions(
+        &mut self,
+        existing: types::ModVersion,
+        required: types::ModVersion,
+    ) -> Result<VersionId> {
+        let requirements = self
+            .requirements
+            .get(&existing.project_id)
+            .cloned()
+            .unwrap_or_default();
+        if requirements
+            .iter()
+            .any(|x| x.version != required.version_id)
+        {
+            let slug = self
+                .mod_db
+                .get_project_by_id(&existing.project_id)
+                .map_or_else(|| existing.project_id.to_string(), |x| x.slug.to_string());
+            let mut failure = Failure::new(format!(
+                "the collected versions of {slug} do not satisfy every dependent"
+            ));
+            for requirement in &requirements {
+                let version_number = match requirement.version == required.version_id {
+                    true => &required.version_number,
+                    false => &existing.version_number,
+                };
+                failure
+                    .reasons
+                    .push(match requirement.dependent == requirement.version {
+                        true => format!("{version_number} is pinned by the config"),
+                        false => format!(
+                            "{} requires {version_number}",
+                            self.describe_version(&requirement.dependent)
+                        ),
+                    });
+            }
+            self.conflicted = true;
+            return Err(Error::Unresolved(Box::new(failure)));
+        }
+        if !required
+            .loaders
+            .iter()
+            .any(|x| existing.loaders.contains(x))
+        {
+            let problem = format!(
+                "{} shares no loader with {}",
+                required.version_number, existing.version_number
+            );
+            return Err(self.conflicting_versions(&existing, &required, problem));
+        }
+        let slug = self
+            .mod_db
+            .get_project_by_id(&existing.project_id)
+            .map_or_else(|| existing.project_id.to_string(), |x| x.slug.to_string());
+        let project = self
+            .mod_config
+            .projects()
+            .into_iter()
+            .chain(self.mod_config.optional_projects())
+            .find(|x| x.name.as_str() == slug)
+            .unwrap_or_else(|| self.mod_config.new_project(&slug));
+        if !self.supports_targets(&project, &required)? {
+            let targets = project
+                .game_versions
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+            let problem = format!(
+                "{} does not support {}",
+                required.version_number,
+                targets.join(", ")
+            );
+            return Err(self.conflicting_versions(&existing, &required, problem));
+        }
+        status!(
+            "  {}",
+            tr!(
+                "replacing-required",
+                old = &existing.version_number,
+                new = &required.version_number
+            )
+        );
+        let version_id = required.version_id.clone();
+        self.mod_db.replace_version(&existing.version_id, required);
+        Ok(version_id)
+    }
+
+    /// Explain why a version required by a dependency can not replace the collected version of
+    /// its project, with what requires each of them
+    fn conflicting_versions(
+        &self,
+        existing: &types::ModVersion,
+        required: &types::ModVersion,
+        problem: String,
+    ) -> Error {
+        let slug = self
+            .mod_db
+            .get_project_by_id(&existing.project_id)
+            .map_or_else(|| existing.project_id.to_string(), |x| x.slug.to_string());
+        let mut failure = Failure::new(format!("conflicting versions of {slug}"));
+        let reasons = self
+            .mod_db
+            .get_provenance(&existing.version_id)
+            .map(|x| x.reasons.clone())
+            .unwrap_or_default();
+        for reason in reasons {
+            failure.reasons.push(format!(
+                "{} is {}",
+                existing.version_number,
+                describe_reason(&self.mod_db, &reason)
+            ));
+        }
+        let requirements = self
+            .requirements
+            .get(&existing.project_id)
+            .into_iter()
+            .flatten()
+            .filter(|x| x.version == required.version_id);
+        for requirement in requirements {
+            failure.reasons.push(format!(
+                "{} requires {}",
+                self.describe_version(&requirement.dependent),
+                required.version_number
+            ));
+        }
+        failure.reasons.push(problem);
+        Error::Unresolved(Box::new(failure))
+    }
+
+    /// Check if a version supports a game version a config project targets, or an earlier
+    /// patch within its tolerance. A version that lists no game versions is not checked.
+    fn supports_targets(
+        &mut self,
+        project: &config::ConfigProject,
+        version: &types::ModVersion,
+    ) -> Result<bool> {
+        if version.game_versions.is_empty() {
+            return Ok(true);
+        }
+        let known = self.game_versions()?;
+        let mut targets = Vec::new();
+        for game_version in &project.game_versions {
+            targets.extend(
+                known
+                    .expand(*game_version)
+                    .unwrap_or_else(|_| vec![*game_version]),
+            );
+            targets.extend(known.earlier_patches(*game_version, project.game_version_tolerance));
+        }
+        Ok(version
+            .game_versions
+            .iter()
+            .any(|x| targets.iter().any(|target| target.is_equivalent(x))))
+    }
+
+    /// Restrict the loaders of a project with a configured type to those whose files are that type
+    /// of content, so it is resolved and installed as that type. If none of its loaders are,
+    /// every loader of the type is tried, like `minecraft` for resource packs.
+    fn typed_project(&mut self, project: &config::ConfigProject) -> Result<config::ConfigProject> {
+        let mut project = project.clone();
+        let Some(project_type) = project.project_type else {
+            return Ok(project);
+        };
+        let table = self.loaders();
+        let mut loaders = project
+            .loaders
+            .iter()
+            .copied()
+            .filter(|x| table.content_type(Some(*x)) == project_type)
+            .collect::<Vec<_>>();
+        if loaders.is_empty() {
+            loaders = table.loaders_of(project_type);
+        }
+        if loaders.is_empty() {
+            let mut failure = Failure::new(format!("project {}", project.name));
+            failure
+                .reasons
+                .push(format!("no loader loads content of type {project_type}"));
+            return Err(Error::Unresolved(Box::new(failure)));
+        }
+        project.loaders = loaders;
+        Ok(project)
+    }
+
+    /// Find the candidate versions of a project, newest first, for the first of its game versions
+    /// and loaders in priority order that has any, then for the earlier patches of its game
+    /// versions within its tolerance. If none is found, the error explains why each
+    /// candidate was rejected, and suggests alternatives if no version was published at all.
+    fn find_project_versions(
+        &mut self,
+        project: &config::ConfigProject,
+    ) -> Result<(Vec<types::ModVersion>, ModLoader)> {
+        let mut failure = Failure::new(format!("project {}", project.name));
+        let mut published = false;
+        let mut targets = project.game_versions.clone();
+        if project.game_version_tolerance > 0 {
+            let known = self.game_versions()?;
+            let earlier = project
+                .game_versions
+                .iter()
+                .flat_map(|x| known.earlier_patches(*x, project.game_version_tolerance))
+                .collect::<Vec<_>>();
+            for version in earlier {
+                if !targets.iter().any(|x| x.is_equivalent(&version)) {
+                    targets.push(version);
+                }
+            }
+        }
+        for (index, game_version) in targets.iter().enumerate() {
+            let game_versions = match self.game_versions()?.expand(*game_version) {
+                Ok(x) => x,
+                Err(e) => {
+                    failure.reasons.push(format!("{game_version}: {e}"));
+                    continue;
+                }
+            };
+            for loader in &project.loaders {
+                let versions = self.backend()?.get_project_versions(
+                    project.name.as_str(),
+                    &game_versions,
+                    &[*loader],
+                )?;
+                if versions.is_empty() {
+                    failure.reasons.push(format!(
+                        "{game_version} with {loader}: no versions published"
+                    ));
+                    continue;
+                }
+                published = true;
+                let versions = self.filter_versions(&project.name, versions)?;
+                if versions.is_empty() {
+                    failure.reasons.push(format!(
+                        "{game_version} with {loader}: every version was rejected by hooks.script"
+                    ));
+                    continue;
+                }
+                match self.select_versions(versions, project.featured_only) {
+                    Ok(x) => {
+                        if index >= project.game_versions.len() {
+                            status!(
+                                "  {}",
+                                tr!("tolerated-game-version", version = game_version.to_string())
+                            );
+                        }
+                        return Ok((x, *loader));
+                    }
+                    Err(reason) => failure
+                        .reasons
+                        .push(format!("{game_version} with {loader}: {reason}")),
+                }
+            }
+        }
+        if !published {
+            let suggestions = self.suggest_alternatives(project);
+            if !suggestions.is_empty() {
+                failure
+                    .reasons
+                    .push(format!("alternatives: {}", suggestions.join(", ")));
+            }
+        }
+        Err(Error::Unresolved(Box::new(failure)))
+    }
+
+    /// Suggest projects that could replace a project that was not found or has no versions for its
+    /// first game version and loader: known equivalents that have a version, then similar projects
+    /// found by searching the registry, if it can be searched
+    fn suggest_alternatives(&mut self, project: &config::ConfigProject) -> Vec<String> {
+        let (Some(game_version), Some(loader)) =
+            (project.game_versions.first(), project.loaders.first())
+        else {
+            return vec![];
+        };
+        let (Ok(game_versions), Ok(backend)) = (
+            self.game_versions().and_then(|x| x.expand(*game_version)),
+            self.backend(),
+        ) else {
+            return vec![];
+        };
+        let mut result = alternatives::equivalents(project.name.as_str())
+            .iter()
+            .filter(|x| {
+                backend
+                    .get_project_versions(x, &game_versions, &[*loader])
+                    .is_ok_and(|x| !x.is_empty())
+            })
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        if backend.name() != "modrinth" || self.mod_config.registry_fixture.is_some() {
+            return result;
+        }
+        let (name, categories) = match self.mod_db.get_project_by_slug(&project.name) {
+            Some(x) => (x.name.clone(), x.categories.clone()),
+            None => (project.name.as_str().replace('-', " "), vec![]),
+        };
+        let facets = labrinth::SearchFacets {
+            game_version: Some(game_versions[0]),
+            loader: Some(*loader),
+            categories: categories.clone(),
+            ..Default::default()
+        };
+        let hits = self
+            .client
+            .search(&name, &facets, alternatives::MAX_SEARCHED * 3);
+        let exclude = result.iter().map(String::as_str).collect::<Vec<_>>();
+        let searched = alternatives::rank(
+            project.name.as_str(),
+            &name,
+            &categories,
+            hits.unwrap_or_default(),
+            &exclude,
+        );
+        result.extend(
+            searched
+                .into_iter()
+                .take(alternatives::MAX_SEARCHED)
+                .map(|x| x.slug),
+        );
+        result
+    }
+
+    /// Keep the candidate versions of a project that the hook script accepts
+    fn filter_versions(
+        &self,
+        slug: &ProjectSlug,
+        versions: Vec<types::ModVersion>,
+    ) -> Result<Vec<types::ModVersion>> {
+        let Some(hooks) = self.mod_config.hooks.get() else {
+            return Ok(versions);
+        };
+        let mut result = Vec::new();
+        for version in versions {
+            if hooks.filter_version(slug.as_str(), &version)? {
+                result.push(version);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Keep the candidate versions of a project that were published before update.min-age, and
+    /// are featured if required, newest first. If none is left, the error says why.
+    fn select_versions(
+        &self,
+        mut versions: Vec<types::ModVersion>,
+        featured_only: bool,
+    ) -> std::result::Result<Vec<types::ModVersion>, &'static str> {
+        if featured_only {
+            versions.retain(|x| x.featured);
+            if versions.is_empty() {
+                return Err("no featured versions");
+            }
+        }
+        if let Some(min_age) = self.mod_config.update.min_age {
+            match min_age.before(chrono::Utc::now().naive_utc()) {
+                Ok(cutoff) => versions.retain(|x| x.date_published <= cutoff),
+                Err(_) => versions.clear(),
+            }
+        }
+        if versions.is_empty() {
+            return Err("every version is newer than update.min-age");
+        }
+        versions.sort_by_key(|x| std::cmp::Reverse(x.date_published));
+        Ok(versions)
+    }
+
+    /// Collect one project and a version by a project id. A frozen project keeps its locked
+    /// version, otherwise the chosen version or the latest is collected.
+    fn collect_config_project(
+        &mut self,
+        project: &config::ConfigProject,
+        chosen: Option<(types::ModVersion, ModLoader)>,
+    ) -> Result<VersionId> {
+        if let Some(version_id) = &project.version_id {
+            return self.collect_pinned_version(project, version_id);
+        }
         let project_id = match self.mod_db.get_project_by_slug(&project.name) {
             Some(x) => x.project_id.clone(),
-            None => self.collect_project_by_slug(&project.name)?,
+            None => match self.collect_project_by_slug(&project.name) {
+                Ok(x) => x,
+                Err(e @ Error::ProjectNotFound(_)) => {
+                    let mut failure = Failure::new(format!("project {}", project.name));
+                    failure.reasons.push(e.to_string());
+                    let suggestions = self.suggest_alternatives(project);
+                    if !suggestions.is_empty() {
+                        failure
+                            .reasons
+                            .push(format!("alternatives: {}", suggestions.join(", ")));
+                    }
+                    return Err(Error::Unresolved(Box::new(failure)));
+                }
+                Err(e) => return Err(e),
+            },
         };
-        let version_id = match self
-            .mod_db
-            .get_preferred_by_id(&project_id)
+        let version_id = match project
+            .loaders
+            .iter()
+            .find_map(|x| self.mod_db.get_preferred(&project_id, *x))
             .map(|x| x.version_id.clone())
         {
             Some(x) => x,
             None => {
-                let version = self.client.get_project_version_latest(
-                    project.name.as_str(),
-                    project.game_version,
-                    project.loader,
-                )?;
+                let locked = project
+                    .loaders
+                    .iter()
+                    .find_map(|x| self.locked.get(project_id.as_str(), Some(*x)))
+                    .filter(|_| project.frozen);
+                let (version, loader) = match locked {
+                    Some(x) => {
+                        status!("  {}", tr!("frozen-at", version = &x.version_number));
+                        let version = self.backend()?.get_version(&x.version_id)?;
+                        let loader = project
+                            .loaders
+                            .iter()
+                            .find(|x| version.loaders.contains(x))
+                            .or(version.loaders.first())
+                            .copied()
+                            .unwrap_or(self.mod_config.defaults.loader);
+                        self.require(&project_id, &version.version_id, &version.version_id);
+                        (version, loader)
+                    }
+                    None => match chosen {
+                        Some(x) => x,
+                        None => {
+                            let (versions, loader) = self.find_project_versions(project)?;
+                            let version = versions.into_iter().next().expect("Versions are kept");
+                            (version, loader)
+                        }
+                    },
+                };
                 let version_id = version.version_id.clone();
-                self.mod_db.add_version(version);
+                self.add_version(version);
                 self.mod_db
-                    .set_preferred_version(project_id, version_id.clone());
+                    .set_preferred_version(project_id, loader, version_id.clone());
                 version_id
             }
         };
         Ok(version_id)
     }
 
+    /// Collect the exact version a config project names by id, and its project. The version
+    /// requires itself, so a dependency that requires another version of the project conflicts.
+    /// The version is recorded as pinned by the config.
+    fn collect_pinned_version(
+        &mut self,
+        project: &config::ConfigProject,
+        version_id: &VersionId,
+    ) -> Result<VersionId> {
+        let version = match self.mod_db.get_version(version_id) {
+            Some(x) => x.clone(),
+            None => self.backend()?.get_version(version_id.as_str())?,
+        };
+        let project_id = self.collect_project_by_id(&version.project_id)?;
+        self.require(&project_id, &version.version_id, &version.version_id);
+        let loader = project
+            .loaders
+            .iter()
+            .find(|x| version.loaders.contains(x))
+            .or(version.loaders.first())
+            .copied()
+            .unwrap_or(self.mod_config.defaults.loader);
+        let version_id = match self.mod_db.get_preferred(&project_id, loader).cloned() {
+            Some(existing) if existing.version_id == version.version_id => existing.version_id,
+            Some(existing) => self.reconcile_versions(existing, version)?,
+            None => {
+                let version_id = version.version_id.clone();
+                self.add_version(version);
+                self.mod_db
+                    .set_preferred_version(project_id, loader, version_id.clone());
+                version_id
+            }
+        };
+        self.mod_db.add_reason(&version_id, Reason::Pinned);
+        Ok(version_id)
+    }
+
+    /// Choose the loader a dependency is resolved for from the loaders it supports, preferring
+    /// the default loader, then loaders that work without a mod loader
+    fn choose_loader(
+        &mut self,
+        project_id: &ProjectId,
+        loaders: &[ModLoader],
+    ) -> Result<ModLoader> {
+        let default_loader = self.mod_config.defaults.loader;
+        if loaders.contains(&default_loader) {
+            return Ok(default_loader);
+        }
+        let table = self.loaders();
+        match loaders.iter().find(|x| table.is_loader_independent(**x)) {
+            Some(x) => Ok(*x),
+            None => Err(Error::NoCompatibleLoader {
+                project: project_id.to_string(),
+                loaders: loaders.iter().map(|x| x.to_string()).collect(),
+            }),
+        }
+    }
+
     /// Collect the appropriate version of a project
     fn collect_project_version(&mut self, project_id: &ProjectId) -> Result<VersionId> {
         let pid = self.collect_project_by_id(project_id)?;
@@ -135,37 +938,63 @@ impl<'a> ModSolver<'a> {
                     key: project_id.to_string(),
                     msg: "Project was not added".into(),
                 })?;
-        if mod_project
-            .loaders
-            .contains(&self.mod_config.defaults.loader)
-        {
-            self.collect_config_project(&config::ConfigProject {
-                name: mod_project.slug.clone(),
-                game_version: self.mod_config.defaults.game_version,
-                loader: self.mod_config.defaults.loader,
-            })
-        } else if mod_project.loaders.contains(&ModLoader::Minecraft) {
-            self.collect_config_project(&config::ConfigProject {
-                name: mod_project.slug.clone(),
-                game_version: self.mod_config.defaults.game_version,
-                loader: ModLoader::Minecraft,
-            })
-        } else if mod_project.loaders.contains(&ModLoader::Datapack) {
-            self.collect_config_project(&config::ConfigProject {
-                name: mod_project.slug.clone(),
-                game_version: self.mod_config.defaults.game_version,
-                loader: ModLoader::Datapack,
-            })
-        } else {
-            todo!(
-                "No idea how to resolve this one {}, {:?}",
-                mod_project.slug,
-                mod_project.loaders
-            )
+        let (slug, project_loaders) = (mod_project.slug.clone(), mod_project.loaders.clone());
+        let loader =
+            self.choose_loader(&pid, &project_loaders)
+                .map_err(|_| Error::NoCompatibleLoader {
+                    project: slug.to_string(),
+                    loaders: project_loaders.iter().map(|x| x.to_string()).collect(),
+                })?;
+        self.collect_config_project(
+            &config::ConfigProject {
+                name: slug,
+                game_versions: vec![self.mod_config.defaults.game_version],
+                loaders: vec![loader],
+                requires: vec![],
+                note: None,
+                frozen: false,
+                featured_only: self.mod_config.defaults.featured_only,
+                game_version_tolerance: self.mod_config.defaults.game_version_tolerance,
+                install_as: None,
+                max_file_size: None,
+                project_type: None,
+                version_id: None,
+            },
+            None,
+        )
+    }
+
+    /// Check if a dependency is used on the target side, according to its project's metadata. A
+    /// client-only library is not needed on a server, and vice versa.
+    fn is_needed_on_target(&mut self, dep: &ModLink) -> Result<bool> {
+        let target = self.mod_config.defaults.target;
+        if target == Side::Both {
+            return Ok(true);
+        }
+        let project_id = match dep {
+            ModLink::ProjectId(x) => x.clone(),
+            ModLink::VersionId(x) => self.fetch_version(x)?.project_id,
+            ModLink::ProjectSlug(x) => self.collect_project_by_slug(x)?,
+        };
+        let project_id = self.collect_project_by_id(&project_id)?;
+        let side = self
+            .mod_db
+            .get_project_by_id(&project_id)
+            .map_or(Side::Both, |x| x.side);
+        if !side.includes(target) {
+            status!(
+                "  {}",
+                tr!(
+                    "skipping-side",
+                    project = project_id.as_str(),
+                    target = target.to_string()
+                )
+            );
         }
+        Ok(side.includes(target))
     }
 
-    /// Collect all the dependencies of a version. If one is missing, they are not collected.
+    /// Collect all the dependencies of a version
     fn collect_dependencies(&mut self, version_id: &VersionId) -> Result<Vec<VersionId>> {
         let Some(version) = self.mod_db.get_version(version_id) else {
             return Err(Error::LocalCacheMiss {
@@ -174,40 +1003,266 @@ impl<'a> ModSolver<'a> {
             });
         };
         let deps = version.dependencies.clone();
+        let dependent = format!("{} {}", version.name, version.version_number);
         let mut found_deps = Vec::<VersionId>::new();
         for dep in &deps {
-            if self.mod_db.contains_key(dep) {
+            let existing = match dep {
+                ModLink::ProjectId(x) => match self.mod_db.get_project_by_id(x) {
+                    Some(project) => {
+                        let loaders = project.loaders.clone();
+                        self.choose_loader(x, &loaders)
+                            .ok()
+                            .and_then(|loader| self.mod_db.get_preferred(x, loader))
+                            .map(|x| x.version_id.clone())
+                    }
+                    None => None,
+                },
+                ModLink::VersionId(x) => self
+                    .mod_db
+                    .get_version(x)
+                    .map(|x| (x.project_id.clone(), x.version_id.clone()))
+                    .map(|(project_id, x)| {
+                        self.require(&project_id, version_id, &x);
+                        x
+                    }),
+                ModLink::ProjectSlug(_) => None,
+            };
+            if let Some(x) = existing {
+                self.mod_db
+                    .add_reason(&x, Reason::DependencyOf(version_id.clone()));
+                continue;
+            }
+            if !self.is_needed_on_target(dep)? {
                 continue;
             }
             let collected = match dep {
                 ModLink::ProjectId(x) => self.collect_project_version(x),
-                ModLink::VersionId(x) => self.collect_version(x),
+                ModLink::VersionId(x) => self.collect_version(x, version_id),
                 ModLink::ProjectSlug(_) => {
                     unimplemented!("A dependency will never be a project slug");
                 }
             };
-            if collected.is_err() {
-                for each in &found_deps {
-                    self.mod_db.remove(&each.clone().into());
-                }
-            }
-            let collected = collected?;
-            let deps_res = self.collect_dependencies(&collected);
-            let mut collected = match deps_res {
-                Ok(mut x) => {
-                    x.push(collected);
-                    x
-                }
-                Err(e) => {
-                    self.mod_db.remove(&collected.into());
-                    for each in &found_deps {
-                        self.mod_db.remove(&each.clone().into());
-                    }
-                    return Err(e);
-                }
-            };
-            found_deps.append(&mut collected);
+            let context = |e: Error| e.context(format!("dependency {dep} of {dependent}"));
+            let collected = collected.map_err(context)?;
+            self.mod_db
+                .add_reason(&collected, Reason::DependencyOf(version_id.clone()));
+            let mut collected_deps = self.collect_dependencies(&collected).map_err(context)?;
+            found_deps.append(&mut collected_deps);
+            found_deps.push(collected);
         }
         Ok(found_deps)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry_fixture::FixtureRegistry;
+    use std::path::Path;
+
+    /// Load a config that resolves against the bundled registry
+    fn config(text: &str) -> config::Config {
+        let mut config = config::Config::loads(text).expect("Config shall parse");
+        config.paths.data = ".test/solver".into();
+        config.registry_fixture = Some(Arc::new(
+            FixtureRegistry::load(Path::new("fixtures/registry.json"))
+                .expect("Registry shall load"),
+        ));
+        config
+    }
+
+    fn solve(text: &str) -> Result<types::ModDB> {
+        ModSolver::new(&config(text)).solve().map(|x| x.mod_db)
+    }
+
+    #[test]
+    fn test_shared_library() {
+        let mod_db = solve(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            shulkerboxtooltip = {}
+            zoomify = {}
+            "#,
+        )
+        .expect("The pack shall resolve");
+        let preferred = |project_id: &str| {
+            mod_db
+                .get_preferred(&project_id.to_string().into(), ModLoader::Fabric)
+                .map(|x| x.version_number.clone())
+        };
+        assert_eq!(
+            preferred("w7ThoJFB").as_deref(),
+            Some("2.14.2+1.21.5"),
+            "An older version shall be chosen when the latest needs another library version"
+        );
+        assert_eq!(
+            preferred("9s6osm5g").as_deref(),
+            Some("18.0.145+fabric"),
+            "One version of a shared library shall satisfy every dependent"
+        );
+
+        let Err(error) = solve(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            cloth-config = { version-id = "0r0t6gYQ" }
+            shulkerboxtooltip = {}
+            "#,
+        ) else {
+            panic!("The pack shall not resolve");
+        };
+        assert!(
+            error.to_string().contains(
+                "tried 1 older versions of shulkerboxtooltip, the search is limited to 5"
+            ),
+            "The limit of the search shall be reported, got {error}"
+        );
+        assert!(
+            error
+                .to_string()
+                .contains("the collected versions of cloth-config do not satisfy every dependent"),
+            "The conflict shall be reported, got {error}"
+        );
+    }
+
+    #[test]
+    fn test_pinned_version() {
+        let mod_db = solve(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            cloth-config = { version-id = "0r0t6gYQ" }
+            "#,
+        )
+        .expect("The pack shall resolve");
+        assert_eq!(
+            mod_db
+                .get_provenance(&"0r0t6gYQ".to_string().into())
+                .map(|x| x.reasons.clone()),
+            Some(vec![Reason::Pinned, Reason::Configured]),
+            "A version pinned by the config shall be recorded as pinned"
+        );
+    }
+
+    #[test]
+    fn test_required_game_version() {
+        let Err(error) = solve(
+            r#"
+            [defaults]
+            game_version = "1.21.5"
+            loader = "fabric"
+
+            [projects]
+            sodium = {}
+            sodium-extra = {}
+            "#,
+        ) else {
+            panic!("The pack shall not resolve");
+        };
+        let error = error.to_string();
+        assert!(
+            error.contains("conflicting versions of sodium")
+                && error.contains("mc1.21.5-0.6.13-fabric is configured")
+                && error
+                    .contains("sodium-extra mc1.21.5-0.6.2+fabric requires mc1.21.4-0.6.10-fabric")
+                && error.contains("mc1.21.4-0.6.10-fabric does not support 1.21.5"),
+            "A required version for another game version shall not replace the collected one, \
+            got {error}"
+        );
+    }
+
+    #[test]
+    fn test_alternatives() {
+        let text = |slug: &str| {
+            format!(
+                "[defaults]\ngame_version = \"1.21.5\"\nloader = \"fabric\"\n\n\
+                [projects]\n{slug} = {{}}\n"
+            )
+        };
+        let Err(error) = solve(&text("okzoomer")) else {
+            panic!("The pack shall not resolve");
+        };
+        let error = error.to_string();
+        assert!(
+            error.contains("alternatives: zoomify"),
+            "Equivalents that have a version shall be suggested, got {error}"
+        );
+
+        let Err(error) = solve(&text("optifine")) else {
+            panic!("The pack shall not resolve");
+        };
+        let error = error.to_string();
+        assert!(
+            error.contains("No project named \"optifine\"")
+                && error.contains("alternatives: sodium, iris")
+                && !error.contains("embeddium"),
+            "Equivalents shall be suggested for a project that was not found, got {error}"
+        );
+    }
+
+    #[test]
+    fn test_gated_projects() {
+        let text = |optional: bool| {
+            let gated = r#"lithium = { requires = ["distanthorizons"] }"#;
+            let (required, optional) = if optional { ("", gated) } else { (gated, "") };
+            format!(
+                r#"
+                [defaults]
+                game_version = "1.21.5"
+                loader = "fabric"
+
+                [projects]
+                sodium = {{}}
+                {required}
+
+                [optional-projects]
+                distanthorizons = {{}}
+                {optional}
+                "#
+            )
+        };
+        let config = config(&text(true));
+        let mut solver = ModSolver::new(&config);
+        let skipped = solver
+            .collect_gated_projects()
+            .expect("An optional gated project shall not fail");
+        assert_eq!(
+            skipped.iter().map(|(x, _)| x.as_str()).collect::<Vec<_>>(),
+            vec!["lithium"],
+            "An optional project shall be skipped once when its requirements are not met"
+        );
+        let solution = ModSolver::new(&config)
+            .solve()
+            .expect("An optional gated project shall not fail");
+        assert_eq!(
+            solution
+                .skipped
+                .iter()
+                .map(|(x, _)| x.as_str())
+                .collect::<Vec<_>>(),
+            vec!["distanthorizons", "lithium"],
+            "Solving shall return each skipped optional project once"
+        );
+
+        let Err(error) = solve(&text(false)) else {
+            panic!("A required gated project shall fail when its requirements are not met");
+        };
+        assert!(
+            matches!(
+                error,
+                Error::RequirementsUnmet { ref requires, .. } if requires == &["distanthorizons"]
+            ),
+            "The unmet requirement shall be reported, got {error}"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Mutex;
+
+use crate::i18n::tr;
+use crate::output::{self, status};
+
+/// What happened during a run, counted as it goes and printed at the end
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// Versions resolved
+    pub resolved: usize,
+    /// Files downloaded
+    pub downloaded: usize,
+    /// Files already in the cache
+    pub cached: usize,
+    /// Files installed
+    pub installed: usize,
+    /// Projects skipped, like optional projects that could not be resolved
+    pub skipped: usize,
+    /// Projects and files that failed
+    pub failed: usize,
+    /// What to do about what was skipped or failed, in the order it happened
+    pub next_steps: Vec<String>,
+}
+
+/// The summary of this run
+static SUMMARY: Mutex<Summary> = Mutex::new(Summary {
+    resolved: 0,
+    downloaded: 0,
+    cached: 0,
+    installed: 0,
+    skipped: 0,
+    failed: 0,
+    next_steps: Vec::new(),
+});
+
+/// Record something that happened in the summary of this run
+pub fn record<F>(f: F)
+where
+    F: FnOnce(&mut Summary),
+{
+    f(&mut SUMMARY.lock().expect("Summary lock is not poisoned"));
+}
+
+/// Take the summary of this run, leaving an empty one
+pub fn take() -> Summary {
+    std::mem::take(&mut SUMMARY.lock().expect("Summary lock is not poisoned"))
+}
+
+impl Summary {
+    /// Check if nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Suggest a next step, unless it was already suggested
+    pub fn suggest(&mut self, step: String) {
+        if !self.next_steps.contains(&step) {
+            self.next_steps.push(step);
+        }
+    }
+
+    /// Print the counts and the next steps, unless nothing was recorded
+    pub fn print(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let style = match (self.failed, self.skipped) {
+            (0, 0) => output::Style::Good,
+            (0, _) => output::Style::Warning,
+            _ => output::Style::Error,
+        };
+        let counts = tr!(
+            "summary",
+            resolved = self.resolved,
+            downloaded = self.downloaded,
+            cached = self.cached,
+            installed = self.installed,
+            skipped = self.skipped,
+            failed = self.failed
+        );
+        status!("{}", output::get().paint(style, &counts));
+        if !self.next_steps.is_empty() {
+            status!("{}", tr!("next-steps"));
+            for step in &self.next_steps {
+                status!("  - {step}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut summary = Summary::default();
+        assert!(summary.is_empty());
+        summary.failed += 2;
+        summary.suggest("mcmod resume".into());
+        summary.suggest("mcmod verify".into());
+        summary.suggest("mcmod resume".into());
+        assert!(!summary.is_empty());
+        assert_eq!(
+            summary.next_steps,
+            vec!["mcmod resume", "mcmod verify"],
+            "Each next step shall be suggested once"
+        );
+    }
+}
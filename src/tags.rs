@@ -0,0 +1,390 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::labrinth;
+use crate::types::{
+    GameVersion, LoaderInfo, MinecraftReleaseSuffix, MinecraftVersion, ModLoader, ProjectType,
+};
+
+/// How long a cached tag list is used before it is fetched again
+const TAG_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Get the path of a cached tag list
+fn tag_cache_path(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join("tags").join(format!("{name}.json"))
+}
+
+/// Check if a cached file exists and is younger than the cache TTL
+fn is_fresh(path: &Path) -> bool {
+    path.metadata()
+        .and_then(|x| x.modified())
+        .ok()
+        .and_then(|x| x.elapsed().ok())
+        .is_some_and(|x| x < TAG_CACHE_TTL)
+}
+
+/// Load a list from the cache in the data directory, fetching it if the cache is stale. If it
+/// can not be fetched, a stale cache is used.
+pub fn load_cached<T, F>(data_dir: &Path, name: &str, fetch: F) -> Result<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+    F: FnOnce() -> Result<T>,
+{
+    let path = tag_cache_path(data_dir, name);
+    let read_cache = || -> Result<T> {
+        Ok(serde_json::from_str(
+            std::fs::read_to_string(&path)?.as_str(),
+        )?)
+    };
+    if is_fresh(&path)
+        && let Ok(x) = read_cache()
+    {
+        return Ok(x);
+    }
+    match fetch() {
+        Ok(x) => {
+            std::fs::create_dir_all(path.parent().expect("Tag cache path has a parent"))?;
+            std::fs::write(&path, serde_json::to_string(&x)?)?;
+            Ok(x)
+        }
+        Err(e) => read_cache().map_err(|_| e),
+    }
+}
+
+/// The official list of Minecraft versions
+pub struct GameVersions {
+    versions: Vec<GameVersion>,
+}
+
+impl GameVersions {
+    /// Construct from a list of game versions
+    pub fn new(versions: Vec<GameVersion>) -> Self {
+        Self { versions }
+    }
+
+    /// Load the list of game versions, using the cache in the data directory when it is fresh
+    pub fn load(client: &labrinth::Client, data_dir: &Path) -> Result<Self> {
+        load_cached(data_dir, "game_version", || client.get_game_versions()).map(Self::new)
+    }
+
+    /// Get the versions in the official list, newest first
+    pub fn versions(&self) -> &[GameVersion] {
+        &self.versions
+    }
+
+    /// Check if a version is in the official list, treating `1.21` and `1.21.0` as equal
+    pub fn contains(&self, version: &MinecraftVersion) -> bool {
+        self.versions
+            .iter()
+            .any(|x| x.version.is_equivalent(version))
+    }
+
+    /// Expand a version into the versions to query. A wildcard version like `1.21.x` expands to
+    /// every release of 1.21, and a version like `1.21` also includes `1.21.0`, since projects
+    /// may be tagged with either. Fails if the version is not in the official list.
+    pub fn expand(&self, version: MinecraftVersion) -> Result<Vec<MinecraftVersion>> {
+        let mut result = Vec::<MinecraftVersion>::new();
+        if self.contains(&version) {
+            result.push(version);
+            result.extend(version.equivalent());
+        }
+        if version.is_wildcard() {
+            result.extend(
+                self.versions
+                    .iter()
+                    .map(|x| x.version)
+                    .filter(|x| *x != version && x.matches_wildcard(&version)),
+            );
+        }
+        if result.is_empty() {
+            return Err(Error::UnknownGameVersion(version.to_string()));
+        }
+        Ok(result)
+    }
+
+    /// Get up to `count` earlier patches of a release in the official list, newest first, like
+    /// `1.21.4` and `1.21.3` for `1.21.5`
+    pub fn earlier_patches(
+        &self,
+        version: MinecraftVersion,
+        count: usize,
+    ) -> Vec<MinecraftVersion> {
+        let release = |x: MinecraftVersion| match x {
+            MinecraftVersion::Release {
+                major,
+                minor,
+                patch,
+                suffix: MinecraftReleaseSuffix::None,
+            } => Some((major, minor, patch.unwrap_or(0))),
+            _ => None,
+        };
+        let Some((major, minor, patch)) = release(version) else {
+            return vec![];
+        };
+        let mut result = self
+            .versions
+            .iter()
+            .filter_map(|x| Some((release(x.version)?, x.version)))
+            .filter(|((x, y, z), _)| (*x, *y) == (major, minor) && *z < patch)
+            .collect::<Vec<_>>();
+        result.sort_by_key(|((_, _, z), _)| std::cmp::Reverse(*z));
+        result.dedup_by_key(|((_, _, z), _)| *z);
+        result.into_iter().take(count).map(|(_, x)| x).collect()
+    }
+}
+
+/// The mod loaders and the project types they can load
+pub struct LoaderTable {
+    loaders: Vec<LoaderInfo>,
+}
+
+impl LoaderTable {
+    /// Construct from a list of loaders
+    pub fn new(loaders: Vec<LoaderInfo>) -> Self {
+        Self { loaders }
+    }
+
+    /// Get the table of the loaders known to this version of the program, used when the list can
+    /// not be fetched
+    pub fn builtin() -> Self {
+        let info = |loader: ModLoader, project_type: ProjectType| LoaderInfo {
+            name: loader.to_string(),
+            supported_project_types: vec![project_type],
+        };
+        Self::new(vec![
+            info(ModLoader::Minecraft, ProjectType::Resourcepack),
+            info(ModLoader::Datapack, ProjectType::Datapack),
+            info(ModLoader::Canvas, ProjectType::Shader),
+            info(ModLoader::Iris, ProjectType::Shader),
+            info(ModLoader::Optifine, ProjectType::Shader),
+            info(ModLoader::Vanilla, ProjectType::Shader),
+        ])
+    }
+
+    /// Load the list of loaders, using the cache in the data directory when it is fresh, or the
+    /// builtin table if it can not be fetched
+    pub fn load(client: &labrinth::Client, data_dir: &Path) -> Self {
+        load_cached(data_dir, "loader", || client.get_loaders())
+            .map(Self::new)
+            .unwrap_or_else(|_| Self::builtin())
+    }
+
+    /// Get the project types a loader can load. Unlisted loaders are assumed to load mods.
+    pub fn project_types(&self, loader: ModLoader) -> &[ProjectType] {
+        let name = loader.to_string();
+        self.loaders
+            .iter()
+            .find(|x| x.name == name)
+            .map_or(&[ProjectType::Mod], |x| {
+                x.supported_project_types.as_slice()
+            })
+    }
+
+    /// Get the kind of content that files for a loader are, preferring mods
+    pub fn content_type(&self, loader: Option<ModLoader>) -> ProjectType {
+        let Some(loader) = loader else {
+            return ProjectType::Mod;
+        };
+        let types = self.project_types(loader);
+        if types.is_empty() || types.contains(&ProjectType::Mod) {
+            ProjectType::Mod
+        } else {
+            types[0]
+        }
+    }
+
+    /// Get the loaders whose files are content of a project type, in the order of the table
+    pub fn loaders_of(&self, project_type: ProjectType) -> Vec<ModLoader> {
+        self.loaders
+            .iter()
+            .filter_map(|x| x.name.parse::<ModLoader>().ok())
+            .filter(|x| self.content_type(Some(*x)) == project_type)
+            .collect()
+    }
+
+    /// Get the directory under .minecraft that files for a loader are installed into
+    pub fn install_dir(&self, loader: Option<ModLoader>) -> &'static str {
+        self.content_type(loader).install_dir()
+    }
+
+    /// Get the shader loader a project provides, if it is named after one, like Iris by `iris`
+    pub fn shader_loader_of(&self, slug: &str) -> Option<ModLoader> {
+        self.loaders_of(ProjectType::Shader)
+            .into_iter()
+            .find(|x| x.to_string() == slug)
+    }
+
+    /// Check if the loaders of a version are shader loaders, so it is a shader
+    pub fn is_shader(&self, loaders: &[ModLoader]) -> bool {
+        !loaders.is_empty()
+            && loaders
+                .iter()
+                .all(|x| self.content_type(Some(*x)) == ProjectType::Shader)
+    }
+
+    /// Check if the files of a loader work without any mod loader, like resource packs and data
+    /// packs, so that they can be used as a fallback for any configured loader
+    pub fn is_loader_independent(&self, loader: ModLoader) -> bool {
+        let types = self.project_types(loader);
+        !types.is_empty()
+            && types
+                .iter()
+                .all(|x| matches!(x, ProjectType::Resourcepack | ProjectType::Datapack))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GameVersionType;
+
+    fn release(version: &str) -> GameVersion {
+        GameVersion {
+            version: version.into(),
+            version_type: GameVersionType::Release,
+            major: false,
+        }
+    }
+
+    #[test]
+    fn test_official_versions_round_trip() {
+        use crate::fixtures::{FixtureMode, Fixtures};
+        let dir = PathBuf::from("fixtures/api");
+        let mode = match std::env::var_os("MCMOD_BLESS") {
+            Some(_) => FixtureMode::Record,
+            None => FixtureMode::Replay,
+        };
+        let data_dir = Path::new(".test/tags");
+        let _ = std::fs::remove_dir_all(data_dir);
+        let client = labrinth::Client::new().with_fixtures(Some(Fixtures::new(dir.clone(), mode)));
+        let versions = GameVersions::load(&client, data_dir)
+            .expect("GameVersions shall load the recorded official list");
+        let text = std::fs::read_to_string(dir.join("api.modrinth.com_v2_tag_game_version"))
+            .expect("The official list shall be recorded");
+        let expected = serde_json::from_str::<Vec<serde_json::Value>>(&text)
+            .expect("The official list shall be JSON")
+            .into_iter()
+            .map(|x| x["version"].as_str().map(String::from))
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(
+            versions
+                .versions()
+                .iter()
+                .map(|x| Some(x.version.to_string()))
+                .collect::<Vec<_>>(),
+            expected,
+            "Every official version shall format exactly the string it was parsed from. Run the \
+            tests with MCMOD_BLESS=1 to record the list again"
+        );
+    }
+
+    #[test]
+    fn test_expand_wildcard() {
+        let versions = GameVersions::new(vec![
+            release("1.21.5"),
+            release("1.21.4"),
+            release("1.20.6"),
+            release("1.21.5-rc1"),
+        ]);
+        assert_eq!(
+            versions
+                .expand("1.21.x".into())
+                .expect("GameVersions shall expand a wildcard"),
+            vec![
+                MinecraftVersion::from("1.21.5"),
+                MinecraftVersion::from("1.21.4")
+            ],
+            "GameVersions shall expand a wildcard to every release"
+        );
+    }
+
+    #[test]
+    fn test_earlier_patches() {
+        let versions = GameVersions::new(vec![
+            release("1.21.5"),
+            release("1.21.5-rc1"),
+            release("1.21.4"),
+            release("1.21.3"),
+            release("1.21"),
+            release("1.20.6"),
+        ]);
+        assert_eq!(
+            versions.earlier_patches("1.21.5".into(), 2),
+            vec![
+                MinecraftVersion::from("1.21.4"),
+                MinecraftVersion::from("1.21.3")
+            ],
+            "Only the nearest releases of the same minor version shall be tried"
+        );
+        assert_eq!(
+            versions.earlier_patches("1.21.3".into(), 5),
+            vec![MinecraftVersion::from("1.21")],
+            "Earlier patches shall stop at the first release of the minor version"
+        );
+        assert!(versions.earlier_patches("1.21".into(), 5).is_empty());
+    }
+
+    #[test]
+    fn test_loader_install_dir() {
+        let loaders = LoaderTable::new(vec![
+            LoaderInfo {
+                name: "canvas".into(),
+                supported_project_types: vec![ProjectType::Shader],
+            },
+            LoaderInfo {
+                name: "fabric".into(),
+                supported_project_types: vec![ProjectType::Mod, ProjectType::Modpack],
+            },
+        ]);
+        assert_eq!(loaders.install_dir(Some(ModLoader::Canvas)), "shaderpacks");
+        assert_eq!(
+            LoaderTable::builtin().install_dir(Some(ModLoader::Iris)),
+            "shaderpacks"
+        );
+        assert_eq!(loaders.install_dir(Some(ModLoader::Fabric)), "mods");
+        assert_eq!(
+            loaders.install_dir(Some(ModLoader::Quilt)),
+            "mods",
+            "LoaderTable shall install unlisted loaders as mods"
+        );
+        assert!(LoaderTable::builtin().is_loader_independent(ModLoader::Datapack));
+        assert!(!LoaderTable::builtin().is_loader_independent(ModLoader::Iris));
+        assert!(LoaderTable::builtin().is_shader(&[ModLoader::Iris, ModLoader::Optifine]));
+        assert_eq!(
+            LoaderTable::builtin().shader_loader_of("iris"),
+            Some(ModLoader::Iris),
+            "LoaderTable shall find the shader loader a project is named after"
+        );
+        assert_eq!(LoaderTable::builtin().shader_loader_of("sodium"), None);
+        assert!(
+            !LoaderTable::builtin().is_shader(&[ModLoader::Fabric, ModLoader::Iris]),
+            "LoaderTable shall not treat mods as shaders"
+        );
+    }
+
+    #[test]
+    fn test_expand_unknown() {
+        let versions = GameVersions::new(vec![release("1.21.5")]);
+        assert_eq!(
+            versions
+                .expand("1.21.5".into())
+                .expect("GameVersions shall accept a known version"),
+            vec![MinecraftVersion::from("1.21.5")]
+        );
+        assert_eq!(
+            GameVersions::new(vec![release("1.21")])
+                .expand("1.21.0".into())
+                .expect("GameVersions shall accept an equivalent version"),
+            vec![
+                MinecraftVersion::from("1.21.0"),
+                MinecraftVersion::from("1.21")
+            ],
+            "GameVersions shall query both spellings of a version"
+        );
+        versions
+            .expand("1.21.55".into())
+            .expect_err("GameVersions shall reject an unknown version");
+    }
+}
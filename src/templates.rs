@@ -0,0 +1,92 @@
+use crate::error::{Error, Result};
+use crate::labrinth;
+
+/// A starter config for a new pack
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Template {
+    /// The name given to `init --template`
+    pub name: String,
+    /// What the pack is for
+    pub description: String,
+    /// The TOML text of the config
+    pub config: String,
+}
+
+impl Template {
+    /// Construct a template from a config whose first line is a comment describing it
+    fn builtin(name: &str, config: &str) -> Self {
+        let description = config
+            .lines()
+            .next()
+            .and_then(|x| x.strip_prefix("# "))
+            .unwrap_or_default();
+        Self {
+            name: name.into(),
+            description: description.into(),
+            config: config.into(),
+        }
+    }
+}
+
+/// Get the templates built into the program
+pub fn builtin() -> Vec<Template> {
+    vec![
+        Template::builtin("basic", include_str!("../templates/basic.toml")),
+        Template::builtin(
+            "performance-fabric",
+            include_str!("../templates/performance-fabric.toml"),
+        ),
+        Template::builtin(
+            "performance-neoforge",
+            include_str!("../templates/performance-neoforge.toml"),
+        ),
+        Template::builtin(
+            "server-fabric",
+            include_str!("../templates/server-fabric.toml"),
+        ),
+    ]
+}
+
+/// Load the templates from a JSON index, a list of objects with a name, description, and config
+pub fn load_index(client: &labrinth::Client, url: &str) -> Result<Vec<Template>> {
+    Ok(serde_json::from_slice(&client.download_file(url)?)?)
+}
+
+/// Find a template by name
+pub fn find(templates: Vec<Template>, name: &str) -> Result<Template> {
+    let available = templates.iter().map(|x| x.name.clone()).collect();
+    templates
+        .into_iter()
+        .find(|x| x.name == name)
+        .ok_or_else(|| Error::UnknownTemplate {
+            name: name.into(),
+            available,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_builtin() {
+        for template in builtin() {
+            assert!(
+                !template.description.is_empty(),
+                "Template {} shall have a description",
+                template.name
+            );
+            Config::loads(&template.config).unwrap_or_else(|e| {
+                panic!("Template {} shall be a valid config: {e}", template.name)
+            });
+        }
+        assert_eq!(
+            find(builtin(), "performance-fabric")
+                .expect("Templates shall be found by name")
+                .name,
+            "performance-fabric"
+        );
+        find(builtin(), "performance").expect_err("Templates shall not match partial names");
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::lockfile::display_reason;
+use crate::types::{ModDB, ModVersion, Reason, VersionId};
+
+/// The lines that connect a node of the tree to its parent and siblings
+struct Branches {
+    /// Before a child that has more siblings after it
+    middle: &'static str,
+    /// Before the last child
+    last: &'static str,
+    /// Under a child that has more siblings after it
+    through: &'static str,
+    /// Under the last child
+    blank: &'static str,
+}
+
+impl Branches {
+    fn new(ascii: bool) -> Self {
+        match ascii {
+            true => Self {
+                middle: "|-- ",
+                last: "`-- ",
+                through: "|   ",
+                blank: "    ",
+            },
+            false => Self {
+                middle: "\u{251c}\u{2500}\u{2500} ",
+                last: "\u{2514}\u{2500}\u{2500} ",
+                through: "\u{2502}   ",
+                blank: "    ",
+            },
+        }
+    }
+}
+
+/// Get the name of the project of a version
+fn project_name(mod_db: &ModDB, version: &ModVersion) -> String {
+    mod_db
+        .get_project_by_id(&version.project_id)
+        .map_or(version.name.clone(), |x| x.name.clone())
+}
+
+/// The edges of the graph to follow when writing a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// From each version to the dependencies it collected
+    Dependencies,
+    /// From each version to the versions that depend on it
+    Dependents,
+}
+
+/// The resolved versions as a graph between each version and the dependencies it collected
+struct Graph<'a> {
+    mod_db: &'a ModDB,
+    /// The versions collected for a reason other than being a dependency, or for no known reason
+    roots: Vec<&'a ModVersion>,
+    /// The dependencies of each version
+    children: HashMap<&'a VersionId, Vec<&'a ModVersion>>,
+    /// The versions that depend on each version
+    parents: HashMap<&'a VersionId, Vec<&'a ModVersion>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(mod_db: &'a ModDB) -> Self {
+        let mut roots = Vec::new();
+        let mut children = HashMap::<&VersionId, Vec<&ModVersion>>::new();
+        let mut parents = HashMap::<&VersionId, Vec<&ModVersion>>::new();
+        for version in mod_db.get_versions() {
+            let reasons = mod_db
+                .get_provenance(&version.version_id)
+                .map_or(&[][..], |x| x.reasons.as_slice());
+            let dependents = mod_db.get_dependents(&version.version_id);
+            for parent in &dependents {
+                children
+                    .entry(&parent.version_id)
+                    .or_default()
+                    .push(version);
+            }
+            let is_root = dependents.is_empty();
+            if !is_root {
+                parents.insert(&version.version_id, dependents);
+            }
+            if is_root
+                || reasons
+                    .iter()
+                    .any(|x| !matches!(x, Reason::DependencyOf(_)))
+            {
+                roots.push(version);
+            }
+        }
+        roots.sort_by_key(|x| project_name(mod_db, x).to_lowercase());
+        for versions in children.values_mut().chain(parents.values_mut()) {
+            versions.sort_by_key(|x| project_name(mod_db, x).to_lowercase());
+        }
+        Self {
+            mod_db,
+            roots,
+            children,
+            parents,
+        }
+    }
+
+    /// Get the versions a version has edges to in a direction
+    fn edges(&self, version: &ModVersion, direction: Direction) -> &[&'a ModVersion] {
+        let edges = match direction {
+            Direction::Dependencies => &self.children,
+            Direction::Dependents => &self.parents,
+        };
+        edges
+            .get(&version.version_id)
+            .map_or(&[][..], |x| x.as_slice())
+    }
+
+    /// Describe a version, with why it was collected if `is_root`, or else only the name and version
+    fn label(&self, version: &ModVersion, is_root: bool) -> String {
+        let mut label = format!(
+            "{} {}",
+            project_name(self.mod_db, version),
+            version.version_number
+        );
+        let reasons = self
+            .mod_db
+            .get_provenance(&version.version_id)
+            .map_or(&[][..], |x| x.reasons.as_slice())
+            .iter()
+            .filter(|x| !matches!(x, Reason::DependencyOf(_)))
+            .map(|x| display_reason(self.mod_db, x))
+            .collect::<Vec<_>>();
+        if is_root && !reasons.is_empty() {
+            label.push_str(&format!(" ({})", reasons.join(", ")));
+        }
+        label
+    }
+
+    /// Write the versions a version has edges to in a direction under it, marking versions already
+    /// written with `(*)` instead of writing their edges again. Versions written as dependents
+    /// are described with why they were collected, so it shows where each chain starts.
+    fn write_children(
+        &self,
+        version: &ModVersion,
+        direction: Direction,
+        prefix: &str,
+        branches: &Branches,
+        shown: &mut HashSet<&'a VersionId>,
+        text: &mut String,
+    ) {
+        let children = self.edges(version, direction);
+        for (index, child) in children.iter().enumerate() {
+            let is_last = index + 1 == children.len();
+            let branch = if is_last {
+                branches.last
+            } else {
+                branches.middle
+            };
+            let label = self.label(child, direction == Direction::Dependents);
+            text.push_str(&format!("{prefix}{branch}{label}"));
+            if !shown.insert(&child.version_id) {
+                text.push_str(" (*)\n");
+                continue;
+            }
+            text.push('\n');
+            let under = if is_last {
+                branches.blank
+            } else {
+                branches.through
+            };
+            let prefix = format!("{prefix}{under}");
+            self.write_children(child, direction, &prefix, branches, shown, text);
+        }
+    }
+}
+
+/// Render the resolved versions as trees: each configured project with the dependencies it
+/// required under it, and theirs under them. A dependency required by several projects is only
+/// expanded the first time, and marked with `(*)` after that.
+pub fn render(mod_db: &ModDB, ascii: bool) -> String {
+    let graph = Graph::new(mod_db);
+    let branches = Branches::new(ascii);
+    let mut shown = HashSet::new();
+    let mut text = String::new();
+    for root in &graph.roots {
+        text.push_str(&graph.label(root, true));
+        if !shown.insert(&root.version_id) {
+            text.push_str(" (*)\n");
+            continue;
+        }
+        text.push('\n');
+        let direction = Direction::Dependencies;
+        graph.write_children(root, direction, "", &branches, &mut shown, &mut text);
+    }
+    text
+}
+
+/// Explain why a version was resolved: the version with the projects that required it, and the
+/// versions that depend on it as a tree, with the projects that required them under them, up to
+/// the projects the config asked for
+pub fn explain(mod_db: &ModDB, version: &ModVersion, ascii: bool) -> String {
+    let graph = Graph::new(mod_db);
+    let branches = Branches::new(ascii);
+    let mut text = graph.label(version, true);
+    let dependents = graph.edges(version, Direction::Dependents);
+    if !dependents.is_empty() {
+        let names = dependents
+            .iter()
+            .map(|x| project_name(mod_db, x))
+            .collect::<Vec<_>>();
+        text.push_str(&format!(" required by {}", names.join(", ")));
+    }
+    text.push('\n');
+    let mut shown = HashSet::from([&version.version_id]);
+    let direction = Direction::Dependents;
+    graph.write_children(version, direction, "", &branches, &mut shown, &mut text);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ModLoader, ModProject, Side};
+
+    fn add(mod_db: &mut ModDB, slug: &str, reasons: &[Reason]) {
+        mod_db.add_project(ModProject {
+            project_id: slug.to_uppercase().into(),
+            name: slug.into(),
+            slug: slug.into(),
+            description: String::new(),
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            side: Side::Both,
+            license: None,
+            icon_url: None,
+            categories: vec![],
+            stats: None,
+        });
+        let version_id = VersionId::from(format!("{slug}-v"));
+        mod_db.add_version(ModVersion {
+            project_id: slug.to_uppercase().into(),
+            version_id: version_id.clone(),
+            name: slug.into(),
+            version_number: "1.0".into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files: vec![],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        });
+        for reason in reasons {
+            mod_db.add_reason(&version_id, reason.clone());
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let of = |slug: &str| Reason::DependencyOf(format!("{slug}-v").into());
+        let mut mod_db = ModDB::default();
+        add(&mut mod_db, "modmenu", &[Reason::Configured]);
+        add(&mut mod_db, "fabric-api", &[of("modmenu"), of("sodium")]);
+        add(&mut mod_db, "sodium", &[Reason::Configured, of("iris")]);
+        add(&mut mod_db, "iris", &[Reason::Configured]);
+        add(&mut mod_db, "lib", &[of("fabric-api")]);
+        assert_eq!(
+            render(&mod_db, true),
+            "iris 1.0 (configured)\n\
+            `-- sodium 1.0\n    \
+            `-- fabric-api 1.0\n        \
+            `-- lib 1.0\n\
+            modmenu 1.0 (configured)\n\
+            `-- fabric-api 1.0 (*)\n\
+            sodium 1.0 (configured) (*)\n",
+            "Dependencies shall be shown under the projects that required them, and only once"
+        );
+        let lib = mod_db
+            .get_version(&String::from("lib-v").into())
+            .expect("Version was added");
+        assert_eq!(
+            explain(&mod_db, lib, true),
+            "lib 1.0 required by fabric-api\n\
+            `-- fabric-api 1.0\n    \
+            |-- modmenu 1.0 (configured)\n    \
+            `-- sodium 1.0 (configured)\n        \
+            `-- iris 1.0 (configured)\n",
+            "The chains of dependents shall be shown up to the configured projects"
+        );
+    }
+}
@@ -11,6 +11,7 @@ use crate::error::{Error, Result};
     Debug,
     Clone,
     Copy,
+    Hash,
     clap::ValueEnum,
     strum::EnumString,
     strum::Display,
@@ -37,6 +38,7 @@ pub enum ModLoader {
     BtaBabric,
     #[strum(to_string = "bukkit")]
     Bukkit,
+    #[serde(rename = "bungeecord")]
     #[strum(to_string = "bungeecord")]
     BungeeCord,
     #[strum(to_string = "canvas")]
@@ -49,11 +51,14 @@ pub enum ModLoader {
     JavaAgent,
     #[strum(to_string = "legacy-fabric")]
     LegacyFabric,
+    #[serde(rename = "liteloader")]
     #[strum(to_string = "liteloader")]
     LiteLoader,
     #[allow(clippy::enum_variant_names)]
+    #[serde(rename = "modloader")]
     #[strum(to_string = "modloader")]
     ModLoader,
+    #[serde(rename = "nilloader")]
     #[strum(to_string = "nilloader")]
     NilLoader,
     #[strum(to_string = "optifine")]
@@ -76,6 +81,11 @@ pub enum ModLoader {
     Velocity,
     #[strum(to_string = "waterfall")]
     Waterfall,
+    /// A loader that is not known to this version of the program
+    #[serde(other)]
+    #[strum(to_string = "unknown")]
+    #[value(skip)]
+    Unknown,
 }
 
 /// Minecraft version structure
@@ -92,6 +102,13 @@ pub enum MinecraftVersion {
         /// Release suffix
         suffix: MinecraftReleaseSuffix,
     },
+    /// Every patch of a release, written like `1.21.x`
+    ReleaseWildcard {
+        /// Major version number
+        major: u8,
+        /// Minor version number
+        minor: u8,
+    },
     Snapshot {
         /// The year the snapshot was published
         year: u8,
@@ -108,6 +125,56 @@ pub enum MinecraftVersion {
         /// Patch version number
         patch: Option<u8>,
     },
+    Alpha {
+        /// Major version number
+        major: u8,
+        /// Minor version number
+        minor: u8,
+        /// Patch version number
+        patch: Option<u8>,
+    },
+    /// A version that does not follow any of the other formats, such as April Fools versions
+    Other(RawVersion),
+    /// The latest release, written `latest`, looked up in Mojang's version manifest
+    LatestRelease,
+    /// The latest snapshot, written `latest-snapshot`, looked up in Mojang's version manifest
+    LatestSnapshot,
+}
+
+/// The most bytes a [RawVersion] can hold
+const RAW_VERSION_CAPACITY: usize = 40;
+
+/// A version string stored inline, so that [MinecraftVersion] can stay `Copy`
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct RawVersion {
+    len: u8,
+    bytes: [u8; RAW_VERSION_CAPACITY],
+}
+
+impl RawVersion {
+    /// Store a version string, or None if it is empty or too long
+    pub fn new(value: &str) -> Option<Self> {
+        if value.is_empty() || value.len() > RAW_VERSION_CAPACITY {
+            return None;
+        }
+        let mut bytes = [0u8; RAW_VERSION_CAPACITY];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Some(Self {
+            len: value.len() as u8,
+            bytes,
+        })
+    }
+
+    /// Get the stored version string
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).expect("RawVersion is always utf-8")
+    }
+}
+
+impl std::fmt::Debug for RawVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
@@ -131,16 +198,19 @@ impl std::fmt::Display for MinecraftVersion {
                 suffix,
             } => write!(
                 f,
-                "{}.{}.{}{}",
+                "{}.{}{}{}",
                 major,
                 minor,
-                patch.map_or_else(|| String::from("x"), |x| x.to_string()),
+                patch.map_or_else(String::new, |x| format!(".{x}")),
                 suffix
             ),
+            MinecraftVersion::ReleaseWildcard { major, minor } => {
+                write!(f, "{}.{}.x", major, minor)
+            }
             MinecraftVersion::Snapshot { year, week, ident } => {
                 write!(
                     f,
-                    "{}w{}{}",
+                    "{:02}w{:02}{}",
                     year,
                     week,
                     ident.map_or_else(
@@ -156,12 +226,28 @@ impl std::fmt::Display for MinecraftVersion {
             } => {
                 write!(
                     f,
-                    "b{}.{}.{}",
+                    "b{}.{}{}",
                     major,
                     minor,
-                    patch.map_or_else(|| String::from("x"), |x| x.to_string())
+                    patch.map_or_else(String::new, |x| format!(".{x}"))
                 )
             }
+            MinecraftVersion::Alpha {
+                major,
+                minor,
+                patch,
+            } => {
+                write!(
+                    f,
+                    "a{}.{}{}",
+                    major,
+                    minor,
+                    patch.map_or_else(String::new, |x| format!(".{x}"))
+                )
+            }
+            MinecraftVersion::Other(raw) => write!(f, "{}", raw.as_str()),
+            MinecraftVersion::LatestRelease => write!(f, "latest"),
+            MinecraftVersion::LatestSnapshot => write!(f, "latest-snapshot"),
         }
     }
 }
@@ -188,13 +274,13 @@ impl TryFrom<String> for MinecraftReleaseSuffix {
         if value.is_empty() {
             return Ok(MinecraftReleaseSuffix::None);
         }
-        let kind = value
-            .get(0..value.len() - 1)
-            .ok_or_else(|| Error::InvalidMinecraftVersion(value.clone()))?;
-        let number = value
-            .get(value.len() - 1..)
-            .and_then(|x| x.parse::<u8>().ok())
+        let (kind, number) = value
+            .find(|x: char| x.is_ascii_digit())
+            .map(|x| value.split_at(x))
             .ok_or_else(|| Error::InvalidMinecraftVersion(value.clone()))?;
+        let number = number
+            .parse::<u8>()
+            .map_err(|_| Error::InvalidMinecraftVersion(value.clone()))?;
         match kind {
             "pre" => Ok(MinecraftReleaseSuffix::PreRelease(number)),
             "rc" => Ok(MinecraftReleaseSuffix::Candidate(number)),
@@ -212,6 +298,22 @@ impl From<MinecraftVersion> for String {
 impl TryFrom<String> for MinecraftVersion {
     type Error = Error;
     fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "latest" => return Ok(MinecraftVersion::LatestRelease),
+            "latest-snapshot" => return Ok(MinecraftVersion::LatestSnapshot),
+            _ => {}
+        }
+        MinecraftVersion::parse_known(&value).or_else(|e| {
+            RawVersion::new(&value)
+                .map(MinecraftVersion::Other)
+                .ok_or(e)
+        })
+    }
+}
+
+impl MinecraftVersion {
+    /// Parse a version in one of the known release, snapshot, beta, or alpha formats
+    fn parse_known(value: &str) -> Result<Self> {
         let parts: Vec<_> = value.split(&['.', '-']).collect();
         let parse_u8 = |s: &str| -> Result<u8> {
             s.parse::<u8>()
@@ -223,8 +325,18 @@ impl TryFrom<String> for MinecraftVersion {
                 if parts.len() != 2 {
                     return Err(Error::InvalidMinecraftVersion(value.to_string()));
                 }
+                let is_snapshot = (2..=3).contains(&parts[1].len())
+                    && parts[1].chars().take(2).all(|x| x.is_ascii_digit())
+                    && parts[1].chars().skip(2).all(|x| x.is_ascii_lowercase());
+                if !is_snapshot {
+                    return Err(Error::InvalidMinecraftVersion(value.to_string()));
+                }
                 let year = parse_u8(parts[0])?;
-                let week = parse_u8(parts[1].get(0..2).expect(""))?;
+                let week = parse_u8(
+                    parts[1]
+                        .get(0..2)
+                        .ok_or_else(|| Error::InvalidMinecraftVersion(value.to_string()))?,
+                )?;
                 let ident = parts[1]
                     .matches(|x: char| x.is_ascii_alphabetic())
                     .next()
@@ -243,6 +355,22 @@ impl TryFrom<String> for MinecraftVersion {
                     patch,
                 })
             }
+            2 | 3 if value.starts_with('a') => {
+                let (major, minor) = (parse_u8(&parts[0][1..])?, parse_u8(parts[1])?);
+                let patch = match parts.get(2) {
+                    Some(x) => Some(parse_u8(x)?),
+                    None => None,
+                };
+                Ok(MinecraftVersion::Alpha {
+                    major,
+                    minor,
+                    patch,
+                })
+            }
+            3 if parts[2].eq_ignore_ascii_case("x") => Ok(MinecraftVersion::ReleaseWildcard {
+                major: parse_u8(parts[0])?,
+                minor: parse_u8(parts[1])?,
+            }),
             2..=4 => {
                 let (major, minor) = (parse_u8(parts[0])?, parse_u8(parts[1])?);
                 let (patch, suffix) = match (parts.get(2), parts.get(3)) {
@@ -250,8 +378,6 @@ impl TryFrom<String> for MinecraftVersion {
                     (Some(x), None) => {
                         if value.contains('-') {
                             (None, MinecraftReleaseSuffix::try_from(x.to_string())?)
-                        } else if x.eq_ignore_ascii_case("x") {
-                            (None, MinecraftReleaseSuffix::None)
                         } else {
                             (Some(parse_u8(x)?), MinecraftReleaseSuffix::None)
                         }
@@ -289,8 +415,175 @@ impl From<&str> for MinecraftVersion {
     }
 }
 
+/// The side of the game a project is used on, or that files are installed for
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    clap::ValueEnum,
+    strum::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Side {
+    Client,
+    Server,
+    #[default]
+    Both,
+}
+
+impl Side {
+    /// Check if a project used on this side is needed when installing for a target side
+    pub fn includes(&self, target: Side) -> bool {
+        *self == Side::Both || target == Side::Both || *self == target
+    }
+}
+
+/// Enumeration of the kinds of projects
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Clone,
+    Copy,
+    clap::ValueEnum,
+    strum::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ProjectType {
+    Mod,
+    Modpack,
+    Resourcepack,
+    Shader,
+    Datapack,
+    Plugin,
+    /// A project type that is not known to this version of the program
+    #[serde(other)]
+    #[value(skip)]
+    Unknown,
+}
+
+impl ProjectType {
+    /// Get the directory under .minecraft that files of this project type are installed into
+    pub fn install_dir(&self) -> &'static str {
+        match self {
+            ProjectType::Resourcepack => "resourcepacks",
+            ProjectType::Shader => "shaderpacks",
+            ProjectType::Datapack => "datapacks",
+            _ => "mods",
+        }
+    }
+}
+
+/// A mod loader and the project types it can load
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct LoaderInfo {
+    /// The name of the loader
+    pub name: String,
+    /// The project types the loader can load
+    pub supported_project_types: Vec<ProjectType>,
+}
+
+/// The kind of a Minecraft version in the official game version list
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameVersionType {
+    Release,
+    Snapshot,
+    Beta,
+    Alpha,
+}
+
+/// A Minecraft version from the official game version list
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GameVersion {
+    /// The version
+    pub version: MinecraftVersion,
+    /// The kind of version
+    pub version_type: GameVersionType,
+    /// Whether this is a major version
+    pub major: bool,
+}
+
+impl MinecraftVersion {
+    /// Check if this is a wildcard version like `1.21.x`, which stands for every patch of that
+    /// release
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, MinecraftVersion::ReleaseWildcard { .. })
+    }
+
+    /// Check if this is `latest` or `latest-snapshot`, which must be looked up before use
+    pub fn is_latest(&self) -> bool {
+        matches!(
+            self,
+            MinecraftVersion::LatestRelease | MinecraftVersion::LatestSnapshot
+        )
+    }
+
+    /// Get the other spelling of a release with a patch of zero, like `1.21.0` for `1.21`
+    pub fn equivalent(&self) -> Option<MinecraftVersion> {
+        match *self {
+            MinecraftVersion::Release {
+                major,
+                minor,
+                patch: None,
+                suffix,
+            } => Some(MinecraftVersion::Release {
+                major,
+                minor,
+                patch: Some(0),
+                suffix,
+            }),
+            MinecraftVersion::Release {
+                major,
+                minor,
+                patch: Some(0),
+                suffix,
+            } => Some(MinecraftVersion::Release {
+                major,
+                minor,
+                patch: None,
+                suffix,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Check if two versions are the same, treating `1.21` and `1.21.0` as equal
+    pub fn is_equivalent(&self, other: &MinecraftVersion) -> bool {
+        self == other || self.equivalent().is_some_and(|x| x == *other)
+    }
+
+    /// Check if this version is matched by a wildcard version
+    pub fn matches_wildcard(&self, wildcard: &MinecraftVersion) -> bool {
+        match (self, wildcard) {
+            (
+                MinecraftVersion::Release {
+                    major,
+                    minor,
+                    suffix: MinecraftReleaseSuffix::None,
+                    ..
+                },
+                MinecraftVersion::ReleaseWildcard {
+                    major: wildcard_major,
+                    minor: wildcard_minor,
+                },
+            ) => major == wildcard_major && minor == wildcard_minor,
+            _ => false,
+        }
+    }
+}
+
 /// An internal database of the projects and versions collected
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct ModDB {
     /// A mapping of project ids to project data
     projects: HashMap<ProjectId, ModProject>,
@@ -298,8 +591,17 @@ pub struct ModDB {
     versions: HashMap<VersionId, ModVersion>,
     /// A mapping of project slugs to project ids
     project_slugs: HashMap<ProjectSlug, ProjectId>,
-    /// A map of project ids to preferred versions
-    project_versions: HashMap<ProjectId, VersionId>,
+    /// A map of project ids and the loaders they were resolved for to preferred versions, so
+    /// that each flavor of a project, like a mod and a data pack, is resolved separately
+    project_versions: HashMap<(ProjectId, ModLoader), VersionId>,
+    /// A map of version ids to the loader they were resolved for
+    version_loaders: HashMap<VersionId, ModLoader>,
+    /// A map of version ids to why they were collected
+    provenance: HashMap<VersionId, Provenance>,
+    /// A map of file hashes of every algorithm to the versions containing the files
+    file_hashes: HashMap<String, VersionId>,
+    /// A map of file names to the versions containing the files
+    file_names: HashMap<String, VersionId>,
 }
 
 impl ModDB {
@@ -311,27 +613,15 @@ impl ModDB {
     }
     /// Insert a version into the database, and return the previous version at the same version_id
     pub fn add_version(&mut self, version: ModVersion) -> Option<ModVersion> {
-        self.versions.insert(version.version_id.clone(), version)
-    }
-    pub fn contains_key(&self, mod_link: &ModLink) -> bool {
-        match mod_link {
-            ModLink::ProjectId(x) => self.projects.contains_key(x),
-            ModLink::ProjectSlug(x) => self.project_slugs.contains_key(x),
-            ModLink::VersionId(x) => self.versions.contains_key(x),
-        }
-    }
-    pub fn remove(&mut self, mod_link: &ModLink) {
-        match mod_link {
-            ModLink::ProjectId(x) => {
-                self.projects.remove(x);
-            }
-            ModLink::ProjectSlug(x) => {
-                self.project_slugs.remove(x);
-            }
-            ModLink::VersionId(x) => {
-                self.versions.remove(x);
+        for file in &version.files {
+            for hash in file.hashes.values() {
+                self.file_hashes
+                    .insert(hash.clone(), version.version_id.clone());
             }
+            self.file_names
+                .insert(file.name.clone(), version.version_id.clone());
         }
+        self.versions.insert(version.version_id.clone(), version)
     }
     /// Get a vector of all collected versions
     pub fn get_versions(&self) -> Vec<&ModVersion> {
@@ -349,20 +639,161 @@ impl ModDB {
     pub fn get_version(&self, version_id: &VersionId) -> Option<&ModVersion> {
         self.versions.get(version_id)
     }
-    /// Set the preferred version for a project, and return the previous preferred version
+    /// Set the preferred version for a project and loader, and return the previous preferred
+    /// version
     pub fn set_preferred_version(
         &mut self,
         project_id: ProjectId,
+        loader: ModLoader,
         version_id: VersionId,
     ) -> Option<VersionId> {
-        self.project_versions.insert(project_id, version_id)
+        self.version_loaders.insert(version_id.clone(), loader);
+        self.project_versions
+            .insert((project_id, loader), version_id)
+    }
+    /// Get the loader a version was resolved for
+    pub fn get_loader(&self, version_id: &VersionId) -> Option<ModLoader> {
+        self.version_loaders.get(version_id).copied()
     }
-    /// Get the preferred version of a project by its id
-    pub fn get_preferred_by_id(&self, project_id: &ProjectId) -> Option<&ModVersion> {
+    /// Get the preferred version of a project for a loader
+    pub fn get_preferred(&self, project_id: &ProjectId, loader: ModLoader) -> Option<&ModVersion> {
         self.project_versions
-            .get(project_id)
+            .get(&(project_id.clone(), loader))
             .and_then(|x| self.versions.get(x))
     }
+    /// Check if a project has a preferred version for any loader
+    pub fn has_preferred(&self, project_id: &ProjectId) -> bool {
+        self.project_versions.keys().any(|(x, _)| x == project_id)
+    }
+    /// Get the version containing a file with a hash of any algorithm
+    pub fn get_version_by_hash(&self, hash: &str) -> Option<&ModVersion> {
+        self.versions.get(self.file_hashes.get(hash)?)
+    }
+    /// Get the version containing a file with a name
+    pub fn get_version_by_filename(&self, filename: &str) -> Option<&ModVersion> {
+        self.versions.get(self.file_names.get(filename)?)
+    }
+    /// Replace a collected version with another version of the same project, which becomes
+    /// preferred wherever the old version was and keeps the reasons it was collected
+    pub fn replace_version(&mut self, old: &VersionId, version: ModVersion) {
+        self.versions.remove(old);
+        self.file_hashes.retain(|_, x| x != old);
+        self.file_names.retain(|_, x| x != old);
+        if let Some(old) = self.provenance.remove(old) {
+            let provenance = self
+                .provenance
+                .entry(version.version_id.clone())
+                .or_default();
+            for reason in old.reasons {
+                if !provenance.reasons.contains(&reason) {
+                    provenance.reasons.push(reason);
+                }
+            }
+            provenance.note = provenance.note.take().or(old.note);
+        }
+        let (old_edge, new_edge) = (
+            Reason::DependencyOf(old.clone()),
+            Reason::DependencyOf(version.version_id.clone()),
+        );
+        for provenance in self.provenance.values_mut() {
+            let reasons = &mut provenance.reasons;
+            if let Some(index) = reasons.iter().position(|x| *x == old_edge) {
+                match reasons.contains(&new_edge) {
+                    true => _ = reasons.remove(index),
+                    false => reasons[index] = new_edge.clone(),
+                }
+            }
+        }
+        for preferred in self.project_versions.values_mut() {
+            if preferred == old {
+                *preferred = version.version_id.clone();
+            }
+        }
+        if let Some(loader) = self.version_loaders.remove(old) {
+            self.version_loaders
+                .insert(version.version_id.clone(), loader);
+        }
+        self.add_version(version);
+    }
+    /// Record a reason that a version was collected, ignoring repeated reasons
+    pub fn add_reason(&mut self, version_id: &VersionId, reason: Reason) {
+        let provenance = self.provenance.entry(version_id.clone()).or_default();
+        if !provenance.reasons.contains(&reason) {
+            provenance.reasons.push(reason);
+        }
+    }
+    /// Record the note the config gives for a version
+    pub fn set_note(&mut self, version_id: &VersionId, note: String) {
+        self.provenance.entry(version_id.clone()).or_default().note = Some(note);
+    }
+    /// Record the registry a version was found in
+    pub fn set_registry(&mut self, version_id: &VersionId, registry: Registry) {
+        self.provenance
+            .entry(version_id.clone())
+            .or_default()
+            .registry = registry;
+    }
+    /// Get why a version was collected and where it came from
+    pub fn get_provenance(&self, version_id: &VersionId) -> Option<&Provenance> {
+        self.provenance.get(version_id)
+    }
+    /// Get the collected versions that depend on a version, in the order they were found
+    pub fn get_dependents(&self, version_id: &VersionId) -> Vec<&ModVersion> {
+        self.get_provenance(version_id)
+            .map_or(&[][..], |x| x.reasons.as_slice())
+            .iter()
+            .filter_map(|x| match x {
+                Reason::DependencyOf(x) => self.get_version(x),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Why a version was collected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The project is in the projects of the config
+    Configured,
+    /// The project is in the optional projects of the config
+    Optional,
+    /// The config pins the project to this version
+    Pinned,
+    /// Another version depends on this one
+    DependencyOf(VersionId),
+}
+
+/// The name of the registry backend a version was found in, like `modrinth`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Registry(String);
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self("modrinth".into())
+    }
+}
+
+impl From<&str> for Registry {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Why a version was collected and where it came from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Provenance {
+    /// Every reason the version was collected, in the order they were found
+    pub reasons: Vec<Reason>,
+    /// The registry the version was found in
+    pub registry: Registry,
+    /// Why the config includes the project
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -372,6 +803,16 @@ pub enum ModLink {
     VersionId(VersionId),
 }
 
+impl std::fmt::Display for ModLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModLink::ProjectId(x) => write!(f, "project {x}"),
+            ModLink::ProjectSlug(x) => write!(f, "project {x}"),
+            ModLink::VersionId(x) => write!(f, "version {x}"),
+        }
+    }
+}
+
 impl From<ProjectId> for ModLink {
     fn from(value: ProjectId) -> Self {
         Self::ProjectId(value)
@@ -477,22 +918,46 @@ impl std::fmt::Display for VersionId {
     }
 }
 
-#[derive(Debug)]
+/// How popular a project is and when it was last updated, as reported by its registry
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectStats {
+    /// How many times the files of the project were downloaded
+    pub downloads: u64,
+    /// How many users follow the project
+    pub followers: u64,
+    /// When any version of the project was last published or the project was last edited
+    pub updated: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
 pub struct ModProject {
     pub project_id: ProjectId,
     pub name: String,
     pub slug: ProjectSlug,
     // pub version_ids: Vec<VersionId>,
-    // pub game_versions: Vec<MinecraftVersion>,
+    /// The summary of the project
+    pub description: String,
+    /// The game versions that any version of the project supports, oldest first
+    pub game_versions: Vec<MinecraftVersion>,
     pub loaders: Vec<ModLoader>,
+    pub side: Side,
+    pub license: Option<String>,
+    pub icon_url: Option<String>,
+    /// The categories the registry lists the project under, like `optimization`
+    pub categories: Vec<String>,
+    /// How popular the project is, if the registry reports it
+    pub stats: Option<ProjectStats>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModVersion {
     pub project_id: ProjectId,
     pub version_id: VersionId,
     pub name: String,
-    #[cfg(test)]
+    pub version_number: String,
+    pub changelog: Option<String>,
+    /// Whether the author marked the version as featured, usually a stable recommended build
+    pub featured: bool,
     pub game_versions: Vec<MinecraftVersion>,
     pub loaders: Vec<ModLoader>,
     pub files: Vec<ModFile>,
@@ -500,19 +965,162 @@ pub struct ModVersion {
     pub date_published: chrono::NaiveDateTime,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModFile {
     pub url: String,
     pub name: String,
+    /// The hashes of the file by algorithm, like `sha1` and `sha512`
+    pub hashes: std::collections::BTreeMap<String, String>,
+    /// The size of the file in bytes
+    pub size: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_file_indexes() {
+        let mut mod_db = ModDB::default();
+        mod_db.add_version(ModVersion {
+            project_id: String::from("P").into(),
+            version_id: String::from("V").into(),
+            name: "Sodium".into(),
+            version_number: "0.6".into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files: vec![ModFile {
+                url: "https://cdn.modrinth.com/sodium.jar".into(),
+                name: "sodium.jar".into(),
+                hashes: [
+                    ("sha1".into(), "aaaa".into()),
+                    ("sha512".into(), "bbbb".into()),
+                ]
+                .into(),
+                size: 1,
+            }],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        });
+        let found = |x: Option<&ModVersion>| x.map(|x| x.version_id.to_string());
+        assert_eq!(found(mod_db.get_version_by_hash("aaaa")), Some("V".into()));
+        assert_eq!(
+            found(mod_db.get_version_by_hash("bbbb")),
+            Some("V".into()),
+            "ModDB shall index every hash of a file"
+        );
+        assert_eq!(
+            found(mod_db.get_version_by_filename("sodium.jar")),
+            Some("V".into()),
+            "ModDB shall index file names"
+        );
+        assert_eq!(found(mod_db.get_version_by_hash("cccc")), None);
+    }
+
+    #[test]
+    fn test_replace_version() {
+        let version = |version_id: &str| ModVersion {
+            project_id: String::from("P").into(),
+            version_id: String::from(version_id).into(),
+            name: "Sodium".into(),
+            version_number: version_id.into(),
+            changelog: None,
+            featured: false,
+            game_versions: vec![],
+            loaders: vec![ModLoader::Fabric],
+            files: vec![ModFile {
+                url: String::new(),
+                name: format!("sodium-{version_id}.jar"),
+                hashes: Default::default(),
+                size: 1,
+            }],
+            dependencies: vec![],
+            date_published: chrono::NaiveDateTime::default(),
+        };
+        let (old, new) = (
+            VersionId::from(String::from("A")),
+            VersionId::from(String::from("B")),
+        );
+        let mut mod_db = ModDB::default();
+        mod_db.add_version(version("A"));
+        mod_db.set_preferred_version(String::from("P").into(), ModLoader::Fabric, old.clone());
+        mod_db.add_reason(&old, Reason::Configured);
+        let dependency = VersionId::from(String::from("D"));
+        mod_db.add_reason(&dependency, Reason::DependencyOf(old.clone()));
+        mod_db.replace_version(&old, version("B"));
+        assert_eq!(
+            mod_db.get_versions().len(),
+            1,
+            "ModDB shall remove the replaced version"
+        );
+        assert_eq!(
+            mod_db
+                .get_preferred(&String::from("P").into(), ModLoader::Fabric)
+                .map(|x| x.version_id.clone()),
+            Some(new.clone())
+        );
+        assert_eq!(
+            mod_db.get_loader(&new),
+            Some(ModLoader::Fabric),
+            "ModDB shall keep the loader the replaced version was resolved for"
+        );
+        assert_eq!(
+            mod_db.get_provenance(&new).map(|x| x.reasons.clone()),
+            Some(vec![Reason::Configured]),
+            "ModDB shall keep why the replaced version was collected"
+        );
+        assert_eq!(
+            mod_db
+                .get_provenance(&dependency)
+                .map(|x| x.reasons.clone()),
+            Some(vec![Reason::DependencyOf(new.clone())]),
+            "ModDB shall move the dependencies of the replaced version to the new version"
+        );
+        assert!(
+            mod_db
+                .get_preferred(&String::from("P").into(), ModLoader::Datapack)
+                .is_none(),
+            "ModDB shall key preferred versions by loader"
+        );
+        assert!(mod_db.get_version_by_filename("sodium-A.jar").is_none());
+    }
+
+    #[test]
+    fn test_provenance() {
+        let mut mod_db = ModDB::default();
+        let version_id = VersionId::from(String::from("AAAA"));
+        let dependent = VersionId::from(String::from("BBBB"));
+        mod_db.add_reason(&version_id, Reason::Configured);
+        mod_db.add_reason(&version_id, Reason::DependencyOf(dependent.clone()));
+        mod_db.add_reason(&version_id, Reason::Configured);
+        let provenance = mod_db
+            .get_provenance(&version_id)
+            .expect("ModDB shall record provenance");
+        assert_eq!(
+            provenance.reasons,
+            vec![Reason::Configured, Reason::DependencyOf(dependent)],
+            "ModDB shall record each reason once"
+        );
+        assert_eq!(provenance.registry.to_string(), "modrinth");
+        mod_db.set_registry(&version_id, "local".into());
+        assert_eq!(
+            mod_db
+                .get_provenance(&version_id)
+                .map(|x| x.registry.clone()),
+            Some(Registry::from("local")),
+            "ModDB shall record the registry"
+        );
+        assert!(
+            mod_db.get_dependents(&version_id).is_empty(),
+            "ModDB shall only list dependents that were collected"
+        );
+    }
+
     #[test]
     fn test_version_full() {
-        let parsed = MinecraftVersion::try_from("1.23.4")
+        let parsed = MinecraftVersion::try_from(String::from("1.23.4"))
             .expect("MinecraftVersion shall be able to parse a version string");
         assert_eq!(
             parsed,
@@ -527,7 +1135,7 @@ mod tests {
 
     #[test]
     fn test_version_rc() {
-        let parsed = MinecraftVersion::try_from("1.23.4-rc5").expect(
+        let parsed = MinecraftVersion::try_from(String::from("1.23.4-rc5")).expect(
             "MinecraftVersion shall be able to parse a version string of a release candidate",
         );
         assert_eq!(
@@ -543,21 +1151,19 @@ mod tests {
 
     #[test]
     fn test_version_patch_x() {
-        let parsed = MinecraftVersion::try_from("1.23.x").expect("MinecraftVersion shall be able to parse a version string where the patch version is 'x'");
+        let parsed = MinecraftVersion::try_from(String::from("1.23.x")).expect("MinecraftVersion shall be able to parse a version string where the patch version is 'x'");
         assert_eq!(
             parsed,
-            MinecraftVersion::Release {
+            MinecraftVersion::ReleaseWildcard {
                 major: 1,
                 minor: 23,
-                patch: None,
-                suffix: MinecraftReleaseSuffix::None,
             }
         );
     }
 
     #[test]
     fn test_version_patch_none() {
-        let parsed = MinecraftVersion::try_from("1.23").expect("MinecraftVersion shall be able to parse a version string where the patch version is not given");
+        let parsed = MinecraftVersion::try_from(String::from("1.23")).expect("MinecraftVersion shall be able to parse a version string where the patch version is not given");
         assert_eq!(
             parsed,
             MinecraftVersion::Release {
@@ -571,7 +1177,7 @@ mod tests {
 
     #[test]
     fn test_version_pre() {
-        let parsed = MinecraftVersion::try_from("1.23.4-pre5").expect("MinecraftVersion shall be able to parse a version string where the patch version is not given");
+        let parsed = MinecraftVersion::try_from(String::from("1.23.4-pre5")).expect("MinecraftVersion shall be able to parse a version string where the patch version is not given");
         assert_eq!(
             parsed,
             MinecraftVersion::Release {
@@ -585,7 +1191,7 @@ mod tests {
 
     #[test]
     fn test_version_snapshot_twodigit() {
-        let parsed = MinecraftVersion::try_from("12w34a")
+        let parsed = MinecraftVersion::try_from(String::from("12w34a"))
             .expect("MinecraftVersion shal lbe able to parse a snapshot version string");
         assert_eq!(
             parsed,
@@ -599,7 +1205,7 @@ mod tests {
 
     #[test]
     fn test_version_snapshot_onedigit() {
-        let parsed = MinecraftVersion::try_from("12w03a")
+        let parsed = MinecraftVersion::try_from(String::from("12w03a"))
             .expect("MinecraftVersion shal lbe able to parse a snapshot version string");
         assert_eq!(
             parsed,
@@ -613,7 +1219,7 @@ mod tests {
 
     #[test]
     fn test_version_snapshot_noident() {
-        let parsed = MinecraftVersion::try_from("12w34")
+        let parsed = MinecraftVersion::try_from(String::from("12w34"))
             .expect("MinecraftVersion shal lbe able to parse a snapshot version string");
         assert_eq!(
             parsed,
@@ -624,4 +1230,117 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_version_alpha() {
+        let parsed = MinecraftVersion::try_from(String::from("a1.2.6"))
+            .expect("MinecraftVersion shall be able to parse an alpha version string");
+        assert_eq!(
+            parsed,
+            MinecraftVersion::Alpha {
+                major: 1,
+                minor: 2,
+                patch: Some(6),
+            }
+        )
+    }
+
+    #[test]
+    fn test_version_other() {
+        for value in [
+            "3D Shareware v1.34",
+            "1.RV-Pre1",
+            "20w14infinite",
+            "23w13a_or_b",
+            "b1.1_02",
+            "23w1",
+            "1w",
+        ] {
+            let parsed = MinecraftVersion::try_from(String::from(value))
+                .expect("MinecraftVersion shall be able to parse any version string");
+            assert!(
+                matches!(parsed, MinecraftVersion::Other(_)),
+                "MinecraftVersion shall fall back to a raw version for {value:?}"
+            );
+            assert_eq!(
+                parsed.to_string(),
+                value,
+                "MinecraftVersion shall format a raw version unchanged"
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_equivalent() {
+        let short = MinecraftVersion::from("1.21");
+        let long = MinecraftVersion::from("1.21.0");
+        assert!(
+            short.is_equivalent(&long) && long.is_equivalent(&short),
+            "MinecraftVersion shall treat a missing patch as zero"
+        );
+        assert!(
+            !short.is_equivalent(&MinecraftVersion::from("1.21.1")),
+            "MinecraftVersion shall not treat other patches as equivalent"
+        );
+        assert!(
+            !short.is_equivalent(&MinecraftVersion::from("1.21-pre1")),
+            "MinecraftVersion shall not treat other suffixes as equivalent"
+        );
+    }
+
+    #[test]
+    fn test_version_latest() {
+        assert_eq!(
+            MinecraftVersion::from("latest"),
+            MinecraftVersion::LatestRelease,
+            "MinecraftVersion shall parse the latest release keyword"
+        );
+        assert!(MinecraftVersion::from("latest-snapshot").is_latest());
+        assert!(!MinecraftVersion::from("1.21.5").is_latest());
+    }
+
+    #[test]
+    fn test_version_round_trip() {
+        for value in [
+            "1.21.5",
+            "1.21",
+            "1.21.x",
+            "1.20.5-rc1",
+            "1.16.5-rc10",
+            "1.21-pre1",
+            "1.14.3-pre4",
+            "2.0",
+            "25w14a",
+            "24w03b",
+            "13w01a",
+            "09w05a",
+            "12w34",
+            "b1.7.3",
+            "b1.7",
+            "b1.1_02",
+            "a1.2.6",
+            "a1.0.4",
+            "c0.30_01c",
+            "inf-20100618",
+            "rd-132211",
+            "3D Shareware v1.34",
+            "1.RV-Pre1",
+            "20w14infinite",
+            "22w13oneblockatatime",
+            "23w13a_or_b",
+            "24w14potato",
+            "25w14craftmine",
+            "1.14_combat-212796",
+            "latest",
+            "latest-snapshot",
+        ] {
+            let parsed = MinecraftVersion::try_from(String::from(value))
+                .expect("MinecraftVersion shall be able to parse any version string");
+            assert_eq!(
+                parsed.to_string(),
+                value,
+                "MinecraftVersion shall format exactly the string it parsed"
+            );
+        }
+    }
 }
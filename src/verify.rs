@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use sha1::Digest;
+
+use crate::error::{Error, Result};
+
+/// The hashes of a file, in the algorithms used by Modrinth
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+/// An algorithm that Modrinth identifies files by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha1,
+    #[default]
+    Sha512,
+}
+
+impl FileHashes {
+    /// Get the hash in an algorithm
+    pub fn get(&self, algorithm: HashAlgorithm) -> &str {
+        match algorithm {
+            HashAlgorithm::Sha1 => &self.sha1,
+            HashAlgorithm::Sha512 => &self.sha512,
+        }
+    }
+
+    /// Check if these hashes match the expected hashes, preferring sha512. Returns None if none
+    /// of the expected algorithms are known.
+    pub fn matches(&self, expected: &BTreeMap<String, String>) -> Option<bool> {
+        if let Some(x) = expected.get("sha512") {
+            Some(x.eq_ignore_ascii_case(&self.sha512))
+        } else {
+            expected
+                .get("sha1")
+                .map(|x| x.eq_ignore_ascii_case(&self.sha1))
+        }
+    }
+}
+
+/// Hash a file in one pass
+pub fn hash_file(path: &Path) -> Result<FileHashes> {
+    let mut file = std::fs::File::open(path)?;
+    let mut sha1 = sha1::Sha1::new();
+    let mut sha512 = sha2::Sha512::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        sha1.update(&buffer[..count]);
+        sha512.update(&buffer[..count]);
+    }
+    Ok(FileHashes {
+        sha1: format!("{:x}", sha1.finalize()),
+        sha512: format!("{:x}", sha512.finalize()),
+    })
+}
+
+/// How many bytes from the start and from the end of a file are hashed for its sample hash
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Hash the size of a file with its first and last bytes, which is much faster than hashing
+/// large files in full and still catches truncated or overwritten files
+pub fn sample_hash(path: &Path) -> Result<String> {
+    use std::io::{Seek, SeekFrom};
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = Vec::new();
+    (&mut file).take(SAMPLE_SIZE).read_to_end(&mut head)?;
+    let mut tail = Vec::new();
+    if size > SAMPLE_SIZE {
+        file.seek(SeekFrom::Start(tail_start(size)))?;
+        file.read_to_end(&mut tail)?;
+    }
+    Ok(sample_digest(size, &head, &tail))
+}
+
+/// Get the sample hash of bytes in memory, equal to that of a file with the bytes
+pub fn sample_hash_bytes(bytes: &[u8]) -> String {
+    let size = bytes.len() as u64;
+    let head = &bytes[..SAMPLE_SIZE.min(size) as usize];
+    let tail = match size > SAMPLE_SIZE {
+        true => &bytes[tail_start(size) as usize..],
+        false => &[],
+    };
+    sample_digest(size, head, tail)
+}
+
+/// Get where the sampled end of a file starts, after its sampled start
+fn tail_start(size: u64) -> u64 {
+    SAMPLE_SIZE.max(size.saturating_sub(SAMPLE_SIZE))
+}
+
+/// Hash the size and sampled bytes of a file
+fn sample_digest(size: u64, head: &[u8], tail: &[u8]) -> String {
+    let mut sha1 = sha1::Sha1::new();
+    sha1.update(size.to_le_bytes());
+    sha1.update(head);
+    sha1.update(tail);
+    format!("{:x}", sha1.finalize())
+}
+
+/// Hash bytes in memory
+pub fn hash_bytes(bytes: &[u8]) -> FileHashes {
+    FileHashes {
+        sha1: format!("{:x}", sha1::Sha1::digest(bytes)),
+        sha512: format!("{:x}", sha2::Sha512::digest(bytes)),
+    }
+}
+
+/// The state of a file compared to its expected hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum FileState {
+    /// The file matches its expected hashes
+    Ok,
+    /// The file exists but its contents differ
+    Modified,
+    /// The file does not exist
+    Missing,
+    /// There is no known hash to compare the file to
+    Unknown,
+}
+
+/// A file to check against its expected hashes
+#[derive(Debug, Clone)]
+pub struct FileCheck {
+    pub path: PathBuf,
+    pub expected: BTreeMap<String, String>,
+}
+
+impl FileCheck {
+    /// Hash the file and compare it to the expected hashes
+    fn run(&self) -> Result<FileState> {
+        if !self.path.is_file() {
+            return Ok(FileState::Missing);
+        }
+        let hashes = hash_file(&self.path)?;
+        Ok(match hashes.matches(&self.expected) {
+            Some(true) => FileState::Ok,
+            Some(false) => FileState::Modified,
+            None => FileState::Unknown,
+        })
+    }
+}
+
+/// Check many files in parallel with a number of worker threads. Zero workers uses one per CPU.
+pub fn check_files(checks: &[FileCheck], workers: usize) -> Result<Vec<FileState>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map_err(|e| Error::IO(std::io::Error::other(e)))?;
+    pool.install(|| checks.par_iter().map(FileCheck::run).collect())
+}
+
+/// Hash many files in parallel with a number of worker threads. Zero workers uses one per CPU.
+pub fn hash_files(paths: &[PathBuf], workers: usize) -> Result<Vec<FileHashes>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map_err(|e| Error::IO(std::io::Error::other(e)))?;
+    pool.install(|| paths.par_iter().map(|x| hash_file(x)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_files() {
+        let dir = PathBuf::from(".test/verify");
+        std::fs::create_dir_all(&dir).expect("Failure to create test path");
+        std::fs::write(dir.join("a.txt"), "abc").expect("Failure to write test file");
+        std::fs::write(dir.join("b.txt"), "abd").expect("Failure to write test file");
+        let hashes = hash_file(&dir.join("a.txt")).expect("Files shall be hashed");
+        assert_eq!(hashes.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        let expected = BTreeMap::from([("sha1".to_string(), hashes.sha1.clone())]);
+        let check = |name: &str, expected: &BTreeMap<String, String>| FileCheck {
+            path: dir.join(name),
+            expected: expected.clone(),
+        };
+        let states = check_files(
+            &[
+                check("a.txt", &expected),
+                check("b.txt", &expected),
+                check("c.txt", &expected),
+                check("a.txt", &BTreeMap::new()),
+            ],
+            2,
+        )
+        .expect("Files shall be checked");
+        assert_eq!(
+            states,
+            vec![
+                FileState::Ok,
+                FileState::Modified,
+                FileState::Missing,
+                FileState::Unknown
+            ],
+            "Each file shall be compared to its expected hash"
+        );
+    }
+}
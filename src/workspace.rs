@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+/// The workspace file loaded when none is given
+pub const DEFAULT_PATH: &str = "./mcmod-workspace.toml";
+
+/// Several configs managed together, like one per server of a network
+#[derive(Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Workspace {
+    /// The paths of the configs, relative to the workspace file
+    pub configs: Vec<PathBuf>,
+
+    /// The metadata cache directory shared by the configs, relative to the workspace file. If
+    /// omitted, the configs share the metadata cache of the first config.
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Workspace {
+    /// Load a workspace file, making its paths relative to the working directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut workspace = toml::from_str::<Self>(&std::fs::read_to_string(path)?)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for config in &mut workspace.configs {
+            *config = dir.join(&*config);
+        }
+        workspace.cache_dir = workspace.cache_dir.map(|x| dir.join(x));
+        Ok(workspace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_workspace() {
+        let dir = Path::new(".test/workspace");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).expect("Failure to create test dir");
+        let path = dir.join("mcmod-workspace.toml");
+        std::fs::write(
+            &path,
+            "configs = [\"lobby/mcmod.toml\", \"survival.toml\"]\ncache-dir = \"cache\"\n",
+        )
+        .expect("Failure to write workspace");
+        assert_eq!(
+            Workspace::load(&path).expect("Workspace shall load"),
+            Workspace {
+                configs: vec![dir.join("lobby/mcmod.toml"), dir.join("survival.toml")],
+                cache_dir: Some(dir.join("cache")),
+            },
+            "Workspace paths shall be relative to the workspace file"
+        );
+    }
+}
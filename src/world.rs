@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::types::{MinecraftVersion, Side};
+
+/// How deeply lists and compounds may be nested in an NBT file, far deeper than level.dat is
+const MAX_NBT_DEPTH: usize = 128;
+
+/// The data version of each release since 1.9, when data versions were added, oldest first
+const DATA_VERSIONS: &[(&str, i32)] = &[
+    ("1.9", 169),
+    ("1.9.1", 175),
+    ("1.9.2", 176),
+    ("1.9.3", 183),
+    ("1.9.4", 184),
+    ("1.10", 510),
+    ("1.10.1", 511),
+    ("1.10.2", 512),
+    ("1.11", 819),
+    ("1.11.1", 921),
+    ("1.11.2", 922),
+    ("1.12", 1139),
+    ("1.12.1", 1241),
+    ("1.12.2", 1343),
+    ("1.13", 1519),
+    ("1.13.1", 1628),
+    ("1.13.2", 1631),
+    ("1.14", 1952),
+    ("1.14.1", 1957),
+    ("1.14.2", 1963),
+    ("1.14.3", 1968),
+    ("1.14.4", 1976),
+    ("1.15", 2225),
+    ("1.15.1", 2227),
+    ("1.15.2", 2230),
+    ("1.16", 2566),
+    ("1.16.1", 2567),
+    ("1.16.2", 2578),
+    ("1.16.3", 2580),
+    ("1.16.4", 2584),
+    ("1.16.5", 2586),
+    ("1.17", 2724),
+    ("1.17.1", 2730),
+    ("1.18", 2860),
+    ("1.18.1", 2865),
+    ("1.18.2", 2975),
+    ("1.19", 3105),
+    ("1.19.1", 3117),
+    ("1.19.2", 3120),
+    ("1.19.3", 3218),
+    ("1.19.4", 3337),
+    ("1.20", 3463),
+    ("1.20.1", 3465),
+    ("1.20.2", 3578),
+    ("1.20.3", 3698),
+    ("1.20.4", 3700),
+    ("1.20.5", 3837),
+    ("1.20.6", 3839),
+    ("1.21", 3953),
+    ("1.21.1", 3955),
+    ("1.21.2", 4080),
+    ("1.21.3", 4082),
+    ("1.21.4", 4189),
+    ("1.21.5", 4325),
+    ("1.21.6", 4435),
+    ("1.21.7", 4438),
+    ("1.21.8", 4440),
+    ("1.21.9", 4554),
+    ("1.21.10", 4556),
+];
+
+/// Get the data version of a release of the game, if it is known
+pub fn data_version(version: &MinecraftVersion) -> Option<i32> {
+    DATA_VERSIONS
+        .iter()
+        .find(|(name, _)| MinecraftVersion::from(*name).is_equivalent(version))
+        .map(|(_, x)| *x)
+}
+
+/// A value in an NBT file, like level.dat
+#[derive(Debug, Clone, PartialEq)]
+enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Get a member of a compound tag
+    fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(x) => x.get(name),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the big-endian NBT format, stopping at the end of its input
+struct NbtReader<'a> {
+    bytes: &'a [u8],
+    /// How many lists and compounds the reader is inside of
+    depth: usize,
+}
+
+impl<'a> NbtReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, depth: 0 }
+    }
+
+    /// Read a list or compound, failing if it is nested too deeply
+    fn nested(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> std::result::Result<Tag, String>,
+    ) -> std::result::Result<Tag, String> {
+        if self.depth >= MAX_NBT_DEPTH {
+            return Err("NBT is nested too deeply".into());
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Take the next bytes, failing at the end of the input
+    fn take(&mut self, count: usize) -> std::result::Result<&'a [u8], String> {
+        if self.bytes.len() < count {
+            return Err("unexpected end of NBT".into());
+        }
+        let (result, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(result)
+    }
+
+    fn array<const N: usize>(&mut self) -> std::result::Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("Took exactly N bytes"))
+    }
+
+    /// Read a length, which is never negative
+    fn length(&mut self) -> std::result::Result<usize, String> {
+        usize::try_from(i32::from_be_bytes(self.array()?)).map_err(|_| "negative length".into())
+    }
+
+    fn string(&mut self) -> std::result::Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    /// Read the payload of a tag of a type
+    fn payload(&mut self, tag_type: u8) -> std::result::Result<Tag, String> {
+        Ok(match tag_type {
+            1 => Tag::Byte(i8::from_be_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(i32::from_be_bytes(self.array()?)),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.length()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|x| *x as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => self.nested(|reader| {
+                let item_type = reader.array::<1>()?[0];
+                let len = reader.length()?;
+                Ok(Tag::List(
+                    (0..len)
+                        .map(|_| reader.payload(item_type))
+                        .collect::<std::result::Result<_, _>>()?,
+                ))
+            })?,
+            10 => self.nested(|reader| {
+                let mut members = HashMap::new();
+                loop {
+                    let member_type = reader.array::<1>()?[0];
+                    if member_type == 0 {
+                        break;
+                    }
+                    let name = reader.string()?;
+                    members.insert(name, reader.payload(member_type)?);
+                }
+                Ok(Tag::Compound(members))
+            })?,
+            11 => {
+                let len = self.length()?;
+                Tag::IntArray(
+                    (0..len)
+                        .map(|_| Ok(i32::from_be_bytes(self.array()?)))
+                        .collect::<std::result::Result<_, String>>()?,
+                )
+            }
+            12 => {
+                let len = self.length()?;
+                Tag::LongArray(
+                    (0..len)
+                        .map(|_| Ok(i64::from_be_bytes(self.array()?)))
+                        .collect::<std::result::Result<_, String>>()?,
+                )
+            }
+            x => return Err(format!("unknown NBT tag type {x}")),
+        })
+    }
+
+    /// Read the named root tag of a file
+    fn root(&mut self) -> std::result::Result<Tag, String> {
+        let tag_type = self.array::<1>()?[0];
+        self.string()?;
+        self.payload(tag_type)
+    }
+}
+
+/// The version of the game a world was last saved with, from its level.dat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldVersion {
+    /// The data version, which increases with every version of the game
+    pub data_version: i32,
+    /// The name of the version, like `1.21.5`. Worlds older than 1.9 do not have it.
+    pub name: Option<String>,
+}
+
+impl WorldVersion {
+    /// Read the version from the gzipped contents of level.dat
+    fn from_level_dat(bytes: &[u8]) -> std::result::Result<Self, String> {
+        let mut data = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+        let root = NbtReader::new(&data).root()?;
+        let level = root.get("Data").ok_or("level.dat has no Data")?;
+        let data_version = match level.get("DataVersion") {
+            Some(Tag::Int(x)) => *x,
+            _ => 0,
+        };
+        let name = match level.get("Version").and_then(|x| x.get("Name")) {
+            Some(Tag::String(x)) => Some(x.clone()),
+            _ => None,
+        };
+        Ok(Self { data_version, name })
+    }
+
+    /// Get the game version the world was last saved with, if it is known
+    pub fn game_version(&self) -> Option<MinecraftVersion> {
+        self.name
+            .as_ref()
+            .and_then(|x| MinecraftVersion::try_from(x.clone()).ok())
+    }
+
+    /// Check if the world was saved with a version older than a release, by their data versions.
+    /// Returns None if the data version of the release is not known.
+    pub fn is_older(&self, version: &MinecraftVersion) -> Option<bool> {
+        data_version(version).map(|x| self.data_version < x)
+    }
+}
+
+/// Get the name of the world a server loads, from `level-name` in its server.properties, or else
+/// `world`
+pub fn level_name(server_dir: &Path) -> String {
+    std::fs::read_to_string(server_dir.join("server.properties"))
+        .ok()
+        .and_then(|text| {
+            text.lines()
+                .filter(|x| !x.trim_start().starts_with(['#', '!']))
+                .filter_map(|x| x.split_once('='))
+                .find(|(key, _)| key.trim() == "level-name")
+                .map(|(_, value)| value.trim().to_string())
+        })
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| "world".into())
+}
+
+/// Get the directory data packs are installed into for a target side, relative to the install
+/// directory. The server always loads one world, named by `world` or its server.properties, and
+/// the client loads a world under `saves` if `world` names one.
+pub fn install_world_dir(target: Side, install_dir: &Path, world: Option<&str>) -> Option<PathBuf> {
+    match target {
+        Side::Server => Some(PathBuf::from(
+            world.map_or_else(|| level_name(install_dir), str::to_string),
+        )),
+        _ => world.map(|x| Path::new("saves").join(x)),
+    }
+}
+
+/// Read the version of the game a world was last saved with
+pub fn load_version(world_dir: &Path) -> Result<WorldVersion> {
+    let path = world_dir.join("level.dat");
+    if !world_dir.is_dir() {
+        return Err(Error::MissingDirectory(world_dir.to_path_buf()));
+    }
+    WorldVersion::from_level_dat(&std::fs::read(&path)?)
+        .map_err(|reason| Error::InvalidWorld { path, reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Write a level.dat like the game does, with the data version and version name
+    fn write_level_dat(world_dir: &Path, data_version: i32, name: &str) {
+        let string = |x: &str| [&(x.len() as u16).to_be_bytes()[..], x.as_bytes()].concat();
+        let mut nbt = vec![10];
+        nbt.extend(string(""));
+        nbt.push(10);
+        nbt.extend(string("Data"));
+        nbt.push(1);
+        nbt.extend(string("hardcore"));
+        nbt.push(0);
+        nbt.push(3);
+        nbt.extend(string("DataVersion"));
+        nbt.extend(data_version.to_be_bytes());
+        nbt.push(9);
+        nbt.extend(string("ServerBrands"));
+        nbt.push(8);
+        nbt.extend(1i32.to_be_bytes());
+        nbt.extend(string("vanilla"));
+        nbt.push(10);
+        nbt.extend(string("Version"));
+        nbt.push(8);
+        nbt.extend(string("Name"));
+        nbt.extend(string(name));
+        nbt.push(0);
+        nbt.push(0);
+        nbt.push(0);
+        std::fs::create_dir_all(world_dir).expect("Failure to create world");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(&nbt)
+            .expect("Failure to compress level.dat");
+        std::fs::write(
+            world_dir.join("level.dat"),
+            encoder.finish().expect("Failure to compress level.dat"),
+        )
+        .expect("Failure to write level.dat");
+    }
+
+    #[test]
+    fn test_install_world_dir() {
+        let server = Path::new(".test/world/server");
+        std::fs::create_dir_all(server).expect("Failure to create server");
+        let _ = std::fs::remove_file(server.join("server.properties"));
+        assert_eq!(
+            install_world_dir(Side::Server, server, None),
+            Some(PathBuf::from("world")),
+            "The server shall load the world named world by default"
+        );
+        std::fs::write(
+            server.join("server.properties"),
+            "#Minecraft server properties\nlevel-name=survival\nmotd=A Minecraft Server\n",
+        )
+        .expect("Failure to write server.properties");
+        assert_eq!(
+            install_world_dir(Side::Server, server, None),
+            Some(PathBuf::from("survival")),
+            "The server shall load the world named in server.properties"
+        );
+        assert_eq!(
+            install_world_dir(Side::Server, server, Some("creative")),
+            Some(PathBuf::from("creative"))
+        );
+        assert_eq!(
+            install_world_dir(Side::Client, server, Some("New World")),
+            Some(PathBuf::from("saves/New World"))
+        );
+        assert_eq!(install_world_dir(Side::Client, server, None), None);
+    }
+
+    #[test]
+    fn test_load_version() {
+        let world = Path::new(".test/world/saves/New World");
+        write_level_dat(world, 4325, "1.21.5");
+        assert_eq!(
+            load_version(world).expect("The world version shall be read"),
+            WorldVersion {
+                data_version: 4325,
+                name: Some("1.21.5".into())
+            }
+        );
+        std::fs::write(world.join("level.dat"), b"not gzip").expect("Failure to write level.dat");
+        assert!(
+            matches!(load_version(world), Err(Error::InvalidWorld { .. })),
+            "A corrupt level.dat shall be reported"
+        );
+        assert!(matches!(
+            load_version(Path::new(".test/world/saves/Missing")),
+            Err(Error::MissingDirectory(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_older() {
+        let world = WorldVersion {
+            data_version: 4189,
+            name: Some("1.21.4".into()),
+        };
+        assert_eq!(world.is_older(&"1.21.5".into()), Some(true));
+        assert_eq!(world.is_older(&"1.21.4".into()), Some(false));
+        assert_eq!(
+            world.is_older(&"1.21".into()),
+            Some(false),
+            "A missing patch shall be treated as zero"
+        );
+        assert_eq!(
+            world.is_older(&"25w15a".into()),
+            None,
+            "Versions without a known data version shall not be compared"
+        );
+        let legacy = WorldVersion {
+            data_version: 0,
+            name: None,
+        };
+        assert_eq!(
+            legacy.is_older(&"1.9".into()),
+            Some(true),
+            "Worlds saved before data versions shall be older than every release"
+        );
+    }
+
+    #[test]
+    fn test_nbt_depth() {
+        let mut nbt = vec![9, 0, 0];
+        for _ in 0..100_000 {
+            nbt.push(9);
+            nbt.extend(1i32.to_be_bytes());
+        }
+        assert_eq!(
+            NbtReader::new(&nbt).root(),
+            Err("NBT is nested too deeply".into()),
+            "Deeply nested NBT shall be rejected instead of overflowing the stack"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::error::Result;
+
+/// The signature of the end of central directory record
+const EOCD_SIGNATURE: u32 = 0x06054b50;
+/// The size of the end of central directory record without its comment
+const EOCD_SIZE: usize = 22;
+/// The signature of a central directory entry
+const ENTRY_SIGNATURE: u32 = 0x02014b50;
+/// The size of a central directory entry without its name, extra field, and comment
+const ENTRY_SIZE: usize = 46;
+/// The most bytes read from the end of a file to find the central directory, the end record with
+/// the longest comment
+const TAIL_SIZE: u64 = (EOCD_SIZE + u16::MAX as usize) as u64;
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// An entry of a zip file, as described by its central directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ZipEntry {
+    name: Vec<u8>,
+    method: u16,
+    time: u16,
+    date: u16,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    /// Where the local header of the entry starts
+    offset: u64,
+}
+
+impl ZipEntry {
+    /// Check if the entry has the same contents as another, so its bytes can be reused
+    fn same_as(&self, other: &ZipEntry) -> bool {
+        (
+            &self.name,
+            self.method,
+            self.time,
+            self.date,
+            self.crc32,
+            self.compressed_size,
+            self.uncompressed_size,
+        ) == (
+            &other.name,
+            other.method,
+            other.time,
+            other.date,
+            other.crc32,
+            other.compressed_size,
+            other.uncompressed_size,
+        )
+    }
+}
+
+/// The central directory of a zip file, which lists its entries at the end of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CentralDirectory {
+    /// The entries, in the order they are stored in the file
+    entries: Vec<ZipEntry>,
+    /// Where the central directory starts, which is where the entries end
+    offset: u64,
+}
+
+/// Find the central directory in the bytes at the end of a file, returning its range in the file.
+/// Zip64 files are not supported.
+fn find_central_directory(tail: &[u8], tail_offset: u64) -> Option<Range<u64>> {
+    let at = (0..=tail.len().checked_sub(EOCD_SIZE)?)
+        .rev()
+        .find(|x| u32_at(tail, *x) == Some(EOCD_SIGNATURE))?;
+    let size = u32_at(tail, at + 12)? as u64;
+    let offset = u32_at(tail, at + 16)? as u64;
+    if size == u32::MAX as u64
+        || offset == u32::MAX as u64
+        || offset + size > tail_offset + at as u64
+    {
+        return None;
+    }
+    Some(offset..offset + size)
+}
+
+/// Parse the entries of a central directory that starts at an offset
+fn parse_central_directory(bytes: &[u8], offset: u64) -> Option<CentralDirectory> {
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while at < bytes.len() {
+        if u32_at(bytes, at)? != ENTRY_SIGNATURE {
+            return None;
+        }
+        let name_len = u16_at(bytes, at + 28)? as usize;
+        let extra_len = u16_at(bytes, at + 30)? as usize;
+        let comment_len = u16_at(bytes, at + 32)? as usize;
+        let name_start = at + ENTRY_SIZE;
+        entries.push(ZipEntry {
+            name: bytes.get(name_start..name_start + name_len)?.to_vec(),
+            method: u16_at(bytes, at + 10)?,
+            time: u16_at(bytes, at + 12)?,
+            date: u16_at(bytes, at + 14)?,
+            crc32: u32_at(bytes, at + 16)?,
+            compressed_size: u32_at(bytes, at + 20)?,
+            uncompressed_size: u32_at(bytes, at + 24)?,
+            offset: u32_at(bytes, at + 42)? as u64,
+        });
+        at = name_start + name_len + extra_len + comment_len;
+    }
+    entries.sort_by_key(|x| x.offset);
+    Some(CentralDirectory { entries, offset })
+}
+
+/// Read the central directory of a whole zip file
+fn read_central_directory(bytes: &[u8]) -> Option<CentralDirectory> {
+    let range = find_central_directory(bytes, 0)?;
+    let start = usize::try_from(range.start).ok()?;
+    let end = usize::try_from(range.end).ok()?;
+    parse_central_directory(bytes.get(start..end)?, range.start)
+}
+
+/// Where the bytes of a part of the new file come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Copied from this range of the old file
+    Reuse(Range<u64>),
+    /// Downloaded from this range of the new file
+    Fetch(Range<u64>),
+}
+
+/// Plan how to build the entries of the new file, reusing the entries of the old file that did
+/// not change. Adjacent ranges are merged, so each download covers as much as it can.
+fn plan(old: &CentralDirectory, new: &CentralDirectory) -> Vec<Segment> {
+    let span = |directory: &CentralDirectory, index: usize| {
+        let entry = &directory.entries[index];
+        let end = directory
+            .entries
+            .get(index + 1)
+            .map_or(directory.offset, |x| x.offset);
+        entry.offset..end
+    };
+    let old_entries = old
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(index, x)| (x.name.as_slice(), (x, span(old, index))))
+        .collect::<HashMap<_, _>>();
+    let mut segments = Vec::<Segment>::new();
+    let mut push = |segment: Segment| match (segments.last_mut(), segment) {
+        (Some(Segment::Fetch(last)), Segment::Fetch(x)) if last.end == x.start => last.end = x.end,
+        (Some(Segment::Reuse(last)), Segment::Reuse(x)) if last.end == x.start => last.end = x.end,
+        (_, segment) => segments.push(segment),
+    };
+    let first = new.entries.first().map_or(new.offset, |x| x.offset);
+    if first > 0 {
+        push(Segment::Fetch(0..first));
+    }
+    for (index, entry) in new.entries.iter().enumerate() {
+        let range = span(new, index);
+        match old_entries.get(entry.name.as_slice()) {
+            Some((old_entry, old_range))
+                if old_entry.same_as(entry)
+                    && old_range.end - old_range.start == range.end - range.start =>
+            {
+                push(Segment::Reuse(old_range.clone()))
+            }
+            _ => push(Segment::Fetch(range)),
+        }
+    }
+    segments
+}
+
+/// Build a new version of a zip file from an old version, downloading only the entries that
+/// changed with a function that fetches a range of the new file. Returns the new file and how many
+/// bytes were fetched, or None if either file is not a zip that can be updated this way. The
+/// result must be checked against the hashes of the new file, since an entry whose local header
+/// changed but whose central directory entry did not is reused as it was.
+pub fn update<F>(old: &[u8], new_size: u64, mut fetch: F) -> Option<(Vec<u8>, u64)>
+where
+    F: FnMut(Range<u64>) -> Result<Vec<u8>>,
+{
+    let old_directory = read_central_directory(old)?;
+    let tail_offset = new_size.saturating_sub(TAIL_SIZE);
+    let tail = fetch(tail_offset..new_size).ok()?;
+    let range = find_central_directory(&tail, tail_offset)?;
+    let mut fetched = tail.len() as u64;
+    let directory_bytes = match range.start.checked_sub(tail_offset) {
+        Some(start) => tail.get(start as usize..)?.to_vec(),
+        None => {
+            let head = fetch(range.start..tail_offset).ok()?;
+            fetched += head.len() as u64;
+            [head, tail].concat()
+        }
+    };
+    let end = (range.end - range.start) as usize;
+    let new_directory = parse_central_directory(directory_bytes.get(..end)?, range.start)?;
+    let mut result = Vec::with_capacity(new_size as usize);
+    for segment in plan(&old_directory, &new_directory) {
+        match segment {
+            Segment::Reuse(x) => {
+                result.extend_from_slice(old.get(x.start as usize..x.end as usize)?)
+            }
+            Segment::Fetch(x) => {
+                let bytes = fetch(x.clone()).ok()?;
+                if bytes.len() as u64 != x.end - x.start {
+                    return None;
+                }
+                fetched += bytes.len() as u64;
+                result.extend(bytes);
+            }
+        }
+    }
+    result.extend(directory_bytes);
+    (result.len() as u64 == new_size).then_some((result, fetched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a zip file of stored entries, with a fixed time so unchanged entries are identical
+    fn write_zip(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+        for (name, data, crc32) in entries {
+            let header = |signature: u32| {
+                let mut x = signature.to_le_bytes().to_vec();
+                x.extend(20u16.to_le_bytes());
+                x
+            };
+            let fields = |x: &mut Vec<u8>| {
+                x.extend(0u16.to_le_bytes());
+                x.extend(0u16.to_le_bytes());
+                x.extend(0x6000u16.to_le_bytes());
+                x.extend(0x5a21u16.to_le_bytes());
+                x.extend(crc32.to_le_bytes());
+                x.extend((data.len() as u32).to_le_bytes());
+                x.extend((data.len() as u32).to_le_bytes());
+                x.extend((name.len() as u16).to_le_bytes());
+                x.extend(0u16.to_le_bytes());
+            };
+            let mut entry = header(ENTRY_SIGNATURE);
+            entry.extend(20u16.to_le_bytes());
+            fields(&mut entry);
+            entry.extend([0u8; 10]);
+            entry.extend((bytes.len() as u32).to_le_bytes());
+            entry.extend(name.as_bytes());
+            directory.extend(entry);
+            let mut local = header(0x04034b50);
+            fields(&mut local);
+            local.extend(name.as_bytes());
+            local.extend(*data);
+            bytes.extend(local);
+        }
+        let offset = bytes.len() as u32;
+        let size = directory.len() as u32;
+        bytes.extend(directory);
+        bytes.extend(EOCD_SIGNATURE.to_le_bytes());
+        bytes.extend([0u8; 4]);
+        bytes.extend((entries.len() as u16).to_le_bytes());
+        bytes.extend((entries.len() as u16).to_le_bytes());
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_update() {
+        let textures = vec![7u8; 2 * TAIL_SIZE as usize];
+        let old = write_zip(&[
+            ("pack.mcmeta", b"{\"pack\": 1}", 1),
+            ("assets/stone.png", &textures, 2),
+            ("assets/dirt.png", b"old dirt", 3),
+        ]);
+        let new = write_zip(&[
+            ("pack.mcmeta", b"{\"pack\": 2}", 4),
+            ("assets/stone.png", &textures, 2),
+            ("assets/dirt.png", b"new dirt", 5),
+            ("assets/grass.png", b"grass", 6),
+        ]);
+        let mut requests = Vec::new();
+        let (bytes, fetched) = update(&old, new.len() as u64, |range| {
+            requests.push(range.clone());
+            Ok(new[range.start as usize..range.end.min(new.len() as u64) as usize].to_vec())
+        })
+        .expect("The zip shall be updated");
+        assert_eq!(
+            bytes, new,
+            "The updated zip shall be identical to the new zip"
+        );
+        assert!(
+            fetched < textures.len() as u64,
+            "Unchanged entries shall not be downloaded"
+        );
+        assert_eq!(
+            requests.len(),
+            3,
+            "Changed entries shall be downloaded with one request per run of entries"
+        );
+        assert_eq!(
+            update(b"not a zip", new.len() as u64, |_| Ok(new.clone())),
+            None,
+            "Files that are not zips shall not be updated"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::Write;
+
+use crate::error::{Error, Result};
+
+/// The signature of a local file header
+const LOCAL_SIGNATURE: u32 = 0x04034b50;
+/// The signature of a central directory entry
+const ENTRY_SIGNATURE: u32 = 0x02014b50;
+/// The signature of the end of central directory record
+const EOCD_SIGNATURE: u32 = 0x06054b50;
+/// The version of the format needed to extract deflated entries
+const VERSION: u16 = 20;
+/// The flag telling that names are UTF-8
+const UTF8_FLAG: u16 = 0x0800;
+/// The deflate compression method
+const DEFLATE: u16 = 8;
+/// The DOS date of every entry, 1980-01-01, so the same entries always make the same archive
+const DATE: u16 = 0x0021;
+
+/// Convert a size or offset to the 32 bits the format has without zip64
+fn u32_of(value: usize) -> Result<u32> {
+    u32::try_from(value)
+        .map_err(|_| Error::IO(std::io::Error::other("zip archive is larger than 4 GiB")))
+}
+
+/// Write a zip archive of entries, each a name and contents, with every entry deflated
+pub fn write_zip(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in entries {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+        let fields = |x: &mut Vec<u8>| -> Result<()> {
+            x.extend(VERSION.to_le_bytes());
+            x.extend(UTF8_FLAG.to_le_bytes());
+            x.extend(DEFLATE.to_le_bytes());
+            x.extend(0u16.to_le_bytes());
+            x.extend(DATE.to_le_bytes());
+            x.extend(crc.sum().to_le_bytes());
+            x.extend(u32_of(compressed.len())?.to_le_bytes());
+            x.extend(u32_of(data.len())?.to_le_bytes());
+            x.extend((name.len() as u16).to_le_bytes());
+            x.extend(0u16.to_le_bytes());
+            Ok(())
+        };
+        directory.extend(ENTRY_SIGNATURE.to_le_bytes());
+        directory.extend(VERSION.to_le_bytes());
+        fields(&mut directory)?;
+        directory.extend([0u8; 10]);
+        directory.extend(u32_of(bytes.len())?.to_le_bytes());
+        directory.extend(name.as_bytes());
+        bytes.extend(LOCAL_SIGNATURE.to_le_bytes());
+        fields(&mut bytes)?;
+        bytes.extend(name.as_bytes());
+        bytes.extend(compressed);
+    }
+    let count = u16::try_from(entries.len())
+        .map_err(|_| Error::IO(std::io::Error::other("zip archive has too many entries")))?;
+    let offset = u32_of(bytes.len())?;
+    let size = u32_of(directory.len())?;
+    bytes.extend(directory);
+    bytes.extend(EOCD_SIGNATURE.to_le_bytes());
+    bytes.extend([0u8; 4]);
+    bytes.extend(count.to_le_bytes());
+    bytes.extend(count.to_le_bytes());
+    bytes.extend(size.to_le_bytes());
+    bytes.extend(offset.to_le_bytes());
+    bytes.extend(0u16.to_le_bytes());
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Read the entries of an archive written by [write_zip], by their local headers
+    fn read_zip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+        let u32_at = |at: usize| {
+            u32::from_le_bytes(bytes[at..at + 4].try_into().expect("Slice has 4 bytes")) as usize
+        };
+        let mut result = Vec::new();
+        let mut at = 0;
+        while u32_at(at) == LOCAL_SIGNATURE as usize {
+            let (size, name_len) = (u32_at(at + 18), u16_at(at + 26));
+            let name = String::from_utf8_lossy(&bytes[at + 30..at + 30 + name_len]).into_owned();
+            let start = at + 30 + name_len;
+            let mut data = Vec::new();
+            flate2::read::DeflateDecoder::new(&bytes[start..start + size])
+                .read_to_end(&mut data)
+                .expect("Entry shall inflate");
+            result.push((name, data));
+            at = start + size;
+        }
+        result
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let entries = vec![
+            ("manifest.json".to_string(), b"{}".to_vec()),
+            ("overrides/mods/ä.jar".to_string(), vec![7u8; 4096]),
+            ("overrides/empty.txt".to_string(), vec![]),
+        ];
+        let bytes = write_zip(&entries).expect("Archive shall be written");
+        assert_eq!(
+            read_zip(&bytes),
+            entries,
+            "Entries shall be read back as written"
+        );
+        assert_eq!(
+            &bytes[bytes.len() - 22..bytes.len() - 18],
+            EOCD_SIGNATURE.to_le_bytes(),
+            "Archive shall end with the end of central directory record"
+        );
+        assert_eq!(
+            write_zip(&entries).expect("Archive shall be written"),
+            bytes,
+            "The same entries shall make the same archive"
+        );
+    }
+}